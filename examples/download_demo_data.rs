@@ -16,6 +16,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         max_retries: 3,
         timeout_seconds: 30,
         requests_per_second: 2.0,
+        ..Default::default()
     };
 
     let downloader = YahooFinanceDownloader::with_config(config)?;
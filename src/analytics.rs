@@ -6,11 +6,21 @@
 
 pub mod containers;
 pub mod calculators;
+pub mod expression;
+pub mod garch;
+pub mod indicators;
 mod lag;
+pub mod matrix;
+pub mod missing;
+pub mod pca;
+pub mod plugin;
+pub mod portfolio;
 pub mod registry;
+pub mod regression;
+pub mod risk;
 #[cfg(test)]
 pub(crate) mod testing;
-mod windows;
+pub mod windows;
 
 use crate::asset_key::AssetKey;
 use crate::dag::{DagError, NodeId, NodeParams};
@@ -439,13 +449,13 @@ mod tests {
 
     #[test]
     fn test_returns_nan_handling() {
-        // Test NaN handling (convert to 0)
+        // Test NaN handling (missing prices give missing returns, not zeros)
         let prices = vec![100.0, f64::NAN, 110.0];
         let returns = testing::calculate_returns(&prices);
 
         assert!(returns[0].is_nan(), "First return should be NaN");
-        assert_eq!(returns[1], 0.0, "NaN price should produce 0 return");
-        assert_eq!(returns[2], 0.0, "Return after NaN should be 0");
+        assert!(returns[1].is_nan(), "NaN price should produce NaN return");
+        assert!(returns[2].is_nan(), "Return after NaN should be NaN");
     }
 
     #[test]
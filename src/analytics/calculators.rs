@@ -1,16 +1,16 @@
+use std::collections::VecDeque;
+
+/// Log return between two prices; NaN when either price is missing or not positive,
+/// so that a node's missing-data policy decides how the gap is treated.
 pub(crate) fn log_return_value(current: f64, lagged: f64) -> f64 {
-    if lagged <= 0.0 || current <= 0.0 || lagged.is_nan() || current.is_nan() {
-        return 0.0;
+    if lagged.is_nan() || current.is_nan() || lagged <= 0.0 || current <= 0.0 {
+        return f64::NAN;
     }
 
-    let value = (current / lagged).ln();
-    if value.is_nan() {
-        0.0
-    } else {
-        value
-    }
+    (current / lagged).ln()
 }
 
+/// Population standard deviation of the non-NaN values; NaN when none is valid.
 pub fn population_std_dev(values: &[f64]) -> f64 {
     let valid_values: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
     if valid_values.is_empty() {
@@ -41,10 +41,502 @@ pub fn log_return_window(window: &[f64]) -> f64 {
     log_return_value(window[window.len() - 1], window[0])
 }
 
+/// Pearson correlation of two equally sized samples.
+///
+/// Pairs where either value is NaN are dropped. Returns NaN when fewer than two
+/// valid pairs remain or when either sample has zero variance.
+pub fn pearson_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let pairs: Vec<(f64, f64)> = xs
+        .iter()
+        .zip(ys.iter())
+        .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+        .map(|(&x, &y)| (x, y))
+        .collect();
+    if pairs.len() < 2 {
+        return f64::NAN;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+
+    if var_x == 0.0 || var_y == 0.0 {
+        return f64::NAN;
+    }
+    cov / (var_x.sqrt() * var_y.sqrt())
+}
+
+/// Drawdown of a level from its running peak: 0 at a new high, negative below it.
+pub fn drawdown(level: f64, peak: f64) -> f64 {
+    if level.is_nan() || peak.is_nan() || peak <= 0.0 {
+        return f64::NAN;
+    }
+    level / peak - 1.0
+}
+
+/// Largest peak-to-trough decline within a window of levels, as a non-positive
+/// fraction. NaN levels are skipped; returns NaN when no level is valid.
+pub fn max_drawdown(levels: &[f64]) -> f64 {
+    let mut peak = f64::NAN;
+    let mut worst = f64::NAN;
+    for &level in levels.iter().filter(|v| !v.is_nan()) {
+        if peak.is_nan() || level > peak {
+            peak = level;
+        }
+        let current = drawdown(level, peak);
+        if worst.is_nan() || current < worst {
+            worst = current;
+        }
+    }
+    worst
+}
+
+/// Central moments `(mean, m2, m3, m4)` of the non-NaN values.
+fn central_moments(values: &[f64]) -> Option<(f64, f64, f64, f64)> {
+    let (mean, count) = mean_of_valid(values)?;
+    let n = count as f64;
+    let (mut m2, mut m3, mut m4) = (0.0, 0.0, 0.0);
+    for value in values.iter().filter(|v| !v.is_nan()) {
+        let d = value - mean;
+        m2 += d * d;
+        m3 += d * d * d;
+        m4 += d * d * d * d;
+    }
+    Some((mean, m2 / n, m3 / n, m4 / n))
+}
+
+/// Population skewness (`m3 / m2^1.5`) of the non-NaN values.
+///
+/// Returns NaN with fewer than three valid values or zero variance.
+pub fn skewness(values: &[f64]) -> f64 {
+    match central_moments(values) {
+        Some((_, m2, m3, _)) if valid_count(values) >= 3 && m2 > 0.0 => m3 / m2.powf(1.5),
+        _ => f64::NAN,
+    }
+}
+
+/// Population excess kurtosis (`m4 / m2^2 - 3`) of the non-NaN values.
+///
+/// Returns NaN with fewer than four valid values or zero variance.
+pub fn excess_kurtosis(values: &[f64]) -> f64 {
+    match central_moments(values) {
+        Some((_, m2, _, m4)) if valid_count(values) >= 4 && m2 > 0.0 => m4 / (m2 * m2) - 3.0,
+        _ => f64::NAN,
+    }
+}
+
+fn valid_count(values: &[f64]) -> usize {
+    values.iter().filter(|v| !v.is_nan()).count()
+}
+
+fn mean_of_valid(values: &[f64]) -> Option<(f64, usize)> {
+    let valid: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    if valid.is_empty() {
+        return None;
+    }
+    Some((valid.iter().sum::<f64>() / valid.len() as f64, valid.len()))
+}
+
+/// Annualized ratio of the mean per-period return to a per-period dispersion,
+/// scaled by `sqrt(periods_per_year)`. NaN values are skipped; returns NaN with
+/// fewer than two valid values or a zero or missing dispersion.
+fn annualized_ratio(returns: &[f64], dispersion: f64, periods_per_year: f64) -> f64 {
+    match mean_of_valid(returns) {
+        Some((mean, count)) if count >= 2 && dispersion != 0.0 && !dispersion.is_nan() => {
+            mean / dispersion * periods_per_year.sqrt()
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// Annualized Sharpe ratio of per-period excess returns over the volatility
+/// (population standard deviation) of the returns.
+pub fn sharpe_ratio(excess_returns: &[f64], volatility: f64, periods_per_year: f64) -> f64 {
+    annualized_ratio(excess_returns, volatility, periods_per_year)
+}
+
+/// Downside deviation below zero: root mean square of the negative values,
+/// averaged over all valid observations.
+pub fn downside_deviation(values: &[f64]) -> f64 {
+    let valid: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    if valid.is_empty() {
+        return f64::NAN;
+    }
+    let sum_sq: f64 = valid.iter().map(|v| v.min(0.0).powi(2)).sum();
+    (sum_sq / valid.len() as f64).sqrt()
+}
+
+/// Annualized Sortino ratio of per-period excess returns.
+///
+/// Like [`sharpe_ratio`] but over the [`downside_deviation`] of the returns, so
+/// only returns below zero are penalized. NaN without downside observations.
+pub fn sortino_ratio(excess_returns: &[f64], downside: f64, periods_per_year: f64) -> f64 {
+    annualized_ratio(excess_returns, downside, periods_per_year)
+}
+
+/// Annualized information ratio of per-period active returns (asset minus
+/// benchmark): mean active return over tracking error.
+pub fn information_ratio(active_returns: &[f64], periods_per_year: f64) -> f64 {
+    annualized_ratio(
+        active_returns,
+        population_std_dev(active_returns),
+        periods_per_year,
+    )
+}
+
+/// Calmar ratio: annualized mean log return over the magnitude of the maximum
+/// drawdown. Returns NaN when there is no drawdown to scale by.
+pub fn calmar_ratio(returns: &[f64], max_drawdown: f64, periods_per_year: f64) -> f64 {
+    match mean_of_valid(returns) {
+        Some((mean, _)) if !max_drawdown.is_nan() && max_drawdown != 0.0 => {
+            mean * periods_per_year / max_drawdown.abs()
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// Quantile `q` (0 to 1) of the non-NaN values, interpolating linearly between
+/// order statistics. Returns NaN when no value is valid.
+pub fn quantile(values: &[f64], q: f64) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    sorted.sort_by(f64::total_cmp);
+    sorted_quantile(&sorted, q)
+}
+
+fn sorted_quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() || q.is_nan() {
+        return f64::NAN;
+    }
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Percentile rank (0 to 100) of `value` among the non-NaN values: the share at
+/// or below it, counting ties as half. Returns NaN when no value is valid.
+pub fn percentile_rank(values: &[f64], value: f64) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    sorted.sort_by(f64::total_cmp);
+    sorted_percentile_rank(&sorted, value)
+}
+
+fn sorted_percentile_rank(sorted: &[f64], value: f64) -> f64 {
+    if sorted.is_empty() || value.is_nan() {
+        return f64::NAN;
+    }
+    let below = sorted.partition_point(|v| *v < value);
+    let at_or_below = sorted.partition_point(|v| *v <= value);
+    100.0 * (below as f64 + 0.5 * (at_or_below - below) as f64) / sorted.len() as f64
+}
+
+/// Z-score of the last value against the mean and population standard deviation
+/// of the non-NaN values. Returns NaN when the last value is NaN or there is no
+/// dispersion.
+pub fn z_score(values: &[f64]) -> f64 {
+    match (values.last(), mean_of_valid(values)) {
+        (Some(&last), Some((mean, _))) => {
+            let std_dev = population_std_dev(values);
+            if last.is_nan() || std_dev == 0.0 {
+                f64::NAN
+            } else {
+                (last - mean) / std_dev
+            }
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// Moments of the last `window` observations, updated in O(1) per observation.
+///
+/// Keeps running power sums of the values, shifted by the first value seen to
+/// limit cancellation. NaN observations occupy a slot in the window but are
+/// left out of the moments, as in [`skewness`] and [`excess_kurtosis`].
+#[derive(Debug, Clone)]
+pub struct RollingMoments {
+    window: usize,
+    values: VecDeque<f64>,
+    shift: Option<f64>,
+    count: usize,
+    sums: [f64; 4],
+}
+
+impl RollingMoments {
+    pub fn new(window: usize) -> Self {
+        RollingMoments {
+            window: window.max(1),
+            values: VecDeque::new(),
+            shift: None,
+            count: 0,
+            sums: [0.0; 4],
+        }
+    }
+
+    /// Adds an observation, evicting the oldest once the window is full.
+    pub fn push(&mut self, value: f64) {
+        if self.values.len() == self.window {
+            if let Some(oldest) = self.values.pop_front() {
+                self.accumulate(oldest, -1.0);
+            }
+        }
+        self.values.push_back(value);
+        self.accumulate(value, 1.0);
+    }
+
+    /// Replaces the most recent observation (a revised value for the same timestamp).
+    pub fn replace_last(&mut self, value: f64) {
+        match self.values.pop_back() {
+            Some(last) => {
+                self.accumulate(last, -1.0);
+                self.values.push_back(value);
+                self.accumulate(value, 1.0);
+            }
+            None => self.push(value),
+        }
+    }
+
+    fn accumulate(&mut self, value: f64, sign: f64) {
+        if value.is_nan() {
+            return;
+        }
+        let shift = *self.shift.get_or_insert(value);
+        let d = value - shift;
+        self.sums[0] += sign * d;
+        self.sums[1] += sign * d * d;
+        self.sums[2] += sign * d * d * d;
+        self.sums[3] += sign * d * d * d * d;
+        if sign > 0.0 {
+            self.count += 1;
+        } else {
+            self.count -= 1;
+            if self.count == 0 {
+                // Start afresh so rounding left in the sums does not carry over
+                self.shift = None;
+                self.sums = [0.0; 4];
+            }
+        }
+    }
+
+    /// Observations in the window, including NaN ones.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether the window holds `window` observations.
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.window
+    }
+
+    /// Central moments `(mean, m2, m3, m4)` of the valid observations.
+    fn central_moments(&self) -> Option<(f64, f64, f64, f64)> {
+        if self.count == 0 {
+            return None;
+        }
+        let n = self.count as f64;
+        let shift = self.shift.unwrap_or(0.0);
+        let [s1, s2, s3, s4] = self.sums;
+        let a = s1 / n;
+        let m2 = (s2 / n - a * a).max(0.0);
+        let m3 = s3 / n - 3.0 * a * s2 / n + 2.0 * a.powi(3);
+        let m4 = s4 / n - 4.0 * a * s3 / n + 6.0 * a * a * s2 / n - 3.0 * a.powi(4);
+        Some((shift + a, m2, m3, m4))
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.central_moments().map_or(f64::NAN, |(mean, ..)| mean)
+    }
+
+    /// Population standard deviation, as [`population_std_dev`].
+    pub fn std_dev(&self) -> f64 {
+        self.central_moments().map_or(f64::NAN, |(_, m2, ..)| m2.sqrt())
+    }
+
+    /// Population skewness, as [`skewness`].
+    pub fn skewness(&self) -> f64 {
+        match self.central_moments() {
+            Some((_, m2, m3, _)) if self.count >= 3 && m2 > 0.0 => m3 / m2.powf(1.5),
+            _ => f64::NAN,
+        }
+    }
+
+    /// Population excess kurtosis, as [`excess_kurtosis`].
+    pub fn excess_kurtosis(&self) -> f64 {
+        match self.central_moments() {
+            Some((_, m2, _, m4)) if self.count >= 4 && m2 > 0.0 => m4 / (m2 * m2) - 3.0,
+            _ => f64::NAN,
+        }
+    }
+
+    /// Z-score of the most recent observation, as [`z_score`].
+    pub fn z_score(&self) -> f64 {
+        let last = self.values.back().copied().unwrap_or(f64::NAN);
+        let std_dev = self.std_dev();
+        if last.is_nan() || std_dev == 0.0 {
+            f64::NAN
+        } else {
+            (last - self.mean()) / std_dev
+        }
+    }
+}
+
+/// The last `window` observations kept in sorted order for quantile queries.
+///
+/// Each update locates the inserted and evicted values by binary search instead
+/// of re-sorting the window.
+#[derive(Debug, Clone)]
+pub struct RollingOrderStatistics {
+    window: usize,
+    values: VecDeque<f64>,
+    sorted: Vec<f64>,
+}
+
+impl RollingOrderStatistics {
+    pub fn new(window: usize) -> Self {
+        RollingOrderStatistics {
+            window: window.max(1),
+            values: VecDeque::new(),
+            sorted: Vec::new(),
+        }
+    }
+
+    /// Adds an observation, evicting the oldest once the window is full.
+    pub fn push(&mut self, value: f64) {
+        if self.values.len() == self.window {
+            if let Some(oldest) = self.values.pop_front() {
+                self.remove_sorted(oldest);
+            }
+        }
+        self.values.push_back(value);
+        self.insert_sorted(value);
+    }
+
+    /// Replaces the most recent observation (a revised value for the same timestamp).
+    pub fn replace_last(&mut self, value: f64) {
+        match self.values.pop_back() {
+            Some(last) => {
+                self.remove_sorted(last);
+                self.values.push_back(value);
+                self.insert_sorted(value);
+            }
+            None => self.push(value),
+        }
+    }
+
+    fn insert_sorted(&mut self, value: f64) {
+        if !value.is_nan() {
+            let index = self.sorted.partition_point(|v| *v < value);
+            self.sorted.insert(index, value);
+        }
+    }
+
+    fn remove_sorted(&mut self, value: f64) {
+        if !value.is_nan() {
+            let index = self.sorted.partition_point(|v| *v < value);
+            self.sorted.remove(index);
+        }
+    }
+
+    /// Whether the window holds `window` observations.
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.window
+    }
+
+    /// Quantile `q` of the valid observations, as [`quantile`].
+    pub fn quantile(&self, q: f64) -> f64 {
+        sorted_quantile(&self.sorted, q)
+    }
+
+    /// Percentile rank of the most recent observation, as [`percentile_rank`].
+    pub fn percentile_rank(&self) -> f64 {
+        let last = self.values.back().copied().unwrap_or(f64::NAN);
+        sorted_percentile_rank(&self.sorted, last)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn ratios_scale_mean_by_dispersion() {
+        let returns = [0.01, -0.02, 0.03, 0.0];
+        let mean = 0.005;
+        let std_dev = population_std_dev(&returns);
+        assert!((sharpe_ratio(&returns, std_dev, 1.0) - mean / std_dev).abs() < 1e-12);
+        assert!((sharpe_ratio(&returns, std_dev, 4.0) - 2.0 * mean / std_dev).abs() < 1e-12);
+        assert!((information_ratio(&returns, 4.0) - 2.0 * mean / std_dev).abs() < 1e-12);
+
+        let downside = (0.02f64.powi(2) / 4.0).sqrt();
+        assert!((downside_deviation(&returns) - downside).abs() < 1e-12);
+        assert!((sortino_ratio(&returns, downside, 1.0) - mean / downside).abs() < 1e-12);
+
+        assert!((calmar_ratio(&returns, -0.1, 252.0) - mean * 252.0 / 0.1).abs() < 1e-9);
+        assert!(calmar_ratio(&returns, 0.0, 252.0).is_nan());
+        assert!(sharpe_ratio(&[0.01, 0.01], 0.0, 252.0).is_nan());
+        assert!(sharpe_ratio(&[0.01], 0.01, 252.0).is_nan());
+        assert!(sortino_ratio(&[0.01, 0.02], 0.0, 252.0).is_nan());
+        assert!(information_ratio(&[0.01, 0.01], 252.0).is_nan());
+    }
+
+    #[test]
+    fn rolling_moments_match_full_window_calculations() {
+        let values = [0.01, -0.02, 0.03, f64::NAN, 0.005, -0.015, 0.04, -0.01, 0.02];
+        let mut moments = RollingMoments::new(5);
+        for (i, &value) in values.iter().enumerate() {
+            moments.push(value);
+            let window = &values[(i + 1).saturating_sub(5)..=i];
+            let close = |a: f64, b: f64| (a - b).abs() < 1e-9 || (a.is_nan() && b.is_nan());
+            assert!(close(moments.skewness(), skewness(window)));
+            assert!(close(moments.excess_kurtosis(), excess_kurtosis(window)));
+            assert!(close(moments.z_score(), z_score(window)));
+            assert!(close(moments.std_dev(), population_std_dev(window)));
+        }
+
+        // A revised value for the latest observation replaces it
+        moments.replace_last(0.1);
+        let revised = [0.005, -0.015, 0.04, -0.01, 0.1];
+        assert!((moments.skewness() - skewness(&revised)).abs() < 1e-9);
+        assert!(moments.is_full());
+    }
+
+    #[test]
+    fn order_statistics_track_sorted_window() {
+        let values = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0];
+        let mut order = RollingOrderStatistics::new(4);
+        for (i, &value) in values.iter().enumerate() {
+            order.push(value);
+            let window = &values[(i + 1).saturating_sub(4)..=i];
+            assert_eq!(order.quantile(0.25), quantile(window, 0.25));
+            assert_eq!(order.percentile_rank(), percentile_rank(window, value));
+        }
+        // Window [5, 9, 2, 6]
+        assert_eq!(order.quantile(0.5), 5.5);
+        assert_eq!(order.percentile_rank(), 62.5);
+        order.replace_last(1.0);
+        assert_eq!(order.percentile_rank(), 12.5);
+        assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.9), 4.6);
+        assert!(quantile(&[f64::NAN], 0.5).is_nan());
+    }
+
+    #[test]
+    fn max_drawdown_finds_deepest_trough_after_peak() {
+        let levels = [100.0, 120.0, 90.0, 110.0, 95.0, 130.0];
+        assert!((max_drawdown(&levels) - (90.0 / 120.0 - 1.0)).abs() < 1e-12);
+        assert_eq!(max_drawdown(&[1.0, 2.0, 3.0]), 0.0);
+        assert!(max_drawdown(&[f64::NAN]).is_nan());
+        assert!(drawdown(50.0, 0.0).is_nan());
+    }
+
     #[test]
     fn log_return_window_requires_two_values() {
         assert!(log_return_window(&[]).is_nan());
@@ -53,8 +545,9 @@ mod tests {
 
     #[test]
     fn log_return_window_handles_invalid_prices() {
-        assert_eq!(log_return_window(&[100.0, -5.0]), 0.0);
-        assert_eq!(log_return_window(&[f64::NAN, 200.0]), 0.0);
+        assert!(log_return_window(&[100.0, -5.0]).is_nan());
+        assert!(log_return_window(&[f64::NAN, 200.0]).is_nan());
+        assert!(log_return_window(&[0.0, 200.0]).is_nan());
     }
 
     #[test]
@@ -86,4 +579,22 @@ mod tests {
         let second = ema_step(Some(first), 110.0, 0.1);
         assert!((second - (0.1 * 110.0 + 0.9 * first)).abs() < 1e-12);
     }
+
+    #[test]
+    fn pearson_correlation_of_linear_series_is_one() {
+        let xs = [1.0, 2.0, 3.0, 4.0];
+        let ys = [2.0, 4.0, 6.0, 8.0];
+        assert!((pearson_correlation(&xs, &ys) - 1.0).abs() < 1e-12);
+        let inverted: Vec<f64> = ys.iter().map(|y| -y).collect();
+        assert!((pearson_correlation(&xs, &inverted) + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pearson_correlation_skips_nan_and_degenerate_input() {
+        assert!(pearson_correlation(&[1.0], &[2.0]).is_nan());
+        assert!(pearson_correlation(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0]).is_nan());
+        let xs = [1.0, f64::NAN, 2.0, 3.0];
+        let ys = [1.0, 5.0, 2.0, 3.0];
+        assert!((pearson_correlation(&xs, &ys) - 1.0).abs() < 1e-12);
+    }
 }
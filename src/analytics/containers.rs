@@ -61,7 +61,7 @@ mod tests {
         let analytic = LogReturnAnalytic;
         let expected = (105.0_f64 / 100.0_f64).ln();
         assert_eq!(analytic.compute(None, 105.0, 100.0), expected);
-        assert_eq!(analytic.compute(None, -1.0, 100.0), 0.0);
+        assert!(analytic.compute(None, -1.0, 100.0).is_nan());
     }
 
     #[test]
@@ -0,0 +1,903 @@
+//! Formula analytics: a small expression language over other nodes.
+//!
+//! An `expression` node evaluates a formula such as `vol_20 / vol_60` or
+//! `close - ema(close, 0.94)` for one asset (or portfolio). Each series the
+//! formula names becomes a dependency of the node; the formula is evaluated
+//! whenever one of them updates, with each series taken as of that timestamp.
+//!
+//! - Series: `close` (or `price`), `returns`, `drawdown`, and windowed
+//!   analytics written `<name>_<window>`, e.g. `vol_20`, `sma_50`, `rsi_14`.
+//! - Arithmetic `+ - * / ^`, unary minus and parentheses.
+//! - Comparisons `< <= > >= == !=`, giving 1 when true and 0 when false.
+//! - `abs`, `sqrt`, `log`, `exp` and two-argument `min`/`max`.
+//! - Functions over the formula's own history: `lag(x, n)`, `sma(x, n)`
+//!   (or `mean`), `sum(x, n)`, `std(x, n)` (population), `rolling_min(x, n)`,
+//!   `rolling_max(x, n)` and `ema(x, p)`, where `p` is a decay below 1
+//!   (`0.94` weighs the previous average by 0.94) or a period of at least 1.
+//!
+//! Undefined values are NaN and propagate.
+
+use crate::dag::{AnalyticType, NodeKey, WindowSpec};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Series a formula reads from another node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub analytic: AnalyticType,
+    /// Window of a windowed analytic such as `vol_20`
+    pub window: Option<usize>,
+}
+
+impl Reference {
+    fn parse(name: &str) -> Option<Self> {
+        let plain = |analytic| {
+            Some(Reference {
+                analytic,
+                window: None,
+            })
+        };
+        match name {
+            "close" | "price" => return plain(AnalyticType::DataProvider),
+            "returns" => return plain(AnalyticType::Returns),
+            "drawdown" => return plain(AnalyticType::Drawdown),
+            _ => {}
+        }
+
+        let (name, window) = name.rsplit_once('_')?;
+        let window = window.parse::<usize>().ok().filter(|w| *w > 0)?;
+        let analytic = match name {
+            "vol" => AnalyticType::Volatility,
+            name => AnalyticType::from_str(name),
+        };
+        let windowed = matches!(
+            analytic,
+            AnalyticType::Volatility
+                | AnalyticType::StdDev
+                | AnalyticType::Sma
+                | AnalyticType::ExponentialMovingAverage
+                | AnalyticType::Rsi
+                | AnalyticType::Skewness
+                | AnalyticType::Kurtosis
+                | AnalyticType::ZScore
+                | AnalyticType::PercentileRank
+                | AnalyticType::MaxDrawdown
+        );
+        windowed.then_some(Reference {
+            analytic,
+            window: Some(window),
+        })
+    }
+
+    /// Whether `key` is the node this reference reads.
+    pub fn matches(&self, key: &NodeKey) -> bool {
+        key.analytic.satisfies(self.analytic) && key.window == self.window.map(WindowSpec::fixed)
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.analytic, self.window) {
+            (AnalyticType::DataProvider, _) => write!(f, "close"),
+            (analytic, Some(window)) => write!(f, "{}_{}", analytic, window),
+            (analytic, None) => write!(f, "{}", analytic),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl BinaryOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Power => "^",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+        }
+    }
+
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Subtract => 2,
+            BinaryOp::Multiply | BinaryOp::Divide => 3,
+            BinaryOp::Power => 5,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, a: f64, b: f64) -> f64 {
+        let truth = |value: bool| {
+            if a.is_nan() || b.is_nan() {
+                f64::NAN
+            } else if value {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        match self {
+            BinaryOp::Add => a + b,
+            BinaryOp::Subtract => a - b,
+            BinaryOp::Multiply => a * b,
+            BinaryOp::Divide => a / b,
+            BinaryOp::Power => a.powf(b),
+            BinaryOp::Less => truth(a < b),
+            BinaryOp::LessEqual => truth(a <= b),
+            BinaryOp::Greater => truth(a > b),
+            BinaryOp::GreaterEqual => truth(a >= b),
+            BinaryOp::Equal => truth(a == b),
+            BinaryOp::NotEqual => truth(a != b),
+        }
+    }
+}
+
+/// Function of the current values of its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Abs,
+    Sqrt,
+    Log,
+    Exp,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "abs" => Some(Function::Abs),
+            "sqrt" => Some(Function::Sqrt),
+            "log" | "ln" => Some(Function::Log),
+            "exp" => Some(Function::Exp),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::Abs => "abs",
+            Function::Sqrt => "sqrt",
+            Function::Log => "log",
+            Function::Exp => "exp",
+            Function::Min => "min",
+            Function::Max => "max",
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Min | Function::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            Function::Abs => args[0].abs(),
+            Function::Sqrt => args[0].sqrt(),
+            Function::Log => args[0].ln(),
+            Function::Exp => args[0].exp(),
+            // NaN-propagating, unlike f64::min and f64::max
+            Function::Min if args[0].is_nan() || args[1].is_nan() => f64::NAN,
+            Function::Max if args[0].is_nan() || args[1].is_nan() => f64::NAN,
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+        }
+    }
+}
+
+/// Function of the recent history of its argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rolling {
+    Lag(usize),
+    Sma(usize),
+    Sum(usize),
+    Std(usize),
+    Min(usize),
+    Max(usize),
+    /// Decay below 1 or period of at least 1
+    Ema(f64),
+}
+
+impl Rolling {
+    fn parse(name: &str, parameter: f64) -> Option<Result<Self, String>> {
+        let count = || {
+            if parameter >= 1.0 && parameter.fract() == 0.0 {
+                Ok(parameter as usize)
+            } else {
+                Err(format!("{}() needs a whole number of at least 1", name))
+            }
+        };
+        Some(match name {
+            "lag" => count().map(Rolling::Lag),
+            "sma" | "mean" => count().map(Rolling::Sma),
+            "sum" => count().map(Rolling::Sum),
+            "std" => count().map(Rolling::Std),
+            "rolling_min" => count().map(Rolling::Min),
+            "rolling_max" => count().map(Rolling::Max),
+            "ema" if parameter > 0.0 => Ok(Rolling::Ema(parameter)),
+            "ema" => Err("ema() needs a positive decay or period".to_string()),
+            _ => return None,
+        })
+    }
+
+    fn is_rolling(name: &str) -> bool {
+        Rolling::parse(name, 1.0).is_some()
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Rolling::Lag(_) => "lag",
+            Rolling::Sma(_) => "sma",
+            Rolling::Sum(_) => "sum",
+            Rolling::Std(_) => "std",
+            Rolling::Min(_) => "rolling_min",
+            Rolling::Max(_) => "rolling_max",
+            Rolling::Ema(_) => "ema",
+        }
+    }
+
+    fn parameter(self) -> f64 {
+        match self {
+            Rolling::Lag(n)
+            | Rolling::Sma(n)
+            | Rolling::Sum(n)
+            | Rolling::Std(n)
+            | Rolling::Min(n)
+            | Rolling::Max(n) => n as f64,
+            Rolling::Ema(p) => p,
+        }
+    }
+
+    /// Weight of the newest value in an EMA.
+    fn alpha(p: f64) -> f64 {
+        if p < 1.0 {
+            1.0 - p
+        } else {
+            2.0 / (p + 1.0)
+        }
+    }
+
+    /// Earlier observations needed before the output settles.
+    fn lookback(self) -> usize {
+        match self {
+            Rolling::Lag(n) => n,
+            Rolling::Sma(n) | Rolling::Sum(n) | Rolling::Std(n) => n - 1,
+            Rolling::Min(n) | Rolling::Max(n) => n - 1,
+            // The period whose EMA has the same weight on the newest value
+            Rolling::Ema(p) => (2.0 / Rolling::alpha(p) - 1.0).ceil() as usize,
+        }
+    }
+
+    fn state(self) -> RollingState {
+        match self {
+            Rolling::Ema(_) => RollingState::Ema(None),
+            _ => RollingState::Window(VecDeque::new()),
+        }
+    }
+
+    fn update(self, state: &mut RollingState, value: f64) -> f64 {
+        match (self, state) {
+            (Rolling::Ema(p), RollingState::Ema(average)) => {
+                if !value.is_nan() {
+                    let alpha = Rolling::alpha(p);
+                    *average = Some(match *average {
+                        Some(previous) => alpha * value + (1.0 - alpha) * previous,
+                        None => value,
+                    });
+                }
+                average.unwrap_or(f64::NAN)
+            }
+            (rolling, RollingState::Window(window)) => {
+                let size = match rolling {
+                    Rolling::Lag(n) => n + 1,
+                    _ => rolling.parameter() as usize,
+                };
+                window.push_back(value);
+                if window.len() > size {
+                    window.pop_front();
+                }
+                if window.len() < size {
+                    return f64::NAN;
+                }
+                let n = size as f64;
+                match rolling {
+                    Rolling::Lag(_) => window[0],
+                    Rolling::Sma(_) => window.iter().sum::<f64>() / n,
+                    Rolling::Sum(_) => window.iter().sum(),
+                    Rolling::Std(_) => {
+                        let mean = window.iter().sum::<f64>() / n;
+                        let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+                        variance.sqrt()
+                    }
+                    Rolling::Min(_) | Rolling::Max(_) if window.iter().any(|v| v.is_nan()) => {
+                        f64::NAN
+                    }
+                    Rolling::Min(_) => window.iter().copied().fold(f64::INFINITY, f64::min),
+                    Rolling::Max(_) => window.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                    Rolling::Ema(_) => unreachable!("EMA keeps an average, not a window"),
+                }
+            }
+            (_, RollingState::Ema(_)) => unreachable!("window function given EMA state"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum RollingState {
+    Window(VecDeque<f64>),
+    Ema(Option<f64>),
+}
+
+/// Parsed formula.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Series(Reference),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+    Rolling(Rolling, Box<Expr>),
+}
+
+impl Expr {
+    /// Precedence of the outermost operation, for printing parentheses.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(op, _, _) => op.precedence(),
+            Expr::Negate(_) => 4,
+            _ => 6,
+        }
+    }
+
+    fn collect_references(&self, references: &mut Vec<Reference>) {
+        match self {
+            Expr::Series(reference) => {
+                if !references.contains(reference) {
+                    references.push(*reference);
+                }
+            }
+            Expr::Number(_) => {}
+            Expr::Negate(inner) | Expr::Rolling(_, inner) => inner.collect_references(references),
+            Expr::Binary(_, left, right) => {
+                left.collect_references(references);
+                right.collect_references(references);
+            }
+            Expr::Call(_, args) => args
+                .iter()
+                .for_each(|arg| arg.collect_references(references)),
+        }
+    }
+
+    fn collect_states(&self, states: &mut Vec<RollingState>) {
+        match self {
+            Expr::Series(_) | Expr::Number(_) => {}
+            Expr::Negate(inner) => inner.collect_states(states),
+            Expr::Rolling(rolling, inner) => {
+                states.push(rolling.state());
+                inner.collect_states(states);
+            }
+            Expr::Binary(_, left, right) => {
+                left.collect_states(states);
+                right.collect_states(states);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.collect_states(states)),
+        }
+    }
+
+    fn lookback(&self) -> usize {
+        match self {
+            Expr::Series(_) | Expr::Number(_) => 0,
+            Expr::Negate(inner) => inner.lookback(),
+            Expr::Rolling(rolling, inner) => rolling.lookback() + inner.lookback(),
+            Expr::Binary(_, left, right) => left.lookback().max(right.lookback()),
+            Expr::Call(_, args) => args.iter().map(Expr::lookback).max().unwrap_or(0),
+        }
+    }
+
+    /// Evaluates one observation; rolling states are visited in the order
+    /// `collect_states` created them.
+    fn evaluate(
+        &self,
+        value: &dyn Fn(&Reference) -> f64,
+        states: &mut [RollingState],
+        next: &mut usize,
+    ) -> f64 {
+        match self {
+            Expr::Number(number) => *number,
+            Expr::Series(reference) => value(reference),
+            Expr::Negate(inner) => -inner.evaluate(value, states, next),
+            Expr::Binary(op, left, right) => {
+                let a = left.evaluate(value, states, next);
+                let b = right.evaluate(value, states, next);
+                op.apply(a, b)
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<f64> = args
+                    .iter()
+                    .map(|arg| arg.evaluate(value, states, next))
+                    .collect();
+                function.apply(&args)
+            }
+            Expr::Rolling(rolling, inner) => {
+                let index = *next;
+                *next += 1;
+                let input = inner.evaluate(value, states, next);
+                rolling.update(&mut states[index], input)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter<'_>, expr: &Expr, parenthesize: bool| {
+            if parenthesize {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        };
+        match self {
+            Expr::Number(number) => write!(f, "{}", number),
+            Expr::Series(reference) => write!(f, "{}", reference),
+            Expr::Negate(inner) => {
+                write!(f, "-")?;
+                operand(f, inner, inner.precedence() < 4)
+            }
+            Expr::Binary(op, left, right) => {
+                let precedence = op.precedence();
+                // `^` groups to the right, comparisons do not chain and
+                // everything else groups to the left
+                let (left_parens, right_parens) = if *op == BinaryOp::Power {
+                    (left.precedence() <= precedence, right.precedence() < 4)
+                } else if precedence == BinaryOp::Less.precedence() {
+                    (
+                        left.precedence() <= precedence,
+                        right.precedence() <= precedence,
+                    )
+                } else {
+                    (
+                        left.precedence() < precedence,
+                        right.precedence() <= precedence,
+                    )
+                };
+                operand(f, left, left_parens)?;
+                write!(f, " {} ", op.symbol())?;
+                operand(f, right, right_parens)
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}({})", function.name(), args.join(", "))
+            }
+            Expr::Rolling(rolling, inner) => {
+                write!(f, "{}({}, {})", rolling.name(), inner, rolling.parameter())
+            }
+        }
+    }
+}
+
+/// Formula with the series it reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+    references: Vec<Reference>,
+}
+
+/// Rolling-function state of a formula evaluated one observation at a time.
+#[derive(Debug, Clone)]
+pub struct FormulaState {
+    rolling: Vec<RollingState>,
+}
+
+impl Formula {
+    /// Parses a formula, e.g. `vol_20 / vol_60`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr = parser.comparison()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected '{}' in formula", token));
+        }
+        let mut references = Vec::new();
+        expr.collect_references(&mut references);
+        if references.is_empty() {
+            return Err("Formula must reference at least one series".to_string());
+        }
+        Ok(Formula { expr, references })
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Series the formula reads, in order of first appearance.
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// Observations of its inputs the formula needs before its first settled value.
+    pub fn lookback(&self) -> usize {
+        self.expr.lookback()
+    }
+
+    /// Fresh state for evaluating the formula from its first observation.
+    pub fn state(&self) -> FormulaState {
+        let mut rolling = Vec::new();
+        self.expr.collect_states(&mut rolling);
+        FormulaState { rolling }
+    }
+
+    /// Evaluates the next observation, given the value of each reference in
+    /// the order of [`Formula::references`].
+    pub fn evaluate(&self, values: &[f64], state: &mut FormulaState) -> f64 {
+        let value = |reference: &Reference| {
+            self.references
+                .iter()
+                .position(|r| r == reference)
+                .and_then(|index| values.get(index).copied())
+                .unwrap_or(f64::NAN)
+        };
+        self.expr.evaluate(&value, &mut state.rolling, &mut 0)
+    }
+}
+
+/// Canonical text of the formula, used to key its node.
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 14] = [
+    "<=", ">=", "==", "!=", "<", ">", "+", "-", "*", "/", "^", "(", ")", ",",
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let length = if c.is_ascii_digit() || c == '.' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..length]
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number '{}' in formula", &rest[..length]))?;
+            tokens.push(Token::Number(number));
+            length
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..length].to_lowercase()));
+            length
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| rest.starts_with(**symbol))
+                .ok_or_else(|| format!("Unexpected '{}' in formula", c))?;
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        };
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser, loosest binding first:
+/// comparison, `+ -`, `* /`, unary minus, `^`, then atoms.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(token) => Err(format!("Expected '{}' but found '{}'", symbol, token)),
+                None => Err(format!("Expected '{}' at end of formula", symbol)),
+            }
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("<")) => BinaryOp::Less,
+            Some(Token::Symbol("<=")) => BinaryOp::LessEqual,
+            Some(Token::Symbol(">")) => BinaryOp::Greater,
+            Some(Token::Symbol(">=")) => BinaryOp::GreaterEqual,
+            Some(Token::Symbol("==")) => BinaryOp::Equal,
+            Some(Token::Symbol("!=")) => BinaryOp::NotEqual,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            let op = if self.eat("+") {
+                BinaryOp::Add
+            } else if self.eat("-") {
+                BinaryOp::Subtract
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinaryOp::Multiply
+            } else if self.eat("/") {
+                BinaryOp::Divide
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("-") {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.eat("^") {
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(
+                BinaryOp::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Symbol("(")) => {
+                let expr = self.comparison()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) if self.eat("(") => self.call(&name),
+            Some(Token::Ident(name)) => Reference::parse(&name)
+                .map(Expr::Series)
+                .ok_or_else(|| format!("Unknown series '{}' in formula", name)),
+            Some(token) => Err(format!("Unexpected '{}' in formula", token)),
+            None => Err("Formula ended unexpectedly".to_string()),
+        }
+    }
+
+    /// Arguments of a call whose opening parenthesis has been read.
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.comparison()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+
+        if let Some(function) = Function::parse(name) {
+            if args.len() != function.arity() {
+                return Err(format!(
+                    "{}() takes {} argument(s), got {}",
+                    name,
+                    function.arity(),
+                    args.len()
+                ));
+            }
+            return Ok(Expr::Call(function, args));
+        }
+        if !Rolling::is_rolling(name) {
+            return Err(format!("Unknown function '{}' in formula", name));
+        }
+        let (input, parameter) = match <[Expr; 2]>::try_from(args) {
+            Ok([input, Expr::Number(parameter)]) => (input, parameter),
+            _ => {
+                return Err(format!(
+                    "{}() takes a series and a number, e.g. {}(close, 20)",
+                    name, name
+                ))
+            }
+        };
+        let rolling = Rolling::parse(name, parameter).expect("checked above")?;
+        Ok(Expr::Rolling(rolling, Box::new(input)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_all(formula: &Formula, rows: &[&[f64]]) -> Vec<f64> {
+        let mut state = formula.state();
+        rows.iter()
+            .map(|values| formula.evaluate(values, &mut state))
+            .collect()
+    }
+
+    #[test]
+    fn parses_references_and_precedence() {
+        let formula = Formula::parse("vol_20 / VOL_60").unwrap();
+        assert_eq!(
+            formula.references(),
+            [
+                Reference {
+                    analytic: AnalyticType::Volatility,
+                    window: Some(20)
+                },
+                Reference {
+                    analytic: AnalyticType::Volatility,
+                    window: Some(60)
+                },
+            ]
+        );
+        assert_eq!(formula.to_string(), "volatility_20 / volatility_60");
+
+        let formula = Formula::parse("close - 2 * -returns ^ 2 >= (1 - close) - 1").unwrap();
+        assert_eq!(
+            formula.to_string(),
+            "close - 2 * -returns ^ 2 >= 1 - close - 1"
+        );
+        for text in [
+            "(close - 1) * 2",
+            "close - (1 - close)",
+            "(-close) ^ 2",
+            "2 ^ 3 ^ close",
+        ] {
+            let formula = Formula::parse(text).unwrap();
+            assert_eq!(formula.to_string(), text);
+            assert_eq!(Formula::parse(&formula.to_string()).unwrap(), formula);
+        }
+        assert_eq!(
+            evaluate_all(&Formula::parse("2 ^ 3 ^ close").unwrap(), &[&[2.0]]),
+            [512.0]
+        );
+    }
+
+    #[test]
+    fn printed_formulas_parse_back_to_themselves() {
+        for text in [
+            "(close > 1) > 0",
+            "close > (returns < 0)",
+            "(close > 1) == (returns <= 0)",
+            "-(close > 1) * 2",
+            "(close > 1) ^ 2",
+            "2 ^ -close ^ 2",
+            "-(-close) ^ 2",
+            "(-close ^ 2) ^ 3",
+            "close - -(returns - 1)",
+            "sma(close > lag(close, 1), 5) != 0",
+        ] {
+            let formula = Formula::parse(text).unwrap();
+            let printed = formula.to_string();
+            let reparsed = Formula::parse(&printed)
+                .unwrap_or_else(|e| panic!("{text} printed as {printed}: {e}"));
+            assert_eq!(reparsed, formula, "{text} printed as {printed}");
+            assert_eq!(reparsed.to_string(), printed);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_formulas() {
+        for text in [
+            "",
+            "1 + 2",
+            "close +",
+            "close = 1",
+            "foo_20",
+            "vol_0",
+            "lag(close)",
+            "lag(close, 1.5)",
+            "sma(close, returns)",
+            "ema(close, 0)",
+            "median(close, 3)",
+            "abs(close, 2)",
+            "(close",
+            "close close",
+        ] {
+            assert!(Formula::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn rolling_functions_keep_their_own_history() {
+        let formula =
+            Formula::parse("close - lag(close, 2) + sma(close, 2) * (close > 2)").unwrap();
+        assert_eq!(formula.lookback(), 2);
+        let values = evaluate_all(&formula, &[&[1.0], &[2.0], &[4.0], &[3.0]]);
+        assert!(values[0].is_nan() && values[1].is_nan());
+        assert_eq!(values[2], 4.0 - 1.0 + 3.0);
+        assert_eq!(values[3], 3.0 - 2.0 + 3.5);
+
+        // A missing value leaves an EMA where it was
+        let ema = Formula::parse("ema(close, 0.5) - ema(close, 1)").unwrap();
+        assert_eq!(ema.lookback(), 3);
+        let values = evaluate_all(&ema, &[&[2.0], &[f64::NAN], &[4.0]]);
+        assert_eq!(values, [0.0, 0.0, -1.0]);
+
+        let stats =
+            Formula::parse("std(close, 2) + rolling_max(close, 3) - sum(close, 2)").unwrap();
+        let values = evaluate_all(&stats, &[&[1.0], &[3.0], &[2.0]]);
+        assert_eq!(values[2], 0.5 + 3.0 - 5.0);
+    }
+}
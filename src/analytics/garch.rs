@@ -0,0 +1,443 @@
+//! GARCH(1,1) conditional volatility.
+//!
+//! Returns are modelled as zero-mean with conditional variance
+//! `sigma2[t + 1] = omega + alpha * r[t]^2 + beta * sigma2[t]`. Parameters are
+//! fitted by maximum likelihood (Gaussian) over a training window, after which
+//! the variance is updated recursively one return at a time.
+
+use std::f64::consts::PI;
+
+/// Output holding the one-step-ahead conditional volatility.
+pub const VOLATILITY: &str = "volatility";
+/// Output holding the volatility forecast `horizon` steps ahead.
+pub const FORECAST: &str = "forecast";
+/// Output holding the fitted constant term.
+pub const OMEGA: &str = "omega";
+/// Output holding the fitted weight on the latest squared return.
+pub const ALPHA: &str = "alpha";
+/// Output holding the fitted weight on the previous variance.
+pub const BETA: &str = "beta";
+/// Output holding the volatility the forecasts revert to.
+pub const LONG_RUN_VOLATILITY: &str = "long_run_volatility";
+
+/// Every output of a GARCH node, conditional volatility first.
+pub const OUTPUTS: [&str; 6] = [
+    VOLATILITY,
+    FORECAST,
+    OMEGA,
+    ALPHA,
+    BETA,
+    LONG_RUN_VOLATILITY,
+];
+
+/// Smallest training window a fit is attempted on.
+pub const MIN_TRAINING_WINDOW: usize = 10;
+
+/// Parameters of a GARCH(1,1) model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GarchParams {
+    pub omega: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl GarchParams {
+    /// `alpha + beta`; below one for a stationary model.
+    pub fn persistence(&self) -> f64 {
+        self.alpha + self.beta
+    }
+
+    /// Unconditional variance `omega / (1 - alpha - beta)`.
+    pub fn long_run_variance(&self) -> f64 {
+        self.omega / (1.0 - self.persistence())
+    }
+
+    /// Variance of the next period given this period's variance and return.
+    pub fn next_variance(&self, variance: f64, ret: f64) -> f64 {
+        self.omega + self.alpha * ret * ret + self.beta * variance
+    }
+
+    /// Variance `horizon` periods ahead, given the one-step-ahead variance.
+    pub fn forecast_variance(&self, next_variance: f64, horizon: usize) -> f64 {
+        if horizon <= 1 {
+            return next_variance;
+        }
+        let long_run = self.long_run_variance();
+        let steps = (horizon - 1).min(i32::MAX as usize) as i32;
+        long_run + self.persistence().powi(steps) * (next_variance - long_run)
+    }
+}
+
+/// Gaussian log-likelihood of `returns` under `params`, with the recursion
+/// started at the returns' mean square.
+pub fn log_likelihood(params: &GarchParams, returns: &[f64]) -> f64 {
+    let mut variance = initial_variance(returns);
+    let mut total = 0.0;
+    for &ret in returns {
+        if variance.is_nan() || variance <= 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        total -= 0.5 * ((2.0 * PI).ln() + variance.ln() + ret * ret / variance);
+        variance = params.next_variance(variance, ret);
+    }
+    total
+}
+
+fn initial_variance(returns: &[f64]) -> f64 {
+    returns.iter().map(|r| r * r).sum::<f64>() / returns.len() as f64
+}
+
+/// Maximum likelihood GARCH(1,1) fit of a training window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GarchFit {
+    pub params: GarchParams,
+    /// Conditional variance of the last training return
+    pub variance: f64,
+    /// Conditional variance of the period after the training window
+    pub next_variance: f64,
+}
+
+/// Fits GARCH(1,1) to `returns` by maximising the Gaussian likelihood with a
+/// Nelder-Mead search, constrained to `omega > 0`, `alpha, beta >= 0` and
+/// `alpha + beta < 1`.
+///
+/// Returns `None` for fewer than `MIN_TRAINING_WINDOW` returns, non-finite
+/// returns or a window without variation.
+pub fn fit_garch(returns: &[f64]) -> Option<GarchFit> {
+    if returns.len() < MIN_TRAINING_WINDOW || returns.iter().any(|r| !r.is_finite()) {
+        return None;
+    }
+    let sample = initial_variance(returns);
+    if sample.is_nan() || sample <= 0.0 {
+        return None;
+    }
+
+    // Search over ln(omega), the logit of persistence and the logit of alpha's
+    // share of it, which keeps every candidate inside the constraints
+    let decode = |x: &[f64; 3]| {
+        let persistence = logistic(x[1]);
+        let share = logistic(x[2]);
+        GarchParams {
+            omega: x[0].exp(),
+            alpha: persistence * share,
+            beta: persistence * (1.0 - share),
+        }
+    };
+    let start = [(sample * 0.1).ln(), logit(0.9), logit(0.1 / 0.9)];
+    let best = nelder_mead(
+        |x| {
+            let ll = log_likelihood(&decode(x), returns);
+            if ll.is_finite() {
+                -ll
+            } else {
+                f64::INFINITY
+            }
+        },
+        start,
+        0.5,
+        2000,
+    );
+    let params = decode(&best);
+
+    let mut variance = sample;
+    let mut next_variance = sample;
+    for &ret in returns {
+        variance = next_variance;
+        next_variance = params.next_variance(variance, ret);
+    }
+    Some(GarchFit {
+        params,
+        variance,
+        next_variance,
+    })
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn logit(p: f64) -> f64 {
+    (p / (1.0 - p)).ln()
+}
+
+/// Minimises `f` with the Nelder-Mead simplex method from `start`.
+fn nelder_mead(
+    f: impl Fn(&[f64; 3]) -> f64,
+    start: [f64; 3],
+    step: f64,
+    max_iter: usize,
+) -> [f64; 3] {
+    let mut simplex: Vec<([f64; 3], f64)> = (0..=3)
+        .map(|i| {
+            let mut point = start;
+            if i > 0 {
+                point[i - 1] += step;
+            }
+            (point, f(&point))
+        })
+        .collect();
+    let along = |from: &[f64; 3], to: &[f64; 3], t: f64| -> [f64; 3] {
+        std::array::from_fn(|d| from[d] + t * (to[d] - from[d]))
+    };
+
+    for _ in 0..max_iter {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[3].1);
+        if (worst - best).abs() <= 1e-10 * (best.abs() + 1e-10) {
+            break;
+        }
+
+        let centroid: [f64; 3] = std::array::from_fn(|d| {
+            simplex[..3].iter().map(|(point, _)| point[d]).sum::<f64>() / 3.0
+        });
+        let worst_point = simplex[3].0;
+
+        let reflected = along(&centroid, &worst_point, -1.0);
+        let reflected_value = f(&reflected);
+        if reflected_value < best {
+            let expanded = along(&centroid, &worst_point, -2.0);
+            let expanded_value = f(&expanded);
+            simplex[3] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < simplex[2].1 {
+            simplex[3] = (reflected, reflected_value);
+        } else {
+            let contracted = if reflected_value < worst {
+                along(&centroid, &reflected, 0.5)
+            } else {
+                along(&centroid, &worst_point, 0.5)
+            };
+            let contracted_value = f(&contracted);
+            if contracted_value < worst.min(reflected_value) {
+                simplex[3] = (contracted, contracted_value);
+            } else {
+                let best_point = simplex[0].0;
+                for vertex in simplex.iter_mut().skip(1) {
+                    let shrunk = along(&best_point, &vertex.0, 0.5);
+                    *vertex = (shrunk, f(&shrunk));
+                }
+            }
+        }
+    }
+
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex[0].0
+}
+
+/// GARCH(1,1) model updated one return at a time.
+///
+/// Collects returns until `window` of them are available, fits the parameters
+/// on that training window and from then on only runs the variance recursion.
+/// If the training window cannot be fitted it slides forward until one can.
+/// Non-finite returns are skipped.
+#[derive(Debug, Clone)]
+pub struct GarchFilter {
+    window: usize,
+    training: Vec<f64>,
+    /// Oldest training return the latest one slid out, kept to undo it
+    evicted: Option<f64>,
+    params: Option<GarchParams>,
+    /// Variance of the period before the latest return, kept to undo it
+    previous: f64,
+    /// Conditional variance of the latest return
+    variance: f64,
+    /// Conditional variance of the next period
+    next_variance: f64,
+    last: LastUpdate,
+}
+
+/// What the latest return did to the filter, so it can be replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LastUpdate {
+    None,
+    Trained,
+    Fitted,
+    Filtered,
+}
+
+impl GarchFilter {
+    /// Creates a filter that fits on the first `window` returns.
+    pub fn new(window: usize) -> Self {
+        GarchFilter {
+            window: window.max(MIN_TRAINING_WINDOW),
+            training: Vec::new(),
+            evicted: None,
+            params: None,
+            previous: f64::NAN,
+            variance: f64::NAN,
+            next_variance: f64::NAN,
+            last: LastUpdate::None,
+        }
+    }
+
+    /// Adds the next return.
+    pub fn push(&mut self, ret: f64) {
+        if !ret.is_finite() {
+            self.last = LastUpdate::None;
+            return;
+        }
+        match self.params {
+            Some(params) => {
+                self.previous = self.variance;
+                self.variance = self.next_variance;
+                self.next_variance = params.next_variance(self.variance, ret);
+                self.last = LastUpdate::Filtered;
+            }
+            None => {
+                self.training.push(ret);
+                self.evicted = None;
+                if self.training.len() > self.window {
+                    self.evicted = Some(self.training.remove(0));
+                }
+                self.last = LastUpdate::Trained;
+                if self.training.len() == self.window {
+                    if let Some(fit) = fit_garch(&self.training) {
+                        self.params = Some(fit.params);
+                        self.variance = fit.variance;
+                        self.next_variance = fit.next_variance;
+                        self.last = LastUpdate::Fitted;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces the latest return, e.g. a revised update for the same timestamp.
+    pub fn replace_last(&mut self, ret: f64) {
+        match self.last {
+            LastUpdate::None => {}
+            LastUpdate::Trained | LastUpdate::Fitted => {
+                self.training.pop();
+                if let Some(evicted) = self.evicted.take() {
+                    self.training.insert(0, evicted);
+                }
+                self.params = None;
+            }
+            LastUpdate::Filtered => {
+                self.next_variance = self.variance;
+                self.variance = self.previous;
+            }
+        }
+        self.push(ret);
+    }
+
+    /// Fitted parameters, once the training window is complete.
+    pub fn params(&self) -> Option<GarchParams> {
+        self.params
+    }
+
+    /// One-step-ahead conditional variance; NaN while training.
+    pub fn next_variance(&self) -> f64 {
+        if self.params.is_some() {
+            self.next_variance
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulates GARCH(1,1) returns with a deterministic Gaussian generator.
+    fn simulate(params: GarchParams, n: usize) -> Vec<f64> {
+        let mut state: u64 = 42;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        };
+        let mut variance = params.long_run_variance();
+        (0..n)
+            .map(|_| {
+                let z = (-2.0 * uniform().ln()).sqrt() * (2.0 * PI * uniform()).cos();
+                let ret = variance.sqrt() * z;
+                variance = params.next_variance(variance, ret);
+                ret
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_recovers_simulated_parameters() {
+        let truth = GarchParams {
+            omega: 2e-6,
+            alpha: 0.1,
+            beta: 0.85,
+        };
+        let returns = simulate(truth, 3000);
+        let fit = fit_garch(&returns).unwrap();
+
+        assert!(
+            (fit.params.alpha - truth.alpha).abs() < 0.05,
+            "{:?}",
+            fit.params
+        );
+        assert!(
+            (fit.params.beta - truth.beta).abs() < 0.08,
+            "{:?}",
+            fit.params
+        );
+        assert!(fit.params.persistence() < 1.0);
+        assert!(log_likelihood(&fit.params, &returns) >= log_likelihood(&truth, &returns));
+    }
+
+    #[test]
+    fn forecasts_revert_to_long_run_variance() {
+        let params = GarchParams {
+            omega: 1e-6,
+            alpha: 0.05,
+            beta: 0.9,
+        };
+        let next = 4e-4;
+        assert_eq!(params.forecast_variance(next, 1), next);
+        let far = params.forecast_variance(next, 1000);
+        assert!((far - params.long_run_variance()).abs() < 1e-12);
+        let two = params.forecast_variance(next, 2);
+        assert!((two - params.next_variance(next, next.sqrt())).abs() < 1e-15);
+    }
+
+    #[test]
+    fn filter_replaces_latest_return() {
+        let returns = simulate(
+            GarchParams {
+                omega: 1e-6,
+                alpha: 0.08,
+                beta: 0.9,
+            },
+            40,
+        );
+        let mut revised = GarchFilter::new(20);
+        let mut direct = GarchFilter::new(20);
+        for &ret in &returns {
+            revised.push(ret * 3.0);
+            revised.replace_last(ret);
+            direct.push(ret);
+            assert_eq!(revised.params(), direct.params());
+            let (a, b) = (revised.next_variance(), direct.next_variance());
+            assert!(a == b || (a.is_nan() && b.is_nan()));
+        }
+        assert!(direct.params().is_some());
+        assert!(fit_garch(&returns[..5]).is_none());
+    }
+
+    #[test]
+    fn replacing_a_return_restores_the_training_return_it_slid_out() {
+        // A full training window that was not fitted slides on the next return
+        let mut filter = GarchFilter::new(MIN_TRAINING_WINDOW);
+        filter.training = (1..=MIN_TRAINING_WINDOW).map(|i| i as f64 * 1e-3).collect();
+        let window = filter.training.clone();
+
+        filter.push(0.05);
+        assert_eq!(filter.training[0], 2e-3);
+        assert!(filter.params().is_some());
+        filter.replace_last(f64::NAN);
+        assert_eq!(filter.training, window);
+        assert!(filter.params().is_none());
+    }
+}
@@ -0,0 +1,343 @@
+//! Technical indicators.
+//!
+//! Windowed indicators (SMA, Bollinger bands) are plain functions of a window of
+//! prices. Recursive indicators (EMA, RSI, MACD, ATR) are small `Copy` states
+//! advanced one observation at a time, so push mode can update them in O(1).
+//!
+//! Stored prices are closes only, so ATR's true range reduces to the absolute
+//! close-to-close move.
+
+use crate::analytics::calculators::{ema_step, population_std_dev};
+
+/// Simple moving average of the non-NaN values in a window.
+pub fn sma(window: &[f64]) -> f64 {
+    let valid: Vec<f64> = window.iter().copied().filter(|v| !v.is_nan()).collect();
+    if valid.is_empty() {
+        return f64::NAN;
+    }
+    valid.iter().sum::<f64>() / valid.len() as f64
+}
+
+/// A Bollinger band over a window of prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BandOutput {
+    /// Moving average plus `num_std` standard deviations
+    Upper,
+    /// Moving average
+    Middle,
+    /// Moving average minus `num_std` standard deviations
+    Lower,
+}
+
+impl BandOutput {
+    /// Every band, in the order a Bollinger node reports them (middle first).
+    pub const ALL: [BandOutput; 3] = [BandOutput::Middle, BandOutput::Upper, BandOutput::Lower];
+
+    /// Parses an output name ("upper", "middle", "lower").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "upper" => Some(BandOutput::Upper),
+            "middle" | "mid" | "" => Some(BandOutput::Middle),
+            "lower" => Some(BandOutput::Lower),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BandOutput::Upper => "upper",
+            BandOutput::Middle => "middle",
+            BandOutput::Lower => "lower",
+        }
+    }
+}
+
+/// Bollinger band of a window: SMA shifted by `num_std` population standard deviations.
+pub fn bollinger_band(window: &[f64], num_std: f64, output: BandOutput) -> f64 {
+    let middle = sma(window);
+    let offset = num_std * population_std_dev(window);
+    match output {
+        BandOutput::Upper => middle + offset,
+        BandOutput::Middle => middle,
+        BandOutput::Lower => middle - offset,
+    }
+}
+
+/// A MACD series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MacdOutput {
+    /// Fast EMA minus slow EMA
+    Line,
+    /// EMA of the MACD line
+    Signal,
+    /// MACD line minus signal
+    Histogram,
+}
+
+impl MacdOutput {
+    /// Every series, in the order a MACD node reports them (line first).
+    pub const ALL: [MacdOutput; 3] = [MacdOutput::Line, MacdOutput::Signal, MacdOutput::Histogram];
+
+    /// Parses an output name ("line", "signal", "histogram").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "line" | "macd" | "" => Some(MacdOutput::Line),
+            "signal" => Some(MacdOutput::Signal),
+            "histogram" | "hist" => Some(MacdOutput::Histogram),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MacdOutput::Line => "line",
+            MacdOutput::Signal => "signal",
+            MacdOutput::Histogram => "histogram",
+        }
+    }
+}
+
+/// Exponential moving average with smoothing `2 / (period + 1)`, seeded with
+/// the first value.
+#[derive(Debug, Clone, Copy)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Ema {
+            alpha: 2.0 / (period.max(1) as f64 + 1.0),
+            value: None,
+        }
+    }
+
+    pub fn update(mut self, value: f64) -> Self {
+        if !value.is_nan() {
+            self.value = Some(ema_step(self.value, value, self.alpha));
+        }
+        self
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value.unwrap_or(f64::NAN)
+    }
+}
+
+/// Wilder's smoothing: the simple average of the first `period` values, then
+/// `(previous * (period - 1) + value) / period`.
+#[derive(Debug, Clone, Copy)]
+struct Wilder {
+    period: usize,
+    count: usize,
+    sum: f64,
+    value: Option<f64>,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Wilder {
+            period: period.max(1),
+            count: 0,
+            sum: 0.0,
+            value: None,
+        }
+    }
+
+    fn update(mut self, value: f64) -> Self {
+        let n = self.period as f64;
+        match self.value {
+            Some(previous) => self.value = Some((previous * (n - 1.0) + value) / n),
+            None => {
+                self.count += 1;
+                self.sum += value;
+                if self.count == self.period {
+                    self.value = Some(self.sum / n);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// Relative Strength Index (0-100) with Wilder smoothing of gains and losses.
+#[derive(Debug, Clone, Copy)]
+pub struct Rsi {
+    previous: Option<f64>,
+    gains: Wilder,
+    losses: Wilder,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Rsi {
+            previous: None,
+            gains: Wilder::new(period),
+            losses: Wilder::new(period),
+        }
+    }
+
+    pub fn update(mut self, price: f64) -> Self {
+        if price.is_nan() {
+            return self;
+        }
+        if let Some(previous) = self.previous {
+            let change = price - previous;
+            self.gains = self.gains.update(change.max(0.0));
+            self.losses = self.losses.update((-change).max(0.0));
+        }
+        self.previous = Some(price);
+        self
+    }
+
+    /// NaN until `period` changes have been seen; 50 when prices have not moved.
+    pub fn value(&self) -> f64 {
+        match (self.gains.value, self.losses.value) {
+            (Some(0.0), Some(0.0)) => 50.0,
+            (Some(_), Some(0.0)) => 100.0,
+            (Some(gain), Some(loss)) => 100.0 - 100.0 / (1.0 + gain / loss),
+            _ => f64::NAN,
+        }
+    }
+}
+
+/// Moving Average Convergence/Divergence.
+#[derive(Debug, Clone, Copy)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Macd {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+
+    pub fn update(mut self, price: f64) -> Self {
+        if price.is_nan() {
+            return self;
+        }
+        self.fast = self.fast.update(price);
+        self.slow = self.slow.update(price);
+        self.signal = self.signal.update(self.fast.value() - self.slow.value());
+        self
+    }
+
+    pub fn value(&self, output: MacdOutput) -> f64 {
+        let line = self.fast.value() - self.slow.value();
+        match output {
+            MacdOutput::Line => line,
+            MacdOutput::Signal => self.signal.value(),
+            MacdOutput::Histogram => line - self.signal.value(),
+        }
+    }
+}
+
+/// Average True Range with Wilder smoothing, on close-to-close moves.
+#[derive(Debug, Clone, Copy)]
+pub struct Atr {
+    previous: Option<f64>,
+    range: Wilder,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Atr {
+            previous: None,
+            range: Wilder::new(period),
+        }
+    }
+
+    pub fn update(mut self, price: f64) -> Self {
+        if price.is_nan() {
+            return self;
+        }
+        if let Some(previous) = self.previous {
+            self.range = self.range.update((price - previous).abs());
+        }
+        self.previous = Some(price);
+        self
+    }
+
+    /// NaN until `period` moves have been seen.
+    pub fn value(&self) -> f64 {
+        self.range.value.unwrap_or(f64::NAN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bollinger_bands_straddle_sma() {
+        let window = [1.0, 2.0, 3.0, 4.0];
+        let std_dev = population_std_dev(&window);
+        assert_eq!(sma(&window), 2.5);
+        assert_eq!(bollinger_band(&window, 2.0, BandOutput::Middle), 2.5);
+        assert!(
+            (bollinger_band(&window, 2.0, BandOutput::Upper) - (2.5 + 2.0 * std_dev)).abs() < 1e-12
+        );
+        assert!(
+            (bollinger_band(&window, 2.0, BandOutput::Lower) - (2.5 - 2.0 * std_dev)).abs() < 1e-12
+        );
+    }
+
+    #[test]
+    fn rsi_uses_wilder_smoothing() {
+        // Changes: +1, -1, +2 with period 2
+        let rsi = [10.0, 11.0, 10.0]
+            .iter()
+            .fold(Rsi::new(2), |rsi, &price| rsi.update(price));
+        // Seed averages: gain 0.5, loss 0.5
+        assert!((rsi.value() - 50.0).abs() < 1e-12);
+
+        let rsi = rsi.update(12.0);
+        // gain (0.5 + 2) / 2 = 1.25, loss 0.5 / 2 = 0.25
+        assert!((rsi.value() - (100.0 - 100.0 / (1.0 + 5.0))).abs() < 1e-12);
+        assert!(Rsi::new(2).update(10.0).value().is_nan());
+        assert_eq!(
+            [1.0, 2.0, 3.0]
+                .iter()
+                .fold(Rsi::new(2), |r, &p| r.update(p))
+                .value(),
+            100.0
+        );
+    }
+
+    #[test]
+    fn macd_histogram_is_line_minus_signal() {
+        let macd = [10.0, 11.0, 12.5, 12.0, 13.0]
+            .iter()
+            .fold(Macd::new(2, 4, 3), |macd, &price| macd.update(price));
+        let line = macd.value(MacdOutput::Line);
+        let signal = macd.value(MacdOutput::Signal);
+        assert!(line > 0.0);
+        assert!((macd.value(MacdOutput::Histogram) - (line - signal)).abs() < 1e-12);
+
+        let fast = [10.0, 11.0].iter().fold(Ema::new(2), |e, &p| e.update(p));
+        assert!((fast.value() - (2.0 / 3.0 * 11.0 + 1.0 / 3.0 * 10.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn atr_averages_absolute_moves() {
+        let atr = [10.0, 12.0, 11.0, 11.5]
+            .iter()
+            .fold(Atr::new(2), |atr, &price| atr.update(price));
+        // Seed (2 + 1) / 2 = 1.5, then (1.5 + 0.5) / 2
+        assert!((atr.value() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn outputs_parse() {
+        assert_eq!(MacdOutput::parse("Signal"), Some(MacdOutput::Signal));
+        assert_eq!(BandOutput::parse("lower"), Some(BandOutput::Lower));
+        assert_eq!(BandOutput::parse("outer"), None);
+    }
+}
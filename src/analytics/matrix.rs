@@ -0,0 +1,358 @@
+//! Covariance and correlation matrices of several return series.
+//!
+//! A matrix node estimates the covariance of its assets' returns over a
+//! rolling window and reports every entry of the covariance or correlation
+//! matrix as a named output, plus the number of observations the estimate
+//! used.
+
+use crate::asset_key::AssetKey;
+use std::fmt;
+
+/// Output holding the number of dates in the estimation window.
+pub const OBSERVATIONS: &str = "observations";
+
+/// Decay used by the EWMA estimator when a node does not choose one.
+pub const DEFAULT_LAMBDA: f64 = 0.94;
+
+/// Which matrix a node reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatrixKind {
+    Covariance,
+    Correlation,
+}
+
+impl MatrixKind {
+    /// Parses a matrix name ("covariance", "correlation").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "covariance" | "cov" => Some(MatrixKind::Covariance),
+            "correlation" | "corr" => Some(MatrixKind::Correlation),
+            _ => None,
+        }
+    }
+
+    /// Converts an estimated covariance matrix into the matrix of this kind.
+    pub fn from_covariance(self, covariance: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+        match self {
+            MatrixKind::Covariance => covariance,
+            MatrixKind::Correlation => correlation(&covariance),
+        }
+    }
+}
+
+impl fmt::Display for MatrixKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            MatrixKind::Covariance => "covariance",
+            MatrixKind::Correlation => "correlation",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+/// How the covariance is estimated from a window of returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatrixEstimator {
+    /// Sample covariance with an n - 1 denominator
+    Sample,
+    /// Zero-mean exponentially weighted covariance, most recent return weighted most
+    Ewma,
+    /// Ledoit-Wolf shrinkage of the sample covariance towards a scaled identity
+    LedoitWolf,
+}
+
+impl MatrixEstimator {
+    /// Parses an estimator name ("sample", "ewma", "ledoit_wolf").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "sample" | "" => Some(MatrixEstimator::Sample),
+            "ewma" | "exponential" => Some(MatrixEstimator::Ewma),
+            "ledoit_wolf" | "ledoitwolf" | "shrinkage" => Some(MatrixEstimator::LedoitWolf),
+            _ => None,
+        }
+    }
+
+    /// Covariance of equally long `columns` (one return series per asset);
+    /// `lambda` is the EWMA decay and ignored by the other estimators.
+    ///
+    /// Returns `None` when there are fewer than two observations or the
+    /// lengths differ.
+    pub fn covariance(self, columns: &[Vec<f64>], lambda: f64) -> Option<Vec<Vec<f64>>> {
+        match self {
+            MatrixEstimator::Sample => sample_covariance(columns),
+            MatrixEstimator::Ewma => ewma_covariance(columns, lambda),
+            MatrixEstimator::LedoitWolf => ledoit_wolf_covariance(columns),
+        }
+    }
+}
+
+impl fmt::Display for MatrixEstimator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            MatrixEstimator::Sample => "sample",
+            MatrixEstimator::Ewma => "ewma",
+            MatrixEstimator::LedoitWolf => "ledoit_wolf",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+/// Parses an EWMA decay, which must lie strictly between 0 and 1.
+pub fn parse_lambda(value: Option<&str>) -> Result<f64, String> {
+    let lambda = match value {
+        None => return Ok(DEFAULT_LAMBDA),
+        Some(value) => value
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid EWMA decay '{}'", value.trim()))?,
+    };
+    if lambda > 0.0 && lambda < 1.0 {
+        Ok(lambda)
+    } else {
+        Err(format!(
+            "EWMA decay must be between 0 and 1, got {}",
+            lambda
+        ))
+    }
+}
+
+/// Length shared by `columns`, if there are at least two observations.
+fn observation_count(columns: &[Vec<f64>]) -> Option<usize> {
+    let n = columns.first()?.len();
+    (n >= 2 && columns.iter().all(|column| column.len() == n)).then_some(n)
+}
+
+/// `columns` with their means removed.
+fn centred(columns: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    columns
+        .iter()
+        .map(|column| {
+            let mean = column.iter().sum::<f64>() / column.len() as f64;
+            column.iter().map(|x| x - mean).collect()
+        })
+        .collect()
+}
+
+/// Weighted cross products `sum(w_t * a_t * b_t)` of every pair of columns.
+fn cross_products(columns: &[Vec<f64>], weights: impl Fn(usize) -> f64) -> Vec<Vec<f64>> {
+    columns
+        .iter()
+        .map(|a| {
+            columns
+                .iter()
+                .map(|b| {
+                    a.iter()
+                        .zip(b.iter())
+                        .enumerate()
+                        .map(|(t, (x, y))| weights(t) * x * y)
+                        .sum()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Sample covariance matrix (n - 1 denominator) of equally long `columns`.
+pub fn sample_covariance(columns: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = observation_count(columns)?;
+    let denominator = (n - 1) as f64;
+    Some(cross_products(&centred(columns), |_| 1.0 / denominator))
+}
+
+/// Zero-mean EWMA covariance: observation `t` of `n` is weighted by
+/// `lambda^(n - 1 - t)`, normalised so the weights sum to one.
+pub fn ewma_covariance(columns: &[Vec<f64>], lambda: f64) -> Option<Vec<Vec<f64>>> {
+    let n = observation_count(columns)?;
+    let total: f64 = (0..n).map(|t| lambda.powi((n - 1 - t) as i32)).sum();
+    Some(cross_products(columns, |t| {
+        lambda.powi((n - 1 - t) as i32) / total
+    }))
+}
+
+/// Ledoit-Wolf (2004) shrinkage of the maximum likelihood covariance towards
+/// `mu * I`, where `mu` is the average variance, with the optimal intensity.
+pub fn ledoit_wolf_covariance(columns: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = observation_count(columns)?;
+    let p = columns.len();
+    let x = centred(columns);
+    let s = cross_products(&x, |_| 1.0 / n as f64);
+
+    // Frobenius norms are scaled by 1 / p as in the paper
+    let mu = (0..p).map(|i| s[i][i]).sum::<f64>() / p as f64;
+    let target = |i: usize, j: usize| if i == j { mu } else { 0.0 };
+    let mut d2 = 0.0;
+    let mut b2 = 0.0;
+    for i in 0..p {
+        for j in 0..p {
+            d2 += (s[i][j] - target(i, j)).powi(2);
+            b2 += (0..n)
+                .map(|t| (x[i][t] * x[j][t] - s[i][j]).powi(2))
+                .sum::<f64>();
+        }
+    }
+    d2 /= p as f64;
+    b2 /= p as f64 * (n * n) as f64;
+
+    let shrinkage = if d2 > 0.0 { b2.min(d2) / d2 } else { 0.0 };
+    Some(
+        (0..p)
+            .map(|i| {
+                (0..p)
+                    .map(|j| shrinkage * target(i, j) + (1.0 - shrinkage) * s[i][j])
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// Correlation matrix of a covariance matrix; entries of an asset whose
+/// returns do not vary are NaN.
+pub fn correlation(covariance: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let scale: Vec<f64> = (0..covariance.len())
+        .map(|i| covariance[i][i].sqrt())
+        .collect();
+    covariance
+        .iter()
+        .zip(scale.iter())
+        .map(|(row, si)| {
+            row.iter()
+                .zip(scale.iter())
+                .map(|(value, sj)| {
+                    if *si > 0.0 && *sj > 0.0 {
+                        (value / (si * sj)).clamp(-1.0, 1.0)
+                    } else {
+                        f64::NAN
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Output holding the matrix entry of `row` and `column`, e.g. `spy_xlk`.
+pub fn entry_output(row: &AssetKey, column: &AssetKey) -> String {
+    format!(
+        "{}_{}",
+        row.to_string().to_lowercase(),
+        column.to_string().to_lowercase()
+    )
+}
+
+/// Positions `(row, column)` of the entries a node over `asset_count` assets
+/// reports: off-diagonal entries of the upper triangle row by row, then the
+/// diagonal. The matrix is symmetric, so these determine it.
+pub fn entry_positions(asset_count: usize) -> Vec<(usize, usize)> {
+    let mut positions: Vec<(usize, usize)> = (0..asset_count)
+        .flat_map(|i| (i + 1..asset_count).map(move |j| (i, j)))
+        .collect();
+    positions.extend((0..asset_count).map(|i| (i, i)));
+    positions
+}
+
+/// Every output of a matrix node over `assets`, the entry of the first two
+/// assets first and the observation count last.
+pub fn output_names(assets: &[AssetKey]) -> Vec<String> {
+    let mut names: Vec<String> = entry_positions(assets.len())
+        .into_iter()
+        .map(|(i, j)| entry_output(&assets[i], &assets[j]))
+        .collect();
+    names.push(OBSERVATIONS.to_string());
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<Vec<f64>> {
+        vec![
+            vec![0.01, -0.02, 0.015, 0.003, -0.007, 0.02],
+            vec![0.012, -0.015, 0.01, 0.001, -0.01, 0.018],
+            vec![-0.004, 0.006, 0.002, -0.003, 0.005, -0.001],
+        ]
+    }
+
+    #[test]
+    fn sample_and_ewma_covariances_match_definitions() {
+        let columns = columns();
+        let sample = sample_covariance(&columns).unwrap();
+        let mean = |c: &[f64]| c.iter().sum::<f64>() / 6.0;
+        let (m0, m1) = (mean(&columns[0]), mean(&columns[1]));
+        let expected: f64 = (0..6)
+            .map(|t| (columns[0][t] - m0) * (columns[1][t] - m1))
+            .sum::<f64>()
+            / 5.0;
+        assert!((sample[0][1] - expected).abs() < 1e-15);
+        assert_eq!(sample[0][1], sample[1][0]);
+
+        let ewma = ewma_covariance(&columns, 0.5).unwrap();
+        let weights: Vec<f64> = (0..6).map(|t| 0.5_f64.powi(5 - t)).collect();
+        let total: f64 = weights.iter().sum();
+        let expected: f64 = (0..6)
+            .map(|t| weights[t] * columns[0][t] * columns[2][t])
+            .sum::<f64>()
+            / total;
+        assert!((ewma[0][2] - expected).abs() < 1e-15);
+        assert!(sample_covariance(&[vec![0.01]]).is_none());
+    }
+
+    #[test]
+    fn ledoit_wolf_shrinks_towards_average_variance() {
+        let columns = columns();
+        let shrunk = ledoit_wolf_covariance(&columns).unwrap();
+        let mle: Vec<Vec<f64>> = sample_covariance(&columns)
+            .unwrap()
+            .iter()
+            .map(|row| row.iter().map(|v| v * 5.0 / 6.0).collect())
+            .collect();
+        let mu = (0..3).map(|i| mle[i][i]).sum::<f64>() / 3.0;
+
+        // Every entry lies on the segment from the MLE to the target
+        let intensity = 1.0 - shrunk[0][1] / mle[0][1];
+        assert!(intensity > 0.0 && intensity <= 1.0);
+        for i in 0..3 {
+            for j in 0..3 {
+                let target = if i == j { mu } else { 0.0 };
+                let expected = intensity * target + (1.0 - intensity) * mle[i][j];
+                assert!((shrunk[i][j] - expected).abs() < 1e-15);
+            }
+        }
+    }
+
+    #[test]
+    fn correlation_normalises_covariance() {
+        let corr = correlation(&sample_covariance(&columns()).unwrap());
+        assert!((0..3).all(|i| (corr[i][i] - 1.0).abs() < 1e-12));
+        assert!(corr[0][1] > 0.9 && corr[0][2] < 0.0);
+
+        let flat = correlation(&[vec![0.0, 0.0], vec![0.0, 1.0]]);
+        assert!(flat[0][1].is_nan() && flat[0][0].is_nan());
+        assert_eq!(flat[1][1], 1.0);
+    }
+
+    #[test]
+    fn outputs_cover_upper_triangle() {
+        let assets = [
+            AssetKey::new_equity("SPY").unwrap(),
+            AssetKey::new_equity("XLK").unwrap(),
+            AssetKey::new_equity("TLT").unwrap(),
+        ];
+        assert_eq!(
+            output_names(&assets),
+            [
+                "spy_xlk",
+                "spy_tlt",
+                "xlk_tlt",
+                "spy_spy",
+                "xlk_xlk",
+                "tlt_tlt",
+                "observations"
+            ]
+        );
+        assert_eq!(
+            MatrixEstimator::parse("Ledoit_Wolf"),
+            Some(MatrixEstimator::LedoitWolf)
+        );
+        assert!(parse_lambda(Some("1.0")).is_err());
+    }
+}
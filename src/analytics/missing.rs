@@ -0,0 +1,322 @@
+//! Missing-data policies of analytic nodes.
+//!
+//! A missing observation is a NaN point in a node's input: a price stored as
+//! NaN, a return of an invalid price, or a warm-up value of a windowed parent.
+//! The `missing_data` node parameter chooses how such points are treated before
+//! the node's executor runs, and `min_observations` how many valid inputs its
+//! window needs before it reports a value. Both are applied by
+//! `AnalyticsDag::execute_push_node`, so every executor honors them in pull and
+//! push mode alike. Nodes without either parameter see their inputs unchanged.
+
+use crate::analytics::registry::ParentOutput;
+use crate::dag::{DagError, Node, NodeParams};
+use crate::time_series::TimeSeriesPoint;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Node parameter selecting the [`MissingDataPolicy`].
+pub const MISSING_DATA_PARAMETER: &str = "missing_data";
+
+/// Node parameter holding the minimum number of valid observations.
+pub const MIN_OBSERVATIONS_PARAMETER: &str = "min_observations";
+
+/// How a node treats missing observations in its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissingDataPolicy {
+    /// Missing observations are dropped: the node reports nothing for a
+    /// timestamp whose input is missing, and windows hold valid values only
+    Skip,
+    /// A missing observation takes the last valid value before it
+    ForwardFill,
+    /// Missing observations are interpolated linearly in time between the
+    /// valid values around them; a trailing run is forward-filled until the
+    /// next valid value arrives
+    Interpolate,
+    /// The node reports NaN while its window holds a missing observation
+    Nan,
+}
+
+impl MissingDataPolicy {
+    /// Parses a policy name ("skip", "ffill", "interpolate", "nan").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "skip" => Some(MissingDataPolicy::Skip),
+            "ffill" | "forward_fill" => Some(MissingDataPolicy::ForwardFill),
+            "interpolate" => Some(MissingDataPolicy::Interpolate),
+            "nan" => Some(MissingDataPolicy::Nan),
+            _ => None,
+        }
+    }
+
+    /// Fills or drops the missing observations of a series.
+    fn apply(self, points: &[TimeSeriesPoint]) -> Vec<TimeSeriesPoint> {
+        match self {
+            MissingDataPolicy::Skip => points
+                .iter()
+                .filter(|point| !point.close_price.is_nan())
+                .cloned()
+                .collect(),
+            MissingDataPolicy::ForwardFill => forward_fill(points),
+            MissingDataPolicy::Interpolate => interpolate(points),
+            MissingDataPolicy::Nan => points.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for MissingDataPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            MissingDataPolicy::Skip => "skip",
+            MissingDataPolicy::ForwardFill => "ffill",
+            MissingDataPolicy::Interpolate => "interpolate",
+            MissingDataPolicy::Nan => "nan",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+/// Missing-data handling of one node, read from its parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MissingData {
+    /// Policy applied to the inputs (`None` leaves them unchanged)
+    pub policy: Option<MissingDataPolicy>,
+    /// Valid observations each input needs within the node's window
+    pub min_observations: Option<usize>,
+}
+
+impl MissingData {
+    /// Reads `missing_data` and `min_observations` from node parameters.
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, String> {
+        let policy = params
+            .get(MISSING_DATA_PARAMETER)
+            .map(|value| {
+                MissingDataPolicy::parse(value).ok_or_else(|| {
+                    format!(
+                        "Unknown missing data policy '{}': expected skip, ffill, interpolate or nan",
+                        value
+                    )
+                })
+            })
+            .transpose()?;
+        let min_observations = params
+            .get(MIN_OBSERVATIONS_PARAMETER)
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|count| *count > 0)
+                    .ok_or_else(|| format!("Invalid min_observations '{}'", value))
+            })
+            .transpose()?;
+        Ok(MissingData {
+            policy,
+            min_observations,
+        })
+    }
+
+    /// Reads the missing-data handling of a node.
+    pub fn for_node(node: &Node) -> Result<Self, DagError> {
+        match &node.params {
+            NodeParams::Map(params) => Self::from_params(params)
+                .map_err(|e| DagError::InvalidOperation(format!("{}: {}", node.node_type, e))),
+            _ => Ok(MissingData::default()),
+        }
+    }
+
+    /// Whether the node's inputs pass through unchanged.
+    pub fn is_default(&self) -> bool {
+        self.policy.is_none() && self.min_observations.is_none()
+    }
+
+    /// Whether the node reports nothing for the current timestamp, which under
+    /// `Skip` is when the latest point of an input is missing.
+    pub fn skips(&self, parent_outputs: &[ParentOutput]) -> bool {
+        self.policy == Some(MissingDataPolicy::Skip)
+            && parent_outputs.iter().any(|parent| {
+                parent
+                    .output
+                    .last()
+                    .is_some_and(|point| point.close_price.is_nan())
+            })
+    }
+
+    /// Whether the node must report NaN: an input has fewer than
+    /// `min_observations` valid points, or (under `Nan`) any missing point,
+    /// among its last `window` points.
+    pub fn reports_nan(&self, parent_outputs: &[ParentOutput], window: usize) -> bool {
+        parent_outputs.iter().any(|parent| {
+            let start = parent.output.len().saturating_sub(window);
+            let recent = &parent.output[start..];
+            let valid = recent
+                .iter()
+                .filter(|point| !point.close_price.is_nan())
+                .count();
+            let too_few = self
+                .min_observations
+                .is_some_and(|min_observations| valid < min_observations);
+            let has_missing = self.policy == Some(MissingDataPolicy::Nan) && valid < recent.len();
+            too_few || has_missing
+        })
+    }
+
+    /// The inputs with the policy applied to every parent series.
+    pub fn apply<'a>(&self, parent_outputs: &[ParentOutput<'a>]) -> Vec<ParentOutput<'a>> {
+        parent_outputs
+            .iter()
+            .map(|parent| {
+                let mut parent = parent.clone();
+                if let Some(policy) = self.policy {
+                    parent.output = policy.apply(&parent.output).into();
+                }
+                parent
+            })
+            .collect()
+    }
+}
+
+fn forward_fill(points: &[TimeSeriesPoint]) -> Vec<TimeSeriesPoint> {
+    let mut last_valid = f64::NAN;
+    points
+        .iter()
+        .map(|point| {
+            if point.close_price.is_nan() {
+                TimeSeriesPoint::new(point.timestamp, last_valid)
+            } else {
+                last_valid = point.close_price;
+                point.clone()
+            }
+        })
+        .collect()
+}
+
+fn interpolate(points: &[TimeSeriesPoint]) -> Vec<TimeSeriesPoint> {
+    let mut filled = forward_fill(points);
+    let mut previous: Option<usize> = None;
+    for (i, point) in points.iter().enumerate() {
+        if point.close_price.is_nan() {
+            continue;
+        }
+        if let Some(p) = previous.filter(|p| i > p + 1) {
+            let (start, end) = (&points[p], point);
+            let span = (end.timestamp - start.timestamp).num_seconds() as f64;
+            for gap in &mut filled[p + 1..i] {
+                let elapsed = (gap.timestamp - start.timestamp).num_seconds() as f64;
+                gap.close_price =
+                    start.close_price + (end.close_price - start.close_price) * elapsed / span;
+            }
+        }
+        previous = Some(i);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::{AnalyticType, NodeId};
+    use chrono::{TimeZone, Utc};
+
+    fn series(values: &[f64]) -> Vec<TimeSeriesPoint> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let day = Utc
+                    .with_ymd_and_hms(2024, 1, 1 + i as u32, 16, 0, 0)
+                    .unwrap();
+                TimeSeriesPoint::new(day, value)
+            })
+            .collect()
+    }
+
+    fn values(points: &[TimeSeriesPoint]) -> Vec<f64> {
+        points.iter().map(|point| point.close_price).collect()
+    }
+
+    fn parent(values: &[f64]) -> ParentOutput<'static> {
+        ParentOutput {
+            node_id: NodeId(0),
+            analytic: AnalyticType::Returns,
+            assets: vec![],
+            output_name: None,
+            key: None,
+            output: series(values).into(),
+        }
+    }
+
+    fn missing_data(policy: &str, min_observations: Option<&str>) -> MissingData {
+        let mut params = HashMap::from([(MISSING_DATA_PARAMETER.to_string(), policy.to_string())]);
+        if let Some(min_observations) = min_observations {
+            params.insert(
+                MIN_OBSERVATIONS_PARAMETER.to_string(),
+                min_observations.to_string(),
+            );
+        }
+        MissingData::from_params(&params).unwrap()
+    }
+
+    #[test]
+    fn policies_fill_or_drop_missing_points() {
+        let nan = f64::NAN;
+        let points = series(&[nan, 1.0, nan, nan, 4.0, nan]);
+
+        let skipped = MissingDataPolicy::Skip.apply(&points);
+        assert_eq!(values(&skipped), vec![1.0, 4.0]);
+        assert_eq!(skipped[1].timestamp, points[4].timestamp);
+
+        let filled = values(&MissingDataPolicy::ForwardFill.apply(&points));
+        assert!(filled[0].is_nan());
+        assert_eq!(filled[1..], [1.0, 1.0, 1.0, 4.0, 4.0]);
+
+        let interpolated = values(&MissingDataPolicy::Interpolate.apply(&points));
+        assert!(interpolated[0].is_nan());
+        assert_eq!(interpolated[1..], [1.0, 2.0, 3.0, 4.0, 4.0]);
+
+        let kept = values(&MissingDataPolicy::Nan.apply(&points));
+        assert_eq!(kept.iter().filter(|v| v.is_nan()).count(), 4);
+    }
+
+    #[test]
+    fn nan_reports_and_skips_follow_the_window() {
+        let nan = f64::NAN;
+        let inputs = [parent(&[1.0, nan, 2.0, 3.0])];
+
+        let policy = missing_data("nan", None);
+        assert!(policy.reports_nan(&inputs, 3));
+        assert!(!policy.reports_nan(&inputs, 2));
+        assert!(!policy.skips(&inputs));
+
+        let threshold = missing_data("ffill", Some("3"));
+        assert!(!threshold.reports_nan(&inputs, 4));
+        assert!(threshold.reports_nan(&inputs, 3));
+
+        let skip = missing_data("skip", None);
+        assert!(skip.skips(&[parent(&[1.0, nan])]));
+        assert!(!skip.skips(&inputs));
+    }
+
+    #[test]
+    fn parameters_are_validated() {
+        assert_eq!(
+            MissingData::from_params(&HashMap::new()).unwrap(),
+            MissingData::default()
+        );
+        assert!(MissingData::default().is_default());
+        assert_eq!(
+            missing_data("FFILL", Some("5")),
+            MissingData {
+                policy: Some(MissingDataPolicy::ForwardFill),
+                min_observations: Some(5),
+            }
+        );
+
+        let invalid = |name: &str, value: &str| {
+            let params = HashMap::from([(name.to_string(), value.to_string())]);
+            MissingData::from_params(&params).is_err()
+        };
+        assert!(invalid(MISSING_DATA_PARAMETER, "drop"));
+        assert!(invalid(MIN_OBSERVATIONS_PARAMETER, "0"));
+        assert!(invalid(MIN_OBSERVATIONS_PARAMETER, "three"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Principal component analysis of a universe of return series.
+//!
+//! A PCA node decomposes the sample covariance matrix of its assets' returns
+//! over a rolling window and reports, for each of the first `components`
+//! principal components, the factor return of the latest date, the eigenvalue
+//! (variance the component explains), its share of total variance and the
+//! loading on every asset.
+
+use crate::analytics::matrix::sample_covariance;
+use crate::asset_key::AssetKey;
+
+/// Number of components reported when a node does not choose.
+pub const DEFAULT_COMPONENTS: usize = 3;
+
+/// Maximum number of Jacobi sweeps before giving up on convergence.
+const MAX_SWEEPS: usize = 100;
+
+/// Eigen-decomposition of one window's covariance matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decomposition {
+    /// Variance explained by each component, largest first
+    pub eigenvalues: Vec<f64>,
+    /// Unit-length loadings of each component, one entry per asset; the sign
+    /// is chosen so that the loadings sum to a non-negative value
+    pub loadings: Vec<Vec<f64>>,
+}
+
+impl Decomposition {
+    /// Share of the total variance explained by component `index`; NaN when
+    /// the returns do not vary.
+    pub fn explained_ratio(&self, index: usize) -> f64 {
+        let total: f64 = self.eigenvalues.iter().sum();
+        if total > 0.0 {
+            self.eigenvalues[index] / total
+        } else {
+            f64::NAN
+        }
+    }
+
+    /// Return of the portfolio weighted by the loadings of component `index`.
+    pub fn factor_return(&self, index: usize, returns: &[f64]) -> f64 {
+        self.loadings[index]
+            .iter()
+            .zip(returns.iter())
+            .map(|(loading, r)| loading * r)
+            .sum()
+    }
+}
+
+/// Decomposes the covariance of `columns` (one return series per asset).
+///
+/// Returns `None` when the covariance is undefined or does not converge.
+pub fn principal_components(columns: &[Vec<f64>]) -> Option<Decomposition> {
+    let covariance = sample_covariance(columns)?;
+    let (values, vectors) = symmetric_eigen(covariance)?;
+
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+
+    let eigenvalues = order.iter().map(|&i| values[i].max(0.0)).collect();
+    let loadings = order
+        .iter()
+        .map(|&i| {
+            let mut loading: Vec<f64> = vectors.iter().map(|row| row[i]).collect();
+            if loading.iter().sum::<f64>() < 0.0 {
+                loading.iter_mut().for_each(|value| *value = -*value);
+            }
+            loading
+        })
+        .collect();
+
+    Some(Decomposition {
+        eigenvalues,
+        loadings,
+    })
+}
+
+/// Eigenvalues and eigenvectors (as the columns of the second matrix) of a
+/// symmetric matrix, by cyclic Jacobi rotations.
+fn symmetric_eigen(mut a: Vec<Vec<f64>>) -> Option<(Vec<f64>, Vec<Vec<f64>>)> {
+    let n = a.len();
+    if a.iter().flatten().any(|value| !value.is_finite()) {
+        return None;
+    }
+    let mut v: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    let scale: f64 = a.iter().flatten().map(|value| value * value).sum();
+
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off_diagonal <= scale * 1e-30 {
+            let values = (0..n).map(|i| a[i][i]).collect();
+            return Some((values, v));
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p][q];
+                if apq == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut().chain(v.iter_mut()) {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = c * x - s * y;
+                    row[q] = s * x + c * y;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (x, y) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (xp, yq) = (*x, *y);
+                    *x = c * xp - s * yq;
+                    *y = s * xp + c * yq;
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Parses the `components` parameter: how many leading components a node over
+/// `asset_count` assets reports (up to three by default).
+pub fn parse_components(value: Option<&str>, asset_count: usize) -> Result<usize, String> {
+    let components = match value {
+        Some(value) => value
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|k| *k > 0)
+            .ok_or_else(|| format!("Invalid number of components: {}", value))?,
+        None => DEFAULT_COMPONENTS.min(asset_count),
+    };
+    if components > asset_count {
+        return Err(format!(
+            "Cannot report {} components for {} assets",
+            components, asset_count
+        ));
+    }
+    Ok(components)
+}
+
+/// Output holding the latest return of component `number` (1-based).
+pub fn factor_output(number: usize) -> String {
+    format!("factor_{}", number)
+}
+
+/// Output holding the eigenvalue of component `number`.
+pub fn eigenvalue_output(number: usize) -> String {
+    format!("eigenvalue_{}", number)
+}
+
+/// Output holding the share of variance component `number` explains.
+pub fn explained_ratio_output(number: usize) -> String {
+    format!("explained_ratio_{}", number)
+}
+
+/// Output holding the loading of component `number` on `asset`, e.g.
+/// `loading_1_spy`.
+pub fn loading_output(number: usize, asset: &AssetKey) -> String {
+    format!("loading_{}_{}", number, asset.to_string().to_lowercase())
+}
+
+/// Every output of a PCA over `assets` reporting `components` components,
+/// first factor return first.
+pub fn output_names(assets: &[AssetKey], components: usize) -> Vec<String> {
+    let numbers = 1..=components;
+    let mut names: Vec<String> = numbers.clone().map(factor_output).collect();
+    names.extend(numbers.clone().map(eigenvalue_output));
+    names.extend(numbers.clone().map(explained_ratio_output));
+    for number in numbers {
+        names.extend(assets.iter().map(|asset| loading_output(number, asset)));
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decomposition_reconstructs_covariance() {
+        let columns = vec![
+            vec![0.01, -0.02, 0.015, 0.003, -0.007, 0.02],
+            vec![0.012, -0.015, 0.01, 0.001, -0.01, 0.018],
+            vec![-0.004, 0.006, 0.002, -0.003, 0.005, -0.001],
+        ];
+        let covariance = sample_covariance(&columns).unwrap();
+        let pca = principal_components(&columns).unwrap();
+
+        assert!(pca.eigenvalues.windows(2).all(|pair| pair[0] >= pair[1]));
+        let trace: f64 = (0..3).map(|i| covariance[i][i]).sum();
+        assert!((pca.eigenvalues.iter().sum::<f64>() - trace).abs() < 1e-15);
+        for (i, row) in covariance.iter().enumerate() {
+            for (j, entry) in row.iter().enumerate() {
+                let rebuilt: f64 = pca
+                    .eigenvalues
+                    .iter()
+                    .zip(&pca.loadings)
+                    .map(|(value, loading)| value * loading[i] * loading[j])
+                    .sum();
+                assert!((rebuilt - entry).abs() < 1e-15);
+            }
+        }
+        for loading in &pca.loadings {
+            assert!((loading.iter().map(|l| l * l).sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+        assert!(pca.loadings[0].iter().sum::<f64>() >= 0.0);
+    }
+
+    #[test]
+    fn perfectly_correlated_assets_load_on_one_component() {
+        let x = vec![0.01, -0.02, 0.015, 0.003, -0.007];
+        let y: Vec<f64> = x.iter().map(|v| v * 2.0).collect();
+        let pca = principal_components(&[x.clone(), y]).unwrap();
+
+        assert!((pca.explained_ratio(0) - 1.0).abs() < 1e-12);
+        assert!(pca.eigenvalues[1].abs() < 1e-15);
+        let norm = 5.0_f64.sqrt();
+        assert!((pca.loadings[0][0] - 1.0 / norm).abs() < 1e-12);
+        assert!((pca.loadings[0][1] - 2.0 / norm).abs() < 1e-12);
+        assert!((pca.factor_return(0, &[0.01, 0.02]) - 0.05 / norm).abs() < 1e-12);
+        assert!(principal_components(&[vec![0.01]]).is_none());
+    }
+
+    #[test]
+    fn components_parse_into_named_outputs() {
+        let assets = [
+            AssetKey::new_equity("SPY").unwrap(),
+            AssetKey::new_equity("TLT").unwrap(),
+        ];
+        assert_eq!(parse_components(None, 2), Ok(2));
+        assert!(parse_components(Some("3"), 2).is_err());
+        assert!(parse_components(Some("0"), 2).is_err());
+        assert_eq!(
+            output_names(&assets, 1),
+            [
+                "factor_1",
+                "eigenvalue_1",
+                "explained_ratio_1",
+                "loading_1_spy",
+                "loading_1_tlt"
+            ]
+        );
+    }
+}
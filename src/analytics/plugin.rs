@@ -0,0 +1,192 @@
+//! Parameter schemas for analytics registered at runtime.
+//!
+//! A user-defined analytic is an [`AnalyticDefinition`] whose `analytic_type`
+//! is `AnalyticType::Custom`. It is added to a registry with
+//! [`AnalyticRegistry::register`] together with an [`AnalyticSchema`]. The
+//! schema describes the analytic on `GET /dag/nodes` and checks node keys
+//! before they are resolved: missing defaults are filled in, required
+//! parameters must be present and values must parse as their declared kind.
+//!
+//! [`AnalyticDefinition`]: crate::analytics::registry::AnalyticDefinition
+//! [`AnalyticRegistry::register`]: crate::analytics::AnalyticRegistry::register
+
+use std::collections::HashMap;
+
+/// Name of the parameter that makes a user-defined analytic windowed.
+pub const WINDOW_PARAMETER: &str = "window_size";
+
+/// Kind of value a parameter accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    /// Non-negative whole number, such as a window or period
+    Integer,
+    /// Finite real number
+    Number,
+    /// Free text, such as an asset or a mode
+    String,
+}
+
+impl ParameterKind {
+    /// Name used for the kind in API listings.
+    pub fn name(self) -> &'static str {
+        match self {
+            ParameterKind::Integer => "integer",
+            ParameterKind::Number => "number",
+            ParameterKind::String => "string",
+        }
+    }
+
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            ParameterKind::Integer => value.trim().parse::<usize>().is_ok(),
+            ParameterKind::Number => value
+                .trim()
+                .parse::<f64>()
+                .map(f64::is_finite)
+                .unwrap_or(false),
+            ParameterKind::String => true,
+        }
+    }
+}
+
+/// One parameter of a user-defined analytic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterSchema {
+    pub name: String,
+    pub kind: ParameterKind,
+    pub required: bool,
+    /// Value used when a node key does not set the parameter
+    pub default: Option<String>,
+    pub description: String,
+}
+
+impl ParameterSchema {
+    /// Parameter every node key must set.
+    pub fn required(name: &str, kind: ParameterKind, description: &str) -> Self {
+        ParameterSchema {
+            name: name.to_string(),
+            kind,
+            required: true,
+            default: None,
+            description: description.to_string(),
+        }
+    }
+
+    /// Parameter that takes `default` when a node key does not set it.
+    pub fn optional(name: &str, kind: ParameterKind, default: &str, description: &str) -> Self {
+        ParameterSchema {
+            name: name.to_string(),
+            kind,
+            required: false,
+            default: Some(default.to_string()),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// Description and parameters of a user-defined analytic.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnalyticSchema {
+    pub description: String,
+    pub parameters: Vec<ParameterSchema>,
+    /// Burn-in shown on `GET /dag/nodes`, e.g. `window + 1`
+    pub burnin_days: String,
+}
+
+impl AnalyticSchema {
+    pub fn new(description: &str) -> Self {
+        AnalyticSchema {
+            description: description.to_string(),
+            parameters: Vec::new(),
+            burnin_days: "0".to_string(),
+        }
+    }
+
+    /// Adds a parameter.
+    pub fn parameter(mut self, parameter: ParameterSchema) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    /// Sets the burn-in description.
+    pub fn burnin(mut self, burnin_days: &str) -> Self {
+        self.burnin_days = burnin_days.to_string();
+        self
+    }
+
+    /// Whether the analytic declares a `window_size` parameter, which gives
+    /// its nodes a fixed window.
+    pub fn windowed(&self) -> bool {
+        self.parameters
+            .iter()
+            .any(|parameter| parameter.name == WINDOW_PARAMETER)
+    }
+
+    /// Checks `params` against the schema, filling in defaults.
+    ///
+    /// Parameters the schema does not declare are left alone, so that generic
+    /// parameters such as `override` pass through.
+    pub fn apply(&self, params: &mut HashMap<String, String>) -> Result<(), String> {
+        for parameter in &self.parameters {
+            match params.get(&parameter.name) {
+                Some(value) if !parameter.kind.accepts(value) => {
+                    return Err(format!(
+                        "Invalid {} parameter '{}': {}",
+                        parameter.kind.name(),
+                        parameter.name,
+                        value
+                    ));
+                }
+                Some(_) => {}
+                None => match &parameter.default {
+                    Some(default) => {
+                        params.insert(parameter.name.clone(), default.clone());
+                    }
+                    None if parameter.required => {
+                        return Err(format!("Missing required parameter '{}'", parameter.name));
+                    }
+                    None => {}
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_fills_defaults_and_checks_kinds() {
+        let schema = AnalyticSchema::new("Scaled prices")
+            .parameter(ParameterSchema::required(
+                "factor",
+                ParameterKind::Number,
+                "Multiplier",
+            ))
+            .parameter(ParameterSchema::optional(
+                WINDOW_PARAMETER,
+                ParameterKind::Integer,
+                "5",
+                "Window",
+            ));
+        assert!(schema.windowed());
+
+        let mut params = HashMap::from([("factor".to_string(), "2.5".to_string())]);
+        schema.apply(&mut params).unwrap();
+        assert_eq!(params[WINDOW_PARAMETER], "5");
+
+        let mut missing = HashMap::new();
+        assert!(schema.apply(&mut missing).unwrap_err().contains("factor"));
+
+        let mut invalid = HashMap::from([
+            ("factor".to_string(), "2.5".to_string()),
+            (WINDOW_PARAMETER.to_string(), "-1".to_string()),
+        ]);
+        assert_eq!(
+            schema.apply(&mut invalid),
+            Err("Invalid integer parameter 'window_size': -1".to_string())
+        );
+    }
+}
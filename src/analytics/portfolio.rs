@@ -0,0 +1,291 @@
+//! Portfolio construction primitives.
+//!
+//! A portfolio is a set of constituent price series, target weights and a
+//! rebalance frequency. NAV is normalized to 1.0 at inception (the first
+//! timestamp at which every constituent has a price); weights that do not sum to
+//! one leave the remainder in cash earning nothing.
+
+use crate::time_series::TimeSeriesPoint;
+use chrono::{DateTime, Datelike, Utc};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// How often holdings are reset to the target weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RebalanceFrequency {
+    /// Buy and hold from inception
+    None,
+    /// Reset to target weights at every timestamp
+    Daily,
+    /// Reset to target weights at the first timestamp of each calendar month
+    Monthly,
+}
+
+impl RebalanceFrequency {
+    /// Parses a frequency name ("none", "daily", "monthly").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" | "" => Some(RebalanceFrequency::None),
+            "daily" => Some(RebalanceFrequency::Daily),
+            "monthly" => Some(RebalanceFrequency::Monthly),
+            _ => None,
+        }
+    }
+
+    fn is_due(self, previous: DateTime<Utc>, current: DateTime<Utc>) -> bool {
+        match self {
+            RebalanceFrequency::None => false,
+            RebalanceFrequency::Daily => true,
+            RebalanceFrequency::Monthly => {
+                (previous.year(), previous.month()) != (current.year(), current.month())
+            }
+        }
+    }
+}
+
+impl fmt::Display for RebalanceFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            RebalanceFrequency::None => "none",
+            RebalanceFrequency::Daily => "daily",
+            RebalanceFrequency::Monthly => "monthly",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+/// Parses a comma-separated weight list, defaulting to equal weights.
+pub fn parse_weights(value: Option<&str>, asset_count: usize) -> Result<Vec<f64>, String> {
+    let weights = match value {
+        None => return Ok(vec![1.0 / asset_count.max(1) as f64; asset_count]),
+        Some(list) => list
+            .split(',')
+            .map(|w| {
+                w.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid portfolio weight '{}'", w.trim()))
+            })
+            .collect::<Result<Vec<f64>, String>>()?,
+    };
+
+    if weights.len() != asset_count {
+        return Err(format!(
+            "Portfolio has {} assets but {} weights",
+            asset_count,
+            weights.len()
+        ));
+    }
+    if weights.iter().any(|w| !w.is_finite()) {
+        return Err("Portfolio weights must be finite".to_string());
+    }
+    Ok(weights)
+}
+
+/// Computes the portfolio NAV on the merged calendar of its constituents.
+///
+/// Each constituent uses its latest price at or before every timestamp. NaN
+/// prices are ignored. The NAV is NaN until every constituent has been priced.
+pub fn nav_series(
+    constituents: &[&[TimeSeriesPoint]],
+    weights: &[f64],
+    rebalance: RebalanceFrequency,
+) -> Vec<TimeSeriesPoint> {
+    NavTracker::new(weights.to_vec(), rebalance).extend(constituents)
+}
+
+/// Running NAV of a portfolio: the latest constituent prices, the holdings
+/// since the last rebalance and the last timestamp, so that the NAV can be
+/// extended by new prices without replaying its history.
+#[derive(Debug, Clone)]
+pub struct NavTracker {
+    weights: Vec<f64>,
+    rebalance: RebalanceFrequency,
+    prices: Vec<Option<f64>>,
+    /// Units of each constituent and cash; `None` before inception
+    holdings: Option<(Vec<f64>, f64)>,
+    /// Last timestamp, against which the next rebalance falls due
+    previous: Option<DateTime<Utc>>,
+}
+
+impl NavTracker {
+    pub fn new(weights: Vec<f64>, rebalance: RebalanceFrequency) -> Self {
+        NavTracker {
+            prices: vec![None; weights.len()],
+            weights,
+            rebalance,
+            holdings: None,
+            previous: None,
+        }
+    }
+
+    /// Advances through the merged calendar of `constituents`, given in weight
+    /// order and later than every timestamp seen so far, returning the NAV at
+    /// each timestamp as [`nav_series`] does.
+    pub fn extend(&mut self, constituents: &[&[TimeSeriesPoint]]) -> Vec<TimeSeriesPoint> {
+        let calendar: BTreeSet<DateTime<Utc>> = constituents
+            .iter()
+            .flat_map(|series| series.iter().map(|point| point.timestamp))
+            .collect();
+
+        let mut cursors = vec![0usize; constituents.len()];
+        let mut result = Vec::with_capacity(calendar.len());
+
+        for timestamp in calendar {
+            for (index, series) in constituents.iter().enumerate() {
+                while cursors[index] < series.len() && series[cursors[index]].timestamp <= timestamp
+                {
+                    let price = series[cursors[index]].close_price;
+                    if price.is_finite() {
+                        self.prices[index] = Some(price);
+                    }
+                    cursors[index] += 1;
+                }
+            }
+
+            let current: Option<Vec<f64>> = self.prices.iter().copied().collect();
+            let nav = match (current, self.holdings.as_ref()) {
+                (None, _) => f64::NAN,
+                (Some(current), None) => {
+                    self.holdings = Some(target_holdings(1.0, &current, &self.weights));
+                    1.0
+                }
+                (Some(current), Some((units, cash))) => {
+                    let nav = cash
+                        + units
+                            .iter()
+                            .zip(current.iter())
+                            .map(|(unit, price)| unit * price)
+                            .sum::<f64>();
+                    let due = self
+                        .previous
+                        .map(|prev| self.rebalance.is_due(prev, timestamp))
+                        .unwrap_or(false);
+                    if due {
+                        self.holdings = Some(target_holdings(nav, &current, &self.weights));
+                    }
+                    nav
+                }
+            };
+
+            result.push(TimeSeriesPoint::new(timestamp, nav));
+            self.previous = Some(timestamp);
+        }
+
+        result
+    }
+}
+
+fn target_holdings(nav: f64, prices: &[f64], weights: &[f64]) -> (Vec<f64>, f64) {
+    let units = weights
+        .iter()
+        .zip(prices.iter())
+        .map(|(weight, price)| {
+            if *price == 0.0 {
+                0.0
+            } else {
+                weight * nav / price
+            }
+        })
+        .collect();
+    let cash = nav * (1.0 - weights.iter().sum::<f64>());
+    (units, cash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn series(start: DateTime<Utc>, prices: &[f64]) -> Vec<TimeSeriesPoint> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| TimeSeriesPoint::new(start + Duration::days(i as i64), p))
+            .collect()
+    }
+
+    #[test]
+    fn buy_and_hold_tracks_weighted_price_moves() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap();
+        let a = series(start, &[100.0, 110.0, 121.0]);
+        let b = series(start, &[50.0, 50.0, 25.0]);
+
+        let nav = nav_series(&[&a, &b], &[0.5, 0.5], RebalanceFrequency::None);
+        let values: Vec<f64> = nav.iter().map(|p| p.close_price).collect();
+        assert_eq!(values.len(), 3);
+        assert!((values[0] - 1.0).abs() < 1e-12);
+        assert!((values[1] - 1.05).abs() < 1e-12);
+        assert!((values[2] - (0.5 * 1.21 + 0.5 * 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn daily_rebalance_compounds_weighted_returns() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap();
+        let a = series(start, &[100.0, 110.0, 121.0]);
+        let b = series(start, &[50.0, 50.0, 25.0]);
+
+        let nav = nav_series(&[&a, &b], &[0.5, 0.5], RebalanceFrequency::Daily);
+        // Day 2: 1.05, then each leg is reset to 0.525: 0.525 * 1.1 + 0.525 * 0.5
+        assert!((nav[2].close_price - 1.05 * (0.5 * 1.1 + 0.5 * 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn monthly_rebalance_only_at_month_turn() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 30, 16, 0, 0).unwrap();
+        let a = series(start, &[100.0, 200.0, 200.0, 100.0]);
+        let b = series(start, &[100.0, 100.0, 100.0, 100.0]);
+
+        let nav = nav_series(&[&a, &b], &[0.5, 0.5], RebalanceFrequency::Monthly);
+        // Jan 31 = 1.5 (no rebalance); Feb 1 = 1.5 then rebalanced; Feb 2 halves leg A
+        assert!((nav[1].close_price - 1.5).abs() < 1e-12);
+        assert!((nav[3].close_price - (0.75 * 0.5 + 0.75)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn nav_waits_for_every_constituent_and_carries_prices() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap();
+        let a = series(start, &[100.0, 110.0, 120.0]);
+        let b = series(start + Duration::days(1), &[10.0]);
+
+        let nav = nav_series(&[&a, &b], &[0.5, 0.5], RebalanceFrequency::None);
+        assert!(nav[0].close_price.is_nan());
+        assert!((nav[1].close_price - 1.0).abs() < 1e-12);
+        // B carried forward at 10.0
+        assert!((nav[2].close_price - (0.5 * 120.0 / 110.0 + 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn extending_a_tracker_matches_the_full_series() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 30, 16, 0, 0).unwrap();
+        let a = series(start, &[100.0, 200.0, f64::NAN, 100.0, 120.0]);
+        let b = series(start + Duration::days(1), &[100.0, 90.0, 100.0, 110.0]);
+        let full = nav_series(&[&a, &b], &[0.6, 0.3], RebalanceFrequency::Monthly);
+
+        let mut tracker = NavTracker::new(vec![0.6, 0.3], RebalanceFrequency::Monthly);
+        let mut extended = tracker.extend(&[&a[..2], &b[..1]]);
+        for day in 2..a.len() {
+            extended.extend(tracker.extend(&[&a[day..=day], &b[day - 1..day]]));
+        }
+        assert_eq!(extended.len(), full.len());
+        for (x, y) in extended.iter().zip(full.iter()) {
+            assert_eq!(x.timestamp, y.timestamp);
+            assert!(
+                x.close_price == y.close_price
+                    || (x.close_price.is_nan() && y.close_price.is_nan())
+            );
+        }
+    }
+
+    #[test]
+    fn weights_parse_and_validate() {
+        assert_eq!(parse_weights(None, 4).unwrap(), vec![0.25; 4]);
+        assert_eq!(parse_weights(Some("0.6, 0.4"), 2).unwrap(), vec![0.6, 0.4]);
+        assert!(parse_weights(Some("0.6"), 2).is_err());
+        assert!(parse_weights(Some("x,1"), 2).is_err());
+        assert_eq!(
+            RebalanceFrequency::parse("Monthly"),
+            Some(RebalanceFrequency::Monthly)
+        );
+        assert_eq!(RebalanceFrequency::parse("weekly"), None);
+    }
+}
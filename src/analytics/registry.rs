@@ -1,16 +1,38 @@
+use crate::analytics::calculators::{
+    calmar_ratio, downside_deviation, drawdown, excess_kurtosis, information_ratio, max_drawdown,
+    pearson_correlation, percentile_rank, quantile, sharpe_ratio, skewness, sortino_ratio, z_score,
+    RollingMoments, RollingOrderStatistics,
+};
 use crate::analytics::containers::{
     LogReturnAnalytic, ReturnAnalytic, StdDevVolatilityAnalytic, VolatilityAnalytic,
 };
+use crate::analytics::expression::{Formula, FormulaState, Reference};
+use crate::analytics::garch::{self, GarchFilter};
+use crate::analytics::indicators::{
+    bollinger_band, sma, Atr, BandOutput, Ema, Macd, MacdOutput, Rsi,
+};
 use crate::analytics::lag::{FixedLag, LagAnalytic};
+use crate::analytics::matrix::{self, parse_lambda, MatrixEstimator, MatrixKind};
+use crate::analytics::pca::{self, parse_components};
+use crate::analytics::plugin::{AnalyticSchema, WINDOW_PARAMETER};
+use crate::analytics::portfolio::{nav_series, parse_weights, NavTracker, RebalanceFrequency};
+use crate::analytics::regression::{self, parse_factors};
+use crate::analytics::risk::{expected_shortfall, parse_confidence, value_at_risk, VarMethod};
+use crate::analytics::windows::{
+    FixedWindow, IncrementalWindow, RunningSum, StreamingWindow, WelfordVariance,
+    WindowAccumulator, WindowStatistic,
+};
 use crate::asset_key::AssetKey;
 use crate::dag::{
-    AnalyticType, DagError, Node, NodeId, NodeKey, NodeOutput, NodeParams, WindowSpec,
+    AnalyticType, CustomAnalytic, DagError, Node, NodeId, NodeKey, NodeOutput, NodeParams,
+    WindowSpec,
 };
 use crate::time_series::{DataProvider, DateRange, TimeSeriesPoint};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use std::any::Any;
-use std::collections::{HashMap, VecDeque};
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, OnceLock};
 use tracing::{debug, trace};
 
 fn parse_lag_from_map(params: &HashMap<String, String>) -> usize {
@@ -43,6 +65,48 @@ fn parse_window_from_params(params: &NodeParams) -> usize {
     }
 }
 
+/// The node's parameter map, or an empty map for a node without parameters.
+fn node_params(node: &Node) -> &HashMap<String, String> {
+    static EMPTY: OnceLock<HashMap<String, String>> = OnceLock::new();
+    match &node.params {
+        NodeParams::Map(map) => map,
+        NodeParams::None => EMPTY.get_or_init(HashMap::new),
+    }
+}
+
+/// Positive integer parameter such as an indicator period, or `default`.
+fn parse_period(params: &HashMap<String, String>, name: &str, default: usize) -> usize {
+    params
+        .get(name)
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+/// Periods per year used to annualize ratios (252 trading days by default).
+fn parse_periods_per_year(params: &HashMap<String, String>) -> f64 {
+    params
+        .get("periods_per_year")
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| *value > 0.0)
+        .unwrap_or(252.0)
+}
+
+/// Parses an asset named by a node parameter such as `benchmark` or `risk_free`.
+fn parse_asset_param(
+    params: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<AssetKey>, DagError> {
+    params
+        .get(name)
+        .map(|value| {
+            AssetKey::parse(value).map_err(|e| {
+                DagError::InvalidOperation(format!("Invalid {} asset '{}': {}", name, value, e))
+            })
+        })
+        .transpose()
+}
+
 fn params_with_range(analytic_type: &str, range: &DateRange) -> HashMap<String, String> {
     let mut params = HashMap::new();
     params.insert("analytic_type".to_string(), analytic_type.to_string());
@@ -80,13 +144,279 @@ fn build_lag_series(prices: &[TimeSeriesPoint], lag: usize) -> Vec<TimeSeriesPoi
     result
 }
 
+/// Currency conversion requested for one asset of a node.
+///
+/// Nodes carry `reporting_currency` plus `currency` and `fx_invert` lists that
+/// are comma-separated and aligned with the node's assets.
+struct Conversion {
+    currency: String,
+    reporting_currency: String,
+    invert: bool,
+}
+
+impl Conversion {
+    fn fx_pair(&self) -> Result<AssetKey, DagError> {
+        crate::fx::conversion_pair(&self.currency, &self.reporting_currency, self.invert)
+            .map_err(|e| DagError::InvalidOperation(format!("Invalid currency pair: {}", e)))?
+            .map(|(pair, _)| pair)
+            .ok_or_else(|| {
+                DagError::InvalidOperation("Conversion requires distinct currencies".to_string())
+            })
+    }
+
+    fn insert_into(&self, params: &mut HashMap<String, String>) {
+        params.insert("currency".to_string(), self.currency.clone());
+        params.insert(
+            "reporting_currency".to_string(),
+            self.reporting_currency.clone(),
+        );
+        params.insert("fx_invert".to_string(), self.invert.to_string());
+    }
+}
+
+fn parse_conversion(
+    params: &HashMap<String, String>,
+    index: usize,
+) -> Result<Option<Conversion>, DagError> {
+    let reporting_currency = match params.get("reporting_currency") {
+        Some(value) => value.trim().to_string(),
+        None => return Ok(None),
+    };
+    let currency = params
+        .get("currency")
+        .and_then(|list| list.split(',').nth(index))
+        .map(|value| value.trim().to_string())
+        .ok_or_else(|| {
+            DagError::InvalidOperation(format!(
+                "reporting_currency {} requires the native currency of asset {}",
+                reporting_currency, index
+            ))
+        })?;
+    if currency == reporting_currency {
+        return Ok(None);
+    }
+
+    let invert = params
+        .get("fx_invert")
+        .and_then(|list| list.split(',').nth(index))
+        .map(|value| value.trim() == "true")
+        .unwrap_or(false);
+
+    Ok(Some(Conversion {
+        currency,
+        reporting_currency,
+        invert,
+    }))
+}
+
+/// Copies the conversion of the asset at `index` onto a single-asset dependency.
+fn forward_conversion(
+    key: &NodeKey,
+    index: usize,
+    params: &mut HashMap<String, String>,
+) -> Result<(), DagError> {
+    if let Some(conversion) = parse_conversion(&key.params, index)? {
+        conversion.insert_into(params);
+    }
+    Ok(())
+}
+
+/// Key of the price series feeding the asset at `index`: raw provider prices, or
+/// prices converted into the reporting currency when the key asks for it.
+fn price_source_key(key: &NodeKey, index: usize, range: DateRange) -> Result<NodeKey, DagError> {
+    let asset = key.assets.get(index).cloned().ok_or_else(|| {
+        DagError::InvalidOperation(format!("{} node missing asset {}", key.analytic, index))
+    })?;
+
+    match parse_conversion(&key.params, index)? {
+        None => Ok(NodeKey {
+            analytic: AnalyticType::DataProvider,
+            assets: vec![asset],
+            params: params_with_range("data_provider", &range),
+            range: Some(range),
+            window: None,
+            override_tag: key.override_tag.clone(),
+        }),
+        Some(conversion) => {
+            let mut params = params_with_range("fx_conversion", &range);
+            conversion.insert_into(&mut params);
+            Ok(NodeKey {
+                analytic: AnalyticType::FxConversion,
+                assets: vec![asset, conversion.fx_pair()?],
+                range: Some(range),
+                window: None,
+                override_tag: key.override_tag.clone(),
+                params,
+            })
+        }
+    }
+}
+
+/// Parameters that identify a portfolio and must travel with its dependencies.
+const PORTFOLIO_PARAMS: [&str; 5] = [
+    "weights",
+    "rebalance",
+    "reporting_currency",
+    "currency",
+    "fx_invert",
+];
+
+/// A key describes a portfolio when it carries weights or spans several assets.
+fn is_portfolio(key: &NodeKey) -> bool {
+    key.params.contains_key("weights") || key.assets.len() > 1
+}
+
+/// Parses the weights and rebalance frequency of a portfolio node.
+fn parse_portfolio(
+    params: &HashMap<String, String>,
+    asset_count: usize,
+) -> Result<(Vec<f64>, RebalanceFrequency), DagError> {
+    let weights = parse_weights(params.get("weights").map(String::as_str), asset_count)
+        .map_err(DagError::InvalidOperation)?;
+    let rebalance = match params.get("rebalance") {
+        Some(value) => RebalanceFrequency::parse(value).ok_or_else(|| {
+            DagError::InvalidOperation(format!("Unknown rebalance frequency '{}'", value))
+        })?,
+        None => RebalanceFrequency::None,
+    };
+    Ok((weights, rebalance))
+}
+
+/// Range-tagged params for a portfolio dependency, carrying the portfolio definition.
+fn portfolio_params(
+    key: &NodeKey,
+    analytic_type: &str,
+    range: &DateRange,
+) -> HashMap<String, String> {
+    let mut params = params_with_range(analytic_type, range);
+    for name in PORTFOLIO_PARAMS {
+        if let Some(value) = key.params.get(name) {
+            params.insert(name.to_string(), value.clone());
+        }
+    }
+    params
+}
+
+/// Key of the one-period log returns feeding a returns-based analytic: the
+/// asset's returns, or the portfolio's returns when the key describes a portfolio.
+fn returns_source_key(key: &NodeKey, range: DateRange) -> Result<NodeKey, DagError> {
+    if is_portfolio(key) {
+        return Ok(NodeKey {
+            analytic: AnalyticType::PortfolioReturns,
+            assets: key.assets.clone(),
+            params: portfolio_params(key, "portfolio_returns", &range),
+            range: Some(range),
+            window: None,
+            override_tag: key.override_tag.clone(),
+        });
+    }
+
+    let asset = key.assets.first().cloned().ok_or_else(|| {
+        DagError::InvalidOperation(format!("{} node missing asset", key.analytic))
+    })?;
+    asset_returns_key(key, asset, 0, range)
+}
+
+/// Key of the one-period log returns of `asset`, converted with the entry at
+/// `index` of the key's currency lists.
+fn asset_returns_key(
+    key: &NodeKey,
+    asset: AssetKey,
+    index: usize,
+    range: DateRange,
+) -> Result<NodeKey, DagError> {
+    let mut params = params_with_range("returns", &range);
+    params.insert("lag".to_string(), "1".to_string());
+    forward_conversion(key, index, &mut params)?;
+    Ok(NodeKey {
+        analytic: AnalyticType::Returns,
+        assets: vec![asset],
+        range: Some(range),
+        window: None,
+        override_tag: key.override_tag.clone(),
+        params,
+    })
+}
+
+/// Key of a windowed analytic over the same asset or portfolio as `key`.
+fn same_subject_key(
+    key: &NodeKey,
+    analytic: AnalyticType,
+    range: DateRange,
+    window_size: usize,
+) -> Result<NodeKey, DagError> {
+    let name = analytic.to_string();
+    let mut params = if is_portfolio(key) {
+        portfolio_params(key, &name, &range)
+    } else {
+        let mut params = params_with_range(&name, &range);
+        forward_conversion(key, 0, &mut params)?;
+        params
+    };
+    params.insert("window_size".to_string(), window_size.to_string());
+    Ok(NodeKey {
+        analytic,
+        assets: key.assets.clone(),
+        range: Some(range),
+        window: Some(WindowSpec::fixed(window_size)),
+        override_tag: key.override_tag.clone(),
+        params,
+    })
+}
+
+/// Key of the price level a path-dependent analytic is measured on: the asset's
+/// (possibly converted) prices, or the portfolio NAV when the key describes a portfolio.
+fn level_source_key(key: &NodeKey, range: DateRange) -> Result<NodeKey, DagError> {
+    if is_portfolio(key) {
+        return Ok(NodeKey {
+            analytic: AnalyticType::PortfolioNav,
+            assets: key.assets.clone(),
+            params: portfolio_params(key, "portfolio_nav", &range),
+            range: Some(range),
+            window: None,
+            override_tag: key.override_tag.clone(),
+        });
+    }
+    price_source_key(key, 0, range)
+}
+
+/// Finds the parent history produced by `analytic` for a given asset.
+fn parent_series<'a>(
+    parent_outputs: &'a [ParentOutput],
+    analytic: AnalyticType,
+    asset: &AssetKey,
+) -> &'a [TimeSeriesPoint] {
+    parent_outputs
+        .iter()
+        .find(|parent| {
+            parent.analytic.satisfies(analytic) && parent.assets.first() == Some(asset)
+        })
+        .map(|parent| parent.output.as_ref())
+        .unwrap_or(&[])
+}
+
 /// Executor invoked for a node to perform pull or push calculations.
-pub struct ParentOutput {
+#[derive(Clone)]
+pub struct ParentOutput<'a> {
     pub node_id: NodeId,
     pub analytic: AnalyticType,
-    pub output: Vec<TimeSeriesPoint>,
+    /// Assets of the parent node, used by multi-asset nodes to tell inputs apart
+    pub assets: Vec<AssetKey>,
+    /// Named output of a multi-output parent this series holds (`None` for the primary)
+    pub output_name: Option<String>,
+    /// Key the parent was resolved from, for executors whose parents share a type
+    pub key: Option<NodeKey>,
+    /// History of the output, borrowed from the caller's history unless an
+    /// input policy rewrote it
+    pub output: Cow<'a, [TimeSeriesPoint]>,
 }
 
+/// Per-node state an executor carries between push updates (e.g. a running peak).
+///
+/// The caller owns the state: the push engine keeps one per node, and pull-mode
+/// simulation keeps one per node for the duration of a run.
+pub type ExecutorState = Box<dyn Any + Send + Sync>;
+
 pub trait AnalyticExecutor: Send + Sync {
     /// Execute in pull mode (batch computation of entire time series).
     /// 
@@ -113,6 +443,22 @@ pub trait AnalyticExecutor: Send + Sync {
         value: f64,
     ) -> Result<NodeOutput, DagError>;
 
+    /// Execute in push mode with state carried across updates of the same node.
+    ///
+    /// `state` starts as `None` and is kept by the caller between calls. A node may
+    /// fire more than once for one timestamp, so a repeated timestamp must replace
+    /// the earlier update rather than accumulate. The default ignores the state.
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        value: f64,
+        _state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        self.execute_push(node, parent_outputs, timestamp, value)
+    }
+
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -129,17 +475,30 @@ pub trait AnalyticDefinition: Send + Sync {
 
     /// Executor that performs pull/push work for this node.
     fn executor(&self) -> &dyn AnalyticExecutor;
+
+    /// Named outputs of a multi-output node, primary first; empty for single-output nodes.
+    ///
+    /// Multi-output executors emit `NodeOutput::Named` values in this order. The
+    /// key is given for analytics whose outputs depend on their parameters.
+    fn outputs(&self, _key: &NodeKey) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 /// Registry of analytic definitions wired into the DAG.
+///
+/// Besides the built-in analytics, a registry holds user-defined ones added with
+/// [`AnalyticRegistry::register`] before it is shared with a DAG or the server.
 pub struct AnalyticRegistry {
     definitions: HashMap<AnalyticType, Box<dyn AnalyticDefinition>>,
+    schemas: BTreeMap<CustomAnalytic, AnalyticSchema>,
 }
 
 impl std::fmt::Debug for AnalyticRegistry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AnalyticRegistry")
             .field("definitions", &"<omitted>")
+            .field("custom", &self.schemas.keys().collect::<Vec<_>>())
             .finish()
     }
 }
@@ -158,13 +517,217 @@ impl AnalyticRegistry {
             AnalyticType::Volatility,
             Box::new(VolatilityDefinition::new()),
         );
-        AnalyticRegistry { definitions }
+        definitions.insert(
+            AnalyticType::DownsideDeviation,
+            Box::new(DownsideDeviationDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::FxConversion,
+            Box::new(FxConversionDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::Correlation,
+            Box::new(CorrelationDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::PortfolioNav,
+            Box::new(PortfolioNavDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::PortfolioReturns,
+            Box::new(PortfolioReturnsDefinition::new()),
+        );
+        definitions.insert(AnalyticType::Drawdown, Box::new(DrawdownDefinition::new()));
+        definitions.insert(
+            AnalyticType::MaxDrawdown,
+            Box::new(MaxDrawdownDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::TimeUnderWater,
+            Box::new(TimeUnderWaterDefinition::new()),
+        );
+        definitions.insert(AnalyticType::Sma, Box::new(SmaDefinition::new()));
+        definitions.insert(
+            AnalyticType::BollingerBands,
+            Box::new(BollingerBandsDefinition::new()),
+        );
+        for kind in [
+            IndicatorKind::Ema,
+            IndicatorKind::Rsi,
+            IndicatorKind::Macd,
+            IndicatorKind::Atr,
+        ] {
+            definitions.insert(kind.analytic_type(), Box::new(IndicatorDefinition::new(kind)));
+        }
+        definitions.insert(
+            AnalyticType::ValueAtRisk,
+            Box::new(TailRiskDefinition::new(TailMeasure::ValueAtRisk)),
+        );
+        definitions.insert(
+            AnalyticType::ExpectedShortfall,
+            Box::new(TailRiskDefinition::new(TailMeasure::ExpectedShortfall)),
+        );
+        for statistic in [
+            DistributionStatistic::Skewness,
+            DistributionStatistic::Kurtosis,
+            DistributionStatistic::Quantile,
+            DistributionStatistic::PercentileRank,
+            DistributionStatistic::ZScore,
+        ] {
+            definitions.insert(
+                statistic.analytic_type(),
+                Box::new(DistributionDefinition::new(statistic)),
+            );
+        }
+        definitions.insert(AnalyticType::Regression, Box::new(RegressionDefinition::new()));
+        definitions.insert(AnalyticType::Garch, Box::new(GarchDefinition::new()));
+        definitions.insert(AnalyticType::Pca, Box::new(PcaDefinition::new()));
+        definitions.insert(
+            AnalyticType::CorrelationMatrix,
+            Box::new(MatrixDefinition::new(MatrixKind::Correlation)),
+        );
+        definitions.insert(
+            AnalyticType::CovarianceMatrix,
+            Box::new(MatrixDefinition::new(MatrixKind::Covariance)),
+        );
+        definitions.insert(
+            AnalyticType::Expression,
+            Box::new(ExpressionDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::SharpeRatio,
+            Box::new(SharpeRatioDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::SortinoRatio,
+            Box::new(SortinoRatioDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::CalmarRatio,
+            Box::new(CalmarRatioDefinition::new()),
+        );
+        definitions.insert(
+            AnalyticType::InformationRatio,
+            Box::new(InformationRatioDefinition::new()),
+        );
+        AnalyticRegistry {
+            definitions,
+            schemas: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a user-defined analytic under the name of its
+    /// `AnalyticType::Custom` type.
+    ///
+    /// Names are made of lowercase letters, digits and underscores, and cannot
+    /// shadow a built-in analytic or an analytic registered earlier.
+    pub fn register(
+        &mut self,
+        definition: Box<dyn AnalyticDefinition>,
+        schema: AnalyticSchema,
+    ) -> Result<CustomAnalytic, DagError> {
+        let AnalyticType::Custom(custom) = definition.analytic_type() else {
+            return Err(DagError::InvalidOperation(format!(
+                "Cannot register built-in analytic {}",
+                definition.analytic_type()
+            )));
+        };
+        let name = custom.name();
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            return Err(DagError::InvalidOperation(format!(
+                "Invalid analytic name '{}'",
+                name
+            )));
+        }
+        if AnalyticType::builtin_from_name(name).is_some() {
+            return Err(DagError::InvalidOperation(format!(
+                "Analytic name '{}' is taken by a built-in analytic",
+                name
+            )));
+        }
+        if self.schemas.contains_key(&custom) {
+            return Err(DagError::InvalidOperation(format!(
+                "Analytic '{}' is already registered",
+                name
+            )));
+        }
+
+        self.definitions.insert(AnalyticType::Custom(custom), definition);
+        self.schemas.insert(custom, schema);
+        Ok(custom)
+    }
+
+    /// Analytic type named `name`, including user-defined analytics.
+    ///
+    /// As with `AnalyticType::from_str`, unknown names fall back to the data provider.
+    pub fn analytic_type(&self, name: &str) -> AnalyticType {
+        // Looked up by name: interning would leak every unknown name requested
+        let name = name.trim().to_lowercase();
+        match self.schemas.get_key_value(name.as_str()) {
+            Some((&custom, _)) => AnalyticType::Custom(custom),
+            None => AnalyticType::from_str(&name),
+        }
+    }
+
+    /// Schema of a user-defined analytic.
+    pub fn schema(&self, analytic: AnalyticType) -> Option<&AnalyticSchema> {
+        match analytic {
+            AnalyticType::Custom(custom) => self.schemas.get(&custom),
+            _ => None,
+        }
+    }
+
+    /// User-defined analytics in name order.
+    pub fn custom_analytics(&self) -> impl Iterator<Item = (CustomAnalytic, &AnalyticSchema)> {
+        self.schemas.iter().map(|(custom, schema)| (*custom, schema))
+    }
+
+    /// Checks a user-defined analytic's key against its schema, filling in
+    /// default parameters and the window of windowed analytics. Keys of
+    /// built-in analytics are returned unchanged.
+    pub fn apply_schema(&self, mut key: NodeKey) -> Result<NodeKey, DagError> {
+        let Some(schema) = self.schema(key.analytic) else {
+            return Ok(key);
+        };
+        // Windowed analytics accept `window` as on the HTTP API
+        if schema.windowed() {
+            if let Some(window) = key.params.remove("window") {
+                key.params
+                    .entry(WINDOW_PARAMETER.to_string())
+                    .or_insert(window);
+            }
+        }
+        schema
+            .apply(&mut key.params)
+            .map_err(|e| DagError::InvalidOperation(format!("{}: {}", key.analytic, e)))?;
+        if schema.windowed() {
+            let window = parse_window_from_map(&key.params);
+            if window == 0 {
+                return Err(DagError::InvalidOperation(format!(
+                    "{}: window size must be greater than 0",
+                    key.analytic
+                )));
+            }
+            key.window = Some(WindowSpec::fixed(window));
+        }
+        Ok(key)
     }
 
     /// Returns the definition associated with an analytic type.
     pub fn definition(&self, analytic: AnalyticType) -> Option<&dyn AnalyticDefinition> {
         self.definitions.get(&analytic).map(|boxed| boxed.as_ref())
     }
+
+    /// Named outputs declared for a node key (empty for single-output analytics).
+    pub fn outputs(&self, key: &NodeKey) -> Vec<String> {
+        self.definition(key.analytic)
+            .map(|definition| definition.outputs(key))
+            .unwrap_or_default()
+    }
 }
 
 impl Default for AnalyticRegistry {
@@ -225,8 +788,8 @@ impl MergeExecutor {
         for analytic in &self.sources {
             let slice = parent_outputs
                 .iter()
-                .find(|parent| parent.analytic == *analytic)
-                .map(|parent| parent.output.as_slice())
+                .find(|parent| parent.analytic.satisfies(*analytic))
+                .map(|parent| parent.output.as_ref())
                 .unwrap_or(&[]);
             slices.push(slice);
         }
@@ -338,22 +901,15 @@ impl AnalyticDefinition for ReturnsDefinition {
         let range = require_range(key)?;
         let lag = parse_lag_from_map(&key.params);
 
-        let data_params = params_with_range("data_provider", &range);
         let lag_params = {
             let mut params = params_with_range("lag", &range);
             params.insert("lag".to_string(), lag.to_string());
+            forward_conversion(key, 0, &mut params)?;
             params
         };
 
         Ok(vec![
-            NodeKey {
-                analytic: AnalyticType::DataProvider,
-                assets: key.assets.clone(),
-                range: Some(range.clone()),
-                window: None,
-                override_tag: key.override_tag.clone(),
-                params: data_params,
-            },
+            price_source_key(key, 0, range.clone())?,
             NodeKey {
                 analytic: AnalyticType::Lag,
                 assets: key.assets.clone(),
@@ -384,7 +940,8 @@ impl VolatilityDefinition {
                     let analytic = StdDevVolatilityAnalytic;
                     analytic.compute(asset, window)
                 },
-            )),
+            )
+            .streaming::<WelfordVariance>(|window| window.accumulator().std_dev())),
         }
     }
 }
@@ -403,17 +960,7 @@ impl AnalyticDefinition for VolatilityDefinition {
         let window_size = parse_window_from_map(&key.params);
         let returns_range = extend_range(&range, window_size.saturating_sub(1));
 
-        let mut returns_params = params_with_range("returns", &returns_range);
-        returns_params.insert("lag".to_string(), "1".to_string());
-
-        Ok(vec![NodeKey {
-            analytic: AnalyticType::Returns,
-            assets: key.assets.clone(),
-            range: Some(returns_range),
-            window: None,
-            override_tag: key.override_tag.clone(),
-            params: returns_params,
-        }])
+        Ok(vec![returns_source_key(key, returns_range)?])
     }
 
     fn executor(&self) -> &dyn AnalyticExecutor {
@@ -421,110 +968,2854 @@ impl AnalyticDefinition for VolatilityDefinition {
     }
 }
 
-fn require_range(key: &NodeKey) -> Result<DateRange, DagError> {
-    key.range
-        .clone()
-        .ok_or_else(|| DagError::InvalidOperation("Analytics node missing range".to_string()))
+struct DownsideDeviationDefinition {
+    executor: Box<dyn AnalyticExecutor>,
 }
 
-fn extend_range(range: &DateRange, burn_in_days: usize) -> DateRange {
-    if burn_in_days == 0 {
-        return range.clone();
+impl DownsideDeviationDefinition {
+    fn new() -> Self {
+        DownsideDeviationDefinition {
+            executor: Box::new(WindowedAnalyticExecutor::new(
+                AnalyticType::Returns,
+                |node| parse_window_from_params(&node.params),
+                |_asset, window, _| downside_deviation(window),
+            )),
+        }
     }
-
-    let extra = Duration::days(burn_in_days as i64);
-    let start = range.start.checked_sub_signed(extra).unwrap_or(range.start);
-
-    DateRange::new(start, range.end)
 }
 
-struct DataProviderExecutor;
+impl AnalyticDefinition for DownsideDeviationDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::DownsideDeviation
+    }
 
-impl AnalyticExecutor for DataProviderExecutor {
-    fn execute_pull(
-        &self,
-        node: &Node,
-        _parent_outputs: &[ParentOutput],
-        date_range: &DateRange,
-        provider: &dyn DataProvider,
-    ) -> Result<Vec<TimeSeriesPoint>, DagError> {
-        let asset = node.assets.first().ok_or_else(|| {
-            DagError::ExecutionError("DataProvider node has no assets".to_string())
-        })?;
-        
-        debug!(
-            node_id = node.id.0,
-            asset = %asset,
-            start_date = %date_range.start,
-            end_date = %date_range.end,
-            "DataProviderExecutor: querying time series"
-        );
-        
-        let data = provider.get_time_series(asset, date_range)?;
-        
-        debug!(
-            node_id = node.id.0,
-            asset = %asset,
-            data_point_count = data.len(),
-            "DataProviderExecutor: retrieved time series"
-        );
-        
-        Ok(data)
+    fn node_type(&self) -> &'static str {
+        "downside_deviation"
     }
 
-    fn execute_push(
-        &self,
-        node: &Node,
-        _parent_outputs: &[ParentOutput],
-        timestamp: DateTime<Utc>,
-        value: f64,
-    ) -> Result<NodeOutput, DagError> {
-        trace!(
-            node_id = node.id.0,
-            timestamp = %timestamp,
-            value = value,
-            "DataProviderExecutor: executing push"
-        );
-        
-        Ok(NodeOutput::Single(vec![TimeSeriesPoint::new(
-            timestamp, value,
-        )]))
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let window_size = parse_window_from_map(&key.params);
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![returns_source_key(key, returns_range)?])
     }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
     }
 }
 
-struct LagDefinition {
+struct FxConversionDefinition {
     executor: Box<dyn AnalyticExecutor>,
 }
 
-impl LagDefinition {
+impl FxConversionDefinition {
     fn new() -> Self {
-        LagDefinition {
-            executor: Box::new(WindowedAnalyticExecutor::new(
-                AnalyticType::DataProvider,
-                |node| parse_lag_from_params(&node.params) + 1,
-                |_asset, window, window_size| {
-                    if window.len() < window_size {
-                        f64::NAN
-                    } else {
-                        window.first().copied().unwrap_or(f64::NAN)
-                    }
-                },
-            )),
+        FxConversionDefinition {
+            executor: Box::new(FxConversionExecutor),
         }
     }
 }
 
-impl AnalyticDefinition for LagDefinition {
+impl AnalyticDefinition for FxConversionDefinition {
     fn analytic_type(&self) -> AnalyticType {
-        AnalyticType::Lag
+        AnalyticType::FxConversion
     }
 
     fn node_type(&self) -> &'static str {
-        "lag"
+        "fx_conversion"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        if key.assets.len() != 2 {
+            return Err(DagError::InvalidOperation(
+                "FX conversion requires an asset and an FX pair".to_string(),
+            ));
+        }
+
+        Ok(key
+            .assets
+            .iter()
+            .map(|asset| NodeKey {
+                analytic: AnalyticType::DataProvider,
+                assets: vec![asset.clone()],
+                range: Some(range.clone()),
+                window: None,
+                override_tag: key.override_tag.clone(),
+                params: params_with_range("data_provider", &range),
+            })
+            .collect())
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Re-expresses an asset's prices in a reporting currency.
+///
+/// Emits only when the asset itself has a price at the current timestamp, using
+/// the latest FX rate observed at or before that timestamp.
+struct FxConversionExecutor;
+
+impl AnalyticExecutor for FxConversionExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let (asset, pair) = match node.assets.as_slice() {
+            [asset, pair] => (asset, pair),
+            _ => {
+                return Err(DagError::ExecutionError(
+                    "FX conversion requires an asset and an FX pair".to_string(),
+                ))
+            }
+        };
+
+        let price = match parent_series(parent_outputs, AnalyticType::DataProvider, asset).last() {
+            Some(point) if point.timestamp == timestamp => point.close_price,
+            _ => return Ok(NodeOutput::None),
+        };
+        let rate = parent_series(parent_outputs, AnalyticType::DataProvider, pair)
+            .iter()
+            .rev()
+            .find(|point| point.timestamp <= timestamp)
+            .map(|point| point.close_price)
+            .unwrap_or(f64::NAN);
+
+        let invert = match &node.params {
+            NodeParams::Map(map) => map.get("fx_invert").map(|v| v == "true").unwrap_or(false),
+            NodeParams::None => false,
+        };
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            price = price,
+            rate = rate,
+            invert = invert,
+            "FxConversionExecutor: converting price"
+        );
+
+        let value = if invert { price / rate } else { price * rate };
+        Ok(NodeOutput::Scalar(value))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct CorrelationDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl CorrelationDefinition {
+    fn new() -> Self {
+        CorrelationDefinition {
+            executor: Box::new(AlignedPairExecutor::new(
+                AnalyticType::Returns,
+                |node| parse_window_from_params(&node.params),
+                pearson_correlation,
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for CorrelationDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Correlation
+    }
+
+    fn node_type(&self) -> &'static str {
+        "correlation"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        if key.assets.len() != 2 {
+            return Err(DagError::InvalidOperation(
+                "Correlation requires exactly two assets".to_string(),
+            ));
+        }
+        let window_size = parse_window_from_map(&key.params);
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        key.assets
+            .iter()
+            .enumerate()
+            .map(|(index, asset)| {
+                let mut params = params_with_range("returns", &returns_range);
+                params.insert("lag".to_string(), "1".to_string());
+                forward_conversion(key, index, &mut params)?;
+                Ok(NodeKey {
+                    analytic: AnalyticType::Returns,
+                    assets: vec![asset.clone()],
+                    range: Some(returns_range.clone()),
+                    window: None,
+                    override_tag: key.override_tag.clone(),
+                    params,
+                })
+            })
+            .collect()
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Executor for two-asset analytics over date-aligned windows of the same input.
+///
+/// The inputs of the node's first and second asset are aligned on calendar date,
+/// and the last `window_size` overlapping dates are handed to the compute function.
+struct AlignedPairExecutor {
+    source: AnalyticType,
+    window_size_fn: Arc<dyn Fn(&Node) -> usize + Send + Sync>,
+    compute_fn: PairComputeFn,
+}
+
+type PairComputeFn = Arc<dyn Fn(&[f64], &[f64]) -> f64 + Send + Sync>;
+
+impl AlignedPairExecutor {
+    fn new(
+        source: AnalyticType,
+        window_size_fn: impl Fn(&Node) -> usize + Send + Sync + 'static,
+        compute_fn: impl Fn(&[f64], &[f64]) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        AlignedPairExecutor {
+            source,
+            window_size_fn: Arc::new(window_size_fn),
+            compute_fn: Arc::new(compute_fn),
+        }
+    }
+}
+
+/// Aligns two series on calendar date, keeping the last `window` shared dates.
+fn align_by_date(
+    left: &[TimeSeriesPoint],
+    right: &[TimeSeriesPoint],
+    window: usize,
+) -> (Vec<f64>, Vec<f64>) {
+    let right_by_date: HashMap<NaiveDate, f64> = right
+        .iter()
+        .map(|point| (point.timestamp.date_naive(), point.close_price))
+        .collect();
+
+    let mut xs = Vec::with_capacity(window);
+    let mut ys = Vec::with_capacity(window);
+    for point in left.iter().rev() {
+        if xs.len() == window {
+            break;
+        }
+        if let Some(&y) = right_by_date.get(&point.timestamp.date_naive()) {
+            xs.push(point.close_price);
+            ys.push(y);
+        }
+    }
+    xs.reverse();
+    ys.reverse();
+    (xs, ys)
+}
+
+impl AnalyticExecutor for AlignedPairExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let (first, second) = match node.assets.as_slice() {
+            [first, second] => (first, second),
+            _ => {
+                return Err(DagError::ExecutionError(format!(
+                    "{} requires exactly two assets",
+                    node.node_type
+                )))
+            }
+        };
+
+        let window_size = (self.window_size_fn)(node);
+        let (xs, ys) = align_by_date(
+            parent_series(parent_outputs, self.source, first),
+            parent_series(parent_outputs, self.source, second),
+            window_size,
+        );
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            aligned_points = xs.len(),
+            "AlignedPairExecutor: computing over aligned window"
+        );
+
+        Ok(NodeOutput::Scalar((self.compute_fn)(&xs, &ys)))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct PortfolioNavDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl PortfolioNavDefinition {
+    fn new() -> Self {
+        PortfolioNavDefinition {
+            executor: Box::new(PortfolioNavExecutor),
+        }
+    }
+}
+
+impl AnalyticDefinition for PortfolioNavDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::PortfolioNav
+    }
+
+    fn node_type(&self) -> &'static str {
+        "portfolio_nav"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        if key.assets.is_empty() {
+            return Err(DagError::InvalidOperation(
+                "Portfolio requires at least one asset".to_string(),
+            ));
+        }
+        parse_portfolio(&key.params, key.assets.len())?;
+
+        (0..key.assets.len())
+            .map(|index| price_source_key(key, index, range.clone()))
+            .collect()
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Computes portfolio NAV from the constituents' price histories.
+///
+/// Without state the NAV is rebuilt from inception on every update. With state
+/// a [`NavTracker`] carries the holdings from one update to the next, starting
+/// from the history before the first update; both apply rebalancing alike.
+struct PortfolioNavExecutor;
+
+/// Push state of a portfolio NAV node.
+///
+/// Keeps the tracker as of the previous timestamp so that a repeated update for
+/// the current timestamp is applied to the same starting point.
+struct PortfolioNavState {
+    timestamp: Option<DateTime<Utc>>,
+    previous: NavTracker,
+    current: NavTracker,
+}
+
+impl PortfolioNavExecutor {
+    fn portfolio(node: &Node) -> Result<(Vec<f64>, RebalanceFrequency), DagError> {
+        parse_portfolio(node_params(node), node.assets.len())
+    }
+
+    fn constituents<'a>(
+        node: &Node,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Vec<&'a [TimeSeriesPoint]> {
+        node.assets
+            .iter()
+            .map(|asset| parent_series(parent_outputs, AnalyticType::DataProvider, asset))
+            .collect()
+    }
+}
+
+impl AnalyticExecutor for PortfolioNavExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let (weights, rebalance) = Self::portfolio(node)?;
+        let constituents = Self::constituents(node, parent_outputs);
+        let nav = nav_series(&constituents, &weights, rebalance);
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            rebalance = %rebalance,
+            nav_points = nav.len(),
+            "PortfolioNavExecutor: computed NAV"
+        );
+
+        match nav.last() {
+            Some(point) if point.timestamp == timestamp => Ok(NodeOutput::Scalar(point.close_price)),
+            _ => Ok(NodeOutput::None),
+        }
+    }
+
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let (weights, rebalance) = Self::portfolio(node)?;
+        let constituents = Self::constituents(node, parent_outputs);
+        // Prices before this update, and those at it
+        let (history, latest): (Vec<_>, Vec<_>) = constituents
+            .iter()
+            .map(|series| series.split_at(series.partition_point(|p| p.timestamp < timestamp)))
+            .unzip();
+
+        let state = state
+            .get_or_insert_with(|| {
+                let mut tracker = NavTracker::new(weights, rebalance);
+                tracker.extend(&history);
+                Box::new(PortfolioNavState {
+                    timestamp: None,
+                    previous: tracker.clone(),
+                    current: tracker,
+                })
+            })
+            .downcast_mut::<PortfolioNavState>()
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp != Some(timestamp) {
+            state.previous = state.current.clone();
+            state.timestamp = Some(timestamp);
+        }
+        state.current = state.previous.clone();
+        let nav = state.current.extend(&latest);
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            rebalance = %rebalance,
+            nav = ?nav.last().map(|point| point.close_price),
+            "PortfolioNavExecutor: extended NAV"
+        );
+
+        match nav.last() {
+            Some(point) if point.timestamp == timestamp => {
+                Ok(NodeOutput::Scalar(point.close_price))
+            }
+            _ => Ok(NodeOutput::None),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct PortfolioReturnsDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl PortfolioReturnsDefinition {
+    fn new() -> Self {
+        PortfolioReturnsDefinition {
+            executor: Box::new(WindowedAnalyticExecutor::new(
+                AnalyticType::PortfolioNav,
+                |_| 2,
+                |asset, window, window_size| {
+                    if window.len() < window_size || window.iter().any(|v| v.is_nan()) {
+                        f64::NAN
+                    } else {
+                        LogReturnAnalytic.compute(asset, window[1], window[0])
+                    }
+                },
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for PortfolioReturnsDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::PortfolioReturns
+    }
+
+    fn node_type(&self) -> &'static str {
+        "portfolio_returns"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let nav_range = extend_range(&range, 1);
+
+        Ok(vec![NodeKey {
+            analytic: AnalyticType::PortfolioNav,
+            assets: key.assets.clone(),
+            params: portfolio_params(key, "portfolio_nav", &nav_range),
+            range: Some(nav_range),
+            window: None,
+            override_tag: key.override_tag.clone(),
+        }])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct DrawdownDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl DrawdownDefinition {
+    fn new() -> Self {
+        DrawdownDefinition {
+            executor: Box::new(RunningPeakExecutor::new(PeakMeasure::Drawdown)),
+        }
+    }
+}
+
+impl AnalyticDefinition for DrawdownDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Drawdown
+    }
+
+    fn node_type(&self) -> &'static str {
+        "drawdown"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        Ok(vec![level_source_key(key, require_range(key)?)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct TimeUnderWaterDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl TimeUnderWaterDefinition {
+    fn new() -> Self {
+        TimeUnderWaterDefinition {
+            executor: Box::new(RunningPeakExecutor::new(PeakMeasure::TimeUnderWater)),
+        }
+    }
+}
+
+impl AnalyticDefinition for TimeUnderWaterDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::TimeUnderWater
+    }
+
+    fn node_type(&self) -> &'static str {
+        "time_under_water"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        Ok(vec![level_source_key(key, require_range(key)?)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct MaxDrawdownDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl MaxDrawdownDefinition {
+    fn new() -> Self {
+        MaxDrawdownDefinition {
+            executor: Box::new(WindowedAnalyticExecutor::new(
+                AnalyticType::DataProvider,
+                |node| parse_window_from_params(&node.params),
+                |_asset, window, window_size| {
+                    if window.len() < window_size {
+                        f64::NAN
+                    } else {
+                        max_drawdown(window)
+                    }
+                },
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for MaxDrawdownDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::MaxDrawdown
+    }
+
+    fn node_type(&self) -> &'static str {
+        "max_drawdown"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let window_size = parse_window_from_map(&key.params);
+        let level_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![level_source_key(key, level_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct SmaDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl SmaDefinition {
+    fn new() -> Self {
+        SmaDefinition {
+            executor: Box::new(WindowedAnalyticExecutor::new(
+                AnalyticType::DataProvider,
+                |node| parse_period(node_params(node), "window_size", 20),
+                |_asset, window, window_size| {
+                    if window.len() < window_size {
+                        f64::NAN
+                    } else {
+                        sma(window)
+                    }
+                },
+            )
+            .streaming::<RunningSum>(|window| {
+                if window.is_full() {
+                    window.accumulator().mean()
+                } else {
+                    f64::NAN
+                }
+            })),
+        }
+    }
+}
+
+impl AnalyticDefinition for SmaDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Sma
+    }
+
+    fn node_type(&self) -> &'static str {
+        "sma"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let window_size = parse_period(&key.params, "window_size", 20);
+        let level_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![level_source_key(key, level_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct BollingerBandsDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl BollingerBandsDefinition {
+    fn new() -> Self {
+        BollingerBandsDefinition {
+            executor: Box::new(BollingerExecutor),
+        }
+    }
+}
+
+impl AnalyticDefinition for BollingerBandsDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::BollingerBands
+    }
+
+    fn node_type(&self) -> &'static str {
+        "bollinger"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let window_size = parse_period(&key.params, "window_size", 20);
+        let level_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![level_source_key(key, level_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+
+    fn outputs(&self, _key: &NodeKey) -> Vec<String> {
+        BandOutput::ALL.iter().map(|band| band.name().to_string()).collect()
+    }
+}
+
+/// Executor for the Bollinger bands over the last `window_size` prices,
+/// `num_std` (default 2) standard deviations either side of the moving average.
+/// Emits the middle, upper and lower bands; NaN until the window is full.
+struct BollingerExecutor;
+
+impl AnalyticExecutor for BollingerExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        _timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let params = node_params(node);
+        let window_size = parse_period(params, "window_size", 20);
+        let num_std = params
+            .get("num_std")
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(2.0);
+
+        let prices = parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(AnalyticType::DataProvider))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} requires price data", node.node_type))
+            })?;
+        let window: Vec<f64> = if prices.len() < window_size {
+            Vec::new()
+        } else {
+            prices[prices.len() - window_size..]
+                .iter()
+                .map(|point| point.close_price)
+                .collect()
+        };
+
+        Ok(NodeOutput::Named(
+            BandOutput::ALL
+                .iter()
+                .map(|&band| {
+                    let value = if window.is_empty() {
+                        f64::NAN
+                    } else {
+                        bollinger_band(&window, num_std, band)
+                    };
+                    (band.name().to_string(), value)
+                })
+                .collect(),
+        ))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Recursive indicators, advanced one price at a time.
+#[derive(Debug, Clone, Copy)]
+enum IndicatorKind {
+    Ema,
+    Rsi,
+    Macd,
+    Atr,
+}
+
+impl IndicatorKind {
+    fn analytic_type(self) -> AnalyticType {
+        match self {
+            IndicatorKind::Ema => AnalyticType::ExponentialMovingAverage,
+            IndicatorKind::Rsi => AnalyticType::Rsi,
+            IndicatorKind::Macd => AnalyticType::Macd,
+            IndicatorKind::Atr => AnalyticType::Atr,
+        }
+    }
+
+    /// Calendar days of extra history fetched so the indicator has warmed up
+    /// by the start of the requested range.
+    fn warm_up_days(self, params: &HashMap<String, String>) -> usize {
+        match self {
+            IndicatorKind::Ema => parse_period(params, "window_size", 10),
+            IndicatorKind::Rsi | IndicatorKind::Atr => parse_period(params, "window_size", 14),
+            IndicatorKind::Macd => {
+                parse_period(params, "slow", 26) + parse_period(params, "signal", 9)
+            }
+        }
+    }
+}
+
+/// Indicator state configured from a node's params.
+#[derive(Debug, Clone, Copy)]
+enum IndicatorState {
+    Ema(Ema),
+    Rsi(Rsi),
+    Macd(Macd),
+    Atr(Atr),
+}
+
+impl IndicatorState {
+    fn from_params(
+        kind: IndicatorKind,
+        params: &HashMap<String, String>,
+    ) -> Result<Self, DagError> {
+        Ok(match kind {
+            IndicatorKind::Ema => {
+                IndicatorState::Ema(Ema::new(parse_period(params, "window_size", 10)))
+            }
+            IndicatorKind::Rsi => {
+                IndicatorState::Rsi(Rsi::new(parse_period(params, "window_size", 14)))
+            }
+            IndicatorKind::Atr => {
+                IndicatorState::Atr(Atr::new(parse_period(params, "window_size", 14)))
+            }
+            IndicatorKind::Macd => {
+                let fast = parse_period(params, "fast", 12);
+                let slow = parse_period(params, "slow", 26);
+                if fast >= slow {
+                    return Err(DagError::InvalidOperation(format!(
+                        "MACD fast period {} must be shorter than slow period {}",
+                        fast, slow
+                    )));
+                }
+                let signal = parse_period(params, "signal", 9);
+                IndicatorState::Macd(Macd::new(fast, slow, signal))
+            }
+        })
+    }
+
+    fn update(self, price: f64) -> Self {
+        match self {
+            IndicatorState::Ema(ema) => IndicatorState::Ema(ema.update(price)),
+            IndicatorState::Rsi(rsi) => IndicatorState::Rsi(rsi.update(price)),
+            IndicatorState::Macd(macd) => IndicatorState::Macd(macd.update(price)),
+            IndicatorState::Atr(atr) => IndicatorState::Atr(atr.update(price)),
+        }
+    }
+
+    /// Current value; MACD reports its line, signal and histogram together.
+    fn output(&self) -> NodeOutput {
+        match self {
+            IndicatorState::Ema(ema) => NodeOutput::Scalar(ema.value()),
+            IndicatorState::Rsi(rsi) => NodeOutput::Scalar(rsi.value()),
+            IndicatorState::Macd(macd) => NodeOutput::Named(
+                MacdOutput::ALL
+                    .iter()
+                    .map(|&output| (output.name().to_string(), macd.value(output)))
+                    .collect(),
+            ),
+            IndicatorState::Atr(atr) => NodeOutput::Scalar(atr.value()),
+        }
+    }
+}
+
+struct IndicatorDefinition {
+    kind: IndicatorKind,
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl IndicatorDefinition {
+    fn new(kind: IndicatorKind) -> Self {
+        IndicatorDefinition {
+            kind,
+            executor: Box::new(IndicatorExecutor { kind }),
+        }
+    }
+}
+
+impl AnalyticDefinition for IndicatorDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        self.kind.analytic_type()
+    }
+
+    fn node_type(&self) -> &'static str {
+        match self.kind {
+            IndicatorKind::Ema => "ema",
+            IndicatorKind::Rsi => "rsi",
+            IndicatorKind::Macd => "macd",
+            IndicatorKind::Atr => "atr",
+        }
+    }
+
+    fn outputs(&self, _key: &NodeKey) -> Vec<String> {
+        match self.kind {
+            IndicatorKind::Macd => MacdOutput::ALL
+                .iter()
+                .map(|output| output.name().to_string())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        IndicatorState::from_params(self.kind, &key.params)?;
+        let range = require_range(key)?;
+        let level_range = extend_range(&range, self.kind.warm_up_days(&key.params));
+
+        Ok(vec![level_source_key(key, level_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Push state of a recursive indicator node: the EMA, RSI, MACD or ATR
+/// recursion before (`previous`) and after (`current`) the price at `timestamp`.
+/// A revised level for the same timestamp, such as a portfolio NAV updated by
+/// its second constituent, is applied to `previous` again instead of advancing
+/// the recursion twice.
+#[derive(Debug)]
+struct IndicatorExecutorState {
+    timestamp: Option<DateTime<Utc>>,
+    previous: IndicatorState,
+    current: IndicatorState,
+}
+
+/// Executor for recursive indicators on a price or NAV level.
+///
+/// With state, each update is O(1). Without state the indicator is replayed
+/// over the full parent history.
+struct IndicatorExecutor {
+    kind: IndicatorKind,
+}
+
+impl IndicatorExecutor {
+    fn levels<'a>(
+        &self,
+        node: &Node,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Result<&'a [TimeSeriesPoint], DagError> {
+        parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(AnalyticType::DataProvider))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} requires price data", node.node_type))
+            })
+    }
+
+    fn initial_state(&self, node: &Node) -> Result<IndicatorState, DagError> {
+        IndicatorState::from_params(self.kind, node_params(node))
+    }
+}
+
+impl AnalyticExecutor for IndicatorExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        _timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let indicator = self
+            .levels(node, parent_outputs)?
+            .iter()
+            .fold(self.initial_state(node)?, |state, point| {
+                state.update(point.close_price)
+            });
+        Ok(indicator.output())
+    }
+
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let price = self
+            .levels(node, parent_outputs)?
+            .last()
+            .map(|p| p.close_price)
+            .unwrap_or(f64::NAN);
+
+        if state.is_none() {
+            let initial = self.initial_state(node)?;
+            *state = Some(Box::new(IndicatorExecutorState {
+                timestamp: None,
+                previous: initial,
+                current: initial,
+            }));
+        }
+        let state = state
+            .as_mut()
+            .and_then(|state| state.downcast_mut::<IndicatorExecutorState>())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp != Some(timestamp) {
+            state.previous = state.current;
+            state.timestamp = Some(timestamp);
+        }
+        state.current = state.previous.update(price);
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            kind = ?self.kind,
+            price = price,
+            "IndicatorExecutor: advanced indicator"
+        );
+
+        Ok(state.current.output())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Parses the confidence level and estimation method of a tail-risk node.
+fn parse_tail_risk(params: &HashMap<String, String>) -> Result<(f64, VarMethod), DagError> {
+    let confidence = parse_confidence(params.get("confidence").map(String::as_str))
+        .map_err(DagError::InvalidOperation)?;
+    let method = match params.get("method") {
+        Some(value) => VarMethod::parse(value).ok_or_else(|| {
+            DagError::InvalidOperation(format!("Unknown VaR method '{}'", value))
+        })?,
+        None => VarMethod::Historical,
+    };
+    Ok((confidence, method))
+}
+
+/// Loss statistic reported by a tail-risk node.
+#[derive(Debug, Clone, Copy)]
+enum TailMeasure {
+    ValueAtRisk,
+    ExpectedShortfall,
+}
+
+struct TailRiskDefinition {
+    measure: TailMeasure,
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl TailRiskDefinition {
+    fn new(measure: TailMeasure) -> Self {
+        TailRiskDefinition {
+            measure,
+            executor: Box::new(TailRiskExecutor { measure }),
+        }
+    }
+}
+
+impl AnalyticDefinition for TailRiskDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        match self.measure {
+            TailMeasure::ValueAtRisk => AnalyticType::ValueAtRisk,
+            TailMeasure::ExpectedShortfall => AnalyticType::ExpectedShortfall,
+        }
+    }
+
+    fn node_type(&self) -> &'static str {
+        match self.measure {
+            TailMeasure::ValueAtRisk => "value_at_risk",
+            TailMeasure::ExpectedShortfall => "expected_shortfall",
+        }
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        parse_tail_risk(&key.params)?;
+        let range = require_range(key)?;
+        let window_size = parse_window_from_map(&key.params);
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![returns_source_key(key, returns_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Executor for rolling VaR / Expected Shortfall over the last `window_size`
+/// returns, at the node's `confidence` using its `method`. NaN until the window
+/// is full.
+struct TailRiskExecutor {
+    measure: TailMeasure,
+}
+
+impl AnalyticExecutor for TailRiskExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let params = node_params(node);
+        let window_size = parse_window_from_map(params);
+        let (confidence, method) = parse_tail_risk(params)?;
+
+        let returns = parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(AnalyticType::Returns))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} requires returns data", node.node_type))
+            })?;
+        if returns.len() < window_size {
+            return Ok(NodeOutput::Scalar(f64::NAN));
+        }
+        let window: Vec<f64> = returns[returns.len() - window_size..]
+            .iter()
+            .map(|point| point.close_price)
+            .collect();
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            measure = ?self.measure,
+            %method,
+            confidence,
+            "TailRiskExecutor: computing tail risk"
+        );
+
+        let value = match self.measure {
+            TailMeasure::ValueAtRisk => value_at_risk(&window, confidence, method),
+            TailMeasure::ExpectedShortfall => expected_shortfall(&window, confidence, method),
+        };
+        Ok(NodeOutput::Scalar(value))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Distribution statistic reported by a rolling-window node.
+#[derive(Debug, Clone, Copy)]
+enum DistributionStatistic {
+    Skewness,
+    Kurtosis,
+    Quantile,
+    PercentileRank,
+    ZScore,
+}
+
+impl DistributionStatistic {
+    fn analytic_type(self) -> AnalyticType {
+        match self {
+            DistributionStatistic::Skewness => AnalyticType::Skewness,
+            DistributionStatistic::Kurtosis => AnalyticType::Kurtosis,
+            DistributionStatistic::Quantile => AnalyticType::Quantile,
+            DistributionStatistic::PercentileRank => AnalyticType::PercentileRank,
+            DistributionStatistic::ZScore => AnalyticType::ZScore,
+        }
+    }
+
+    /// Rolling state that updates this statistic incrementally.
+    fn rolling_window(self, window_size: usize) -> RollingWindow {
+        match self {
+            DistributionStatistic::Quantile | DistributionStatistic::PercentileRank => {
+                RollingWindow::Order(RollingOrderStatistics::new(window_size))
+            }
+            _ => RollingWindow::Moments(RollingMoments::new(window_size)),
+        }
+    }
+}
+
+/// Parses the `quantile` of a quantile node (default 0.5), which must lie in [0, 1].
+fn parse_quantile(params: &HashMap<String, String>) -> Result<f64, DagError> {
+    let Some(value) = params.get("quantile") else {
+        return Ok(0.5);
+    };
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|q| (0.0..=1.0).contains(q))
+        .ok_or_else(|| {
+            DagError::InvalidOperation(format!("Quantile must be between 0 and 1, got '{}'", value))
+        })
+}
+
+struct DistributionDefinition {
+    statistic: DistributionStatistic,
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl DistributionDefinition {
+    fn new(statistic: DistributionStatistic) -> Self {
+        DistributionDefinition {
+            statistic,
+            executor: Box::new(DistributionExecutor { statistic }),
+        }
+    }
+}
+
+impl AnalyticDefinition for DistributionDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        self.statistic.analytic_type()
+    }
+
+    fn node_type(&self) -> &'static str {
+        match self.statistic {
+            DistributionStatistic::Skewness => "skewness",
+            DistributionStatistic::Kurtosis => "kurtosis",
+            DistributionStatistic::Quantile => "quantile",
+            DistributionStatistic::PercentileRank => "percentile_rank",
+            DistributionStatistic::ZScore => "z_score",
+        }
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        parse_quantile(&key.params)?;
+        let range = require_range(key)?;
+        let window_size = parse_window_from_map(&key.params);
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![returns_source_key(key, returns_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Incrementally maintained window of a distribution node.
+#[derive(Debug)]
+enum RollingWindow {
+    Moments(RollingMoments),
+    Order(RollingOrderStatistics),
+}
+
+impl RollingWindow {
+    fn push(&mut self, value: f64) {
+        match self {
+            RollingWindow::Moments(moments) => moments.push(value),
+            RollingWindow::Order(order) => order.push(value),
+        }
+    }
+
+    fn replace_last(&mut self, value: f64) {
+        match self {
+            RollingWindow::Moments(moments) => moments.replace_last(value),
+            RollingWindow::Order(order) => order.replace_last(value),
+        }
+    }
+
+    fn value(&self, statistic: DistributionStatistic, q: f64) -> f64 {
+        match (self, statistic) {
+            (RollingWindow::Moments(m), _) if !m.is_full() => f64::NAN,
+            (RollingWindow::Order(o), _) if !o.is_full() => f64::NAN,
+            (RollingWindow::Moments(m), DistributionStatistic::Skewness) => m.skewness(),
+            (RollingWindow::Moments(m), DistributionStatistic::Kurtosis) => m.excess_kurtosis(),
+            (RollingWindow::Moments(m), DistributionStatistic::ZScore) => m.z_score(),
+            (RollingWindow::Order(o), DistributionStatistic::Quantile) => o.quantile(q),
+            (RollingWindow::Order(o), DistributionStatistic::PercentileRank) => {
+                o.percentile_rank()
+            }
+            _ => f64::NAN,
+        }
+    }
+}
+
+/// Push state of a distribution node: the rolling window and the timestamp of
+/// its most recent observation, which a repeated update replaces.
+#[derive(Debug)]
+struct DistributionExecutorState {
+    timestamp: Option<DateTime<Utc>>,
+    window: RollingWindow,
+}
+
+/// Executor for rolling skewness, excess kurtosis, quantile, percentile rank and
+/// z-score over the last `window_size` returns. NaN until the window is full.
+///
+/// With state, each update adds one return and evicts the oldest: O(1) for the
+/// moments, a binary search into the sorted window for the order statistics.
+/// Without state the statistic is computed from the window directly.
+struct DistributionExecutor {
+    statistic: DistributionStatistic,
+}
+
+impl DistributionExecutor {
+    fn returns<'a>(
+        &self,
+        node: &Node,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Result<&'a [TimeSeriesPoint], DagError> {
+        parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(AnalyticType::Returns))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} requires returns data", node.node_type))
+            })
+    }
+
+    fn params(node: &Node) -> Result<(usize, f64), DagError> {
+        let params = node_params(node);
+        Ok((parse_window_from_map(params), parse_quantile(params)?))
+    }
+}
+
+impl AnalyticExecutor for DistributionExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        _timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let (window_size, q) = Self::params(node)?;
+        let returns = self.returns(node, parent_outputs)?;
+        if returns.len() < window_size {
+            return Ok(NodeOutput::Scalar(f64::NAN));
+        }
+        let window: Vec<f64> = returns[returns.len() - window_size..]
+            .iter()
+            .map(|point| point.close_price)
+            .collect();
+
+        let value = match self.statistic {
+            DistributionStatistic::Skewness => skewness(&window),
+            DistributionStatistic::Kurtosis => excess_kurtosis(&window),
+            DistributionStatistic::Quantile => quantile(&window, q),
+            DistributionStatistic::PercentileRank => {
+                percentile_rank(&window, window[window.len() - 1])
+            }
+            DistributionStatistic::ZScore => z_score(&window),
+        };
+        Ok(NodeOutput::Scalar(value))
+    }
+
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let (window_size, q) = Self::params(node)?;
+        let latest = self
+            .returns(node, parent_outputs)?
+            .last()
+            .map(|point| point.close_price)
+            .unwrap_or(f64::NAN);
+
+        if state.is_none() {
+            *state = Some(Box::new(DistributionExecutorState {
+                timestamp: None,
+                window: self.statistic.rolling_window(window_size),
+            }));
+        }
+        let state = state
+            .as_mut()
+            .and_then(|state| state.downcast_mut::<DistributionExecutorState>())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp == Some(timestamp) {
+            state.window.replace_last(latest);
+        } else {
+            state.window.push(latest);
+            state.timestamp = Some(timestamp);
+        }
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            statistic = ?self.statistic,
+            latest,
+            "DistributionExecutor: updated rolling window"
+        );
+
+        Ok(NodeOutput::Scalar(state.window.value(self.statistic, q)))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct RegressionDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl RegressionDefinition {
+    fn new() -> Self {
+        RegressionDefinition {
+            executor: Box::new(RegressionExecutor),
+        }
+    }
+}
+
+fn parse_factor_param(params: &HashMap<String, String>) -> Result<Vec<AssetKey>, DagError> {
+    parse_factors(params.get("factors").map(String::as_str)).map_err(DagError::InvalidOperation)
+}
+
+impl AnalyticDefinition for RegressionDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Regression
+    }
+
+    fn node_type(&self) -> &'static str {
+        "regression"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let factors = parse_factor_param(&key.params)?;
+        if !is_portfolio(key) && factors.iter().any(|factor| key.assets.first() == Some(factor)) {
+            return Err(DagError::InvalidOperation(
+                "Regression subject cannot also be a factor".to_string(),
+            ));
+        }
+        let window_size = parse_window_from_map(&key.params);
+        if window_size < factors.len() + 2 {
+            return Err(DagError::InvalidOperation(format!(
+                "Regression window of {} is too short for {} factor(s)",
+                window_size,
+                factors.len()
+            )));
+        }
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        // Factor currency entries follow those of the node's own assets
+        let mut dependencies = vec![returns_source_key(key, returns_range.clone())?];
+        for (offset, factor) in factors.into_iter().enumerate() {
+            dependencies.push(asset_returns_key(
+                key,
+                factor,
+                key.assets.len() + offset,
+                returns_range.clone(),
+            )?);
+        }
+        Ok(dependencies)
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+
+    fn outputs(&self, key: &NodeKey) -> Vec<String> {
+        let factors = parse_factor_param(&key.params).unwrap_or_default();
+        regression::output_names(&factors)
+    }
+}
+
+/// Executor for a rolling OLS regression of the subject's returns on the returns
+/// of the `factors` assets.
+///
+/// Observations are aligned on calendar date and the last `window_size` dates
+/// on which every series has a finite return are fitted. Emits alpha, one beta
+/// per factor, R², the residual of the current observation (NaN when a factor
+/// has no return for it) and the residual volatility; all NaN until the window
+/// is full or when the factors are collinear. Emits only at timestamps where
+/// the subject itself has a return.
+struct RegressionExecutor;
+
+impl AnalyticExecutor for RegressionExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let params = node_params(node);
+        let window_size = parse_window_from_map(params);
+        let factors = parse_factor_param(params)?;
+
+        let returns_of = |assets: &[AssetKey]| {
+            parent_outputs
+                .iter()
+                .find(|parent| {
+                    parent.analytic.satisfies(AnalyticType::Returns) && parent.assets == assets
+                })
+                .map(|parent| parent.output.as_ref())
+                .unwrap_or(&[])
+        };
+        let subject = returns_of(&node.assets);
+        let latest = match subject.last() {
+            Some(point) if point.timestamp == timestamp => point.timestamp.date_naive(),
+            _ => return Ok(NodeOutput::None),
+        };
+        let factor_returns: Vec<HashMap<NaiveDate, f64>> = factors
+            .iter()
+            .map(|factor| {
+                returns_of(std::slice::from_ref(factor))
+                    .iter()
+                    .map(|point| (point.timestamp.date_naive(), point.close_price))
+                    .collect()
+            })
+            .collect();
+
+        let mut ys = Vec::with_capacity(window_size);
+        let mut xs = vec![Vec::with_capacity(window_size); factors.len()];
+        let mut includes_latest = false;
+        for point in subject.iter().rev() {
+            if ys.len() == window_size {
+                break;
+            }
+            let date = point.timestamp.date_naive();
+            let row: Option<Vec<f64>> = factor_returns
+                .iter()
+                .map(|returns| returns.get(&date).copied().filter(|r| r.is_finite()))
+                .collect();
+            if let (Some(row), true) = (row, point.close_price.is_finite()) {
+                includes_latest |= date == latest;
+                ys.push(point.close_price);
+                for (x, value) in xs.iter_mut().zip(row) {
+                    x.push(value);
+                }
+            }
+        }
+        ys.reverse();
+        xs.iter_mut().for_each(|x| x.reverse());
+
+        let fit = if ys.len() == window_size {
+            regression::ols(&ys, &xs)
+        } else {
+            None
+        };
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            factors = factors.len(),
+            observations = ys.len(),
+            fitted = fit.is_some(),
+            "RegressionExecutor: fitted window"
+        );
+
+        let mut values = vec![(
+            regression::ALPHA.to_string(),
+            fit.as_ref().map_or(f64::NAN, |fit| fit.alpha),
+        )];
+        for (index, factor) in factors.iter().enumerate() {
+            let beta = fit.as_ref().map_or(f64::NAN, |fit| fit.betas[index]);
+            values.push((regression::beta_output(factor), beta));
+        }
+        let residual = fit
+            .as_ref()
+            .filter(|_| includes_latest)
+            .and_then(|fit| fit.residuals.last().copied())
+            .unwrap_or(f64::NAN);
+        values.extend([
+            (
+                regression::R_SQUARED.to_string(),
+                fit.as_ref().map_or(f64::NAN, |fit| fit.r_squared),
+            ),
+            (regression::RESIDUAL.to_string(), residual),
+            (
+                regression::RESIDUAL_VOLATILITY.to_string(),
+                fit.as_ref().map_or(f64::NAN, |fit| fit.residual_volatility()),
+            ),
+        ]);
+        Ok(NodeOutput::Named(values))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct GarchDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl GarchDefinition {
+    fn new() -> Self {
+        GarchDefinition {
+            executor: Box::new(GarchExecutor),
+        }
+    }
+}
+
+/// Calendar days certain to hold `observations` trading days: five trading
+/// days a week, one market holiday allowed per 20 trading days and a weekend
+/// for a range that starts mid-week.
+fn trading_days_span(observations: usize) -> usize {
+    (observations + observations / 20) * 7 / 5 + 3
+}
+
+/// Training window of a GARCH node (250 returns by default).
+fn parse_training_window(params: &HashMap<String, String>) -> usize {
+    parse_period(params, "window_size", 250)
+}
+
+impl AnalyticDefinition for GarchDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Garch
+    }
+
+    fn node_type(&self) -> &'static str {
+        "garch"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let window_size = parse_training_window(&key.params);
+        if window_size < garch::MIN_TRAINING_WINDOW {
+            return Err(DagError::InvalidOperation(format!(
+                "GARCH training window must be at least {} returns",
+                garch::MIN_TRAINING_WINDOW
+            )));
+        }
+        // The parameters are fitted on the first `window_size` returns fetched,
+        // so the range must reach back that many trading days, not calendar days
+        let returns_range = extend_range(&range, trading_days_span(window_size));
+
+        Ok(vec![returns_source_key(key, returns_range)?])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+
+    fn outputs(&self, _key: &NodeKey) -> Vec<String> {
+        garch::OUTPUTS.iter().map(|name| name.to_string()).collect()
+    }
+}
+
+/// Push state of a GARCH node: the filter and the timestamp of its most recent
+/// return, which a repeated update replaces.
+#[derive(Debug)]
+struct GarchExecutorState {
+    timestamp: Option<DateTime<Utc>>,
+    filter: GarchFilter,
+}
+
+/// Executor for GARCH(1,1) volatility of the subject's returns.
+///
+/// The parameters are fitted by maximum likelihood on the first `window_size`
+/// returns and the conditional variance is then updated recursively. Emits the
+/// one-step-ahead volatility, the volatility `horizon` steps ahead (default 1),
+/// the fitted parameters and the long-run volatility; NaN while training.
+///
+/// With state each update is one step of the recursion. Without state the
+/// filter is replayed over the whole return history, which gives the same
+/// result.
+struct GarchExecutor;
+
+impl GarchExecutor {
+    fn returns<'a>(
+        node: &Node,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Result<&'a [TimeSeriesPoint], DagError> {
+        parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(AnalyticType::Returns))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} requires returns data", node.node_type))
+            })
+    }
+
+    fn params(node: &Node) -> (usize, usize) {
+        let params = node_params(node);
+        let horizon = parse_period(params, "horizon", 1);
+        (parse_training_window(params), horizon)
+    }
+
+    fn output(filter: &GarchFilter, horizon: usize) -> NodeOutput {
+        let values = match filter.params() {
+            Some(params) => {
+                let next = filter.next_variance();
+                [
+                    next.sqrt(),
+                    params.forecast_variance(next, horizon).sqrt(),
+                    params.omega,
+                    params.alpha,
+                    params.beta,
+                    params.long_run_variance().sqrt(),
+                ]
+            }
+            None => [f64::NAN; 6],
+        };
+        NodeOutput::Named(
+            garch::OUTPUTS
+                .iter()
+                .zip(values)
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+}
+
+impl AnalyticExecutor for GarchExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        _timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let (window_size, horizon) = Self::params(node);
+        let mut filter = GarchFilter::new(window_size);
+        for point in Self::returns(node, parent_outputs)? {
+            filter.push(point.close_price);
+        }
+        Ok(Self::output(&filter, horizon))
+    }
+
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let (window_size, horizon) = Self::params(node);
+        let latest = Self::returns(node, parent_outputs)?
+            .last()
+            .map(|point| point.close_price)
+            .unwrap_or(f64::NAN);
+
+        if state.is_none() {
+            *state = Some(Box::new(GarchExecutorState {
+                timestamp: None,
+                filter: GarchFilter::new(window_size),
+            }));
+        }
+        let state = state
+            .as_mut()
+            .and_then(|state| state.downcast_mut::<GarchExecutorState>())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp == Some(timestamp) {
+            state.filter.replace_last(latest);
+        } else {
+            state.filter.push(latest);
+            state.timestamp = Some(timestamp);
+        }
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            latest,
+            fitted = state.filter.params().is_some(),
+            "GarchExecutor: updated conditional variance"
+        );
+
+        Ok(Self::output(&state.filter, horizon))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct PcaDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl PcaDefinition {
+    fn new() -> Self {
+        PcaDefinition {
+            executor: Box::new(PcaExecutor),
+        }
+    }
+}
+
+fn parse_component_param(
+    params: &HashMap<String, String>,
+    asset_count: usize,
+) -> Result<usize, DagError> {
+    parse_components(params.get("components").map(String::as_str), asset_count)
+        .map_err(DagError::InvalidOperation)
+}
+
+impl AnalyticDefinition for PcaDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Pca
+    }
+
+    fn node_type(&self) -> &'static str {
+        "pca"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        parse_component_param(&key.params, key.assets.len())?;
+        universe_returns_keys(key, "PCA")
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+
+    fn outputs(&self, key: &NodeKey) -> Vec<String> {
+        let components = parse_component_param(&key.params, key.assets.len()).unwrap_or(0);
+        pca::output_names(&key.assets, components)
+    }
+}
+
+/// Keys of the returns of every asset of a universe node such as PCA, over
+/// the node's range extended by its window.
+fn universe_returns_keys(key: &NodeKey, label: &str) -> Result<Vec<NodeKey>, DagError> {
+    let range = require_range(key)?;
+    if key.assets.len() < 2 {
+        return Err(DagError::InvalidOperation(format!(
+            "{} requires at least two assets",
+            label
+        )));
+    }
+    if key.assets.iter().enumerate().any(|(i, asset)| key.assets[..i].contains(asset)) {
+        return Err(DagError::InvalidOperation(format!(
+            "{} universe lists an asset twice",
+            label
+        )));
+    }
+    let window_size = parse_window_from_map(&key.params);
+    if window_size < 2 {
+        return Err(DagError::InvalidOperation(format!(
+            "{} window must hold at least two returns",
+            label
+        )));
+    }
+    let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+    key.assets
+        .iter()
+        .enumerate()
+        .map(|(index, asset)| asset_returns_key(key, asset.clone(), index, returns_range.clone()))
+        .collect()
+}
+
+/// Returns of a universe node's assets aligned on calendar date.
+struct AlignedReturns {
+    /// One column per asset over the last `window_size` dates on which every
+    /// asset has a finite return, oldest first
+    columns: Vec<Vec<f64>>,
+    /// Returns of the current date, when every asset has one
+    latest: Option<Vec<f64>>,
+}
+
+impl AlignedReturns {
+    /// Aligns the returns of `assets`, using the first asset's dates as the
+    /// calendar. `None` when no asset has a return at `timestamp`.
+    fn collect(
+        parent_outputs: &[ParentOutput],
+        assets: &[AssetKey],
+        window_size: usize,
+        timestamp: DateTime<Utc>,
+    ) -> Option<Self> {
+        let series: Vec<&[TimeSeriesPoint]> = assets
+            .iter()
+            .map(|asset| parent_series(parent_outputs, AnalyticType::Returns, asset))
+            .collect();
+        if !series
+            .iter()
+            .any(|returns| returns.last().is_some_and(|point| point.timestamp == timestamp))
+        {
+            return None;
+        }
+        let today = timestamp.date_naive();
+        let by_date: Vec<HashMap<NaiveDate, f64>> = series[1..]
+            .iter()
+            .map(|returns| {
+                returns
+                    .iter()
+                    .map(|point| (point.timestamp.date_naive(), point.close_price))
+                    .collect()
+            })
+            .collect();
+
+        let mut columns = vec![Vec::with_capacity(window_size); assets.len()];
+        let mut latest = None;
+        for point in series[0].iter().rev() {
+            if columns[0].len() == window_size {
+                break;
+            }
+            let date = point.timestamp.date_naive();
+            let row: Option<Vec<f64>> = std::iter::once(Some(point.close_price))
+                .chain(by_date.iter().map(|returns| returns.get(&date).copied()))
+                .map(|value| value.filter(|r| r.is_finite()))
+                .collect();
+            if let Some(row) = row {
+                if date == today {
+                    latest = Some(row.clone());
+                }
+                for (column, value) in columns.iter_mut().zip(row) {
+                    column.push(value);
+                }
+            }
+        }
+        columns.iter_mut().for_each(|column| column.reverse());
+        Some(AlignedReturns { columns, latest })
+    }
+
+    fn observations(&self) -> usize {
+        self.columns[0].len()
+    }
+}
+
+/// Executor for a rolling principal component analysis of the node's assets'
+/// returns.
+///
+/// Returns are aligned on calendar date and the covariance of the last
+/// `window_size` dates on which every asset has a finite return is decomposed.
+/// For each of the first `components` components it emits the factor return
+/// of the current date (NaN when an asset has no return for it), the
+/// eigenvalue, its share of total variance and the loading on each asset; all
+/// NaN until the window is full.
+struct PcaExecutor;
+
+impl AnalyticExecutor for PcaExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let params = node_params(node);
+        let window_size = parse_window_from_map(params);
+        let components = parse_component_param(params, node.assets.len())?;
+
+        let Some(aligned) =
+            AlignedReturns::collect(parent_outputs, &node.assets, window_size, timestamp)
+        else {
+            return Ok(NodeOutput::None);
+        };
+        let decomposition = if aligned.observations() == window_size {
+            pca::principal_components(&aligned.columns)
+        } else {
+            None
+        };
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            assets = node.assets.len(),
+            observations = aligned.observations(),
+            decomposed = decomposition.is_some(),
+            "PcaExecutor: decomposed window"
+        );
+
+        let mut factors = Vec::with_capacity(components);
+        let mut eigenvalues = Vec::with_capacity(components);
+        let mut ratios = Vec::with_capacity(components);
+        let mut loadings = Vec::with_capacity(components * node.assets.len());
+        for index in 0..components {
+            let number = index + 1;
+            let factor = match (&decomposition, &aligned.latest) {
+                (Some(pca), Some(returns)) => pca.factor_return(index, returns),
+                _ => f64::NAN,
+            };
+            factors.push((pca::factor_output(number), factor));
+            eigenvalues.push((
+                pca::eigenvalue_output(number),
+                decomposition.as_ref().map_or(f64::NAN, |pca| pca.eigenvalues[index]),
+            ));
+            ratios.push((
+                pca::explained_ratio_output(number),
+                decomposition.as_ref().map_or(f64::NAN, |pca| pca.explained_ratio(index)),
+            ));
+            for (position, asset) in node.assets.iter().enumerate() {
+                loadings.push((
+                    pca::loading_output(number, asset),
+                    decomposition.as_ref().map_or(f64::NAN, |pca| pca.loadings[index][position]),
+                ));
+            }
+        }
+
+        let mut values = factors;
+        values.extend(eigenvalues);
+        values.extend(ratios);
+        values.extend(loadings);
+        Ok(NodeOutput::Named(values))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct MatrixDefinition {
+    kind: MatrixKind,
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl MatrixDefinition {
+    fn new(kind: MatrixKind) -> Self {
+        MatrixDefinition {
+            kind,
+            executor: Box::new(MatrixExecutor { kind }),
+        }
+    }
+}
+
+/// Covariance estimator and EWMA decay of a matrix node (sample, 0.94 by default).
+fn parse_matrix_estimator(
+    params: &HashMap<String, String>,
+) -> Result<(MatrixEstimator, f64), DagError> {
+    let estimator = match params.get("estimator") {
+        Some(value) => MatrixEstimator::parse(value).ok_or_else(|| {
+            DagError::InvalidOperation(format!("Unknown covariance estimator '{}'", value))
+        })?,
+        None => MatrixEstimator::Sample,
+    };
+    let lambda =
+        parse_lambda(params.get("lambda").map(String::as_str)).map_err(DagError::InvalidOperation)?;
+    Ok((estimator, lambda))
+}
+
+impl AnalyticDefinition for MatrixDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        match self.kind {
+            MatrixKind::Correlation => AnalyticType::CorrelationMatrix,
+            MatrixKind::Covariance => AnalyticType::CovarianceMatrix,
+        }
+    }
+
+    fn node_type(&self) -> &'static str {
+        match self.kind {
+            MatrixKind::Correlation => "correlation_matrix",
+            MatrixKind::Covariance => "covariance_matrix",
+        }
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        parse_matrix_estimator(&key.params)?;
+        universe_returns_keys(key, "Matrix")
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+
+    fn outputs(&self, key: &NodeKey) -> Vec<String> {
+        matrix::output_names(&key.assets)
+    }
+}
+
+/// Executor for a rolling covariance or correlation matrix of the node's
+/// assets' returns.
+///
+/// Returns are aligned as for PCA and the last `window_size` shared dates are
+/// passed to the node's `estimator`. Emits every entry of the upper triangle
+/// (NaN until the window is full) and the number of dates the window holds.
+struct MatrixExecutor {
+    kind: MatrixKind,
+}
+
+impl AnalyticExecutor for MatrixExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let params = node_params(node);
+        let window_size = parse_window_from_map(params);
+        let (estimator, lambda) = parse_matrix_estimator(params)?;
+
+        let Some(aligned) =
+            AlignedReturns::collect(parent_outputs, &node.assets, window_size, timestamp)
+        else {
+            return Ok(NodeOutput::None);
+        };
+        let values = if aligned.observations() == window_size {
+            estimator
+                .covariance(&aligned.columns, lambda)
+                .map(|covariance| self.kind.from_covariance(covariance))
+        } else {
+            None
+        };
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            assets = node.assets.len(),
+            observations = aligned.observations(),
+            estimator = %estimator,
+            "MatrixExecutor: estimated {}",
+            self.kind
+        );
+
+        let mut outputs: Vec<(String, f64)> = matrix::entry_positions(node.assets.len())
+            .into_iter()
+            .map(|(i, j)| {
+                let value = values.as_ref().map_or(f64::NAN, |values| values[i][j]);
+                (matrix::entry_output(&node.assets[i], &node.assets[j]), value)
+            })
+            .collect();
+        outputs.push((matrix::OBSERVATIONS.to_string(), aligned.observations() as f64));
+        Ok(NodeOutput::Named(outputs))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct ExpressionDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl ExpressionDefinition {
+    fn new() -> Self {
+        ExpressionDefinition {
+            executor: Box::new(ExpressionExecutor),
+        }
+    }
+}
+
+/// Parses the `expression` param of a formula node.
+fn parse_formula(params: &HashMap<String, String>) -> Result<Formula, DagError> {
+    let text = params.get("expression").ok_or_else(|| {
+        DagError::InvalidOperation("expression node requires an 'expression' param".to_string())
+    })?;
+    Formula::parse(text)
+        .map_err(|e| DagError::InvalidOperation(format!("Invalid expression '{}': {}", text, e)))
+}
+
+/// Key of the node a formula reads for `reference`, on the formula's asset or
+/// portfolio.
+fn reference_key(
+    key: &NodeKey,
+    reference: &Reference,
+    range: DateRange,
+) -> Result<NodeKey, DagError> {
+    match reference.analytic {
+        AnalyticType::DataProvider => level_source_key(key, range),
+        AnalyticType::Returns => returns_source_key(key, range),
+        analytic => {
+            let mut params = portfolio_params(key, &analytic.to_string(), &range);
+            if let Some(window) = reference.window {
+                params.insert("window_size".to_string(), window.to_string());
+            }
+            Ok(NodeKey {
+                analytic,
+                assets: key.assets.clone(),
+                range: Some(range),
+                window: reference.window.map(WindowSpec::fixed),
+                override_tag: key.override_tag.clone(),
+                params,
+            })
+        }
+    }
+}
+
+impl AnalyticDefinition for ExpressionDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Expression
+    }
+
+    fn node_type(&self) -> &'static str {
+        "expression"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let formula = parse_formula(&key.params)?;
+        let range = extend_range(&require_range(key)?, formula.lookback());
+        formula
+            .references()
+            .iter()
+            .map(|reference| reference_key(key, reference, range.clone()))
+            .collect()
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// Push state of a formula node: the state of its rolling functions (`lag`,
+/// `sma`, `ema`, ...) before and after the observation at `timestamp`. Every
+/// series the formula reads fires the node, so each evaluation restarts from
+/// `previous` and only the first update of a new timestamp commits `current`.
+struct ExpressionExecutorState {
+    timestamp: Option<DateTime<Utc>>,
+    previous: FormulaState,
+    current: FormulaState,
+}
+
+/// Executor evaluating a formula over the latest value of each series it reads.
+///
+/// With state, rolling functions such as `lag` and `sma` advance one
+/// observation per timestamp. Without state the formula is replayed over the
+/// merged history of its inputs.
+struct ExpressionExecutor;
+
+impl ExpressionExecutor {
+    fn formula(node: &Node) -> Result<Formula, DagError> {
+        parse_formula(node_params(node))
+    }
+
+    /// Parent history of each series the formula reads, in reference order.
+    fn inputs<'a>(
+        formula: &Formula,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Result<Vec<&'a [TimeSeriesPoint]>, DagError> {
+        formula
+            .references()
+            .iter()
+            .map(|reference| {
+                parent_outputs
+                    .iter()
+                    .find(|parent| parent.key.as_ref().is_some_and(|key| reference.matches(key)))
+                    .map(|parent| parent.output.as_ref())
+                    .ok_or_else(|| {
+                        DagError::ExecutionError(format!(
+                            "expression has no input for '{}'",
+                            reference
+                        ))
+                    })
+            })
+            .collect()
+    }
+}
+
+impl AnalyticExecutor for ExpressionExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let formula = Self::formula(node)?;
+        let inputs = Self::inputs(&formula, parent_outputs)?;
+
+        let mut timestamps: Vec<DateTime<Utc>> = inputs
+            .iter()
+            .flat_map(|series| series.iter().map(|point| point.timestamp))
+            .filter(|ts| *ts <= timestamp)
+            .collect();
+        timestamps.sort();
+        timestamps.dedup();
+
+        let mut state = formula.state();
+        let mut cursors = vec![0; inputs.len()];
+        let mut values = vec![f64::NAN; inputs.len()];
+        let mut result = f64::NAN;
+        for ts in timestamps {
+            for (i, series) in inputs.iter().enumerate() {
+                while cursors[i] < series.len() && series[cursors[i]].timestamp <= ts {
+                    values[i] = series[cursors[i]].close_price;
+                    cursors[i] += 1;
+                }
+            }
+            result = formula.evaluate(&values, &mut state);
+        }
+        Ok(NodeOutput::Scalar(result))
+    }
+
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let formula = Self::formula(node)?;
+        let values: Vec<f64> = Self::inputs(&formula, parent_outputs)?
+            .iter()
+            .map(|series| series.last().map_or(f64::NAN, |point| point.close_price))
+            .collect();
+
+        let state = state
+            .get_or_insert_with(|| {
+                Box::new(ExpressionExecutorState {
+                    timestamp: None,
+                    previous: formula.state(),
+                    current: formula.state(),
+                })
+            })
+            .downcast_mut::<ExpressionExecutorState>()
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp != Some(timestamp) {
+            state.previous = state.current.clone();
+            state.timestamp = Some(timestamp);
+        }
+        state.current = state.previous.clone();
+        let value = formula.evaluate(&values, &mut state.current);
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            inputs = ?values,
+            value = value,
+            "ExpressionExecutor: evaluated {}",
+            formula
+        );
+
+        Ok(NodeOutput::Scalar(value))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// What a running-peak node reports about its level.
+#[derive(Debug, Clone, Copy)]
+enum PeakMeasure {
+    /// Level relative to the running peak, minus one
+    Drawdown,
+    /// Observations since the running peak was last set
+    TimeUnderWater,
+}
+
+/// Running peak of a level series and how long the level has been below it.
+#[derive(Debug, Clone, Copy, Default)]
+struct PeakTracker {
+    peak: Option<f64>,
+    periods_under_water: usize,
+}
+
+impl PeakTracker {
+    fn update(mut self, level: f64) -> Self {
+        if level.is_nan() {
+            return self;
+        }
+        match self.peak {
+            Some(peak) if level < peak => self.periods_under_water += 1,
+            _ => {
+                self.peak = Some(level);
+                self.periods_under_water = 0;
+            }
+        }
+        self
+    }
+
+    fn measure(&self, measure: PeakMeasure, level: f64) -> f64 {
+        match (self.peak, measure) {
+            (None, _) => f64::NAN,
+            (Some(peak), PeakMeasure::Drawdown) => drawdown(level, peak),
+            (Some(_), PeakMeasure::TimeUnderWater) => self.periods_under_water as f64,
+        }
+    }
+}
+
+/// Push state of a running-peak node.
+///
+/// Keeps the tracker as of the previous timestamp so that a repeated update for
+/// the current timestamp is applied to the same starting point.
+#[derive(Debug, Default)]
+struct RunningPeakState {
+    timestamp: Option<DateTime<Utc>>,
+    previous: PeakTracker,
+    current: PeakTracker,
+}
+
+/// Executor for path-dependent analytics on a price or NAV level.
+///
+/// With state, each update is O(1) against the running peak. Without state the
+/// peak is rebuilt from the full parent history.
+struct RunningPeakExecutor {
+    measure: PeakMeasure,
+}
+
+impl RunningPeakExecutor {
+    fn new(measure: PeakMeasure) -> Self {
+        RunningPeakExecutor { measure }
+    }
+
+    fn levels<'a>(
+        &self,
+        node: &Node,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Result<&'a [TimeSeriesPoint], DagError> {
+        parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(AnalyticType::DataProvider))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!(
+                    "{} requires price level input data",
+                    node.node_type
+                ))
+            })
+    }
+}
+
+impl AnalyticExecutor for RunningPeakExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        _timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let levels = self.levels(node, parent_outputs)?;
+        let tracker = levels
+            .iter()
+            .fold(PeakTracker::default(), |tracker, point| {
+                tracker.update(point.close_price)
+            });
+        let level = levels.last().map(|p| p.close_price).unwrap_or(f64::NAN);
+        Ok(NodeOutput::Scalar(tracker.measure(self.measure, level)))
+    }
+
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let level = self
+            .levels(node, parent_outputs)?
+            .last()
+            .map(|p| p.close_price)
+            .unwrap_or(f64::NAN);
+
+        let state = state
+            .get_or_insert_with(|| Box::new(RunningPeakState::default()))
+            .downcast_mut::<RunningPeakState>()
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp != Some(timestamp) {
+            state.previous = state.current;
+            state.timestamp = Some(timestamp);
+        }
+        state.current = state.previous.update(level);
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            level = level,
+            peak = ?state.current.peak,
+            "RunningPeakExecutor: updated running peak"
+        );
+
+        Ok(NodeOutput::Scalar(state.current.measure(self.measure, level)))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Dependencies of a ratio on excess returns: the subject's returns over the
+/// extended window, the subject's `dispersion` over the same window and, when
+/// `risk_free` is set, the risk-free rate series.
+fn excess_return_dependencies(
+    key: &NodeKey,
+    dispersion: AnalyticType,
+) -> Result<Vec<NodeKey>, DagError> {
+    let range = require_range(key)?;
+    let window_size = parse_window_from_map(&key.params);
+    let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+    let mut dependencies = vec![
+        returns_source_key(key, returns_range.clone())?,
+        same_subject_key(key, dispersion, range, window_size)?,
+    ];
+    if let Some(risk_free) = parse_asset_param(&key.params, "risk_free")? {
+        dependencies.push(NodeKey {
+            analytic: AnalyticType::DataProvider,
+            assets: vec![risk_free],
+            params: params_with_range("data_provider", &returns_range),
+            range: Some(returns_range),
+            window: None,
+            override_tag: key.override_tag.clone(),
+        });
+    }
+    Ok(dependencies)
+}
+
+struct SharpeRatioDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl SharpeRatioDefinition {
+    fn new() -> Self {
+        SharpeRatioDefinition {
+            executor: Box::new(RatioExecutor::new(
+                RatioReference::RiskFree(AnalyticType::Volatility),
+                sharpe_ratio,
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for SharpeRatioDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::SharpeRatio
+    }
+
+    fn node_type(&self) -> &'static str {
+        "sharpe_ratio"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        excess_return_dependencies(key, AnalyticType::Volatility)
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct SortinoRatioDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl SortinoRatioDefinition {
+    fn new() -> Self {
+        SortinoRatioDefinition {
+            executor: Box::new(RatioExecutor::new(
+                RatioReference::RiskFree(AnalyticType::DownsideDeviation),
+                sortino_ratio,
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for SortinoRatioDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::SortinoRatio
+    }
+
+    fn node_type(&self) -> &'static str {
+        "sortino_ratio"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        excess_return_dependencies(key, AnalyticType::DownsideDeviation)
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct CalmarRatioDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl CalmarRatioDefinition {
+    fn new() -> Self {
+        CalmarRatioDefinition {
+            executor: Box::new(RatioExecutor::new(
+                RatioReference::MaxDrawdown,
+                calmar_ratio,
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for CalmarRatioDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::CalmarRatio
+    }
+
+    fn node_type(&self) -> &'static str {
+        "calmar_ratio"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let window_size = parse_window_from_map(&key.params);
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        Ok(vec![
+            returns_source_key(key, returns_range)?,
+            same_subject_key(key, AnalyticType::MaxDrawdown, range, window_size)?,
+        ])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+struct InformationRatioDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl InformationRatioDefinition {
+    fn new() -> Self {
+        InformationRatioDefinition {
+            executor: Box::new(RatioExecutor::new(
+                RatioReference::Benchmark,
+                |active, _, periods_per_year| information_ratio(active, periods_per_year),
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for InformationRatioDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::InformationRatio
+    }
+
+    fn node_type(&self) -> &'static str {
+        "information_ratio"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        let range = require_range(key)?;
+        let benchmark = parse_asset_param(&key.params, "benchmark")?.ok_or_else(|| {
+            DagError::InvalidOperation("Information ratio requires a benchmark asset".to_string())
+        })?;
+        let window_size = parse_window_from_map(&key.params);
+        let returns_range = extend_range(&range, window_size.saturating_sub(1));
+
+        // The benchmark's currency entry follows those of the node's own assets
+        Ok(vec![
+            returns_source_key(key, returns_range.clone())?,
+            asset_returns_key(key, benchmark, key.assets.len(), returns_range)?,
+        ])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        self.executor.as_ref()
+    }
+}
+
+/// What a ratio measures its subject's returns against.
+#[derive(Debug, Clone, Copy)]
+enum RatioReference {
+    /// Excess over the optional `risk_free` series (annualized rates as decimals),
+    /// alongside the subject's latest value of the given dispersion analytic.
+    /// The dispersion node is the one shared by every analytic of the subject's
+    /// returns, so it measures the raw returns and a varying rate only moves the
+    /// numerator
+    RiskFree(AnalyticType),
+    /// Active returns over the `benchmark` asset's returns, aligned by date
+    Benchmark,
+    /// Raw returns, alongside the subject's latest maximum drawdown
+    MaxDrawdown,
+}
+
+type RatioComputeFn = Arc<dyn Fn(&[f64], f64, f64) -> f64 + Send + Sync>;
+
+/// Executor for ratios over a rolling window of the subject's returns.
+///
+/// Returns are adjusted for the reference series and the last `window_size`
+/// observations are handed to the compute function together with the latest
+/// value of the reference's scale node (the dispersion or maximum drawdown; NaN
+/// for a benchmark) and `periods_per_year`.
+/// Emits only at timestamps where the subject itself has a return.
+struct RatioExecutor {
+    reference: RatioReference,
+    compute_fn: RatioComputeFn,
+}
+
+impl RatioExecutor {
+    fn new(
+        reference: RatioReference,
+        compute_fn: impl Fn(&[f64], f64, f64) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        RatioExecutor {
+            reference,
+            compute_fn: Arc::new(compute_fn),
+        }
+    }
+}
+
+/// Latest value at or before `timestamp` in a chronologically ordered series.
+fn value_as_of(series: &[TimeSeriesPoint], timestamp: DateTime<Utc>) -> Option<f64> {
+    let index = series.partition_point(|point| point.timestamp <= timestamp);
+    index.checked_sub(1).map(|i| series[i].close_price)
+}
+
+impl AnalyticExecutor for RatioExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let params = node_params(node);
+        let window_size = parse_window_from_map(params);
+        let periods_per_year = parse_periods_per_year(params);
+
+        let returns = parent_outputs
+            .iter()
+            .find(|parent| {
+                parent.analytic.satisfies(AnalyticType::Returns) && parent.assets == node.assets
+            })
+            .map(|parent| parent.output.as_ref())
+            .unwrap_or(&[]);
+        match returns.last() {
+            Some(point) if point.timestamp == timestamp => {}
+            _ => return Ok(NodeOutput::None),
+        }
+        let window = &returns[returns.len().saturating_sub(window_size)..];
+
+        let latest = |analytic: AnalyticType| {
+            parent_outputs
+                .iter()
+                .find(|parent| parent.analytic.satisfies(analytic))
+                .and_then(|parent| parent.output.last())
+                .map_or(f64::NAN, |point| point.close_price)
+        };
+        let (adjusted, scale): (Vec<f64>, f64) = match self.reference {
+            RatioReference::RiskFree(dispersion) => {
+                let rates = parent_outputs
+                    .iter()
+                    .find(|parent| parent.analytic.satisfies(AnalyticType::DataProvider))
+                    .map(|parent| parent.output.as_ref());
+                let excess = window
+                    .iter()
+                    .map(|point| match rates {
+                        Some(rates) => value_as_of(rates, point.timestamp)
+                            .map(|rate| point.close_price - rate / periods_per_year)
+                            .unwrap_or(f64::NAN),
+                        None => point.close_price,
+                    })
+                    .collect();
+                (excess, latest(dispersion))
+            }
+            RatioReference::Benchmark => {
+                let benchmark = parent_outputs
+                    .iter()
+                    .find(|parent| {
+                        parent.analytic.satisfies(AnalyticType::Returns)
+                            && parent.assets != node.assets
+                    })
+                    .map(|parent| parent.output.as_ref())
+                    .unwrap_or(&[]);
+                let (xs, ys) = align_by_date(returns, benchmark, window_size);
+                let active = xs.iter().zip(ys.iter()).map(|(x, y)| x - y).collect();
+                (active, f64::NAN)
+            }
+            RatioReference::MaxDrawdown => (
+                window.iter().map(|p| p.close_price).collect(),
+                latest(AnalyticType::MaxDrawdown),
+            ),
+        };
+
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            reference = ?self.reference,
+            observations = adjusted.len(),
+            "RatioExecutor: computing ratio"
+        );
+
+        Ok(NodeOutput::Scalar((self.compute_fn)(
+            &adjusted,
+            scale,
+            periods_per_year,
+        )))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn require_range(key: &NodeKey) -> Result<DateRange, DagError> {
+    key.range
+        .clone()
+        .ok_or_else(|| DagError::InvalidOperation("Analytics node missing range".to_string()))
+}
+
+fn extend_range(range: &DateRange, burn_in_days: usize) -> DateRange {
+    if burn_in_days == 0 {
+        return range.clone();
+    }
+
+    let extra = Duration::days(burn_in_days as i64);
+    let start = range.start.checked_sub_signed(extra).unwrap_or(range.start);
+
+    DateRange::new(start, range.end)
+}
+
+struct DataProviderExecutor;
+
+impl AnalyticExecutor for DataProviderExecutor {
+    fn execute_pull(
+        &self,
+        node: &Node,
+        _parent_outputs: &[ParentOutput],
+        date_range: &DateRange,
+        provider: &dyn DataProvider,
+    ) -> Result<Vec<TimeSeriesPoint>, DagError> {
+        let asset = node.assets.first().ok_or_else(|| {
+            DagError::ExecutionError("DataProvider node has no assets".to_string())
+        })?;
+        
+        debug!(
+            node_id = node.id.0,
+            asset = %asset,
+            start_date = %date_range.start,
+            end_date = %date_range.end,
+            "DataProviderExecutor: querying time series"
+        );
+        
+        let data = provider.get_time_series(asset, date_range)?;
+        
+        debug!(
+            node_id = node.id.0,
+            asset = %asset,
+            data_point_count = data.len(),
+            "DataProviderExecutor: retrieved time series"
+        );
+        
+        Ok(data)
+    }
+
+    fn execute_push(
+        &self,
+        node: &Node,
+        _parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            value = value,
+            "DataProviderExecutor: executing push"
+        );
+        
+        Ok(NodeOutput::Single(vec![TimeSeriesPoint::new(
+            timestamp, value,
+        )]))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+struct LagDefinition {
+    executor: Box<dyn AnalyticExecutor>,
+}
+
+impl LagDefinition {
+    fn new() -> Self {
+        LagDefinition {
+            executor: Box::new(WindowedAnalyticExecutor::new(
+                AnalyticType::DataProvider,
+                |node| parse_lag_from_params(&node.params) + 1,
+                |_asset, window, window_size| {
+                    if window.len() < window_size {
+                        f64::NAN
+                    } else {
+                        window.first().copied().unwrap_or(f64::NAN)
+                    }
+                },
+            )),
+        }
+    }
+}
+
+impl AnalyticDefinition for LagDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Lag
+    }
+
+    fn node_type(&self) -> &'static str {
+        "lag"
     }
 
     fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
@@ -534,15 +3825,7 @@ impl AnalyticDefinition for LagDefinition {
         let burn_in = analytic.required_points().saturating_sub(1);
         let provider_range = extend_range(&range, burn_in);
 
-        let provider_params = params_with_range("data_provider", &provider_range);
-        Ok(vec![NodeKey {
-            analytic: AnalyticType::DataProvider,
-            assets: key.assets.clone(),
-            range: Some(provider_range),
-            window: None,
-            override_tag: key.override_tag.clone(),
-            params: provider_params,
-        }])
+        Ok(vec![price_source_key(key, 0, provider_range)?])
     }
 
     fn executor(&self) -> &dyn AnalyticExecutor {
@@ -550,10 +3833,25 @@ impl AnalyticDefinition for LagDefinition {
     }
 }
 
+type IncrementalWindowFn = Arc<dyn Fn(usize) -> Box<dyn IncrementalWindow> + Send + Sync>;
+
+/// Push state of a streaming windowed node: the window and the timestamp of its
+/// most recent observation, which a repeated update replaces.
+struct WindowedExecutorState {
+    timestamp: Option<DateTime<Utc>>,
+    window: Box<dyn IncrementalWindow>,
+}
+
+/// Executor for statistics over the last `window_size` values of one source.
+///
+/// Stateless updates slice the window out of the parent history and call
+/// `compute_fn`. When built with `streaming`, stateful updates instead keep a
+/// `StreamingWindow` that must agree with `compute_fn`, making each update O(1).
 struct WindowedAnalyticExecutor {
     source: AnalyticType,
     window_size_fn: Arc<dyn Fn(&Node) -> usize + Send + Sync>,
     compute_fn: Arc<dyn Fn(Option<&AssetKey>, &[f64], usize) -> f64 + Send + Sync>,
+    incremental_fn: Option<IncrementalWindowFn>,
 }
 
 impl WindowedAnalyticExecutor {
@@ -566,9 +3864,39 @@ impl WindowedAnalyticExecutor {
             source,
             window_size_fn: Arc::new(window_size_fn),
             compute_fn: Arc::new(compute_fn),
+            incremental_fn: None,
         }
     }
 
+    /// Maintains the window incrementally in push mode, reading the output from
+    /// an `A` accumulator with `read`.
+    fn streaming<A>(mut self, read: fn(&StreamingWindow<A>) -> f64) -> Self
+    where
+        A: WindowAccumulator + Send + Sync + 'static,
+    {
+        self.incremental_fn = Some(Arc::new(move |window_size| {
+            let window = FixedWindow::new(window_size).stream::<A>();
+            Box::new(WindowStatistic::new(window, read)) as Box<dyn IncrementalWindow>
+        }));
+        self
+    }
+
+    fn source_points<'a>(
+        &self,
+        parent_outputs: &'a [ParentOutput],
+    ) -> Result<&'a [TimeSeriesPoint], DagError> {
+        parent_outputs
+            .iter()
+            .find(|parent| parent.analytic.satisfies(self.source))
+            .map(|parent| parent.output.as_ref())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!(
+                    "Windowed analytic update requires {} input data",
+                    self.source
+                ))
+            })
+    }
+
     fn extract_values(points: &[TimeSeriesPoint]) -> Vec<f64> {
         points.iter().map(|p| p.close_price).collect()
     }
@@ -581,11 +3909,10 @@ impl WindowedAnalyticExecutor {
         }
 
         let window_size = (self.window_size_fn)(node);
-        let values = Self::extract_values(points);
+        let start = points.len().saturating_sub(window_size);
+        let window = Self::extract_values(&points[start..]);
         let asset = node.assets.first().map(|asset| asset);
-        let start = values.len().saturating_sub(window_size);
-        let window = &values[start..];
-        Ok((self.compute_fn)(asset, window, window_size))
+        Ok((self.compute_fn)(asset, &window, window_size))
     }
 }
 
@@ -605,16 +3932,7 @@ impl AnalyticExecutor for WindowedAnalyticExecutor {
             "WindowedAnalyticExecutor: executing push"
         );
         
-        let points = parent_outputs
-            .iter()
-            .find(|parent| parent.analytic == self.source)
-            .map(|parent| parent.output.as_slice())
-            .ok_or_else(|| {
-                DagError::ExecutionError(format!(
-                    "Windowed analytic update requires {} input data",
-                    self.source
-                ))
-            })?;
+        let points = self.source_points(parent_outputs)?;
 
         trace!(
             node_id = node.id.0,
@@ -633,6 +3951,59 @@ impl AnalyticExecutor for WindowedAnalyticExecutor {
         Ok(NodeOutput::Scalar(value))
     }
 
+    fn execute_push_stateful(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        let Some(incremental_fn) = &self.incremental_fn else {
+            return self.execute_push(node, parent_outputs, timestamp, value);
+        };
+        // Key the window on the source's timestamp, so a firing that brings no new
+        // source value revises the last one instead of pushing it twice
+        let latest = self
+            .source_points(parent_outputs)?
+            .last()
+            .ok_or_else(|| {
+                DagError::ExecutionError(
+                    "Windowed analytic update requires input data".to_string(),
+                )
+            })?;
+
+        if state.is_none() {
+            *state = Some(Box::new(WindowedExecutorState {
+                timestamp: None,
+                window: incremental_fn((self.window_size_fn)(node)),
+            }));
+        }
+        let state = state
+            .as_mut()
+            .and_then(|state| state.downcast_mut::<WindowedExecutorState>())
+            .ok_or_else(|| {
+                DagError::ExecutionError(format!("{} has foreign executor state", node.node_type))
+            })?;
+        if state.timestamp == Some(latest.timestamp) {
+            state.window.replace_last(latest.close_price);
+        } else {
+            state.window.push(latest.close_price);
+            state.timestamp = Some(latest.timestamp);
+        }
+
+        let value = state.window.value();
+        trace!(
+            node_id = node.id.0,
+            timestamp = %timestamp,
+            latest = latest.close_price,
+            computed_value = value,
+            "WindowedAnalyticExecutor: updated streaming window"
+        );
+
+        Ok(NodeOutput::Scalar(value))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -657,4 +4028,213 @@ mod registry_tests {
             .executor();
         assert!(vol_exec.as_any().is::<WindowedAnalyticExecutor>());
     }
+
+    #[test]
+    fn running_peak_state_replaces_repeated_timestamp() {
+        use chrono::TimeZone;
+
+        let executor = RunningPeakExecutor::new(PeakMeasure::Drawdown);
+        let node = Node::new(NodeId(1), "drawdown".to_string(), NodeParams::None, vec![]);
+        let day = |d| Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap();
+        let parent = |points: Vec<TimeSeriesPoint>| {
+            vec![ParentOutput {
+                node_id: NodeId(0),
+                analytic: AnalyticType::DataProvider,
+                assets: vec![],
+                output_name: None,
+                key: None,
+                output: points.into(),
+            }]
+        };
+
+        let mut state = None;
+        let mut push = |ts, level| {
+            let parents = parent(vec![TimeSeriesPoint::new(ts, level)]);
+            match executor
+                .execute_push_stateful(&node, &parents, ts, level, &mut state)
+                .unwrap()
+            {
+                NodeOutput::Scalar(value) => value,
+                other => panic!("unexpected output {:?}", other),
+            }
+        };
+
+        assert_eq!(push(day(1), 100.0), 0.0);
+        // A provisional high on day 2 is superseded by the final day-2 level
+        assert_eq!(push(day(2), 120.0), 0.0);
+        assert!((push(day(2), 90.0) - -0.1).abs() < 1e-12);
+        assert!((push(day(3), 95.0) - -0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn streaming_windows_match_stateless_updates() {
+        use chrono::TimeZone;
+
+        let registry = AnalyticRegistry::new();
+        let mut params = HashMap::new();
+        params.insert("window_size".to_string(), "3".to_string());
+        let day = |d| Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap();
+        let values = [f64::NAN, 0.01, -0.02, 0.03, 0.015, -0.01, 0.02];
+
+        for (analytic, source) in [
+            (AnalyticType::Volatility, AnalyticType::Returns),
+            (AnalyticType::Sma, AnalyticType::DataProvider),
+        ] {
+            let executor = registry.definition(analytic).unwrap().executor();
+            let node = Node::new(
+                NodeId(1),
+                analytic.to_string(),
+                NodeParams::Map(params.clone()),
+                vec![],
+            );
+            let mut history = Vec::new();
+            let mut state = None;
+            for (i, &value) in values.iter().enumerate() {
+                let ts = day(i as u32 + 1);
+                // Each day is first pushed with a provisional value, then revised
+                for value in [value + 1.0, value] {
+                    if history.last().is_some_and(|p: &TimeSeriesPoint| p.timestamp == ts) {
+                        history.pop();
+                    }
+                    history.push(TimeSeriesPoint::new(ts, value));
+                    let parents = vec![ParentOutput {
+                        node_id: NodeId(0),
+                        analytic: source,
+                        assets: vec![],
+                        output_name: None,
+                        key: None,
+                        output: history.as_slice().into(),
+                    }];
+                    let stateless = executor.execute_push(&node, &parents, ts, value).unwrap();
+                    let streamed = executor
+                        .execute_push_stateful(&node, &parents, ts, value, &mut state)
+                        .unwrap();
+                    match (stateless, streamed) {
+                        (NodeOutput::Scalar(a), NodeOutput::Scalar(b)) => assert!(
+                            (a - b).abs() < 1e-12 || (a.is_nan() && b.is_nan()),
+                            "{analytic} on day {}: {a} != {b}",
+                            i + 1
+                        ),
+                        other => panic!("unexpected outputs {:?}", other),
+                    }
+                }
+            }
+            assert!(state.unwrap().is::<WindowedExecutorState>());
+        }
+    }
+
+    /// Reads the MACD signal line of its asset.
+    struct SignalReaderDefinition {
+        executor: SignalReaderExecutor,
+    }
+
+    struct SignalReaderExecutor;
+
+    impl AnalyticDefinition for SignalReaderDefinition {
+        fn analytic_type(&self) -> AnalyticType {
+            AnalyticType::Sma
+        }
+
+        fn node_type(&self) -> &'static str {
+            "signal_reader"
+        }
+
+        fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+            let params = [("fast", "2"), ("slow", "4"), ("signal", "3"), ("output", "signal")];
+            Ok(vec![NodeKey {
+                analytic: AnalyticType::Macd,
+                assets: key.assets.clone(),
+                range: key.range.clone(),
+                window: None,
+                override_tag: None,
+                params: params
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            }])
+        }
+
+        fn executor(&self) -> &dyn AnalyticExecutor {
+            &self.executor
+        }
+    }
+
+    impl AnalyticExecutor for SignalReaderExecutor {
+        fn execute_push(
+            &self,
+            _node: &Node,
+            parent_outputs: &[ParentOutput],
+            _timestamp: DateTime<Utc>,
+            _value: f64,
+        ) -> Result<NodeOutput, DagError> {
+            let parent = &parent_outputs[0];
+            assert_eq!(parent.output_name.as_deref(), Some("signal"));
+            Ok(NodeOutput::Scalar(
+                parent.output.last().map_or(f64::NAN, |p| p.close_price),
+            ))
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn dependencies_read_a_selected_output() {
+        use crate::dag::AnalyticsDag;
+        use crate::time_series::InMemoryDataProvider;
+        use chrono::{Duration, NaiveDate, TimeZone};
+        use std::sync::Arc;
+
+        let asset = AssetKey::new_equity("AAA").unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 16, 0, 0).unwrap();
+        let mut provider = InMemoryDataProvider::new();
+        provider.add_data(
+            asset.clone(),
+            [100.0, 102.0, 101.0, 104.0, 103.0, 99.0]
+                .iter()
+                .enumerate()
+                .map(|(i, &p)| TimeSeriesPoint::new(start + Duration::days(i as i64), p))
+                .collect(),
+        );
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(),
+        );
+
+        let mut registry = AnalyticRegistry::new();
+        registry.definitions.insert(
+            AnalyticType::Sma,
+            Box::new(SignalReaderDefinition {
+                executor: SignalReaderExecutor,
+            }),
+        );
+        let mut dag = AnalyticsDag::new_with_registry(Arc::new(registry));
+        let reader = dag
+            .resolve_node(NodeKey {
+                analytic: AnalyticType::Sma,
+                assets: vec![asset],
+                range: Some(range.clone()),
+                window: None,
+                override_tag: None,
+                params: HashMap::new(),
+            })
+            .unwrap();
+        let macd = dag.get_parents(reader)[0];
+        assert_eq!(dag.edge_outputs(macd, reader), ["signal".to_string()]);
+
+        let read = dag.execute_pull_mode(reader, range.clone(), &provider).unwrap();
+        let outputs = dag.execute_pull_mode_outputs(macd, range, &provider).unwrap();
+        assert_eq!(read, outputs["signal"]);
+        assert_ne!(read, outputs["line"]);
+    }
+
+    #[test]
+    fn unknown_analytic_names_are_not_interned() {
+        let registry = AnalyticRegistry::new();
+        for name in ["never_registered", " Never_Registered "] {
+            assert_eq!(registry.analytic_type(name), AnalyticType::DataProvider);
+        }
+        assert!(!CustomAnalytic::is_interned("never_registered"));
+    }
 }
@@ -0,0 +1,236 @@
+//! Ordinary least squares regression of returns on factor returns.
+//!
+//! A regression node explains its subject's returns as
+//! `alpha + sum(beta_i * factor_i) + residual` over a rolling window, and
+//! reports the intercept, one beta per factor, R² and the residuals as named
+//! outputs.
+
+use crate::asset_key::AssetKey;
+
+/// Output holding the regression intercept.
+pub const ALPHA: &str = "alpha";
+/// Output holding the coefficient of determination.
+pub const R_SQUARED: &str = "r_squared";
+/// Output holding the residual of the latest observation.
+pub const RESIDUAL: &str = "residual";
+/// Output holding the population standard deviation of the window's residuals.
+pub const RESIDUAL_VOLATILITY: &str = "residual_volatility";
+
+/// Least squares fit of one window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OlsFit {
+    /// Intercept
+    pub alpha: f64,
+    /// Slope on each factor, in factor order
+    pub betas: Vec<f64>,
+    /// Share of the dependent variable's variance the factors explain; NaN when
+    /// the dependent variable is constant
+    pub r_squared: f64,
+    /// Observed minus fitted value, one per observation
+    pub residuals: Vec<f64>,
+}
+
+impl OlsFit {
+    /// Population standard deviation of the residuals.
+    pub fn residual_volatility(&self) -> f64 {
+        if self.residuals.is_empty() {
+            return f64::NAN;
+        }
+        let n = self.residuals.len() as f64;
+        let mean = self.residuals.iter().sum::<f64>() / n;
+        let variance = self
+            .residuals
+            .iter()
+            .map(|r| (r - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        variance.sqrt()
+    }
+}
+
+/// Regresses `y` on the `factors` (each as long as `y`) with an intercept.
+///
+/// Returns `None` when there are fewer observations than coefficients, the
+/// series lengths differ, or the factors are collinear.
+pub fn ols(y: &[f64], factors: &[Vec<f64>]) -> Option<OlsFit> {
+    let n = y.len();
+    let k = factors.len();
+    if n < k + 1 || factors.iter().any(|factor| factor.len() != n) {
+        return None;
+    }
+
+    // Centring removes the intercept from the normal equations
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / n as f64;
+    let y_mean = mean(y);
+    let x_means: Vec<f64> = factors.iter().map(|factor| mean(factor)).collect();
+    let centred: Vec<Vec<f64>> = factors
+        .iter()
+        .zip(x_means.iter())
+        .map(|(factor, m)| factor.iter().map(|x| x - m).collect())
+        .collect();
+    let y_centred: Vec<f64> = y.iter().map(|v| v - y_mean).collect();
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+
+    let mut system: Vec<Vec<f64>> = (0..k)
+        .map(|i| {
+            let mut row: Vec<f64> = (0..k).map(|j| dot(&centred[i], &centred[j])).collect();
+            row.push(dot(&centred[i], &y_centred));
+            row
+        })
+        .collect();
+    let betas = solve(&mut system)?;
+
+    let alpha = y_mean
+        - betas
+            .iter()
+            .zip(x_means.iter())
+            .map(|(beta, m)| beta * m)
+            .sum::<f64>();
+    let residuals: Vec<f64> = (0..n)
+        .map(|t| {
+            let fitted = alpha
+                + betas
+                    .iter()
+                    .zip(factors.iter())
+                    .map(|(beta, factor)| beta * factor[t])
+                    .sum::<f64>();
+            y[t] - fitted
+        })
+        .collect();
+
+    let total = dot(&y_centred, &y_centred);
+    let r_squared = if total > 0.0 {
+        1.0 - dot(&residuals, &residuals) / total
+    } else {
+        f64::NAN
+    };
+
+    Some(OlsFit {
+        alpha,
+        betas,
+        r_squared,
+        residuals,
+    })
+}
+
+/// Solves an augmented `k x (k + 1)` linear system by Gaussian elimination with
+/// partial pivoting. `None` when the system is singular.
+fn solve(system: &mut [Vec<f64>]) -> Option<Vec<f64>> {
+    let k = system.len();
+    let scale = system
+        .iter()
+        .flat_map(|row| row[..k].iter())
+        .fold(0.0_f64, |acc, v| acc.max(v.abs()));
+    let tolerance = scale * 1e-12;
+
+    for col in 0..k {
+        let pivot = (col..k).max_by(|&a, &b| {
+            system[a][col]
+                .abs()
+                .partial_cmp(&system[b][col].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        let magnitude = system[pivot][col].abs();
+        if magnitude.is_nan() || magnitude <= tolerance {
+            return None;
+        }
+        system.swap(col, pivot);
+        let (upper, lower) = system.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut solution = vec![0.0; k];
+    for row in (0..k).rev() {
+        let known: f64 = (row + 1..k).map(|j| system[row][j] * solution[j]).sum();
+        solution[row] = (system[row][k] - known) / system[row][row];
+    }
+    Some(solution)
+}
+
+/// Parses the comma-separated `factors` parameter into distinct assets.
+pub fn parse_factors(value: Option<&str>) -> Result<Vec<AssetKey>, String> {
+    let value = value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| "Regression requires at least one factor asset".to_string())?;
+
+    let mut factors: Vec<AssetKey> = Vec::new();
+    for ticker in value.split(',').map(str::trim) {
+        let factor = AssetKey::parse(ticker)
+            .map_err(|e| format!("Invalid factor asset '{}': {}", ticker, e))?;
+        if factors.contains(&factor) {
+            return Err(format!("Factor asset '{}' is listed twice", ticker));
+        }
+        factors.push(factor);
+    }
+    Ok(factors)
+}
+
+/// Output holding the beta on `factor`, e.g. `beta_spy`.
+pub fn beta_output(factor: &AssetKey) -> String {
+    format!("beta_{}", factor.to_string().to_lowercase())
+}
+
+/// Every output of a regression on `factors`, intercept first.
+pub fn output_names(factors: &[AssetKey]) -> Vec<String> {
+    let mut names = vec![ALPHA.to_string()];
+    names.extend(factors.iter().map(beta_output));
+    names.extend([R_SQUARED, RESIDUAL, RESIDUAL_VOLATILITY].map(str::to_string));
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ols_recovers_exact_linear_relationship() {
+        let x1 = vec![0.01, -0.02, 0.015, 0.003, -0.007, 0.02];
+        let x2 = vec![0.002, 0.01, -0.004, 0.006, 0.001, -0.012];
+        let y: Vec<f64> = x1
+            .iter()
+            .zip(x2.iter())
+            .map(|(a, b)| 0.001 + 1.5 * a - 0.5 * b)
+            .collect();
+
+        let fit = ols(&y, &[x1, x2]).unwrap();
+        assert!((fit.alpha - 0.001).abs() < 1e-12);
+        assert!((fit.betas[0] - 1.5).abs() < 1e-10);
+        assert!((fit.betas[1] + 0.5).abs() < 1e-10);
+        assert!((fit.r_squared - 1.0).abs() < 1e-10);
+        assert!(fit.residual_volatility() < 1e-12);
+    }
+
+    #[test]
+    fn ols_rejects_collinear_and_short_windows() {
+        let x = vec![0.01, 0.02, 0.03];
+        let doubled: Vec<f64> = x.iter().map(|v| v * 2.0).collect();
+        assert!(ols(&[0.1, 0.2, 0.4], &[x.clone(), doubled]).is_none());
+        assert!(ols(&[0.1], &[vec![0.01]]).is_none());
+        assert!(ols(&[0.1, 0.2], &[x]).is_none());
+    }
+
+    #[test]
+    fn factors_parse_into_named_outputs() {
+        let factors = parse_factors(Some("SPY, XLK")).unwrap();
+        assert_eq!(
+            output_names(&factors),
+            [
+                "alpha",
+                "beta_spy",
+                "beta_xlk",
+                "r_squared",
+                "residual",
+                "residual_volatility"
+            ]
+        );
+        assert!(parse_factors(None).is_err());
+        assert!(parse_factors(Some("SPY,SPY")).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! Value-at-Risk and Expected Shortfall.
+//!
+//! Both measures are reported as positive losses in return units: a 95% VaR of
+//! 0.02 means returns are expected to fall below -2% on 5% of observations, and
+//! the Expected Shortfall is the average loss on those observations.
+
+use crate::analytics::calculators::{excess_kurtosis, population_std_dev, skewness};
+use std::f64::consts::PI;
+use std::fmt;
+
+/// How the loss distribution is estimated from a window of returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VarMethod {
+    /// Empirical quantile of the observed returns
+    Historical,
+    /// Gaussian with the window's mean and standard deviation
+    Parametric,
+    /// Gaussian quantile adjusted for the window's skewness and excess kurtosis
+    CornishFisher,
+}
+
+impl VarMethod {
+    /// Parses a method name ("historical", "parametric", "cornish_fisher").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "historical" | "" => Some(VarMethod::Historical),
+            "parametric" | "gaussian" => Some(VarMethod::Parametric),
+            "cornish_fisher" | "cornishfisher" => Some(VarMethod::CornishFisher),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VarMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            VarMethod::Historical => "historical",
+            VarMethod::Parametric => "parametric",
+            VarMethod::CornishFisher => "cornish_fisher",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+/// Parses a confidence level, which must lie strictly between 0 and 1.
+pub fn parse_confidence(value: Option<&str>) -> Result<f64, String> {
+    let confidence = match value {
+        None => return Ok(0.95),
+        Some(value) => value
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid confidence level '{}'", value.trim()))?,
+    };
+    if confidence > 0.0 && confidence < 1.0 {
+        Ok(confidence)
+    } else {
+        Err(format!(
+            "Confidence level must be between 0 and 1, got {}",
+            confidence
+        ))
+    }
+}
+
+/// Value-at-Risk of a window of returns at `confidence`.
+///
+/// NaN returns are skipped. Historical VaR needs one valid return, the
+/// distributional methods two (Cornish-Fisher falls back to NaN below four).
+pub fn value_at_risk(returns: &[f64], confidence: f64, method: VarMethod) -> f64 {
+    let tail = 1.0 - confidence;
+    match method {
+        VarMethod::Historical => match tail_losses(returns, tail) {
+            Some(losses) => losses[losses.len() - 1],
+            None => f64::NAN,
+        },
+        VarMethod::Parametric => match mean_and_std(returns) {
+            Some((mean, std_dev)) => -(mean + std_dev * normal_quantile(tail)),
+            None => f64::NAN,
+        },
+        VarMethod::CornishFisher => match cornish_fisher_params(returns) {
+            Some(params) => params.value_at_risk(tail),
+            None => f64::NAN,
+        },
+    }
+}
+
+/// Expected Shortfall (average loss beyond the VaR) of a window of returns.
+///
+/// The Cornish-Fisher variant averages the adjusted VaR over the tail levels.
+pub fn expected_shortfall(returns: &[f64], confidence: f64, method: VarMethod) -> f64 {
+    let tail = 1.0 - confidence;
+    match method {
+        VarMethod::Historical => match tail_losses(returns, tail) {
+            Some(losses) => losses.iter().sum::<f64>() / losses.len() as f64,
+            None => f64::NAN,
+        },
+        VarMethod::Parametric => match mean_and_std(returns) {
+            Some((mean, std_dev)) => -mean + std_dev * normal_pdf(normal_quantile(tail)) / tail,
+            None => f64::NAN,
+        },
+        VarMethod::CornishFisher => match cornish_fisher_params(returns) {
+            Some(params) => {
+                const STEPS: usize = 1000;
+                (0..STEPS)
+                    .map(|i| params.value_at_risk(tail * (i as f64 + 0.5) / STEPS as f64))
+                    .sum::<f64>()
+                    / STEPS as f64
+            }
+            None => f64::NAN,
+        },
+    }
+}
+
+/// The worst `ceil(tail * n)` returns as positive losses, largest first.
+fn tail_losses(returns: &[f64], tail: f64) -> Option<Vec<f64>> {
+    let mut losses: Vec<f64> = returns.iter().filter(|r| !r.is_nan()).map(|r| -r).collect();
+    if losses.is_empty() {
+        return None;
+    }
+    losses.sort_by(|a, b| b.total_cmp(a));
+    let count = ((tail * losses.len() as f64).ceil() as usize).clamp(1, losses.len());
+    losses.truncate(count);
+    Some(losses)
+}
+
+fn mean_and_std(returns: &[f64]) -> Option<(f64, f64)> {
+    let valid: Vec<f64> = returns.iter().copied().filter(|r| !r.is_nan()).collect();
+    if valid.len() < 2 {
+        return None;
+    }
+    let mean = valid.iter().sum::<f64>() / valid.len() as f64;
+    Some((mean, population_std_dev(&valid)))
+}
+
+struct CornishFisher {
+    mean: f64,
+    std_dev: f64,
+    skew: f64,
+    kurtosis: f64,
+}
+
+impl CornishFisher {
+    fn value_at_risk(&self, tail: f64) -> f64 {
+        let z = normal_quantile(tail);
+        let (s, k) = (self.skew, self.kurtosis);
+        let adjusted = z + (z * z - 1.0) * s / 6.0 + (z.powi(3) - 3.0 * z) * k / 24.0
+            - (2.0 * z.powi(3) - 5.0 * z) * s * s / 36.0;
+        -(self.mean + self.std_dev * adjusted)
+    }
+}
+
+fn cornish_fisher_params(returns: &[f64]) -> Option<CornishFisher> {
+    let (mean, std_dev) = mean_and_std(returns)?;
+    let skew = skewness(returns);
+    let kurtosis = excess_kurtosis(returns);
+    if skew.is_nan() || kurtosis.is_nan() {
+        return None;
+    }
+    Some(CornishFisher {
+        mean,
+        std_dev,
+        skew,
+        kurtosis,
+    })
+}
+
+/// Standard normal density.
+pub fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+/// Inverse of the standard normal CDF (Acklam's rational approximation,
+/// relative error below 1.2e-9).
+pub fn normal_quantile(p: f64) -> f64 {
+    if !(p > 0.0 && p < 1.0) {
+        return f64::NAN;
+    }
+
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETURNS: [f64; 10] = [
+        0.01, -0.02, 0.015, -0.005, 0.002, -0.03, 0.012, 0.004, -0.011, 0.008,
+    ];
+
+    #[test]
+    fn normal_quantile_matches_known_values() {
+        assert!((normal_quantile(0.05) + 1.6448536269514729).abs() < 1e-8);
+        assert!((normal_quantile(0.99) - 2.3263478740408408).abs() < 1e-8);
+        assert!(normal_quantile(0.5).abs() < 1e-12);
+        assert!(normal_quantile(0.0).is_nan());
+    }
+
+    #[test]
+    fn historical_measures_use_worst_observations() {
+        // 10% tail of ten returns: the single worst observation
+        assert!((value_at_risk(&RETURNS, 0.9, VarMethod::Historical) - 0.03).abs() < 1e-12);
+        // 20% tail: -0.03 and -0.02
+        assert!((value_at_risk(&RETURNS, 0.8, VarMethod::Historical) - 0.02).abs() < 1e-12);
+        assert!((expected_shortfall(&RETURNS, 0.8, VarMethod::Historical) - 0.025).abs() < 1e-12);
+        assert!(value_at_risk(&[f64::NAN], 0.95, VarMethod::Historical).is_nan());
+    }
+
+    #[test]
+    fn parametric_measures_follow_gaussian_formulas() {
+        let mean = RETURNS.iter().sum::<f64>() / RETURNS.len() as f64;
+        let std_dev = population_std_dev(&RETURNS);
+        let z = normal_quantile(0.05);
+
+        let var = value_at_risk(&RETURNS, 0.95, VarMethod::Parametric);
+        assert!((var - (-mean - std_dev * z)).abs() < 1e-12);
+        let es = expected_shortfall(&RETURNS, 0.95, VarMethod::Parametric);
+        assert!(es > var);
+    }
+
+    #[test]
+    fn cornish_fisher_reduces_to_gaussian_without_higher_moments() {
+        let symmetric = [-0.02, -0.01, 0.0, 0.01, 0.02];
+        let params = CornishFisher {
+            mean: 0.0,
+            std_dev: population_std_dev(&symmetric),
+            skew: 0.0,
+            kurtosis: 0.0,
+        };
+        let gaussian = value_at_risk(&symmetric, 0.95, VarMethod::Parametric);
+        assert!((params.value_at_risk(0.05) - gaussian).abs() < 1e-12);
+
+        let es = expected_shortfall(&RETURNS, 0.95, VarMethod::CornishFisher);
+        assert!(es > value_at_risk(&RETURNS, 0.95, VarMethod::CornishFisher));
+    }
+
+    #[test]
+    fn methods_and_confidence_parse() {
+        assert_eq!(
+            VarMethod::parse("Cornish_Fisher"),
+            Some(VarMethod::CornishFisher)
+        );
+        assert_eq!(VarMethod::parse("monte_carlo"), None);
+        assert_eq!(parse_confidence(None), Ok(0.95));
+        assert_eq!(parse_confidence(Some("0.99")), Ok(0.99));
+        assert!(parse_confidence(Some("1.0")).is_err());
+        assert!(parse_confidence(Some("high")).is_err());
+    }
+}
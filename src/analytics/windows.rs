@@ -2,6 +2,12 @@
 //!
 //! These structs implement `WindowStrategy` so the DAG can reason about burn-in
 //! requirements without knowing the specific analytic primitive.
+//!
+//! `StreamingWindow` is the push-mode counterpart of `FixedWindow`: rather than
+//! re-slicing the window on every update it feeds a `WindowAccumulator` the
+//! values that enter and leave, so each update costs O(1).
+
+use std::collections::VecDeque;
 
 /// Common behavior shared by every windowing strategy.
 pub trait WindowStrategy {
@@ -46,6 +52,16 @@ impl FixedWindow {
 
         result
     }
+
+    /// Starts an empty streaming window of the same size whose statistic, read
+    /// after each push, matches `apply` on the same data.
+    pub fn stream<A: WindowAccumulator>(&self) -> StreamingWindow<A> {
+        StreamingWindow {
+            size: self.size,
+            values: VecDeque::with_capacity(self.size),
+            accumulator: A::default(),
+        }
+    }
 }
 
 impl WindowStrategy for FixedWindow {
@@ -54,6 +70,316 @@ impl WindowStrategy for FixedWindow {
     }
 }
 
+/// A statistic maintained incrementally as values enter and leave a window.
+///
+/// NaN values occupy a slot in the window but are ignored by the statistic, as
+/// the batch primitives ignore them.
+pub trait WindowAccumulator: Default {
+    /// Adds the value entering the window at the newest end.
+    fn add(&mut self, value: f64);
+
+    /// Removes the oldest value as it leaves the window.
+    fn evict(&mut self, value: f64);
+
+    /// Removes the newest value so it can be replaced. `remaining` holds the
+    /// values still in the window; the default rebuilds the statistic from them.
+    fn retract(&mut self, _value: f64, remaining: &VecDeque<f64>) {
+        *self = Self::default();
+        for &value in remaining {
+            self.add(value);
+        }
+    }
+
+    /// The statistic over the values currently in the window.
+    fn value(&self) -> f64;
+}
+
+/// Running sum of the window; `mean` gives the moving average.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunningSum {
+    sum: f64,
+    count: usize,
+}
+
+impl RunningSum {
+    /// Number of non-NaN values in the window.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Mean of the window, NaN when it holds no values.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+
+    fn remove(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count -= 1;
+        // Reset rather than carry rounding error into the next window
+        self.sum = if self.count == 0 {
+            0.0
+        } else {
+            self.sum - value
+        };
+    }
+}
+
+impl WindowAccumulator for RunningSum {
+    fn add(&mut self, value: f64) {
+        if !value.is_nan() {
+            self.sum += value;
+            self.count += 1;
+        }
+    }
+
+    fn evict(&mut self, value: f64) {
+        self.remove(value);
+    }
+
+    fn retract(&mut self, value: f64, _remaining: &VecDeque<f64>) {
+        self.remove(value);
+    }
+
+    fn value(&self) -> f64 {
+        self.sum
+    }
+}
+
+/// Welford's online mean and variance, extended to remove values.
+///
+/// `value` is the population standard deviation, as `population_std_dev`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WelfordVariance {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl WelfordVariance {
+    /// Mean of the window, NaN when it holds no values.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.mean
+        }
+    }
+
+    /// Population variance of the window, NaN when it holds no values.
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            // Removal can leave a tiny negative residue for a constant window
+            (self.m2 / self.count as f64).max(0.0)
+        }
+    }
+
+    /// Population standard deviation of the window.
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    fn remove(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if self.count <= 1 {
+            *self = WelfordVariance::default();
+            return;
+        }
+        let delta = value - self.mean;
+        self.count -= 1;
+        self.mean -= delta / self.count as f64;
+        self.m2 -= delta * (value - self.mean);
+    }
+}
+
+impl WindowAccumulator for WelfordVariance {
+    fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn evict(&mut self, value: f64) {
+        self.remove(value);
+    }
+
+    fn retract(&mut self, value: f64, _remaining: &VecDeque<f64>) {
+        self.remove(value);
+    }
+
+    fn value(&self) -> f64 {
+        self.std_dev()
+    }
+}
+
+/// Monotonic deque holding the candidates for the window maximum (`MAX`) or
+/// minimum. Each value is pushed and popped at most once, so updates are
+/// amortised O(1); retracting the newest value rebuilds from the window.
+#[derive(Debug, Clone, Default)]
+pub struct MonotonicDeque<const MAX: bool> {
+    /// (position, value), best candidate at the front
+    candidates: VecDeque<(u64, f64)>,
+    added: u64,
+    evicted: u64,
+}
+
+/// Rolling maximum of a window.
+pub type RollingMax = MonotonicDeque<true>;
+/// Rolling minimum of a window.
+pub type RollingMin = MonotonicDeque<false>;
+
+impl<const MAX: bool> WindowAccumulator for MonotonicDeque<MAX> {
+    fn add(&mut self, value: f64) {
+        let position = self.added;
+        self.added += 1;
+        if value.is_nan() {
+            return;
+        }
+        while let Some(&(_, back)) = self.candidates.back() {
+            let dominated = if MAX { back <= value } else { back >= value };
+            if !dominated {
+                break;
+            }
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((position, value));
+    }
+
+    fn evict(&mut self, _value: f64) {
+        if self
+            .candidates
+            .front()
+            .is_some_and(|&(position, _)| position == self.evicted)
+        {
+            self.candidates.pop_front();
+        }
+        self.evicted += 1;
+    }
+
+    fn value(&self) -> f64 {
+        self.candidates
+            .front()
+            .map(|&(_, value)| value)
+            .unwrap_or(f64::NAN)
+    }
+}
+
+/// Fixed-size window updated one value at a time, with a `WindowAccumulator`
+/// kept in step. Like `FixedWindow::apply`, the window is partial until `size`
+/// values have been pushed.
+#[derive(Debug, Clone)]
+pub struct StreamingWindow<A> {
+    size: usize,
+    values: VecDeque<f64>,
+    accumulator: A,
+}
+
+impl<A: WindowAccumulator> StreamingWindow<A> {
+    /// Appends a value, evicting the oldest once the window is full.
+    pub fn push(&mut self, value: f64) {
+        if self.values.len() == self.size {
+            if let Some(oldest) = self.values.pop_front() {
+                self.accumulator.evict(oldest);
+            }
+        }
+        self.values.push_back(value);
+        self.accumulator.add(value);
+    }
+
+    /// Replaces the newest value, e.g. a revised update for the same timestamp.
+    /// Pushes when the window is empty.
+    pub fn replace_last(&mut self, value: f64) {
+        if let Some(newest) = self.values.pop_back() {
+            self.accumulator.retract(newest, &self.values);
+        }
+        self.values.push_back(value);
+        self.accumulator.add(value);
+    }
+
+    /// The accumulator for the current window.
+    pub fn accumulator(&self) -> &A {
+        &self.accumulator
+    }
+
+    /// The accumulator's statistic for the current window.
+    pub fn value(&self) -> f64 {
+        self.accumulator.value()
+    }
+
+    /// Number of values in the window, NaN included.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true before the first push.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns true once the window holds `size` values.
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.size
+    }
+}
+
+impl<A> WindowStrategy for StreamingWindow<A> {
+    fn burn_in(&self) -> usize {
+        self.size
+    }
+}
+
+/// Object-safe handle on a streaming window and how its output is read, so an
+/// executor can hold one without naming the accumulator type.
+pub trait IncrementalWindow: Send + Sync {
+    /// Appends a value, evicting the oldest once the window is full.
+    fn push(&mut self, value: f64);
+
+    /// Replaces the newest value.
+    fn replace_last(&mut self, value: f64);
+
+    /// The output for the current window.
+    fn value(&self) -> f64;
+}
+
+/// A `StreamingWindow` paired with the function that reads its output.
+pub struct WindowStatistic<A> {
+    window: StreamingWindow<A>,
+    read: fn(&StreamingWindow<A>) -> f64,
+}
+
+impl<A: WindowAccumulator> WindowStatistic<A> {
+    pub fn new(window: StreamingWindow<A>, read: fn(&StreamingWindow<A>) -> f64) -> Self {
+        WindowStatistic { window, read }
+    }
+}
+
+impl<A: WindowAccumulator + Send + Sync> IncrementalWindow for WindowStatistic<A> {
+    fn push(&mut self, value: f64) {
+        self.window.push(value);
+    }
+
+    fn replace_last(&mut self, value: f64) {
+        self.window.replace_last(value);
+    }
+
+    fn value(&self) -> f64 {
+        (self.read)(&self.window)
+    }
+}
+
 /// Exponential smoothing window that depends on the previous output.
 #[derive(Debug, Clone, Copy)]
 pub struct ExponentialWindow {
@@ -101,10 +427,55 @@ impl WindowStrategy for ExponentialWindow {
 
 #[cfg(test)]
 mod tests {
-    use super::super::calculators::ema_step;
+    use super::super::calculators::{ema_step, population_std_dev};
+    use super::super::indicators::sma;
     use super::ExponentialWindow;
     use super::FixedWindow;
+    use super::WindowAccumulator;
     use super::WindowStrategy;
+    use super::{RollingMax, RollingMin, RunningSum, StreamingWindow, WelfordVariance};
+
+    const DATA: [f64; 12] = [
+        3.0,
+        1.5,
+        f64::NAN,
+        4.0,
+        4.0,
+        2.5,
+        -1.0,
+        6.0,
+        6.0,
+        0.5,
+        f64::NAN,
+        2.0,
+    ];
+
+    fn assert_close(got: &[f64], want: &[f64]) {
+        assert_eq!(got.len(), want.len());
+        for (g, w) in got.iter().zip(want.iter()) {
+            assert!(
+                (g - w).abs() < 1e-12 || (g.is_nan() && w.is_nan()),
+                "{g} != {w}"
+            );
+        }
+    }
+
+    /// Streams `data` through a window, revising each value once before
+    /// settling on it so `replace_last` is exercised alongside `push`.
+    fn stream<A: WindowAccumulator>(
+        size: usize,
+        data: &[f64],
+        read: fn(&StreamingWindow<A>) -> f64,
+    ) -> Vec<f64> {
+        let mut window = FixedWindow::new(size).stream::<A>();
+        data.iter()
+            .map(|&value| {
+                window.push(value * 10.0 - 7.0);
+                window.replace_last(value);
+                read(&window)
+            })
+            .collect()
+    }
 
     #[test]
     fn fixed_window_applies_primitive_to_subarrays() {
@@ -124,4 +495,55 @@ mod tests {
         assert!((result[0] - 10.0).abs() < 1e-12);
         assert!(window.burn_in() == 5);
     }
+
+    #[test]
+    fn streaming_accumulators_match_fixed_window_apply() {
+        for size in [1, 3, 5, 20] {
+            let window = FixedWindow::new(size);
+            assert_close(
+                &stream::<RunningSum>(size, &DATA, |w| w.accumulator().mean()),
+                &window.apply(&DATA, sma),
+            );
+            assert_close(
+                &stream::<WelfordVariance>(size, &DATA, StreamingWindow::value),
+                &window.apply(&DATA, population_std_dev),
+            );
+            let max = |slice: &[f64]| {
+                slice
+                    .iter()
+                    .copied()
+                    .filter(|v| !v.is_nan())
+                    .fold(f64::NAN, f64::max)
+            };
+            let min = |slice: &[f64]| {
+                slice
+                    .iter()
+                    .copied()
+                    .filter(|v| !v.is_nan())
+                    .fold(f64::NAN, f64::min)
+            };
+            assert_close(
+                &stream::<RollingMax>(size, &DATA, StreamingWindow::value),
+                &window.apply(&DATA, max),
+            );
+            assert_close(
+                &stream::<RollingMin>(size, &DATA, StreamingWindow::value),
+                &window.apply(&DATA, min),
+            );
+        }
+    }
+
+    #[test]
+    fn streaming_window_reports_fill_and_burn_in() {
+        let mut window = FixedWindow::new(2).stream::<RunningSum>();
+        assert!(window.is_empty());
+        window.push(1.0);
+        assert!(!window.is_full());
+        window.push(2.0);
+        window.push(4.0);
+        assert!(window.is_full());
+        assert_eq!(window.len(), 2);
+        assert_eq!(window.value(), 6.0);
+        assert_eq!(window.burn_in(), 2);
+    }
 }
@@ -2,7 +2,7 @@ use crate::asset_key::AssetKey;
 
 /// Trait for asset type discrimination and common asset behavior.
 ///
-/// All asset types (Equity, Future) must implement this trait.
+/// All asset types (Equity, Future, FxRate) must implement this trait.
 /// This provides a common interface for working with different asset types
 /// while maintaining immutability.
 pub trait Asset {
@@ -20,6 +20,8 @@ pub enum AssetType {
     Equity,
     /// Futures contract
     Future,
+    /// Foreign exchange rate (currency pair)
+    FxRate,
 }
 
 impl AssetType {
@@ -28,6 +30,7 @@ impl AssetType {
         match self {
             AssetType::Equity => "Equity",
             AssetType::Future => "Future",
+            AssetType::FxRate => "FxRate",
         }
     }
 }
@@ -48,6 +51,12 @@ mod tests {
         assert_eq!(asset_type.as_str(), "Future");
     }
 
+    #[test]
+    fn test_asset_type_fx_rate() {
+        let asset_type = AssetType::FxRate;
+        assert_eq!(asset_type.as_str(), "FxRate");
+    }
+
     #[test]
     fn test_asset_type_discrimination() {
         let equity_type = AssetType::Equity;
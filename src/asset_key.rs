@@ -1,12 +1,14 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
 /// Asset key for uniquely identifying assets.
 ///
-/// Supports two key formats:
+/// Supports three key formats:
 /// - Equity keys: Simple string-based ticker symbols (e.g., "AAPL", "MSFT")
 /// - Futures keys: Composite key with series (underlying) and expiry date
+/// - FX rate keys: Currency pair quoted as units of `quote` per one unit of `base`
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AssetKey {
     /// Equity asset key (ticker symbol)
@@ -16,6 +18,8 @@ pub enum AssetKey {
         series: String,
         expiry_date: NaiveDate,
     },
+    /// FX rate key (base currency / quote currency)
+    FxRate { base: String, quote: String },
 }
 
 impl AssetKey {
@@ -58,6 +62,75 @@ impl AssetKey {
         })
     }
 
+    /// Creates a new FX rate key from a currency pair.
+    ///
+    /// # Arguments
+    /// * `base` - ISO 4217 code of the base currency (e.g., "EUR")
+    /// * `quote` - ISO 4217 code of the quote currency (e.g., "USD")
+    ///
+    /// # Returns
+    /// Returns `Ok(AssetKey::FxRate { ... })` if valid, or `Err` if invalid.
+    ///
+    /// # Errors
+    /// Returns an error if either code is empty or is not a three-letter
+    /// upper-case currency code.
+    pub fn new_fx(base: impl Into<String>, quote: impl Into<String>) -> Result<Self, AssetKeyError> {
+        let base = base.into();
+        let quote = quote.into();
+        Self::validate_currency_code(&base)?;
+        Self::validate_currency_code(&quote)?;
+        Ok(AssetKey::FxRate { base, quote })
+    }
+
+    /// Parses the canonical string encoding produced by [`AssetKey::as_string`].
+    ///
+    /// - `BASE/QUOTE` (e.g. "EUR/USD") is an FX rate key.
+    /// - `SERIES-YYYY-MM-DD` (e.g. "ES-2024-12-20") is a futures key.
+    /// - Anything else is an equity ticker (e.g. "AAPL", "BRK-B").
+    ///
+    /// Equity tickers ending in a `-YYYY-MM-DD` date cannot be encoded, as
+    /// they read as futures.
+    ///
+    /// # Errors
+    /// Returns an error if the key is empty or a component is invalid.
+    pub fn parse(encoded: &str) -> Result<Self, AssetKeyError> {
+        let encoded = encoded.trim();
+        if let Some((base, quote)) = encoded.split_once('/') {
+            return Self::new_fx(base, quote);
+        }
+
+        let future = encoded
+            .len()
+            .checked_sub(11)
+            .filter(|&split| encoded.is_char_boundary(split) && split > 0)
+            .and_then(|split| {
+                let (series, expiry) = encoded.split_at(split);
+                let expiry = expiry.strip_prefix('-')?;
+                let expiry = NaiveDate::parse_from_str(expiry, "%Y-%m-%d").ok()?;
+                Some((series, expiry))
+            });
+        match future {
+            Some((series, expiry_date)) => Self::new_future(series, expiry_date),
+            None => Self::new_equity(encoded),
+        }
+    }
+
+    /// Validates an ISO 4217 style currency code.
+    ///
+    /// # Errors
+    /// Returns an error if the code is empty or is not three upper-case letters.
+    pub fn validate_currency_code(code: &str) -> Result<(), AssetKeyError> {
+        if code.is_empty() {
+            return Err(AssetKeyError::EmptyKey);
+        }
+
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(AssetKeyError::InvalidCurrencyCode);
+        }
+
+        Ok(())
+    }
+
     /// Validates an equity key format.
     ///
     /// Rejects empty strings and strings containing invalid characters.
@@ -109,6 +182,7 @@ impl AssetKey {
             } => {
                 format!("{}-{}", series, expiry_date.format("%Y-%m-%d"))
             }
+            AssetKey::FxRate { base, quote } => format!("{}/{}", base, quote),
         }
     }
 }
@@ -123,10 +197,19 @@ impl fmt::Display for AssetKey {
             } => {
                 write!(f, "{}-{}", series, expiry_date.format("%Y-%m-%d"))
             }
+            AssetKey::FxRate { base, quote } => write!(f, "{}/{}", base, quote),
         }
     }
 }
 
+impl FromStr for AssetKey {
+    type Err = AssetKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AssetKey::parse(s)
+    }
+}
+
 /// Errors that can occur when creating or validating asset keys.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AssetKeyError {
@@ -134,6 +217,8 @@ pub enum AssetKeyError {
     EmptyKey,
     /// The key contains invalid characters
     InvalidCharacters,
+    /// A currency code is not a three-letter upper-case ISO 4217 code
+    InvalidCurrencyCode,
 }
 
 impl fmt::Display for AssetKeyError {
@@ -143,6 +228,9 @@ impl fmt::Display for AssetKeyError {
             AssetKeyError::InvalidCharacters => {
                 write!(f, "Asset key contains invalid characters")
             }
+            AssetKeyError::InvalidCurrencyCode => {
+                write!(f, "Currency code must be three upper-case letters")
+            }
         }
     }
 }
@@ -230,6 +318,27 @@ mod tests {
         assert_eq!(future_key.as_string(), "ES-2024-12-20");
     }
 
+    #[test]
+    fn test_fx_key_creation_and_display() {
+        let key = AssetKey::new_fx("EUR", "USD").unwrap();
+        assert!(matches!(key, AssetKey::FxRate { .. }));
+        assert_eq!(key.as_string(), "EUR/USD");
+        assert_eq!(format!("{}", key), "EUR/USD");
+    }
+
+    #[test]
+    fn test_fx_key_rejects_invalid_codes() {
+        assert_eq!(AssetKey::new_fx("", "USD"), Err(AssetKeyError::EmptyKey));
+        assert_eq!(
+            AssetKey::new_fx("eur", "USD"),
+            Err(AssetKeyError::InvalidCurrencyCode)
+        );
+        assert_eq!(
+            AssetKey::new_fx("EURO", "USD"),
+            Err(AssetKeyError::InvalidCurrencyCode)
+        );
+    }
+
     #[test]
     fn test_asset_key_hashable() {
         use std::collections::HashMap;
@@ -243,4 +352,29 @@ mod tests {
         assert_eq!(map.get(&key2), Some(&"Apple Inc."));
         assert_eq!(map.get(&key3), None);
     }
+
+    #[test]
+    fn test_parse_round_trips_as_string() {
+        let keys = [
+            AssetKey::new_equity("AAPL").unwrap(),
+            AssetKey::new_equity("BRK-B").unwrap(),
+            AssetKey::new_future("ES", NaiveDate::from_ymd_opt(2024, 12, 20).unwrap()).unwrap(),
+            AssetKey::new_future("VX-M", NaiveDate::from_ymd_opt(2025, 1, 22).unwrap()).unwrap(),
+            AssetKey::new_fx("EUR", "USD").unwrap(),
+        ];
+        for key in keys {
+            assert_eq!(AssetKey::parse(&key.as_string()), Ok(key.clone()));
+            assert_eq!(key.to_string().parse::<AssetKey>(), Ok(key));
+        }
+
+        assert_eq!(AssetKey::parse(""), Err(AssetKeyError::EmptyKey));
+        assert_eq!(
+            AssetKey::parse("EUR/usd"),
+            Err(AssetKeyError::InvalidCurrencyCode)
+        );
+        assert_eq!(
+            AssetKey::parse("ES-2024-13-20"),
+            Ok(AssetKey::Equity("ES-2024-13-20".to_string()))
+        );
+    }
 }
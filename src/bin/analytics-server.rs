@@ -1,11 +1,70 @@
 //! Analytics API Server Binary
 //!
 //! Run with: `cargo run --bin analytics-server`
+//!
+//! Import a price file instead of serving with:
+//! `cargo run --bin analytics-server -- import prices.csv --asset AAPL [--<option> <value>]...`
+//! where the options are those of `ImportConfig::from_options` (e.g.
+//! `--date_column Date --existing skip`). Files covering several assets pass
+//! `--asset_column <column>` instead of `--asset`.
+
+use analytics::{
+    import_file, run_server, AssetKey, ImportConfig, ImportFormat, ServerConfig, SqliteDataProvider,
+};
+use std::path::Path;
+
+/// Runs `import <file> [--asset <key>] [--<option> <value>]...` against the database.
+fn run_import(database_path: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (file, flags) = args
+        .split_first()
+        .ok_or("usage: analytics-server import <file> [--asset <key>] [--<option> <value>]...")?;
 
-use analytics::{run_server, ServerConfig};
+    let mut asset = None;
+    let mut options = Vec::new();
+    for pair in flags.chunks(2) {
+        let (name, value) = match pair {
+            [name, value] => (name.trim_start_matches("--"), value.as_str()),
+            _ => return Err(format!("missing value for {}", pair[0]).into()),
+        };
+        match name {
+            "asset" => asset = Some(AssetKey::parse(value)?),
+            _ => options.push((name, value)),
+        }
+    }
+    if let Some(format) = ImportFormat::from_path(Path::new(file)) {
+        if !options.iter().any(|(name, _)| *name == "format") {
+            options.insert(0, ("format", format.name()));
+        }
+    }
+    let config = ImportConfig::from_options(options)?;
+
+    let mut provider = SqliteDataProvider::new(database_path)?;
+    let summary = import_file(&mut provider, file, asset.as_ref(), &config)?;
+    println!(
+        "Imported {} of {} rows into {} ({} already stored)",
+        summary.inserted,
+        summary.rows,
+        database_path,
+        summary.skipped
+    );
+    if summary.flagged > 0 {
+        println!("  {} prices flagged as bad prints", summary.flagged);
+    }
+    for asset in &summary.assets {
+        println!("  {}", asset);
+    }
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import") {
+        let database_path =
+            std::env::var("DATABASE_PATH").unwrap_or_else(|_| "analytics.db".to_string());
+        return run_import(&database_path, &args[1..]);
+    }
+
     // Note: Tracing is initialized in run_server()
     // Set RUST_LOG environment variable to control log level:
     //   RUST_LOG=debug cargo run --bin analytics-server
@@ -19,12 +78,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or(3000);
     let database_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "analytics.db".to_string());
     
-    let config = ServerConfig::new(host, port, database_path);
+    let mut config = ServerConfig::new(host, port, database_path);
+    if let Ok(seconds) = std::env::var("REFRESH_INTERVAL_SECONDS") {
+        config = config.with_refresh_interval(seconds.parse()?);
+    }
 
     println!("🚀 Starting Analytics API Server...");
     println!("   Host: {}", config.host);
     println!("   Port: {}", config.port);
     println!("   Database: {}", config.database_path);
+    if let Some(seconds) = config.refresh_interval_seconds {
+        println!("   Market data refresh: every {}s", seconds);
+    }
     println!();
     println!(
         "Server will be available at: http://{}:{}",
@@ -35,6 +100,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  GET  /health                    - Health check");
     println!("  GET  /assets                    - List assets");
     println!("  GET  /assets/:asset/data        - Get price data");
+    println!("  POST /assets/:asset/data        - Import CSV/Parquet prices");
+    println!("  GET  /ingest/status             - Market data refresh status");
     println!("  GET  /analytics/:asset/:type    - Pull-mode analytics");
     println!("  POST /analytics/batch           - Batch analytics");
     println!("  GET  /dag/nodes                 - List analytics types");
@@ -0,0 +1,118 @@
+//! Trading calendars: the dates on which an asset is expected to have a price.
+
+use crate::time_series::DateRange;
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::collections::{BTreeSet, HashSet};
+
+/// A Monday-to-Friday trading calendar with optional exchange holidays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradingCalendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    /// Creates a calendar trading every weekday.
+    pub fn weekdays() -> Self {
+        TradingCalendar::default()
+    }
+
+    /// Adds dates on which the market is closed.
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    /// Returns the holidays of this calendar, in date order.
+    pub fn holidays(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.holidays.iter().copied()
+    }
+
+    /// Returns true if the market trades on `date`.
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    /// Returns the trading days within `range` (inclusive), in date order.
+    pub fn trading_days(&self, range: &DateRange) -> Vec<NaiveDate> {
+        range
+            .start
+            .iter_days()
+            .take_while(|date| *date <= range.end)
+            .filter(|date| self.is_trading_day(*date))
+            .collect()
+    }
+
+    /// Returns the runs of trading days in `range` that are missing from `stored`.
+    ///
+    /// Each gap starts and ends on a missing trading day; consecutive missing
+    /// trading days separated only by weekends or holidays form one gap.
+    pub fn gaps(&self, range: &DateRange, stored: &HashSet<NaiveDate>) -> Vec<DateRange> {
+        let mut gaps: Vec<DateRange> = Vec::new();
+        let mut open = false;
+        for date in self.trading_days(range) {
+            if stored.contains(&date) {
+                open = false;
+            } else if open {
+                gaps.last_mut().unwrap().end = date;
+            } else {
+                gaps.push(DateRange::new(date, date));
+                open = true;
+            }
+        }
+        gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn test_trading_days_skip_weekends_and_holidays() {
+        let calendar = TradingCalendar::weekdays().with_holidays([date(1), date(15)]);
+        let days = calendar.trading_days(&DateRange::new(date(1), date(16)));
+        assert_eq!(
+            days,
+            vec![
+                date(2),
+                date(3),
+                date(4),
+                date(5),
+                date(8),
+                date(9),
+                date(10),
+                date(11),
+                date(12),
+                date(16)
+            ]
+        );
+        assert!(!calendar.is_trading_day(date(6)));
+        assert!(calendar.is_trading_day(date(8)));
+    }
+
+    #[test]
+    fn test_gaps_merge_runs_across_closed_days() {
+        let calendar = TradingCalendar::weekdays().with_holidays([date(1), date(15)]);
+        let stored: HashSet<NaiveDate> = [date(2), date(3), date(10), date(11)].into();
+        let gaps = calendar.gaps(&DateRange::new(date(1), date(19)), &stored);
+        assert_eq!(
+            gaps,
+            vec![
+                DateRange::new(date(4), date(9)),
+                DateRange::new(date(12), date(19)),
+            ]
+        );
+
+        let all: HashSet<NaiveDate> = calendar
+            .trading_days(&DateRange::new(date(1), date(19)))
+            .into_iter()
+            .collect();
+        assert!(calendar
+            .gaps(&DateRange::new(date(1), date(19)), &all)
+            .is_empty());
+    }
+}
@@ -8,6 +8,7 @@ pub mod visualization;
 
 pub use core::{AnalyticsDag, DagError};
 pub use types::{
-    AnalyticType, Node, NodeId, NodeKey, NodeOutput, NodeParams, WindowKind, WindowSpec,
+    AnalyticType, CustomAnalytic, Node, NodeId, NodeKey, NodeOutput, NodeParams, WindowKind,
+    WindowSpec,
 };
 pub use visualization::DagVisualization;
@@ -4,18 +4,27 @@
 //! for wiring analytics dependencies explicitly with cycle detection, topological sorting,
 //! and parallel execution support.
 
-use crate::analytics::registry::{AnalyticExecutor, AnalyticRegistry, ParentOutput};
+use crate::analytics::missing::{MissingData, MissingDataPolicy};
+use crate::analytics::registry::{
+    AnalyticExecutor, AnalyticRegistry, ExecutorState, ParentOutput,
+};
 use crate::asset_key::AssetKey;
 use crate::dag::types::{Node, NodeId, NodeKey, NodeOutput, NodeParams};
 use crate::dag::AnalyticType;
-use crate::time_series::{DataProvider, DataProviderError, DateRange, TimeSeriesPoint};
+use crate::time_series::{
+    AsOfProvider, DataProvider, DataProviderError, DateRange, TimeSeriesPoint,
+};
 use chrono::{DateTime, Utc};
 use daggy::{petgraph::Direction, Dag, EdgeIndex, NodeIndex, Walker};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, trace};
 
+/// Series of each named output of a multi-output node, keyed by name
+type NamedOutputs = BTreeMap<String, Vec<TimeSeriesPoint>>;
+
 /// Error types for DAG operations
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DagError {
@@ -47,82 +56,202 @@ impl std::fmt::Display for DagError {
 }
 
 impl AnalyticsDag {
+    /// Replays the fetched series through the DAG as if they were pushed live.
+    ///
+    /// Points are grouped by timestamp across all assets. At each timestamp the
+    /// data provider nodes of the assets that have a point fire first, and every
+    /// other node fires only when at least one of its parents produced output at
+    /// that timestamp. A node that fires twice at the same timestamp (e.g. once per
+    /// input asset) replaces its earlier point instead of appending a new one.
     fn simulate_push_from_calendar(
         &self,
         nodes_to_execute: &[NodeId],
-        data_points: &[TimeSeriesPoint],
+        series: &HashMap<AssetKey, Vec<TimeSeriesPoint>>,
         target_node: NodeId,
-    ) -> Result<Vec<TimeSeriesPoint>, DagError> {
+    ) -> Result<(Vec<TimeSeriesPoint>, NamedOutputs), DagError> {
+        let mut calendar: BTreeMap<DateTime<Utc>, HashMap<&AssetKey, f64>> = BTreeMap::new();
+        for (asset, points) in series {
+            for point in points {
+                calendar
+                    .entry(point.timestamp)
+                    .or_default()
+                    .insert(asset, point.close_price);
+            }
+        }
+
         debug!(
-            data_point_count = data_points.len(),
+            asset_count = series.len(),
+            timestamp_count = calendar.len(),
             nodes_to_execute = nodes_to_execute.len(),
             target_node = target_node.0,
             "Starting push-mode simulation from calendar"
         );
-        
+
         let mut push_history: HashMap<NodeId, Vec<TimeSeriesPoint>> = HashMap::new();
+        let mut named_history: HashMap<NodeId, BTreeMap<String, Vec<TimeSeriesPoint>>> =
+            HashMap::new();
+        let mut executor_states: HashMap<NodeId, Option<ExecutorState>> = HashMap::new();
 
-        for (idx, point) in data_points.iter().enumerate() {
+        for (idx, (timestamp, values)) in calendar.iter().enumerate() {
             trace!(
                 point_index = idx,
-                timestamp = %point.timestamp,
-                value = point.close_price,
-                "Processing data point"
+                timestamp = %timestamp,
+                asset_count = values.len(),
+                "Processing calendar timestamp"
             );
+            let timestamp = *timestamp;
+            let mut updated: HashSet<NodeId> = HashSet::new();
+
             for &node_id in nodes_to_execute {
-                let parent_histories: Vec<ParentOutput> = self
-                    .get_parents(node_id)
-                    .iter()
-                    .map(|&parent_id| ParentOutput {
-                        node_id: parent_id,
-                        analytic: self.analytic_type_for_node(parent_id),
-                        output: push_history.get(&parent_id).cloned().unwrap_or_default(),
-                    })
-                    .collect();
+                let node = self.get_node(node_id).ok_or_else(|| {
+                    DagError::NodeNotFound(format!("Node {} not found", node_id.0))
+                })?;
+                let own_value = node.assets.first().and_then(|asset| values.get(asset));
+                let parents = self.get_parents(node_id);
+
+                let value = if self.is_data_provider_node(node_id) {
+                    match own_value {
+                        Some(value) => *value,
+                        None => continue,
+                    }
+                } else {
+                    if !parents.is_empty() && !parents.iter().any(|id| updated.contains(id)) {
+                        continue;
+                    }
+                    // Without an asset of its own, a node sees its first parent's value
+                    let primary_value = parents
+                        .iter()
+                        .min_by_key(|id| id.0)
+                        .and_then(|&id| self.get_node(id))
+                        .and_then(|parent| parent.assets.first())
+                        .and_then(|asset| values.get(asset));
+                    own_value.or(primary_value).copied().unwrap_or(f64::NAN)
+                };
+
+                let parent_histories = self.parent_outputs(node_id, |parent_id, output| {
+                    match output {
+                        Some(name) => named_history
+                            .get(&parent_id)
+                            .and_then(|outputs| outputs.get(name)),
+                        None => push_history.get(&parent_id),
+                    }
+                    .map_or(&[], Vec::as_slice)
+                });
 
                 let output = match self.execute_push_node(
                     node_id,
                     &parent_histories,
-                    point.timestamp,
-                    point.close_price,
+                    timestamp,
+                    value,
+                    executor_states.entry(node_id).or_default(),
                 ) {
                     Ok(output) => output,
                     Err(err) => {
                         if Self::is_insufficient_data_error(&err) {
-                            push_history
-                                .entry(node_id)
-                                .or_insert_with(Vec::new)
-                                .push(TimeSeriesPoint::new(point.timestamp, f64::NAN));
-                            continue;
+                            self.nan_output(node_id)
+                        } else {
+                            return Err(err);
                         }
-                        return Err(err);
                     }
                 };
-                let mut points = Self::node_output_to_timeseries(&output, point.timestamp);
+                let points = Self::node_output_to_timeseries(&output, timestamp);
+                if let NodeOutput::Named(values) = &output {
+                    let outputs = named_history.entry(node_id).or_default();
+                    for (name, value) in values {
+                        Self::upsert_point(
+                            outputs.entry(name.clone()).or_default(),
+                            TimeSeriesPoint::new(timestamp, *value),
+                        );
+                    }
+                }
+
                 if !points.is_empty() {
-                    push_history
-                        .entry(node_id)
-                        .or_insert_with(Vec::new)
-                        .append(&mut points);
-                    
                     trace!(
                         node_id = node_id.0,
                         point_index = idx,
                         output_points = points.len(),
                         "Node output collected"
                     );
+                    let history = push_history.entry(node_id).or_default();
+                    for point in points {
+                        Self::upsert_point(history, point);
+                    }
+                    updated.insert(node_id);
                 }
             }
         }
 
         let result = push_history.get(&target_node).cloned().unwrap_or_default();
+        let outputs = named_history.remove(&target_node).unwrap_or_default();
         debug!(
             target_node = target_node.0,
             result_count = result.len(),
+            output_count = outputs.len(),
             "Push-mode simulation complete"
         );
         
-        Ok(result)
+        Ok((result, outputs))
+    }
+
+    /// Builds a node's inputs from its parents' histories.
+    ///
+    /// Each parent contributes one entry per output the edge selects, or a single
+    /// entry holding its primary output. `history` looks up a parent's series by
+    /// output name (`None` for the primary); the inputs borrow the series, so
+    /// building them costs nothing per point of history.
+    pub(crate) fn parent_outputs<'a>(
+        &self,
+        node_id: NodeId,
+        history: impl Fn(NodeId, Option<&str>) -> &'a [TimeSeriesPoint],
+    ) -> Vec<ParentOutput<'a>> {
+        let mut outputs = Vec::new();
+        for parent_id in self.get_parents(node_id) {
+            let analytic = self.analytic_type_for_node(parent_id);
+            let assets = self
+                .get_node(parent_id)
+                .map(|parent| parent.assets.clone())
+                .unwrap_or_default();
+            let selected = self.edge_outputs(parent_id, node_id);
+            let names: Vec<Option<&str>> = if selected.is_empty() {
+                vec![None]
+            } else {
+                selected.iter().map(|name| Some(name.as_str())).collect()
+            };
+            for name in names {
+                outputs.push(ParentOutput {
+                    node_id: parent_id,
+                    analytic,
+                    assets: assets.clone(),
+                    output_name: name.map(str::to_string),
+                    key: self.node_key(parent_id).cloned(),
+                    output: Cow::Borrowed(history(parent_id, name)),
+                });
+            }
+        }
+        outputs
+    }
+
+    /// NaN output for a node that cannot compute yet, covering every named output.
+    pub(crate) fn nan_output(&self, node_id: NodeId) -> NodeOutput {
+        let outputs = self.node_outputs(node_id);
+        if outputs.is_empty() {
+            NodeOutput::Scalar(f64::NAN)
+        } else {
+            NodeOutput::Named(
+                outputs
+                    .into_iter()
+                    .map(|name| (name, f64::NAN))
+                    .collect(),
+            )
+        }
+    }
+
+    /// Appends a point to a history, replacing the last point if it shares the timestamp.
+    fn upsert_point(history: &mut Vec<TimeSeriesPoint>, point: TimeSeriesPoint) {
+        match history.last_mut() {
+            Some(last) if last.timestamp == point.timestamp => *last = point,
+            _ => history.push(point),
+        }
     }
 
     fn node_output_to_timeseries(
@@ -132,6 +261,10 @@ impl AnalyticsDag {
         match output {
             NodeOutput::Single(points) => points.clone(),
             NodeOutput::Scalar(value) => vec![TimeSeriesPoint::new(timestamp, *value)],
+            NodeOutput::Named(values) => values
+                .first()
+                .map(|(_, value)| vec![TimeSeriesPoint::new(timestamp, *value)])
+                .unwrap_or_default(),
             NodeOutput::Collection(collection) => collection
                 .iter()
                 .flat_map(|points_vec| points_vec.clone())
@@ -162,6 +295,68 @@ impl AnalyticsDag {
         }
     }
 
+    /// Distinct assets read by the data provider nodes of this DAG.
+    ///
+    /// These are the series that must be pushed to drive every node, e.g. both
+    /// the asset and its FX pair for a currency-converted analytic.
+    pub fn data_assets(&self) -> Vec<AssetKey> {
+        let mut assets: Vec<AssetKey> = Vec::new();
+        for node_id in self.node_ids() {
+            if !self.is_data_provider_node(node_id) {
+                continue;
+            }
+            if let Some(asset) = self.get_node(node_id).and_then(|node| node.assets.first()) {
+                if !assets.contains(asset) {
+                    assets.push(asset.clone());
+                }
+            }
+        }
+        assets
+    }
+
+    /// Named outputs declared by a node's analytic (empty for single-output nodes).
+    pub fn node_outputs(&self, node_id: NodeId) -> Vec<String> {
+        self.node_key(node_id)
+            .map(|key| self.registry.outputs(key))
+            .unwrap_or_default()
+    }
+
+    /// Named outputs of `parent` that `child` consumes; empty when it reads the primary.
+    pub fn edge_outputs(&self, parent: NodeId, child: NodeId) -> &[String] {
+        self.edge_outputs
+            .get(&(parent, child))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Separates the `output` selection from a key of a multi-output analytic.
+    ///
+    /// Every output of a multi-output analytic comes from the same node, so the
+    /// selection is not part of the node's identity. Keys of single-output
+    /// analytics are returned unchanged.
+    pub fn split_output(&self, mut key: NodeKey) -> Result<(NodeKey, Option<String>), DagError> {
+        let outputs = self.registry.outputs(&key);
+        if outputs.is_empty() {
+            return Ok((key, None));
+        }
+        match key.params.remove("output") {
+            None => Ok((key, None)),
+            Some(name) => {
+                let name = name.trim().to_lowercase();
+                if outputs.contains(&name) {
+                    Ok((key, Some(name)))
+                } else {
+                    Err(DagError::InvalidOperation(format!(
+                        "Unknown output '{}' for {}; expected one of {}",
+                        name,
+                        key.analytic,
+                        outputs.join(", ")
+                    )))
+                }
+            }
+        }
+    }
+
     pub(crate) fn analytic_type_for_node(&self, node_id: NodeId) -> AnalyticType {
         if let Some(key) = self.node_key(node_id) {
             key.analytic
@@ -256,6 +451,8 @@ pub struct AnalyticsDag {
     node_lookup: HashMap<NodeKey, NodeId>,
     /// Map from NodeId to its NodeKey metadata (if registered)
     node_keys_by_id: HashMap<NodeId, NodeKey>,
+    /// Named outputs of a multi-output parent consumed along an edge (parent, child)
+    edge_outputs: HashMap<(NodeId, NodeId), Vec<String>>,
     /// Registry defining analytic executors and dependencies
     registry: Arc<AnalyticRegistry>,
 }
@@ -276,6 +473,7 @@ impl AnalyticsDag {
             cached_toposort: None,
             node_lookup: HashMap::new(),
             node_keys_by_id: HashMap::new(),
+            edge_outputs: HashMap::new(),
             registry,
         }
     }
@@ -311,7 +509,12 @@ impl AnalyticsDag {
     }
 
     /// Resolves or creates a node based on its metadata key and registered definitions.
+    ///
+    /// An `output` parameter on a multi-output analytic selects one of its outputs
+    /// rather than a distinct node, so every output resolves to the same node.
     pub fn resolve_node(&mut self, key: NodeKey) -> Result<NodeId, DagError> {
+        let (key, _) = self.split_output(key)?;
+        let key = self.registry.apply_schema(key)?;
         if let Some(&existing) = self.node_lookup.get(&key) {
             return Ok(existing);
         }
@@ -324,8 +527,9 @@ impl AnalyticsDag {
         let _ = definition;
         let mut dependency_ids = Vec::new();
         for dep_key in dependency_keys {
+            let (dep_key, output) = self.split_output(dep_key)?;
             let dep_id = self.resolve_node(dep_key)?;
-            dependency_ids.push(dep_id);
+            dependency_ids.push((dep_id, output));
         }
 
         let params = NodeParams::Map(key.params_map());
@@ -333,8 +537,19 @@ impl AnalyticsDag {
         self.node_lookup.insert(key.clone(), node_id);
         self.node_keys_by_id.insert(node_id, key.clone());
 
-        for dep_id in dependency_ids {
-            self.add_edge(dep_id, node_id)?;
+        let mut linked = HashSet::new();
+        for (dep_id, output) in dependency_ids {
+            if linked.insert(dep_id) {
+                self.add_edge(dep_id, node_id)?;
+            }
+            // A multi-output parent read without a selection feeds its primary output
+            let output = output.or_else(|| self.node_outputs(dep_id).into_iter().next());
+            if let Some(output) = output {
+                let selected = self.edge_outputs.entry((dep_id, node_id)).or_default();
+                if !selected.contains(&output) {
+                    selected.push(output);
+                }
+            }
         }
 
         Ok(node_id)
@@ -486,6 +701,7 @@ impl AnalyticsDag {
         self.node_lookup
             .retain(|_, &mut existing_node| existing_node != node_id);
         self.node_keys_by_id.remove(&node_id);
+        self.edge_outputs.retain(|&(_, child), _| child != node_id);
 
         Ok(())
     }
@@ -1038,6 +1254,26 @@ impl AnalyticsDag {
         }
     }
 
+    /// The range to query an asset's prices over in pull mode: the burn-in
+    /// range, reaching further back when the dependencies of an analytic keyed
+    /// a data node of the asset with an earlier start (e.g. a training window
+    /// widened by trading days).
+    fn data_fetch_range(
+        &self,
+        nodes: &[NodeId],
+        asset: Option<&AssetKey>,
+        extended_range: &DateRange,
+    ) -> DateRange {
+        let start = nodes
+            .iter()
+            .filter(|&&id| self.is_data_provider_node(id))
+            .filter(|&&id| self.get_node(id).and_then(|node| node.assets.first()) == asset)
+            .filter_map(|&id| self.node_key(id)?.range.as_ref())
+            .map(|range| range.start)
+            .fold(extended_range.start, std::cmp::min);
+        DateRange::new(start, extended_range.end)
+    }
+
     fn parse_lag_from_node(node: &Node) -> usize {
         if let NodeParams::Map(ref params) = node.params {
             params
@@ -1102,6 +1338,52 @@ impl AnalyticsDag {
         date_range: DateRange,
         provider: &dyn DataProvider,
     ) -> Result<Vec<TimeSeriesPoint>, DagError> {
+        self.pull_outputs(node_id, date_range, provider)
+            .map(|(primary, _)| primary)
+    }
+
+    /// Executes a node in pull-mode on the data as it was known at `as_of`.
+    ///
+    /// Reproduces the series computed at that moment: prices corrected since
+    /// are read with the values they had then, and prices recorded later are
+    /// left out. The provider must keep its price history (see
+    /// [`DataProvider::get_time_series_as_of`]).
+    pub fn execute_pull_mode_as_of(
+        &self,
+        node_id: NodeId,
+        date_range: DateRange,
+        provider: &dyn DataProvider,
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<TimeSeriesPoint>, DagError> {
+        self.execute_pull_mode(
+            node_id,
+            date_range,
+            &AsOfProvider::new(provider, Some(as_of)),
+        )
+    }
+
+    /// Executes a multi-output node in pull-mode, returning every named output.
+    ///
+    /// The map is keyed by the names the analytic declares; it is empty for
+    /// single-output nodes, whose series `execute_pull_mode` returns.
+    pub fn execute_pull_mode_outputs(
+        &self,
+        node_id: NodeId,
+        date_range: DateRange,
+        provider: &dyn DataProvider,
+    ) -> Result<BTreeMap<String, Vec<TimeSeriesPoint>>, DagError> {
+        self.pull_outputs(node_id, date_range, provider)
+            .map(|(_, outputs)| outputs)
+    }
+
+    /// Pull-mode execution returning the primary output and every named output,
+    /// each filtered to `date_range`.
+    fn pull_outputs(
+        &self,
+        node_id: NodeId,
+        date_range: DateRange,
+        provider: &dyn DataProvider,
+    ) -> Result<(Vec<TimeSeriesPoint>, NamedOutputs), DagError> {
         use chrono::Duration;
 
         debug!(
@@ -1143,46 +1425,69 @@ impl AnalyticsDag {
             }
         }
 
-        // Identify a data provider node to drive the push-mode iteration
-        let data_node_id = nodes_to_execute
+        // Query the whole time series of every asset feeding the target upfront,
+        // using the first data provider node found for each asset
+        let mut series: HashMap<AssetKey, Vec<TimeSeriesPoint>> = HashMap::new();
+        for &data_node_id in nodes_to_execute
             .iter()
-            .find(|&&id| self.is_data_provider_node(id))
-            .ok_or_else(|| {
-                DagError::ExecutionError(
-                    "No data provider node found for pull-mode execution".to_string(),
-                )
+            .filter(|&&id| self.is_data_provider_node(id))
+        {
+            let data_node = self.get_node(data_node_id).ok_or_else(|| {
+                DagError::NodeNotFound(format!("Data node {} not found", data_node_id.0))
             })?;
+            if let Some(asset) = data_node.assets.first() {
+                if series.contains_key(asset) {
+                    continue;
+                }
+            }
 
-        debug!(
-            nodes_to_execute = nodes_to_execute.len(),
-            data_node = data_node_id.0,
-            "Querying data provider for time series"
-        );
+            debug!(
+                data_node = data_node_id.0,
+                assets = ?data_node.assets,
+                "Querying data provider for time series"
+            );
+
+            let fetch_range =
+                self.data_fetch_range(&nodes_to_execute, data_node.assets.first(), &extended_range);
+            let executor = self.executor_for_node(data_node, data_node_id)?;
+            let parent_outputs: Vec<ParentOutput> = Vec::new(); // Data provider has no parents
+            let data_points =
+                executor.execute_pull(data_node, &parent_outputs, &fetch_range, provider)?;
+            if let Some(asset) = data_node.assets.first() {
+                series.insert(asset.clone(), data_points);
+            }
+        }
+
+        if series.is_empty() {
+            return Err(DagError::ExecutionError(
+                "No data provider node found for pull-mode execution".to_string(),
+            ));
+        }
 
-        // Query the whole time series from the data provider upfront
-        let data_node = self
-            .get_node(*data_node_id)
-            .ok_or_else(|| DagError::NodeNotFound(format!("Data node {} not found", data_node_id.0)))?;
-        let executor = self.executor_for_node(data_node, *data_node_id)?;
-        let parent_outputs: Vec<ParentOutput> = Vec::new(); // Data provider has no parents
-        let data_points = executor.execute_pull(data_node, &parent_outputs, &extended_range, provider)?;
-        
         debug!(
-            data_point_count = data_points.len(),
-            "Data provider returned time series, starting push-mode simulation"
+            nodes_to_execute = nodes_to_execute.len(),
+            asset_count = series.len(),
+            "Data providers returned time series, starting push-mode simulation"
         );
 
         // Now iterate point by point like push mode, collecting results
-        let simulated =
-            self.simulate_push_from_calendar(&nodes_to_execute, &data_points, node_id)?;
+        let (simulated, outputs) =
+            self.simulate_push_from_calendar(&nodes_to_execute, &series, node_id)?;
 
         // Filter simulation output to the originally requested date range
-        let filtered_result: Vec<TimeSeriesPoint> = simulated
+        let in_range = |points: Vec<TimeSeriesPoint>| -> Vec<TimeSeriesPoint> {
+            points
+                .into_iter()
+                .filter(|point| {
+                    let date = point.timestamp.date_naive();
+                    date >= date_range.start && date <= date_range.end
+                })
+                .collect()
+        };
+        let filtered_result = in_range(simulated);
+        let outputs = outputs
             .into_iter()
-            .filter(|point| {
-                let date = point.timestamp.date_naive();
-                date >= date_range.start && date <= date_range.end
-            })
+            .map(|(name, points)| (name, in_range(points)))
             .collect();
 
         debug!(
@@ -1191,7 +1496,7 @@ impl AnalyticsDag {
             "Pull-mode execution complete"
         );
 
-        Ok(filtered_result)
+        Ok((filtered_result, outputs))
     }
 
     /// Executes multiple DAG nodes in parallel for batch computation
@@ -1322,6 +1627,7 @@ impl AnalyticsDag {
         parent_outputs: &[ParentOutput],
         timestamp: DateTime<Utc>,
         value: f64,
+        state: &mut Option<ExecutorState>,
     ) -> Result<NodeOutput, DagError> {
         let node = self
             .get_node(node_id)
@@ -1343,7 +1649,12 @@ impl AnalyticsDag {
         );
         
         let executor = self.executor_for_node(node, node_id)?;
-        let result = executor.execute_push(node, parent_outputs, timestamp, value)?;
+        let missing = MissingData::for_node(node)?;
+        let result = if missing.is_default() {
+            executor.execute_push_stateful(node, parent_outputs, timestamp, value, state)?
+        } else {
+            self.execute_with_missing_data(node, &missing, parent_outputs, timestamp, value, state)?
+        };
         
         trace!(
             node_id = node_id.0,
@@ -1353,6 +1664,44 @@ impl AnalyticsDag {
         
         Ok(result)
     }
+
+    /// Runs a node's executor on inputs prepared by its missing-data policy.
+    ///
+    /// Interpolation revises earlier inputs once a gap closes, so those nodes
+    /// recompute from their full inputs instead of updating executor state.
+    fn execute_with_missing_data(
+        &self,
+        node: &Node,
+        missing: &MissingData,
+        parent_outputs: &[ParentOutput],
+        timestamp: DateTime<Utc>,
+        value: f64,
+        state: &mut Option<ExecutorState>,
+    ) -> Result<NodeOutput, DagError> {
+        if missing.skips(parent_outputs) {
+            trace!(node_id = node.id.0, timestamp = %timestamp, "Skipping missing input");
+            return Ok(NodeOutput::None);
+        }
+
+        let window = self
+            .node_key(node.id)
+            .and_then(|key| key.window.as_ref())
+            .map(|window| window.burn_in())
+            .unwrap_or(1);
+        let executor = self.executor_for_node(node, node.id)?;
+        let inputs = missing.apply(parent_outputs);
+        let output = if missing.policy == Some(MissingDataPolicy::Interpolate) {
+            executor.execute_push(node, &inputs, timestamp, value)?
+        } else {
+            executor.execute_push_stateful(node, &inputs, timestamp, value, state)?
+        };
+
+        if missing.reports_nan(parent_outputs, window) {
+            Ok(self.nan_output(node.id))
+        } else {
+            Ok(output)
+        }
+    }
 }
 
 impl Default for AnalyticsDag {
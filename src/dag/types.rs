@@ -2,8 +2,11 @@ use crate::asset_key::AssetKey;
 use crate::time_series::{DateRange, TimeSeriesPoint};
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, MutexGuard, OnceLock};
 
 /// Node identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -25,20 +28,151 @@ pub enum AnalyticType {
     Lag,
     Returns,
     Volatility,
+    DownsideDeviation,
     StdDev,
     ExponentialMovingAverage,
+    FxConversion,
+    Correlation,
+    PortfolioNav,
+    PortfolioReturns,
+    Drawdown,
+    MaxDrawdown,
+    TimeUnderWater,
+    SharpeRatio,
+    SortinoRatio,
+    CalmarRatio,
+    InformationRatio,
+    ValueAtRisk,
+    ExpectedShortfall,
+    Sma,
+    Rsi,
+    Macd,
+    BollingerBands,
+    Atr,
+    Skewness,
+    Kurtosis,
+    Quantile,
+    PercentileRank,
+    ZScore,
+    Regression,
+    Garch,
+    Pca,
+    CorrelationMatrix,
+    CovarianceMatrix,
+    Expression,
+    /// Analytic registered at runtime through `AnalyticRegistry::register`
+    Custom(CustomAnalytic),
+}
+
+/// Name of a user-defined analytic.
+///
+/// Names are lowercased and interned so that `AnalyticType` stays `Copy`; each
+/// distinct name is allocated once for the life of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CustomAnalytic(&'static str);
+
+impl CustomAnalytic {
+    /// Interns `name`. Names from requests are looked up through
+    /// `AnalyticRegistry::analytic_type` instead, which interns nothing.
+    pub fn new(name: &str) -> Self {
+        let name = name.trim().to_lowercase();
+        let mut names = interned_names();
+        if let Some(&interned) = names.get(name.as_str()) {
+            return CustomAnalytic(interned);
+        }
+        let interned: &'static str = Box::leak(name.into_boxed_str());
+        names.insert(interned);
+        CustomAnalytic(interned)
+    }
+
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+
+    /// Whether `name` was ever interned.
+    #[cfg(test)]
+    pub(crate) fn is_interned(name: &str) -> bool {
+        interned_names().contains(name)
+    }
+}
+
+// Lookups by name must agree with the derived `Eq`, `Ord` and `Hash`, which
+// compare the names
+impl Borrow<str> for CustomAnalytic {
+    fn borrow(&self) -> &str {
+        self.0
+    }
+}
+
+fn interned_names() -> MutexGuard<'static, HashSet<&'static str>> {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 impl AnalyticType {
-    pub fn from_str(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
+    /// The built-in analytic a name or alias stands for, ignoring case; `None`
+    /// for a name no built-in analytic answers to.
+    pub fn builtin_from_name(value: &str) -> Option<Self> {
+        Some(match value.to_lowercase().as_str() {
+            "data_provider" | "dataprovider" => AnalyticType::DataProvider,
             "lag" => AnalyticType::Lag,
             "returns" => AnalyticType::Returns,
             "volatility" => AnalyticType::Volatility,
+            "downside_deviation" | "downsidedeviation" => AnalyticType::DownsideDeviation,
             "std_dev" | "stddev" => AnalyticType::StdDev,
             "ema" | "exponentialmovingaverage" => AnalyticType::ExponentialMovingAverage,
-            _ => AnalyticType::DataProvider,
-        }
+            "fx_conversion" | "fxconversion" => AnalyticType::FxConversion,
+            "correlation" => AnalyticType::Correlation,
+            "portfolio_nav" | "portfolionav" => AnalyticType::PortfolioNav,
+            "portfolio_returns" | "portfolioreturns" => AnalyticType::PortfolioReturns,
+            "drawdown" => AnalyticType::Drawdown,
+            "max_drawdown" | "maxdrawdown" => AnalyticType::MaxDrawdown,
+            "time_under_water" | "timeunderwater" => AnalyticType::TimeUnderWater,
+            "sharpe" | "sharpe_ratio" => AnalyticType::SharpeRatio,
+            "sortino" | "sortino_ratio" => AnalyticType::SortinoRatio,
+            "calmar" | "calmar_ratio" => AnalyticType::CalmarRatio,
+            "information_ratio" | "informationratio" => AnalyticType::InformationRatio,
+            "var" | "value_at_risk" => AnalyticType::ValueAtRisk,
+            "es" | "expected_shortfall" => AnalyticType::ExpectedShortfall,
+            "sma" => AnalyticType::Sma,
+            "rsi" => AnalyticType::Rsi,
+            "macd" => AnalyticType::Macd,
+            "bollinger" | "bollinger_bands" => AnalyticType::BollingerBands,
+            "atr" => AnalyticType::Atr,
+            "skewness" | "skew" => AnalyticType::Skewness,
+            "kurtosis" | "excess_kurtosis" => AnalyticType::Kurtosis,
+            "quantile" => AnalyticType::Quantile,
+            "percentile_rank" | "percentilerank" => AnalyticType::PercentileRank,
+            "z_score" | "zscore" => AnalyticType::ZScore,
+            "regression" | "ols" => AnalyticType::Regression,
+            "garch" | "garch11" => AnalyticType::Garch,
+            "pca" | "principal_components" => AnalyticType::Pca,
+            "correlation_matrix" => AnalyticType::CorrelationMatrix,
+            "covariance_matrix" => AnalyticType::CovarianceMatrix,
+            "expression" | "formula" => AnalyticType::Expression,
+            _ => return None,
+        })
+    }
+
+    /// Parses an analytic name, falling back to the data provider for names
+    /// of no built-in analytic.
+    pub fn from_str(value: &str) -> Self {
+        Self::builtin_from_name(value).unwrap_or(AnalyticType::DataProvider)
+    }
+
+    /// Whether output of this analytic can stand in for `source` as a node input.
+    ///
+    /// Currency-converted prices and portfolio NAVs are drop-in replacements for
+    /// raw provider prices, and portfolio returns can feed anything built on
+    /// single-asset returns.
+    pub fn satisfies(self, source: AnalyticType) -> bool {
+        self == source
+            || (source == AnalyticType::DataProvider
+                && matches!(self, AnalyticType::FxConversion | AnalyticType::PortfolioNav))
+            || (source == AnalyticType::Returns && self == AnalyticType::PortfolioReturns)
     }
 }
 
@@ -48,9 +182,40 @@ impl std::fmt::Display for AnalyticType {
             AnalyticType::DataProvider => "data_provider",
             AnalyticType::Returns => "returns",
             AnalyticType::Volatility => "volatility",
+            AnalyticType::DownsideDeviation => "downside_deviation",
             AnalyticType::Lag => "lag",
             AnalyticType::StdDev => "std_dev",
             AnalyticType::ExponentialMovingAverage => "ema",
+            AnalyticType::FxConversion => "fx_conversion",
+            AnalyticType::Correlation => "correlation",
+            AnalyticType::PortfolioNav => "portfolio_nav",
+            AnalyticType::PortfolioReturns => "portfolio_returns",
+            AnalyticType::Drawdown => "drawdown",
+            AnalyticType::MaxDrawdown => "max_drawdown",
+            AnalyticType::TimeUnderWater => "time_under_water",
+            AnalyticType::SharpeRatio => "sharpe_ratio",
+            AnalyticType::SortinoRatio => "sortino_ratio",
+            AnalyticType::CalmarRatio => "calmar_ratio",
+            AnalyticType::InformationRatio => "information_ratio",
+            AnalyticType::ValueAtRisk => "value_at_risk",
+            AnalyticType::ExpectedShortfall => "expected_shortfall",
+            AnalyticType::Sma => "sma",
+            AnalyticType::Rsi => "rsi",
+            AnalyticType::Macd => "macd",
+            AnalyticType::BollingerBands => "bollinger",
+            AnalyticType::Atr => "atr",
+            AnalyticType::Skewness => "skewness",
+            AnalyticType::Kurtosis => "kurtosis",
+            AnalyticType::Quantile => "quantile",
+            AnalyticType::PercentileRank => "percentile_rank",
+            AnalyticType::ZScore => "z_score",
+            AnalyticType::Regression => "regression",
+            AnalyticType::Garch => "garch",
+            AnalyticType::Pca => "pca",
+            AnalyticType::CorrelationMatrix => "correlation_matrix",
+            AnalyticType::CovarianceMatrix => "covariance_matrix",
+            AnalyticType::Expression => "expression",
+            AnalyticType::Custom(custom) => custom.name(),
         };
         write!(f, "{repr}")
     }
@@ -164,6 +329,8 @@ pub enum NodeOutput {
     Collection(Vec<Vec<TimeSeriesPoint>>),
     /// Scalar value (e.g., correlation coefficient)
     Scalar(f64),
+    /// Named values of a multi-output node, in declared order (the first is primary)
+    Named(Vec<(String, f64)>),
     /// No output (for sink nodes)
     None,
 }
@@ -20,7 +20,40 @@ fn find_definition_line(analytic_type: AnalyticType) -> Option<usize> {
         AnalyticType::DataProvider => "struct DataProviderDefinition",
         AnalyticType::Returns => "struct ReturnsDefinition",
         AnalyticType::Volatility => "struct VolatilityDefinition",
+        AnalyticType::DownsideDeviation => "struct DownsideDeviationDefinition",
         AnalyticType::Lag => "struct LagDefinition",
+        AnalyticType::FxConversion => "struct FxConversionDefinition",
+        AnalyticType::Correlation => "struct CorrelationDefinition",
+        AnalyticType::PortfolioNav => "struct PortfolioNavDefinition",
+        AnalyticType::PortfolioReturns => "struct PortfolioReturnsDefinition",
+        AnalyticType::Drawdown => "struct DrawdownDefinition",
+        AnalyticType::MaxDrawdown => "struct MaxDrawdownDefinition",
+        AnalyticType::TimeUnderWater => "struct TimeUnderWaterDefinition",
+        AnalyticType::SharpeRatio => "struct SharpeRatioDefinition",
+        AnalyticType::SortinoRatio => "struct SortinoRatioDefinition",
+        AnalyticType::CalmarRatio => "struct CalmarRatioDefinition",
+        AnalyticType::InformationRatio => "struct InformationRatioDefinition",
+        AnalyticType::ValueAtRisk | AnalyticType::ExpectedShortfall => {
+            "struct TailRiskDefinition"
+        }
+        AnalyticType::Sma => "struct SmaDefinition",
+        AnalyticType::BollingerBands => "struct BollingerBandsDefinition",
+        AnalyticType::ExponentialMovingAverage
+        | AnalyticType::Rsi
+        | AnalyticType::Macd
+        | AnalyticType::Atr => "struct IndicatorDefinition",
+        AnalyticType::Skewness
+        | AnalyticType::Kurtosis
+        | AnalyticType::Quantile
+        | AnalyticType::PercentileRank
+        | AnalyticType::ZScore => "struct DistributionDefinition",
+        AnalyticType::Regression => "struct RegressionDefinition",
+        AnalyticType::Garch => "struct GarchDefinition",
+        AnalyticType::Pca => "struct PcaDefinition",
+        AnalyticType::CorrelationMatrix | AnalyticType::CovarianceMatrix => {
+            "struct MatrixDefinition"
+        }
+        AnalyticType::Expression => "struct ExpressionDefinition",
         _ => return None,
     };
     
@@ -34,6 +67,20 @@ fn find_definition_line(analytic_type: AnalyticType) -> Option<usize> {
     None
 }
 
+/// Percent-encodes a query parameter value such as a formula, keeping the
+/// characters other params use (`,` lists, `.` numbers) readable.
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
 /// Represents a node in the DAG visualization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisualizationNode {
@@ -55,6 +102,9 @@ pub struct VisualizationNode {
     pub code_url: Option<String>,
     /// Description of what this node does
     pub description: Option<String>,
+    /// Named outputs of a multi-output node, primary first (empty otherwise)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
 }
 
 /// Position of a node in the visualization
@@ -71,7 +121,7 @@ pub struct VisualizationEdge {
     pub source: usize,
     /// Target node ID
     pub target: usize,
-    /// Edge label (optional)
+    /// Edge label (optional); names the outputs of a multi-output source the target reads
     pub label: Option<String>,
 }
 
@@ -155,7 +205,10 @@ impl AnalyticsDag {
                     
                     // Only create the visualization node if this is the first time we see this key
                     if viz_id == nodes.len() {
-                        let analytic_type = format!("{:?}", key.analytic);
+                        let analytic_type = match key.analytic {
+                            AnalyticType::Custom(custom) => custom.name().to_string(),
+                            other => format!("{:?}", other),
+                        };
                         
                         // Extract and filter valid API parameters (exclude internal metadata)
                         let valid_params = match &node.params {
@@ -164,7 +217,32 @@ impl AnalyticsDag {
                                 map.iter()
                                     .filter(|(k, _)| {
                                         // Only include actual API parameters
-                                        matches!(k.as_str(), "window" | "lag" | "override")
+                                        matches!(
+                                            k.as_str(),
+                                            "window"
+                                                | "lag"
+                                                | "override"
+                                                | "reporting_currency"
+                                                | "weights"
+                                                | "rebalance"
+                                                | "risk_free"
+                                                | "benchmark"
+                                                | "periods_per_year"
+                                                | "confidence"
+                                                | "method"
+                                                | "output"
+                                                | "num_std"
+                                                | "fast"
+                                                | "slow"
+                                                | "signal"
+                                                | "quantile"
+                                                | "factors"
+                                                | "horizon"
+                                                | "components"
+                                                | "estimator"
+                                                | "lambda"
+                                                | "expression"
+                                        )
                                     })
                                     .map(|(k, v)| (k.clone(), v.clone()))
                                     .collect::<HashMap<_, _>>()
@@ -174,11 +252,21 @@ impl AnalyticsDag {
                         
                         // Build data URL with query parameters
                         let data_url = if let Some(asset) = node.assets.first() {
+                            let portfolio = key.params.contains_key("weights");
                             let mut url = match key.analytic {
                                 AnalyticType::DataProvider => {
                                     // DataProvider uses /assets/{asset}/data endpoint
                                     format!("{}/assets/{}/data", api_base_url, asset)
                                 }
+                                _ if portfolio => {
+                                    // Portfolio analytics use /portfolio/{type} endpoint
+                                    let name = match key.analytic {
+                                        AnalyticType::PortfolioNav => "nav".to_string(),
+                                        AnalyticType::PortfolioReturns => "returns".to_string(),
+                                        other => other.to_string(),
+                                    };
+                                    format!("{}/portfolio/{}", api_base_url, name)
+                                }
                                 _ => {
                                     // Analytics use /analytics/{asset}/{type} endpoint
                                     format!(
@@ -201,7 +289,20 @@ impl AnalyticsDag {
                             
                             // Add valid parameters
                             for (k, v) in &valid_params {
-                                query_params.push(format!("{}={}", k, v));
+                                query_params.push(format!("{}={}", k, encode_query_value(v)));
+                            }
+
+                            if portfolio {
+                                let assets: Vec<String> =
+                                    node.assets.iter().map(|a| a.to_string()).collect();
+                                query_params.push(format!("assets={}", assets.join(",")));
+                            }
+
+                            // Pairwise analytics name their second asset explicitly
+                            if key.analytic == AnalyticType::Correlation {
+                                if let Some(other) = node.assets.get(1) {
+                                    query_params.push(format!("with={}", other));
+                                }
                             }
                             
                             if !query_params.is_empty() {
@@ -230,7 +331,7 @@ impl AnalyticsDag {
                         };
 
                         // Get description from node type
-                        let description = Some(format!("{:?} analytic", key.analytic));
+                        let description = Some(format!("{} analytic", analytic_type));
 
                         nodes.push(VisualizationNode {
                             id: viz_id,
@@ -242,6 +343,7 @@ impl AnalyticsDag {
                             data_url,
                             code_url,
                             description,
+                            outputs: self.node_outputs(node_id),
                         });
                     }
                 }
@@ -263,10 +365,15 @@ impl AnalyticsDag {
                             let edge_key = (source_viz_id, target_viz_id);
                             if !edge_set.contains_key(&edge_key) {
                                 edge_set.insert(edge_key, ());
+                                let selected = self.edge_outputs(node_id, child_id);
                                 edges.push(VisualizationEdge {
                                     source: source_viz_id,
                                     target: target_viz_id,
-                                    label: None,
+                                    label: if selected.is_empty() {
+                                        None
+                                    } else {
+                                        Some(selected.join(", "))
+                                    },
                                 });
                             }
                         }
@@ -0,0 +1,178 @@
+use crate::asset::{Asset, AssetType};
+use crate::asset_key::{AssetKey, AssetKeyError};
+use crate::time_series::{DataProvider, DateRange, TimeSeriesPoint};
+use serde::{Deserialize, Serialize};
+
+/// FX rate asset representing a currency pair.
+///
+/// The stored price series is quoted as units of `quote` currency per one
+/// unit of `base` currency (e.g., EUR/USD = 1.08 means 1 EUR buys 1.08 USD).
+/// FX series are stored and queried exactly like any other asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FxRate {
+    /// Unique asset key (currency pair)
+    key: AssetKey,
+    /// Display name (e.g., "Euro / US Dollar")
+    name: String,
+    /// Source or venue for the rate (e.g., "WMR", "ECB")
+    source: String,
+}
+
+impl FxRate {
+    /// Creates a new FX rate asset.
+    ///
+    /// # Arguments
+    /// * `base` - Base currency code (e.g., "EUR")
+    /// * `quote` - Quote currency code (e.g., "USD")
+    /// * `name` - Display name
+    /// * `source` - Source of the fixing
+    ///
+    /// # Returns
+    /// Returns `Ok(FxRate)` if both currency codes are valid, or `Err` if invalid.
+    pub fn new(
+        base: impl Into<String>,
+        quote: impl Into<String>,
+        name: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Result<Self, AssetKeyError> {
+        let key = AssetKey::new_fx(base, quote)?;
+        Ok(FxRate {
+            key,
+            name: name.into(),
+            source: source.into(),
+        })
+    }
+
+    /// Returns the base currency code.
+    pub fn base(&self) -> &str {
+        match &self.key {
+            AssetKey::FxRate { base, .. } => base,
+            _ => unreachable!("FxRate always holds an FX key"),
+        }
+    }
+
+    /// Returns the quote currency code.
+    pub fn quote(&self) -> &str {
+        match &self.key {
+            AssetKey::FxRate { quote, .. } => quote,
+            _ => unreachable!("FxRate always holds an FX key"),
+        }
+    }
+
+    /// Returns the display name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the source of the rate.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The prices of this pair are expressed in the quote currency.
+    pub fn currency(&self) -> &str {
+        self.quote()
+    }
+
+    /// Queries time-series data for this pair from a data provider.
+    ///
+    /// # Errors
+    /// Returns an error if the pair is not found in the data provider or if the query fails.
+    pub fn get_time_series(
+        &self,
+        provider: &dyn DataProvider,
+        date_range: &DateRange,
+    ) -> Result<Vec<TimeSeriesPoint>, crate::time_series::DataProviderError> {
+        provider.get_time_series(self.key(), date_range)
+    }
+}
+
+impl Asset for FxRate {
+    fn key(&self) -> &AssetKey {
+        &self.key
+    }
+
+    fn asset_type(&self) -> AssetType {
+        AssetType::FxRate
+    }
+}
+
+/// Describes how to convert an asset's prices from its native currency into a
+/// reporting currency.
+///
+/// Returns `None` when no conversion is required (same currency). Otherwise
+/// returns the FX pair key to use and whether the stored rate must be inverted.
+/// The direct pair `<native>/<reporting>` is preferred; callers that only hold
+/// the inverse pair can pass `prefer_inverse = true`.
+pub fn conversion_pair(
+    native: &str,
+    reporting: &str,
+    prefer_inverse: bool,
+) -> Result<Option<(AssetKey, bool)>, AssetKeyError> {
+    if native == reporting {
+        return Ok(None);
+    }
+    if prefer_inverse {
+        Ok(Some((AssetKey::new_fx(reporting, native)?, true)))
+    } else {
+        Ok(Some((AssetKey::new_fx(native, reporting)?, false)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[test]
+    fn test_fx_rate_creation() {
+        let pair = FxRate::new("EUR", "USD", "Euro / US Dollar", "WMR").unwrap();
+
+        assert_eq!(pair.base(), "EUR");
+        assert_eq!(pair.quote(), "USD");
+        assert_eq!(pair.currency(), "USD");
+        assert_eq!(pair.asset_type(), AssetType::FxRate);
+        assert_eq!(pair.key().as_string(), "EUR/USD");
+    }
+
+    #[test]
+    fn test_fx_rate_invalid_code() {
+        assert!(FxRate::new("EU", "USD", "bad", "WMR").is_err());
+    }
+
+    #[test]
+    fn test_fx_rate_query_time_series() {
+        use crate::time_series::InMemoryDataProvider;
+
+        let pair = FxRate::new("EUR", "USD", "Euro / US Dollar", "WMR").unwrap();
+        let mut provider = InMemoryDataProvider::new();
+        provider.add_data(
+            pair.key().clone(),
+            vec![TimeSeriesPoint::new(
+                Utc.with_ymd_and_hms(2024, 1, 15, 16, 0, 0).unwrap(),
+                1.09,
+            )],
+        );
+
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        );
+        let points = pair.get_time_series(&provider, &range).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].close_price, 1.09);
+    }
+
+    #[test]
+    fn test_conversion_pair() {
+        assert_eq!(conversion_pair("USD", "USD", false).unwrap(), None);
+
+        let (key, invert) = conversion_pair("EUR", "USD", false).unwrap().unwrap();
+        assert_eq!(key.as_string(), "EUR/USD");
+        assert!(!invert);
+
+        let (key, invert) = conversion_pair("EUR", "USD", true).unwrap().unwrap();
+        assert_eq!(key.as_string(), "USD/EUR");
+        assert!(invert);
+    }
+}
@@ -0,0 +1,692 @@
+//! Bulk import of price files into the SQLite store.
+//!
+//! Reads CSV or Parquet files of close prices, maps their columns onto
+//! timestamps, prices and (optionally) assets, and writes them with
+//! [`SqliteDataProvider::insert_time_series_batch`]. Files are parsed in full
+//! before anything is written, so a malformed row aborts the import.
+//!
+//! Timestamps may be RFC 3339 instants, naive date-times or plain dates.
+//! Naive values are read in the configured UTC offset, and plain dates are
+//! stamped at the configured close time (16:00 by default, like the Yahoo
+//! Finance downloader).
+
+use crate::asset_key::AssetKey;
+use crate::quality::{check_asset, QualityConfig};
+use crate::sqlite_provider::SqliteDataProvider;
+use crate::time_series::{DataProvider, DataProviderError, DateRange, TimeSeriesPoint};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// File format of an import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ImportFormat {
+    /// Parses a format name such as "csv" or "parquet".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "parquet" | "pq" => Some(ImportFormat::Parquet),
+            _ => None,
+        }
+    }
+
+    /// Name of the format, as accepted by [`ImportFormat::from_name`].
+    pub fn name(self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Parquet => "parquet",
+        }
+    }
+
+    /// Infers the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_name)
+    }
+}
+
+/// How rows whose timestamp is already stored are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExistingPoints {
+    /// Replace the stored price
+    #[default]
+    Upsert,
+    /// Keep the stored price and drop the row
+    Skip,
+}
+
+/// Column mapping and parsing options of an import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportConfig {
+    pub format: ImportFormat,
+    /// Column holding the timestamp or date (matched ignoring case)
+    pub timestamp_column: String,
+    /// Column holding the close price
+    pub price_column: String,
+    /// Column holding the asset key, for files covering several assets
+    pub asset_column: Option<String>,
+    /// `chrono` format of timestamps, e.g. `%d/%m/%Y`; detected when `None`
+    pub timestamp_format: Option<String>,
+    /// Offset that naive timestamps and dates are local to
+    pub timezone: FixedOffset,
+    /// Time of day given to plain dates
+    pub close_time: NaiveTime,
+    /// CSV field delimiter
+    pub delimiter: u8,
+    pub existing: ExistingPoints,
+    /// Data-quality rules run on every asset that received prices
+    pub quality: QualityConfig,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        ImportConfig {
+            format: ImportFormat::Csv,
+            timestamp_column: "date".to_string(),
+            price_column: "close".to_string(),
+            asset_column: None,
+            timestamp_format: None,
+            timezone: FixedOffset::east_opt(0).unwrap(),
+            close_time: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+            delimiter: b',',
+            existing: ExistingPoints::Upsert,
+            quality: QualityConfig::default(),
+        }
+    }
+}
+
+impl ImportConfig {
+    /// Builds a config from named options, as given on the command line or in
+    /// a query string; unset options keep their defaults.
+    ///
+    /// Options: `format` (`csv` or `parquet`), `date_column`, `price_column`,
+    /// `asset_column`, `date_format`, `timezone` (a UTC offset), `close_time`
+    /// (`HH:MM`), `delimiter` (one character) and `existing` (`upsert` or
+    /// `skip`).
+    ///
+    /// # Errors
+    /// Returns `InvalidConfig` for unknown options and malformed values.
+    pub fn from_options<'a, I>(options: I) -> Result<Self, ImportError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let invalid = |name: &str, value: &str| {
+            ImportError::InvalidConfig(format!("invalid {} '{}'", name, value))
+        };
+        let mut config = ImportConfig::default();
+        for (name, value) in options {
+            match name {
+                "format" => {
+                    config.format =
+                        ImportFormat::from_name(value).ok_or_else(|| invalid(name, value))?
+                }
+                "date_column" => config.timestamp_column = value.to_string(),
+                "price_column" => config.price_column = value.to_string(),
+                "asset_column" => config.asset_column = Some(value.to_string()),
+                "date_format" => config.timestamp_format = Some(value.to_string()),
+                "timezone" => {
+                    config.timezone = parse_utc_offset(value).ok_or_else(|| invalid(name, value))?
+                }
+                "close_time" => {
+                    config.close_time = NaiveTime::parse_from_str(value, "%H:%M")
+                        .map_err(|_| invalid(name, value))?
+                }
+                "delimiter" => match value.as_bytes() {
+                    [delimiter] => config.delimiter = *delimiter,
+                    _ => return Err(invalid(name, value)),
+                },
+                "existing" => {
+                    config.existing = match value.to_lowercase().as_str() {
+                        "upsert" => ExistingPoints::Upsert,
+                        "skip" => ExistingPoints::Skip,
+                        _ => return Err(invalid(name, value)),
+                    }
+                }
+                _ => {
+                    return Err(ImportError::InvalidConfig(format!(
+                        "unknown option '{}'",
+                        name
+                    )))
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Parses a UTC offset such as `UTC`, `Z`, `+05:30` or `-0400`.
+pub fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportSummary {
+    /// Data rows read from the file
+    pub rows: usize,
+    /// Prices written
+    pub inserted: usize,
+    /// Rows dropped because their timestamp was already stored
+    pub skipped: usize,
+    /// Assets that received prices, in key order
+    pub assets: Vec<AssetKey>,
+    /// Prices of those assets flagged as bad prints by the quality check
+    pub flagged: usize,
+}
+
+/// Errors that can occur during an import.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    /// The file could not be read
+    Io(String),
+    /// A mapped column is not in the file
+    MissingColumn(String),
+    /// A row could not be parsed; rows are numbered from 1, after the header
+    InvalidRow { row: usize, message: String },
+    /// The options do not describe a valid import
+    InvalidConfig(String),
+    /// Writing to the database failed
+    Database(DataProviderError),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Io(msg) => write!(f, "Failed to read file: {}", msg),
+            ImportError::MissingColumn(column) => write!(f, "Column '{}' not found", column),
+            ImportError::InvalidRow { row, message } => write!(f, "Row {}: {}", row, message),
+            ImportError::InvalidConfig(msg) => write!(f, "Invalid import: {}", msg),
+            ImportError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Imports a CSV or Parquet file.
+///
+/// `asset` receives every row unless the config maps an `asset_column`, in
+/// which case each row names its own asset and `asset` must be `None`.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed, or if writing fails.
+/// Nothing is written when parsing fails.
+pub fn import_file<P: AsRef<Path>>(
+    provider: &mut SqliteDataProvider,
+    path: P,
+    asset: Option<&AssetKey>,
+    config: &ImportConfig,
+) -> Result<ImportSummary, ImportError> {
+    let data = std::fs::read(path).map_err(|e| ImportError::Io(e.to_string()))?;
+    import_bytes(provider, Bytes::from(data), asset, config)
+}
+
+/// Imports the contents of a CSV or Parquet file held in memory.
+///
+/// See [`import_file`].
+pub fn import_bytes(
+    provider: &mut SqliteDataProvider,
+    data: Bytes,
+    asset: Option<&AssetKey>,
+    config: &ImportConfig,
+) -> Result<ImportSummary, ImportError> {
+    match (asset, &config.asset_column) {
+        (Some(_), Some(_)) => {
+            return Err(ImportError::InvalidConfig(
+                "an asset column cannot be combined with a fixed asset".to_string(),
+            ))
+        }
+        (None, None) => {
+            return Err(ImportError::InvalidConfig(
+                "either an asset or an asset column is required".to_string(),
+            ))
+        }
+        _ => {}
+    }
+
+    let rows = match config.format {
+        ImportFormat::Csv => read_csv(&data, config)?,
+        ImportFormat::Parquet => read_parquet(data, config)?,
+    };
+
+    let mut summary = ImportSummary {
+        rows: rows.len(),
+        ..ImportSummary::default()
+    };
+    // Later rows win when a file repeats a timestamp
+    let mut by_asset: BTreeMap<String, (AssetKey, BTreeMap<DateTime<Utc>, f64>)> = BTreeMap::new();
+    for (index, row) in rows.into_iter().enumerate() {
+        let invalid = |message: String| ImportError::InvalidRow {
+            row: index + 1,
+            message,
+        };
+        let key = match (asset, row.asset) {
+            (Some(asset), _) => asset.clone(),
+            (None, Some(name)) => AssetKey::parse(&name)
+                .map_err(|e| invalid(format!("invalid asset '{}': {}", name, e)))?,
+            (None, None) => return Err(invalid("missing asset".to_string())),
+        };
+        let timestamp = row.timestamp.resolve(config).map_err(invalid)?;
+        if !row.price.is_finite() {
+            return Err(invalid(format!("invalid price {}", row.price)));
+        }
+        by_asset
+            .entry(key.as_string())
+            .or_insert_with(|| (key, BTreeMap::new()))
+            .1
+            .insert(timestamp, row.price);
+    }
+
+    for (key, prices) in by_asset.into_values() {
+        let existing = match config.existing {
+            ExistingPoints::Upsert => HashSet::new(),
+            ExistingPoints::Skip => stored_timestamps(provider, &key, &prices)?,
+        };
+        let points: Vec<TimeSeriesPoint> = prices
+            .into_iter()
+            .filter(|(timestamp, _)| !existing.contains(timestamp))
+            .map(|(timestamp, price)| TimeSeriesPoint::new(timestamp, price))
+            .collect();
+        summary.skipped += existing.len();
+        summary.inserted += points.len();
+        provider
+            .insert_time_series_batch(&key, &points)
+            .map_err(ImportError::Database)?;
+        if !points.is_empty() {
+            let report =
+                check_asset(provider, &key, &config.quality).map_err(ImportError::Database)?;
+            summary.flagged += report.bad_prints();
+            summary.assets.push(key);
+        }
+    }
+    Ok(summary)
+}
+
+/// Timestamps among `prices` that are already stored for `key`.
+fn stored_timestamps(
+    provider: &SqliteDataProvider,
+    key: &AssetKey,
+    prices: &BTreeMap<DateTime<Utc>, f64>,
+) -> Result<HashSet<DateTime<Utc>>, ImportError> {
+    let (Some(first), Some(last)) = (prices.keys().next(), prices.keys().next_back()) else {
+        return Ok(HashSet::new());
+    };
+    let range = DateRange::new(first.date_naive(), last.date_naive());
+    match provider.available_dates(key, &range) {
+        Ok(dates) => Ok(dates
+            .into_iter()
+            .filter(|timestamp| prices.contains_key(timestamp))
+            .collect()),
+        Err(DataProviderError::AssetNotFound) => Ok(HashSet::new()),
+        Err(e) => Err(ImportError::Database(e)),
+    }
+}
+
+/// Timestamp as found in a file, before time zone handling.
+enum RawTimestamp {
+    Text(String),
+    Date(NaiveDate),
+    Instant(DateTime<Utc>),
+}
+
+impl RawTimestamp {
+    fn resolve(self, config: &ImportConfig) -> Result<DateTime<Utc>, String> {
+        let local = |naive: NaiveDateTime| {
+            config
+                .timezone
+                .from_local_datetime(&naive)
+                .single()
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .ok_or_else(|| format!("invalid local time {}", naive))
+        };
+        match self {
+            RawTimestamp::Instant(timestamp) => Ok(timestamp),
+            RawTimestamp::Date(date) => local(date.and_time(config.close_time)),
+            RawTimestamp::Text(text) => {
+                let text = text.trim();
+                if let Some(format) = &config.timestamp_format {
+                    if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+                        return local(naive);
+                    }
+                    return NaiveDate::parse_from_str(text, format)
+                        .map_err(|e| format!("invalid timestamp '{}': {}", text, e))
+                        .and_then(|date| local(date.and_time(config.close_time)));
+                }
+                if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+                    return Ok(timestamp.with_timezone(&Utc));
+                }
+                for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+                    if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+                        return local(naive);
+                    }
+                }
+                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .map_err(|_| format!("invalid timestamp '{}'", text))
+                    .and_then(|date| local(date.and_time(config.close_time)))
+            }
+        }
+    }
+}
+
+/// One parsed row of an import file.
+struct RawRow {
+    timestamp: RawTimestamp,
+    price: f64,
+    asset: Option<String>,
+}
+
+fn read_csv(data: &[u8], config: &ImportConfig) -> Result<Vec<RawRow>, ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(config.delimiter)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let headers = reader
+        .headers()
+        .map_err(|e| ImportError::Io(e.to_string()))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+            .ok_or_else(|| ImportError::MissingColumn(name.to_string()))
+    };
+    let timestamp_index = column(&config.timestamp_column)?;
+    let price_index = column(&config.price_column)?;
+    let asset_index = config.asset_column.as_deref().map(column).transpose()?;
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let invalid = |message: String| ImportError::InvalidRow {
+            row: index + 1,
+            message,
+        };
+        let record = record.map_err(|e| invalid(e.to_string()))?;
+        let field = |i: usize| record.get(i).unwrap_or_default();
+        let price = field(price_index);
+        rows.push(RawRow {
+            timestamp: RawTimestamp::Text(field(timestamp_index).to_string()),
+            price: price
+                .parse()
+                .map_err(|_| invalid(format!("invalid price '{}'", price)))?,
+            asset: asset_index.map(|i| field(i).to_string()),
+        });
+    }
+    Ok(rows)
+}
+
+fn read_parquet(data: Bytes, config: &ImportConfig) -> Result<Vec<RawRow>, ImportError> {
+    let reader = SerializedFileReader::new(data).map_err(|e| ImportError::Io(e.to_string()))?;
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+    let column = |name: &str| {
+        schema
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .find(|column| column.eq_ignore_ascii_case(name))
+            .ok_or_else(|| ImportError::MissingColumn(name.to_string()))
+    };
+    let timestamp_column = column(&config.timestamp_column)?;
+    let price_column = column(&config.price_column)?;
+    let asset_column = config.asset_column.as_deref().map(column).transpose()?;
+
+    let rows = reader
+        .get_row_iter(None)
+        .map_err(|e| ImportError::Io(e.to_string()))?;
+    let mut parsed = Vec::new();
+    for (index, row) in rows.enumerate() {
+        let invalid = |message: String| ImportError::InvalidRow {
+            row: index + 1,
+            message,
+        };
+        let row = row.map_err(|e| invalid(e.to_string()))?;
+        let field = |name: &str| {
+            row.get_column_iter()
+                .find(|(column, _)| column.as_str() == name)
+                .map(|(_, field)| field)
+                .ok_or_else(|| invalid(format!("missing {}", name)))
+        };
+        let timestamp = match field(&timestamp_column)? {
+            Field::Str(text) => RawTimestamp::Text(text.clone()),
+            Field::Date(days) => RawTimestamp::Date(
+                NaiveDate::from_num_days_from_ce_opt(days + 719_163)
+                    .ok_or_else(|| invalid(format!("invalid date {}", days)))?,
+            ),
+            Field::TimestampMillis(millis) => RawTimestamp::Instant(
+                DateTime::from_timestamp_millis(*millis)
+                    .ok_or_else(|| invalid(format!("invalid timestamp {}", millis)))?,
+            ),
+            Field::TimestampMicros(micros) => RawTimestamp::Instant(
+                DateTime::from_timestamp_micros(*micros)
+                    .ok_or_else(|| invalid(format!("invalid timestamp {}", micros)))?,
+            ),
+            other => return Err(invalid(format!("unsupported timestamp {}", other))),
+        };
+        let price = match field(&price_column)? {
+            Field::Double(value) => *value,
+            Field::Float(value) => *value as f64,
+            Field::Int(value) => *value as f64,
+            Field::Long(value) => *value as f64,
+            Field::Str(text) => text
+                .trim()
+                .parse()
+                .map_err(|_| invalid(format!("invalid price '{}'", text)))?,
+            other => return Err(invalid(format!("unsupported price {}", other))),
+        };
+        let asset = match &asset_column {
+            Some(name) => match field(name)? {
+                Field::Str(text) => Some(text.clone()),
+                other => return Err(invalid(format!("unsupported asset {}", other))),
+            },
+            None => None,
+        };
+        parsed.push(RawRow {
+            timestamp,
+            price,
+            asset,
+        });
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ImportConfig {
+        ImportConfig {
+            timestamp_column: "Date".to_string(),
+            price_column: "Adj Close".to_string(),
+            ..ImportConfig::default()
+        }
+    }
+
+    fn stored(provider: &SqliteDataProvider, key: &AssetKey) -> Vec<TimeSeriesPoint> {
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        );
+        provider.get_time_series(key, &range).unwrap()
+    }
+
+    #[test]
+    fn csv_import_maps_columns_and_skips_existing_points() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let aapl = AssetKey::new_equity("AAPL").unwrap();
+        let csv = "Date,Close,Adj Close\n\
+                   2024-01-02,186.0,185.5\n\
+                   2024-01-03,184.0,183.5\n";
+        let summary =
+            import_bytes(&mut provider, Bytes::from(csv), Some(&aapl), &config()).unwrap();
+        assert_eq!(summary.inserted, 2);
+        assert_eq!(summary.assets, vec![aapl.clone()]);
+        let points = stored(&provider, &aapl);
+        assert_eq!(
+            points[0].timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap()
+        );
+        assert_eq!(points[0].close_price, 185.5);
+
+        let update = "Date,Adj Close\n2024-01-03,999.0\n2024-01-04,182.0\n";
+        let skip = ImportConfig {
+            existing: ExistingPoints::Skip,
+            ..config()
+        };
+        let summary = import_bytes(&mut provider, Bytes::from(update), Some(&aapl), &skip).unwrap();
+        assert_eq!((summary.rows, summary.inserted, summary.skipped), (2, 1, 1));
+        let points = stored(&provider, &aapl);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[1].close_price, 183.5);
+
+        let summary =
+            import_bytes(&mut provider, Bytes::from(update), Some(&aapl), &config()).unwrap();
+        assert_eq!((summary.inserted, summary.skipped), (2, 0));
+        assert_eq!(stored(&provider, &aapl)[1].close_price, 999.0);
+    }
+
+    #[test]
+    fn csv_import_reads_asset_column_and_local_times() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let csv = "symbol;time;price\n\
+                   ES-2024-12-20;02/01/2024 09:30;4750.25\n\
+                   EUR/USD;02/01/2024 09:30;1.0945\n";
+        let config = ImportConfig {
+            timestamp_column: "time".to_string(),
+            price_column: "price".to_string(),
+            asset_column: Some("symbol".to_string()),
+            timestamp_format: Some("%d/%m/%Y %H:%M".to_string()),
+            timezone: parse_utc_offset("-05:00").unwrap(),
+            delimiter: b';',
+            ..ImportConfig::default()
+        };
+        let summary = import_bytes(&mut provider, Bytes::from(csv), None, &config).unwrap();
+        let future = AssetKey::parse("ES-2024-12-20").unwrap();
+        assert_eq!(
+            summary.assets,
+            vec![future.clone(), AssetKey::new_fx("EUR", "USD").unwrap()]
+        );
+        assert_eq!(
+            stored(&provider, &future)[0].timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn parquet_import_reads_typed_columns() {
+        use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int32Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let schema = parse_message_type(
+            "message prices {
+                REQUIRED BYTE_ARRAY ticker (UTF8);
+                REQUIRED INT32 day (DATE);
+                REQUIRED DOUBLE close;
+            }",
+        )
+        .unwrap();
+        let mut buffer = Vec::new();
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut writer =
+            SerializedFileWriter::new(&mut buffer, Arc::new(schema), properties).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        let tickers = [ByteArray::from("AAPL"), ByteArray::from("MSFT")];
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&tickers, None, None)
+            .unwrap();
+        column.close().unwrap();
+        // Days since 1970-01-01, i.e. 2024-01-02
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(&[19_724, 19_724], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<DoubleType>()
+            .write_batch(&[185.5, 370.9], None, None)
+            .unwrap();
+        column.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let config = ImportConfig {
+            format: ImportFormat::Parquet,
+            timestamp_column: "day".to_string(),
+            asset_column: Some("ticker".to_string()),
+            ..ImportConfig::default()
+        };
+        let summary = import_bytes(&mut provider, Bytes::from(buffer), None, &config).unwrap();
+        assert_eq!(summary.inserted, 2);
+        let msft = stored(&provider, &AssetKey::new_equity("MSFT").unwrap());
+        assert_eq!(
+            msft[0].timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap()
+        );
+        assert_eq!(msft[0].close_price, 370.9);
+    }
+
+    #[test]
+    fn invalid_files_are_rejected_before_writing() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let aapl = AssetKey::new_equity("AAPL").unwrap();
+        let missing = "Day,Adj Close\n2024-01-02,1.0\n";
+        assert_eq!(
+            import_bytes(&mut provider, Bytes::from(missing), Some(&aapl), &config()),
+            Err(ImportError::MissingColumn("Date".to_string()))
+        );
+        let bad = "Date,Adj Close\n2024-01-02,1.0\nsoon,2.0\n";
+        assert!(matches!(
+            import_bytes(&mut provider, Bytes::from(bad), Some(&aapl), &config()),
+            Err(ImportError::InvalidRow { row: 2, .. })
+        ));
+        assert!(import_bytes(&mut provider, Bytes::from(bad), None, &config()).is_err());
+        assert_eq!(provider.list_assets(&Default::default()).unwrap().total, 0);
+
+        assert_eq!(parse_utc_offset("+0530"), FixedOffset::east_opt(19_800));
+        let options = [
+            ("format", "parquet"),
+            ("existing", "skip"),
+            ("delimiter", ";"),
+        ];
+        let parsed = ImportConfig::from_options(options).unwrap();
+        assert_eq!(parsed.format, ImportFormat::Parquet);
+        assert_eq!(parsed.existing, ExistingPoints::Skip);
+        assert_eq!(parsed.delimiter, b';');
+        assert!(ImportConfig::from_options([("timezone", "EST")]).is_err());
+        assert!(ImportConfig::from_options([("columns", "date")]).is_err());
+        assert_eq!(parse_utc_offset("EST"), None);
+    }
+}
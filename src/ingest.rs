@@ -0,0 +1,315 @@
+//! Scheduled refresh of stored prices from a market data source.
+//!
+//! [`refresh_all`] brings every asset in the catalog up to a given date: it
+//! computes the trading days missing since the asset's sync watermark,
+//! fetches only those gaps, and records the new watermark (or the error) in
+//! the `sync_state` table, then runs the data-quality checks on assets that
+//! received prices. [`spawn_refresh_job`] repeats this on an interval
+//! and publishes progress in an [`IngestStatus`].
+
+use crate::asset_key::AssetKey;
+use crate::calendar::TradingCalendar;
+use crate::market_data::MarketDataSource;
+use crate::quality::{check_asset, QualityConfig};
+use crate::sqlite_provider::{AssetFilter, AssetSummary, SqliteDataProvider};
+use crate::time_series::{DataProvider, DataProviderError, DateRange, TimeSeriesPoint};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// Configuration of a refresh run.
+#[derive(Debug, Clone)]
+pub struct RefreshConfig {
+    /// First date fetched for assets with neither stored prices nor a watermark
+    /// (default: 2020-01-01)
+    pub history_start: NaiveDate,
+    /// Trading calendars by exchange name (case-insensitive)
+    pub calendars: HashMap<String, TradingCalendar>,
+    /// Calendar for exchanges without an entry in `calendars` (default: weekdays)
+    pub default_calendar: TradingCalendar,
+    /// Data-quality rules run on assets that received prices; the asset's
+    /// trading calendar replaces `quality.calendar`
+    pub quality: QualityConfig,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        RefreshConfig {
+            history_start: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            calendars: HashMap::new(),
+            default_calendar: TradingCalendar::weekdays(),
+            quality: QualityConfig::default(),
+        }
+    }
+}
+
+impl RefreshConfig {
+    /// Sets the trading calendar of an exchange.
+    pub fn with_calendar(mut self, exchange: &str, calendar: TradingCalendar) -> Self {
+        self.calendars.insert(exchange.to_uppercase(), calendar);
+        self
+    }
+
+    /// Returns the trading calendar of `exchange`.
+    pub fn calendar(&self, exchange: &str) -> &TradingCalendar {
+        self.calendars
+            .get(&exchange.to_uppercase())
+            .unwrap_or(&self.default_calendar)
+    }
+}
+
+/// Outcome of refreshing one asset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetRefresh {
+    pub asset: AssetKey,
+    /// Missing date ranges that were fetched
+    pub gaps: Vec<DateRange>,
+    /// Number of prices stored
+    pub points_added: usize,
+    /// Error that stopped the refresh, if any
+    pub error: Option<String>,
+}
+
+/// Outcome of a refresh run over the whole catalog.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefreshSummary {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Number of assets in the catalog
+    pub assets: usize,
+    /// Number of assets that had gaps to fetch
+    pub refreshed: usize,
+    /// Number of prices stored across all assets
+    pub points_added: usize,
+    /// Error message by asset key, for assets whose refresh failed
+    pub failed: BTreeMap<String, String>,
+}
+
+/// State of the background refresh job, as served by `/ingest/status`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IngestStatus {
+    /// Whether a refresh job is scheduled
+    pub enabled: bool,
+    /// Name of the market data source
+    pub source: Option<String>,
+    /// Seconds between refresh runs
+    pub interval_seconds: Option<u64>,
+    /// Whether a refresh run is in progress
+    pub running: bool,
+    /// Summary of the last completed run
+    pub last_run: Option<RefreshSummary>,
+    /// When the next run is due
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+/// Refreshes every asset in the catalog from `source` through `today`.
+///
+/// The provider is locked only while reading gaps and storing prices, so
+/// other users of the database are not blocked by slow downloads. A failing
+/// asset is recorded in its sync state and does not stop the others.
+///
+/// # Errors
+/// Returns an error only if the catalog cannot be listed.
+pub async fn refresh_all<S: MarketDataSource + Sync>(
+    source: &S,
+    provider: &Mutex<SqliteDataProvider>,
+    config: &RefreshConfig,
+    today: NaiveDate,
+) -> Result<RefreshSummary, DataProviderError> {
+    let started_at = Utc::now();
+    let catalog = provider
+        .lock()
+        .await
+        .list_assets(&AssetFilter::default())?
+        .assets;
+    log::info!(
+        "Refreshing {} assets from {} through {}",
+        catalog.len(),
+        source.name(),
+        today
+    );
+
+    let mut summary = RefreshSummary {
+        started_at,
+        finished_at: started_at,
+        assets: catalog.len(),
+        refreshed: 0,
+        points_added: 0,
+        failed: BTreeMap::new(),
+    };
+    for asset in &catalog {
+        let refresh = refresh_asset(source, provider, asset, config, today).await;
+        if !refresh.gaps.is_empty() {
+            summary.refreshed += 1;
+        }
+        summary.points_added += refresh.points_added;
+        if let Some(error) = refresh.error {
+            summary.failed.insert(refresh.asset.as_string(), error);
+        }
+    }
+    summary.finished_at = Utc::now();
+
+    log::info!(
+        "Refresh complete: {} points added, {} of {} assets failed",
+        summary.points_added,
+        summary.failed.len(),
+        summary.assets
+    );
+    Ok(summary)
+}
+
+/// Refreshes one catalog asset from `source` through `today`.
+///
+/// Only trading days after the asset's watermark are considered; an asset
+/// that was never synced is checked from its first stored price, or from
+/// `config.history_start` if it has none. On success the watermark moves to
+/// the last stored price.
+pub async fn refresh_asset<S: MarketDataSource + Sync>(
+    source: &S,
+    provider: &Mutex<SqliteDataProvider>,
+    asset: &AssetSummary,
+    config: &RefreshConfig,
+    today: NaiveDate,
+) -> AssetRefresh {
+    let key = &asset.key;
+    let attempted_at = Utc::now();
+    let mut refresh = AssetRefresh {
+        asset: key.clone(),
+        gaps: Vec::new(),
+        points_added: 0,
+        error: None,
+    };
+
+    let result = fetch_gaps(source, provider, asset, config, today, &mut refresh).await;
+    let mut provider = provider.lock().await;
+    if refresh.points_added > 0 {
+        let quality = QualityConfig {
+            calendar: config.calendar(&asset.exchange).clone(),
+            ..config.quality.clone()
+        };
+        if let Err(e) = check_asset(&mut provider, key, &quality) {
+            log::error!("Quality check of {} failed: {}", key, e);
+        }
+    }
+    let recorded = match &result {
+        Ok(watermark) => provider.record_sync_success(key, *watermark, attempted_at),
+        Err(error) => {
+            log::warn!("Refresh of {} failed: {}", key, error);
+            provider.record_sync_failure(key, error, attempted_at)
+        }
+    };
+    refresh.error = result.err();
+    if let Err(e) = recorded {
+        log::error!("Failed to record sync state for {}: {}", key, e);
+        refresh.error.get_or_insert(e.to_string());
+    }
+    refresh
+}
+
+/// Fetches and stores the gaps of `asset`, returning its new watermark.
+async fn fetch_gaps<S: MarketDataSource + Sync>(
+    source: &S,
+    provider: &Mutex<SqliteDataProvider>,
+    asset: &AssetSummary,
+    config: &RefreshConfig,
+    today: NaiveDate,
+    refresh: &mut AssetRefresh,
+) -> Result<Option<NaiveDate>, String> {
+    let key = &asset.key;
+    let calendar = config.calendar(&asset.exchange);
+
+    let (range, mut stored) = {
+        let provider = provider.lock().await;
+        let state = provider.get_sync_state(key).map_err(|e| e.to_string())?;
+        let start = match state.as_ref().and_then(|state| state.watermark) {
+            Some(watermark) => watermark.succ_opt().unwrap_or(watermark),
+            None => asset.first_date.unwrap_or(config.history_start),
+        };
+        if start > today {
+            return Ok(None);
+        }
+        let range = DateRange::new(start, today);
+        let stored: HashSet<NaiveDate> = provider
+            .available_dates(key, &range)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|timestamp| timestamp.date_naive())
+            .collect();
+        (range, stored)
+    };
+
+    refresh.gaps = calendar.gaps(&range, &stored);
+    for gap in refresh.gaps.clone() {
+        log::debug!("Fetching {} from {} to {}", key, gap.start, gap.end);
+        let points: Vec<TimeSeriesPoint> = source
+            .fetch_history(key, &gap)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|point| {
+                let date = point.timestamp.date_naive();
+                date >= gap.start && date <= gap.end && !stored.contains(&date)
+            })
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+
+        let mut provider = provider.lock().await;
+        provider
+            .insert_time_series_batch(key, &points)
+            .map_err(|e| format!("Failed to store data: {}", e))?;
+        refresh.points_added += points.len();
+        stored.extend(points.iter().map(|point| point.timestamp.date_naive()));
+    }
+
+    Ok(stored.into_iter().max())
+}
+
+/// Runs [`refresh_all`] every `interval` in the background, starting now.
+///
+/// Each run refreshes through the current UTC date and publishes its
+/// progress and summary in `status`.
+pub fn spawn_refresh_job<S>(
+    source: S,
+    provider: Arc<Mutex<SqliteDataProvider>>,
+    status: Arc<RwLock<IngestStatus>>,
+    config: RefreshConfig,
+    interval: Duration,
+) -> JoinHandle<()>
+where
+    S: MarketDataSource + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        {
+            let mut status = status.write().await;
+            status.enabled = true;
+            status.source = Some(source.name().to_string());
+            status.interval_seconds = Some(interval.as_secs());
+        }
+
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            status.write().await.running = true;
+
+            let today = Utc::now().date_naive();
+            let result = refresh_all(&source, &provider, &config, today).await;
+
+            let mut status = status.write().await;
+            status.running = false;
+            status.next_run = chrono::Duration::from_std(interval)
+                .ok()
+                .map(|interval| Utc::now() + interval);
+            match result {
+                Ok(summary) => status.last_run = Some(summary),
+                Err(e) => log::error!("Refresh run failed: {}", e),
+            }
+        }
+    })
+}
@@ -1,10 +1,17 @@
 pub mod analytics;
 pub mod asset;
 pub mod asset_key;
+pub mod calendar;
 pub mod dag;
 pub mod equity;
 pub mod future;
+pub mod fx;
+pub mod importer;
+pub mod ingest;
+pub mod market_data;
+pub mod migrations;
 pub mod push_mode;
+pub mod quality;
 pub mod replay;
 pub mod server;
 pub mod sqlite_provider;
@@ -20,16 +27,34 @@ pub use analytics::{
 };
 pub use asset::{Asset, AssetType};
 pub use asset_key::AssetKey;
+pub use calendar::TradingCalendar;
 pub use dag::{AnalyticsDag, DagError, Node, NodeId, NodeOutput, NodeParams};
 pub use equity::{AssetMetadata, CorporateAction, Equity};
 pub use future::{ExpiryCalendar, Future};
+pub use fx::FxRate;
+pub use importer::{
+    import_bytes, import_file, ExistingPoints, ImportConfig, ImportError, ImportFormat,
+    ImportSummary,
+};
+pub use ingest::{
+    refresh_all, spawn_refresh_job, AssetRefresh, IngestStatus, RefreshConfig, RefreshSummary,
+};
+pub use market_data::mock_vendor::{MockFailure, MockVendorServer};
+pub use market_data::MarketDataSource;
+pub use migrations::SchemaError;
 pub use push_mode::{
     CircularBuffer, InitError, NodePushState, NodeState, PushError, PushModeEngine,
 };
+pub use quality::{
+    check_asset, check_series, FlaggedPoint, FlaggedPoints, QualityConfig, QualityFlag,
+    QualityFilteredProvider, QualityReport,
+};
 pub use replay::{ReplayEngine, ReplayError, ReplayResult};
 pub use server::{run_server, ApiError, AppState, ServerConfig};
-pub use sqlite_provider::SqliteDataProvider;
+pub use sqlite_provider::{SqliteDataProvider, SyncState};
 pub use time_series::{
-    DataProvider, DataProviderError, DateRange, InMemoryDataProvider, TimeSeriesPoint,
+    AsOfProvider, DataProvider, DataProviderError, DateRange, InMemoryDataProvider, TimeSeriesPoint,
+};
+pub use yahoo_finance::{
+    DownloadError, DownloadResult, DownloaderConfig, YahooFinanceDownloader, YAHOO_FINANCE_BASE_URL,
 };
-pub use yahoo_finance::{DownloadError, DownloadResult, DownloaderConfig, YahooFinanceDownloader};
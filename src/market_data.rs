@@ -0,0 +1,258 @@
+//! Pluggable sources of historical market data.
+//!
+//! A [`MarketDataSource`] fetches daily closes for an asset over a date range.
+//! [`YahooFinanceDownloader`](crate::yahoo_finance::YahooFinanceDownloader) is
+//! the production implementation; [`mock_vendor::MockVendorServer`] is a local
+//! stand-in that serves canned CSV so downloads can be exercised offline.
+
+pub mod mock_vendor;
+
+use crate::asset_key::AssetKey;
+use crate::quality::{check_asset, QualityConfig};
+use crate::sqlite_provider::SqliteDataProvider;
+use crate::time_series::{DataProvider, DateRange, TimeSeriesPoint};
+use crate::yahoo_finance::{DownloadError, DownloadResult};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// A vendor of historical daily prices.
+pub trait MarketDataSource {
+    /// Short name of the vendor, used in logs.
+    fn name(&self) -> &str;
+
+    /// Fetches daily closes for `asset_key` within `date_range` (inclusive),
+    /// sorted by timestamp.
+    fn fetch_history(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> impl Future<Output = Result<Vec<TimeSeriesPoint>, DownloadError>> + Send;
+}
+
+/// Downloads historical data from `source` and stores it in SQLite.
+///
+/// Checks for existing data and only stores missing dates (incremental behavior),
+/// then runs the default data-quality checks over the asset's history.
+///
+/// # Arguments
+/// * `source` - The market data source to download from
+/// * `provider` - Mutable reference to SqliteDataProvider for storing data
+/// * `asset_key` - The asset key to download data for
+/// * `date_range` - The date range to download
+///
+/// # Returns
+/// Returns the number of data points downloaded and stored.
+///
+/// # Errors
+/// Returns `DownloadError` if the download or storage operation fails.
+pub async fn download_to_sqlite<S: MarketDataSource>(
+    source: &S,
+    provider: &mut SqliteDataProvider,
+    asset_key: &AssetKey,
+    date_range: &DateRange,
+) -> Result<usize, DownloadError> {
+    let asset_str = asset_key.to_string();
+    log::debug!(
+        "Starting download for asset: {} ({} to {})",
+        asset_str,
+        date_range.start,
+        date_range.end
+    );
+
+    // Check for existing data in SQLite
+    // If asset not found, treat as empty (no existing data)
+    let existing_data = match provider.get_time_series(asset_key, date_range) {
+        Ok(data) => data,
+        Err(crate::time_series::DataProviderError::AssetNotFound) => {
+            // Asset doesn't exist yet - no existing data
+            log::debug!(
+                "Asset {} not found in database, will download all dates",
+                asset_str
+            );
+            Vec::new()
+        }
+        Err(e) => {
+            log::error!("Failed to query existing data for {}: {}", asset_str, e);
+            return Err(DownloadError::ParseError(format!(
+                "Failed to query existing data: {}",
+                e
+            )));
+        }
+    };
+
+    // Extract existing dates
+    let existing_dates: std::collections::HashSet<NaiveDate> = existing_data
+        .iter()
+        .map(|point| point.timestamp.date_naive())
+        .collect();
+
+    // Determine which dates need to be downloaded
+    let mut dates_to_download = Vec::new();
+    let mut current_date = date_range.start;
+    while current_date <= date_range.end {
+        if !existing_dates.contains(&current_date) {
+            dates_to_download.push(current_date);
+        }
+        current_date = current_date.succ_opt().unwrap_or(current_date);
+    }
+
+    // If all dates already exist, return early
+    if dates_to_download.is_empty() {
+        log::info!(
+            "All dates already exist for {}, skipping download",
+            asset_str
+        );
+        return Ok(0);
+    }
+
+    log::info!(
+        "Downloading {} missing dates for {} from {}",
+        dates_to_download.len(),
+        asset_str,
+        source.name()
+    );
+
+    // Download data for the full range (vendors serve ranges, not individual dates)
+    let points = source.fetch_history(asset_key, date_range).await?;
+
+    // Filter out points that already exist in database
+    let new_points: Vec<TimeSeriesPoint> = points
+        .into_iter()
+        .filter(|point| {
+            let point_date = point.timestamp.date_naive();
+            !existing_dates.contains(&point_date)
+        })
+        .collect();
+
+    // Store new points using batch insert
+    if !new_points.is_empty() {
+        log::debug!(
+            "Storing {} new data points for {}",
+            new_points.len(),
+            asset_str
+        );
+        provider
+            .insert_time_series_batch(asset_key, &new_points)
+            .map_err(|e| {
+                log::error!("Failed to store data for {}: {}", asset_str, e);
+                DownloadError::ParseError(format!("Failed to store data: {}", e))
+            })?;
+        log::info!(
+            "Successfully stored {} data points for {}",
+            new_points.len(),
+            asset_str
+        );
+        check_asset(provider, asset_key, &QualityConfig::default()).map_err(|e| {
+            DownloadError::ParseError(format!("Failed to check data quality: {}", e))
+        })?;
+    } else {
+        log::info!("No new data points to store for {}", asset_str);
+    }
+
+    Ok(new_points.len())
+}
+
+/// Downloads historical data for multiple assets with partial failure handling.
+///
+/// Each asset is downloaded independently. If one asset fails, the remaining
+/// assets are still downloaded, and only failed assets are retried, up to
+/// `max_retries` more times.
+///
+/// # Returns
+/// Returns a `DownloadResult` containing:
+/// - A map of successful downloads (asset_key -> number of points downloaded)
+/// - A map of failed downloads (asset_key -> error message)
+pub async fn download_multiple_to_sqlite<S: MarketDataSource>(
+    source: &S,
+    provider: &mut SqliteDataProvider,
+    assets: &[(AssetKey, DateRange)],
+    max_retries: u32,
+) -> DownloadResult {
+    let mut successful: HashMap<String, usize> = HashMap::new();
+    let mut failed: HashMap<String, String> = HashMap::new();
+    let mut to_retry: Vec<(AssetKey, DateRange)> = Vec::new();
+
+    log::info!(
+        "Starting download for {} assets from {}",
+        assets.len(),
+        source.name()
+    );
+
+    // First attempt: download all assets
+    for (asset_key, date_range) in assets {
+        let asset_str = asset_key.to_string();
+        log::info!("Downloading data for asset: {}", asset_str);
+
+        match download_to_sqlite(source, provider, asset_key, date_range).await {
+            Ok(count) => {
+                log::info!(
+                    "Successfully downloaded {} data points for {}",
+                    count,
+                    asset_str
+                );
+                successful.insert(asset_str.clone(), count);
+            }
+            Err(e) => {
+                log::warn!("Failed to download {}: {}", asset_str, e);
+                to_retry.push((asset_key.clone(), date_range.clone()));
+            }
+        }
+    }
+
+    // Retry failed assets
+    if !to_retry.is_empty() {
+        log::info!("Retrying {} failed assets", to_retry.len());
+        let mut retry_attempt = 0;
+        let mut remaining_failures = to_retry;
+
+        while retry_attempt < max_retries && !remaining_failures.is_empty() {
+            retry_attempt += 1;
+            log::info!(
+                "Retry attempt {}/{} for {} assets",
+                retry_attempt,
+                max_retries,
+                remaining_failures.len()
+            );
+
+            let mut next_retry = Vec::new();
+
+            for (asset_key, date_range) in remaining_failures {
+                let asset_str = asset_key.to_string();
+
+                match download_to_sqlite(source, provider, &asset_key, &date_range).await {
+                    Ok(count) => {
+                        log::info!(
+                            "Successfully downloaded {} data points for {} on retry",
+                            count,
+                            asset_str
+                        );
+                        successful.insert(asset_str.clone(), count);
+                    }
+                    Err(e) => {
+                        log::warn!("Retry {} failed for {}: {}", retry_attempt, asset_str, e);
+                        next_retry.push((asset_key, date_range));
+                    }
+                }
+            }
+
+            remaining_failures = next_retry;
+        }
+
+        // Record final failures
+        for (asset_key, _) in remaining_failures {
+            let asset_str = asset_key.to_string();
+            let error_msg = format!("Failed after {} retry attempts", max_retries);
+            log::error!("Asset {} failed after all retries", asset_str);
+            failed.insert(asset_str, error_msg);
+        }
+    }
+
+    log::info!(
+        "Download complete: {} successful, {} failed",
+        successful.len(),
+        failed.len()
+    );
+
+    DownloadResult { successful, failed }
+}
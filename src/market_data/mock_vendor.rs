@@ -0,0 +1,194 @@
+//! Local stand-in for a market data vendor.
+//!
+//! [`MockVendorServer`] speaks the Yahoo Finance download protocol on a
+//! loopback port and serves canned CSV, so the downloader's retry, rate-limit
+//! and parsing paths can be tested end to end without network access. Point
+//! a downloader at it through `DownloaderConfig::base_url`.
+
+use crate::time_series::TimeSeriesPoint;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::task::JoinHandle;
+
+/// A scripted failure returned instead of the canned data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockFailure {
+    /// Respond with this HTTP status and an empty body
+    Status(u16),
+    /// Respond with 429 Too Many Requests and a `Retry-After` header in seconds
+    RateLimited { retry_after_seconds: u64 },
+    /// Respond with 200 and this body instead of the canned CSV
+    Body(String),
+}
+
+/// A download request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// Symbol from the request path
+    pub symbol: String,
+    /// `period1` query parameter (start, Unix seconds)
+    pub period1: Option<i64>,
+    /// `period2` query parameter (end, Unix seconds)
+    pub period2: Option<i64>,
+    /// When the request arrived
+    pub received_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    csv: Mutex<HashMap<String, String>>,
+    failures: Mutex<HashMap<String, VecDeque<MockFailure>>>,
+    requests: Mutex<Vec<MockRequest>>,
+}
+
+/// A Yahoo-compatible HTTP server on `127.0.0.1` serving canned CSV.
+///
+/// The server runs on the current Tokio runtime and stops when dropped.
+/// Unknown symbols get a 404.
+#[derive(Debug)]
+pub struct MockVendorServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockVendorServer {
+    /// Starts the server on an ephemeral loopback port.
+    pub async fn start() -> std::io::Result<Self> {
+        let state = Arc::new(MockState::default());
+        let app = Router::new()
+            .route("/v7/finance/download/:symbol", get(download))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                log::error!("Mock vendor server stopped: {}", e);
+            }
+        });
+
+        Ok(MockVendorServer {
+            addr,
+            state,
+            handle,
+        })
+    }
+
+    /// Base URL to use as `DownloaderConfig::base_url`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Serves `csv` verbatim for `symbol`.
+    pub fn add_csv(&self, symbol: &str, csv: impl Into<String>) {
+        self.state
+            .csv
+            .lock()
+            .unwrap()
+            .insert(symbol.to_string(), csv.into());
+    }
+
+    /// Serves `points` for `symbol` as a Yahoo Finance CSV
+    /// (`Date,Open,High,Low,Close,Adj Close,Volume`, one row per point).
+    pub fn add_series(&self, symbol: &str, points: &[TimeSeriesPoint]) {
+        let mut csv = String::from("Date,Open,High,Low,Close,Adj Close,Volume\n");
+        for point in points {
+            let price = point.close_price;
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},1000000\n",
+                point.timestamp.date_naive().format("%Y-%m-%d"),
+                price,
+                price,
+                price,
+                price,
+                price
+            ));
+        }
+        self.add_csv(symbol, csv);
+    }
+
+    /// Queues `failure` as the response to the next request for `symbol`.
+    ///
+    /// Failures are served in the order they were queued, before the canned data.
+    pub fn fail_next(&self, symbol: &str, failure: MockFailure) {
+        self.state
+            .failures
+            .lock()
+            .unwrap()
+            .entry(symbol.to_string())
+            .or_default()
+            .push_back(failure);
+    }
+
+    /// Returns every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Returns how many requests were received for `symbol`.
+    pub fn request_count(&self, symbol: &str) -> usize {
+        self.state
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.symbol == symbol)
+            .count()
+    }
+}
+
+impl Drop for MockVendorServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn download(
+    State(state): State<Arc<MockState>>,
+    Path(symbol): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let period = |name: &str| params.get(name).and_then(|value| value.parse().ok());
+    state.requests.lock().unwrap().push(MockRequest {
+        symbol: symbol.clone(),
+        period1: period("period1"),
+        period2: period("period2"),
+        received_at: Instant::now(),
+    });
+
+    let failure = state
+        .failures
+        .lock()
+        .unwrap()
+        .get_mut(&symbol)
+        .and_then(|queue| queue.pop_front());
+    match failure {
+        Some(MockFailure::Status(code)) => StatusCode::from_u16(code)
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response(),
+        Some(MockFailure::RateLimited {
+            retry_after_seconds,
+        }) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after_seconds.to_string())],
+        )
+            .into_response(),
+        Some(MockFailure::Body(body)) => body.into_response(),
+        None => match state.csv.lock().unwrap().get(&symbol) {
+            Some(csv) => ([(header::CONTENT_TYPE, "text/csv")], csv.clone()).into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                format!("No data found for {}", symbol),
+            )
+                .into_response(),
+        },
+    }
+}
@@ -0,0 +1,329 @@
+//! Versioned schema migrations of the SQLite store.
+//!
+//! The schema version of a database is the highest version recorded in its
+//! `schema_version` table. Opening a database applies the [`MIGRATIONS`] above
+//! that version in order, each in its own transaction together with its
+//! `schema_version` row, so a failed migration leaves the database at the
+//! previous version and processes opening the database at once apply each
+//! migration only once. A database whose version is newer than the last known
+//! migration is refused rather than written by code that does not know it.
+//!
+//! Databases created before versioning have no `schema_version` table and are
+//! at version 0. Migrations create tables and columns only if they are missing,
+//! so such databases are adopted in place. New migrations are appended with
+//! the next version; released migrations are never edited.
+
+use chrono::Utc;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+/// Knowledge time of prices stored before their versions were recorded.
+const UNVERSIONED_RECORDED_AT: &str = "1970-01-01T00:00:00.000000Z";
+
+/// One step of the schema history.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Schema version reached once the migration is applied
+    pub version: u32,
+    /// Summary recorded in `schema_version`
+    pub description: &'static str,
+    /// Applies the migration; runs inside a transaction
+    pub apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+/// Every migration, ordered by version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Assets, prices and stored analytics",
+        apply: create_core_tables,
+    },
+    Migration {
+        version: 2,
+        description: "Data-quality flags and refresh state",
+        apply: create_quality_and_sync_tables,
+    },
+    Migration {
+        version: 3,
+        description: "Knowledge times and history of replaced prices",
+        apply: add_price_history,
+    },
+];
+
+/// Errors opening or migrating a database.
+#[derive(Debug)]
+pub enum SchemaError {
+    /// The database was written by a newer version of the application
+    NewerVersion { found: u32, latest: u32 },
+    /// A migration failed; its changes were rolled back
+    Migration {
+        version: u32,
+        source: rusqlite::Error,
+    },
+    /// Reading or recording the schema version failed
+    Database(rusqlite::Error),
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::NewerVersion { found, latest } => write!(
+                f,
+                "Database schema version {} is newer than the latest known version {}",
+                found, latest
+            ),
+            SchemaError::Migration { version, source } => {
+                write!(
+                    f,
+                    "Migration to schema version {} failed: {}",
+                    version, source
+                )
+            }
+            SchemaError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<rusqlite::Error> for SchemaError {
+    fn from(e: rusqlite::Error) -> Self {
+        SchemaError::Database(e)
+    }
+}
+
+/// The version a fully migrated database has.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Reads the schema version of a database; 0 if it was never migrated.
+pub fn schema_version(conn: &Connection) -> Result<u32, SchemaError> {
+    let versioned = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type='table' AND name='schema_version'")?
+        .exists([])?;
+    if !versioned {
+        return Ok(0);
+    }
+    let version: Option<u32> =
+        conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+            row.get(0)
+        })?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Brings a database to the latest schema version, returning that version.
+///
+/// # Errors
+/// Returns [`SchemaError::NewerVersion`] without changing anything if the
+/// database is newer than this build, or the first migration that failed.
+pub fn migrate(conn: &Connection) -> Result<u32, SchemaError> {
+    apply_migrations(conn, MIGRATIONS)
+}
+
+fn apply_migrations(conn: &Connection, migrations: &[Migration]) -> Result<u32, SchemaError> {
+    let latest = migrations.last().map_or(0, |migration| migration.version);
+    loop {
+        // An immediate transaction takes the write lock before the version is
+        // read, so a second process opening the database waits here and then
+        // sees the migrations the first one applied. Dropping the transaction
+        // on an error rolls it back.
+        let transaction = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        let current = schema_version(&transaction)?;
+        if current > latest {
+            return Err(SchemaError::NewerVersion {
+                found: current,
+                latest,
+            });
+        }
+        let Some(migration) = migrations.iter().find(|m| m.version > current) else {
+            return Ok(latest);
+        };
+
+        transaction.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        (migration.apply)(&transaction).map_err(|source| SchemaError::Migration {
+            version: migration.version,
+            source,
+        })?;
+        transaction.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                migration.version,
+                migration.description,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        transaction.commit()?;
+    }
+}
+
+/// Checks if a table has a column.
+fn column_exists(conn: &Connection, table_name: &str, column_name: &str) -> rusqlite::Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table_name))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for column in columns {
+        if column? == column_name {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn create_core_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS assets (
+            asset_key TEXT PRIMARY KEY,
+            asset_data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS time_series_data (
+            asset_key TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            close_price REAL NOT NULL,
+            PRIMARY KEY (asset_key, timestamp)
+        );
+        CREATE INDEX IF NOT EXISTS idx_time_series_asset_key ON time_series_data(asset_key);
+        CREATE INDEX IF NOT EXISTS idx_time_series_timestamp ON time_series_data(timestamp);
+        CREATE TABLE IF NOT EXISTS analytics (
+            asset_key TEXT NOT NULL,
+            date TEXT NOT NULL,
+            analytics_name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (asset_key, date, analytics_name)
+        );",
+    )
+}
+
+fn create_quality_and_sync_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS quality_flags (
+            asset_key TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            flag TEXT NOT NULL,
+            detail TEXT NOT NULL,
+            PRIMARY KEY (asset_key, timestamp, flag)
+        );
+        CREATE TABLE IF NOT EXISTS sync_state (
+            asset_key TEXT PRIMARY KEY,
+            watermark TEXT,
+            last_attempt TEXT,
+            last_success TEXT,
+            last_error TEXT
+        );",
+    )
+}
+
+fn add_price_history(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "time_series_data", "recorded_at")? {
+        conn.execute(
+            &format!(
+                "ALTER TABLE time_series_data
+                ADD COLUMN recorded_at TEXT NOT NULL DEFAULT '{}'",
+                UNVERSIONED_RECORDED_AT
+            ),
+            [],
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS time_series_history (
+            asset_key TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            close_price REAL NOT NULL,
+            recorded_at TEXT NOT NULL,
+            superseded_at TEXT NOT NULL,
+            PRIMARY KEY (asset_key, timestamp, recorded_at)
+        );",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_exists(conn: &Connection, table_name: &str) -> bool {
+        conn.prepare("SELECT 1 FROM sqlite_master WHERE type='table' AND name=?1")
+            .unwrap()
+            .exists([table_name])
+            .unwrap()
+    }
+
+    #[test]
+    fn migrations_are_ordered_by_version() {
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+        let expected: Vec<u32> = (1..=MIGRATIONS.len() as u32).collect();
+        assert_eq!(versions, expected);
+    }
+
+    #[test]
+    fn new_databases_reach_the_latest_version_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert_eq!(migrate(&conn).unwrap(), latest_version());
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert!(column_exists(&conn, "time_series_data", "recorded_at").unwrap());
+
+        // Migrating again applies nothing
+        migrate(&conn).unwrap();
+        let applied: u32 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(applied, latest_version());
+    }
+
+    #[test]
+    fn newer_databases_are_refused_untouched() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        let newer = latest_version() + 1;
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'future', '')",
+            [newer],
+        )
+        .unwrap();
+
+        let result = migrate(&conn);
+        assert!(matches!(
+            result,
+            Err(SchemaError::NewerVersion { found, latest })
+                if found == newer && latest == latest_version()
+        ));
+        assert_eq!(schema_version(&conn).unwrap(), newer);
+    }
+
+    #[test]
+    fn failed_migrations_are_rolled_back() {
+        fn create_a(conn: &Connection) -> rusqlite::Result<()> {
+            conn.execute_batch("CREATE TABLE a (x INTEGER)")
+        }
+        fn create_b_then_fail(conn: &Connection) -> rusqlite::Result<()> {
+            conn.execute_batch("CREATE TABLE b (x INTEGER); INSERT INTO missing VALUES (1)")
+        }
+        let migrations = [
+            Migration {
+                version: 1,
+                description: "a",
+                apply: create_a,
+            },
+            Migration {
+                version: 2,
+                description: "b",
+                apply: create_b_then_fail,
+            },
+        ];
+
+        let conn = Connection::open_in_memory().unwrap();
+        let result = apply_migrations(&conn, &migrations);
+        assert!(matches!(
+            result,
+            Err(SchemaError::Migration { version: 2, .. })
+        ));
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        assert!(table_exists(&conn, "a"));
+        assert!(!table_exists(&conn, "b"));
+    }
+}
@@ -4,12 +4,12 @@
 //! automatically update when new data arrives, propagating changes through
 //! the DAG dependency chain.
 
-use crate::analytics::registry::ParentOutput;
+use crate::analytics::registry::{ExecutorState, ParentOutput};
 use crate::asset_key::AssetKey;
 use crate::dag::{AnalyticsDag, Node, NodeId, NodeOutput, NodeParams};
 use crate::time_series::{DataProvider, DataProviderError, TimeSeriesPoint};
 use chrono::{DateTime, Utc};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::error::Error;
 use std::fmt;
 
@@ -48,6 +48,8 @@ pub struct NodePushState {
     pub last_computed_timestamp: Option<DateTime<Utc>>,
     /// Full history of this node's outputs
     pub output_history: Vec<TimeSeriesPoint>,
+    /// Full history of each named output of a multi-output node
+    pub named_history: BTreeMap<String, Vec<TimeSeriesPoint>>,
     /// Input buffer for rolling window analytics
     pub input_buffer: Option<CircularBuffer<f64>>,
     /// Current state of the node
@@ -63,6 +65,7 @@ impl NodePushState {
         NodePushState {
             last_computed_timestamp: None,
             output_history: Vec::new(),
+            named_history: BTreeMap::new(),
             input_buffer: buffer_capacity.map(CircularBuffer::new),
             state: NodeState::Uninitialized,
         }
@@ -84,6 +87,45 @@ impl NodePushState {
         self.output_history.push(point);
     }
 
+    /// Appends an output point, replacing the latest point if it has the same timestamp
+    ///
+    /// Nodes fed by several assets can fire more than once for one timestamp
+    /// (once per asset update); the latest computation supersedes the earlier one.
+    /// The input buffer (if any) is updated the same way, so each timestamp
+    /// occupies one slot of the window.
+    pub fn upsert_output(&mut self, point: TimeSeriesPoint) {
+        match self.output_history.last_mut() {
+            Some(last) if last.timestamp == point.timestamp => {
+                self.last_computed_timestamp = Some(point.timestamp);
+                if let Some(buffer) = &mut self.input_buffer {
+                    buffer.replace_last(point.close_price);
+                }
+                *last = point;
+            }
+            _ => {
+                self.push_to_buffer(point.close_price);
+                self.append_output(point);
+            }
+        }
+    }
+
+    /// Appends a point to a named output, replacing the latest point if it has the same timestamp
+    pub fn upsert_named_output(&mut self, name: &str, point: TimeSeriesPoint) {
+        let history = self.named_history.entry(name.to_string()).or_default();
+        match history.last_mut() {
+            Some(last) if last.timestamp == point.timestamp => *last = point,
+            _ => history.push(point),
+        }
+    }
+
+    /// Gets the history of a named output (empty if the node has no such output)
+    pub fn get_named_history(&self, name: &str) -> &[TimeSeriesPoint] {
+        self.named_history
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
     /// Gets the last computed timestamp
     pub fn get_last_timestamp(&self) -> Option<DateTime<Utc>> {
         self.last_computed_timestamp
@@ -228,6 +270,8 @@ pub struct PushModeEngine {
     dag: AnalyticsDag,
     /// Push state for each node
     node_states: HashMap<NodeId, NodePushState>,
+    /// Executor state for each node (e.g. running peaks)
+    executor_states: HashMap<NodeId, Option<ExecutorState>>,
     /// Registered callbacks per node
     callbacks: HashMap<NodeId, Vec<Callback>>,
     /// Whether engine has been initialized
@@ -243,6 +287,7 @@ impl PushModeEngine {
         let mut engine = PushModeEngine {
             dag,
             node_states: HashMap::new(),
+            executor_states: HashMap::new(),
             callbacks: HashMap::new(),
             is_initialized: false,
         };
@@ -329,11 +374,17 @@ impl PushModeEngine {
             return Ok(());
         }
 
-        // Validate timestamp ordering for affected nodes
+        // Validate timestamp ordering for affected nodes. Nodes spanning several
+        // assets may already hold this timestamp from another asset's update.
         for node_id in &affected_nodes {
+            let shared = self
+                .dag
+                .get_node(*node_id)
+                .map(|node| node.assets.len() > 1)
+                .unwrap_or(false);
             if let Some(state) = self.node_states.get(node_id) {
                 if let Some(last_ts) = state.get_last_timestamp() {
-                    if timestamp <= last_ts {
+                    if timestamp < last_ts || (timestamp == last_ts && !shared) {
                         return Err(PushError::OutOfOrder {
                             timestamp,
                             last_computed: last_ts,
@@ -369,8 +420,15 @@ impl PushModeEngine {
             .filter(|id| all_affected.contains(id))
             .collect();
 
-        // Propagate through affected nodes
+        // Propagate through affected nodes. A node only recomputes when it is a
+        // source node or when at least one of its parents produced output.
+        let mut updated: Vec<NodeId> = Vec::new();
         for node_id in sorted_affected {
+            let parents = self.dag.get_parents(node_id);
+            if !parents.is_empty() && !parents.iter().any(|id| updated.contains(id)) {
+                continue;
+            }
+
             // Set node state to Computing
             if let Some(state) = self.node_states.get_mut(&node_id) {
                 state.set_state(NodeState::Computing);
@@ -388,23 +446,31 @@ impl PushModeEngine {
                             NodeOutput::Single(points_vec) => {
                                 // points_vec is Vec<TimeSeriesPoint>
                                 for point in points_vec {
-                                    state.append_output(point.clone());
-                                    // Also push to buffer if this node has one
-                                    state.push_to_buffer(point.close_price);
+                                    state.upsert_output(point.clone());
                                 }
                             }
                             NodeOutput::Scalar(value) => {
                                 // Create a TimeSeriesPoint from scalar
                                 let point = TimeSeriesPoint::new(timestamp, *value);
-                                state.append_output(point);
-                                state.push_to_buffer(*value);
+                                state.upsert_output(point);
+                            }
+                            NodeOutput::Named(values) => {
+                                // The first declared output is the node's primary series
+                                if let Some((_, primary)) = values.first() {
+                                    state.upsert_output(TimeSeriesPoint::new(timestamp, *primary));
+                                }
+                                for (name, value) in values {
+                                    state.upsert_named_output(
+                                        name,
+                                        TimeSeriesPoint::new(timestamp, *value),
+                                    );
+                                }
                             }
                             NodeOutput::Collection(collection) => {
                                 // Collection is Vec<Vec<TimeSeriesPoint>>
                                 for points_vec in collection {
                                     for point in points_vec {
-                                        state.append_output(point.clone());
-                                        state.push_to_buffer(point.close_price);
+                                        state.upsert_output(point.clone());
                                     }
                                 }
                             }
@@ -412,6 +478,9 @@ impl PushModeEngine {
                                 // No output to store
                             }
                         }
+                        if !matches!(output, NodeOutput::None) {
+                            updated.push(node_id);
+                        }
 
                         // Set state to Ready
                         state.set_state(NodeState::Ready);
@@ -481,7 +550,7 @@ impl PushModeEngine {
     /// # Returns
     /// NodeOutput from execution, or error
     fn execute_node(
-        &self,
+        &mut self,
         node_id: NodeId,
         _asset: AssetKey,
         timestamp: DateTime<Utc>,
@@ -490,9 +559,10 @@ impl PushModeEngine {
         // Ensure the node exists
         self.get_node_from_dag(node_id)?;
 
-        let inputs = self.get_parent_histories(node_id)?;
+        let inputs = Self::parent_histories(&self.dag, &self.node_states, node_id);
+        let state = self.executor_states.entry(node_id).or_default();
         self.dag
-            .execute_push_node(node_id, &inputs, timestamp, value)
+            .execute_push_node(node_id, &inputs, timestamp, value, state)
             .map_err(|e| PushError::PropagationFailed {
                 node_id,
                 error: e.to_string(),
@@ -501,30 +571,36 @@ impl PushModeEngine {
 
     /// Gets outputs from all parent nodes
     ///
+    /// The outputs borrow the parents' histories rather than copying them, so
+    /// an update costs the same however long the histories have grown. Takes
+    /// the engine's fields apart so the executor state can be borrowed mutably
+    /// alongside.
+    ///
     /// # Arguments
+    /// * `dag` - The engine's DAG
+    /// * `node_states` - The engine's node states holding the histories
     /// * `node_id` - The node whose parents to query
     ///
     /// # Returns
-    /// Vector of NodeOutput from each parent
-    fn get_parent_histories(&self, node_id: NodeId) -> Result<Vec<ParentOutput>, PushError> {
-        let parent_ids = self.dag.get_parents(node_id);
-
-        let mut outputs = Vec::new();
-        for parent_id in parent_ids {
-            // Get parent's output history
-            if let Some(parent_state) = self.node_states.get(&parent_id) {
-                let history = parent_state.get_history().to_vec();
-                if !history.is_empty() {
-                    outputs.push(ParentOutput {
-                        node_id: parent_id,
-                        analytic: self.dag.analytic_type_for_node(parent_id),
-                        output: history,
-                    });
-                }
-            }
-        }
+    /// Vector of the history of each parent output
+    fn parent_histories<'a>(
+        dag: &AnalyticsDag,
+        node_states: &'a HashMap<NodeId, NodePushState>,
+        node_id: NodeId,
+    ) -> Vec<ParentOutput<'a>> {
+        let mut outputs = dag.parent_outputs(node_id, |parent_id, output| {
+            node_states
+                .get(&parent_id)
+                .map(|parent_state| match output {
+                    Some(name) => parent_state.get_named_history(name),
+                    None => parent_state.get_history(),
+                })
+                .unwrap_or(&[])
+        });
+        // Parents that have not produced anything yet are not inputs
+        outputs.retain(|parent| !parent.output.is_empty());
 
-        Ok(outputs)
+        outputs
     }
 
     /// Returns whether the engine has been initialized
@@ -592,6 +668,25 @@ impl PushModeEngine {
             .ok_or(PushError::NodeNotFound(node_id))
     }
 
+    /// Gets the full history of a named output of a multi-output node
+    ///
+    /// # Arguments
+    /// * `node_id` - The node to query
+    /// * `output` - Output name declared by the node's analytic
+    ///
+    /// # Returns
+    /// Output history (empty if the node has no such output), or error if node doesn't exist
+    pub fn get_output_history(
+        &self,
+        node_id: NodeId,
+        output: &str,
+    ) -> Result<Vec<TimeSeriesPoint>, PushError> {
+        self.node_states
+            .get(&node_id)
+            .map(|state| state.get_named_history(output).to_vec())
+            .ok_or(PushError::NodeNotFound(node_id))
+    }
+
     /// Gets the most recent output for a node
     ///
     /// # Arguments
@@ -737,6 +832,14 @@ impl<T: Clone> CircularBuffer<T> {
         self.data.push_back(value);
     }
 
+    /// Replaces the newest value, or adds the value if the buffer is empty
+    pub fn replace_last(&mut self, value: T) {
+        match self.data.back_mut() {
+            Some(last) => *last = value,
+            None => self.data.push_back(value),
+        }
+    }
+
     /// Returns a slice view of the current buffer contents
     ///
     /// The slice is ordered from oldest to newest value.
@@ -1017,6 +1120,21 @@ mod tests {
         assert_eq!(state1.get_state(), &NodeState::Ready);
     }
 
+    #[test]
+    fn test_revised_output_replaces_buffer_entry() {
+        use chrono::{TimeZone, Utc};
+
+        let mut push_state = NodePushState::new(Some(3));
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap();
+        push_state.upsert_output(TimeSeriesPoint::new(day(1), 10.0));
+        push_state.upsert_output(TimeSeriesPoint::new(day(2), 20.0));
+        // A second computation for the same timestamp revises the first
+        push_state.upsert_output(TimeSeriesPoint::new(day(2), 25.0));
+
+        assert_eq!(push_state.get_history().len(), 2);
+        assert_eq!(push_state.get_buffer_slice().unwrap(), vec![10.0, 25.0]);
+    }
+
     #[test]
     fn test_node_state_without_buffer() {
         let push_state = NodePushState::new(None);
@@ -1682,4 +1800,49 @@ mod tests {
             "Callback should have been invoked"
         );
     }
+
+    #[test]
+    fn push_inputs_borrow_parent_histories() {
+        use chrono::{TimeZone, Utc};
+        use std::borrow::Cow;
+
+        let mut dag = AnalyticsDag::new();
+        let asset = AssetKey::new_equity("AAPL").unwrap();
+        let data_node = dag.add_node(
+            "data_provider".to_string(),
+            NodeParams::None,
+            vec![asset.clone()],
+        );
+        let returns_node =
+            dag.add_node("returns".to_string(), NodeParams::None, vec![asset.clone()]);
+        let mut vol_params = HashMap::new();
+        vol_params.insert("window_size".to_string(), "5".to_string());
+        let vol_node = dag.add_node(
+            "volatility".to_string(),
+            NodeParams::Map(vol_params),
+            vec![asset.clone()],
+        );
+        dag.add_edge(data_node, returns_node).unwrap();
+        dag.add_edge(returns_node, vol_node).unwrap();
+
+        let mut engine = PushModeEngine::new(dag);
+        engine.is_initialized = true;
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 16, 0, 0).unwrap();
+        for i in 0..1000 {
+            let ts = start + chrono::Duration::days(i);
+            engine
+                .push_data(asset.clone(), ts, 100.0 + (i % 7) as f64)
+                .unwrap();
+        }
+
+        // An update reads the parents' histories in place, whatever their length
+        let inputs = PushModeEngine::parent_histories(&engine.dag, &engine.node_states, vol_node);
+        assert_eq!(inputs.len(), 1);
+        let history = engine.node_states[&returns_node].get_history();
+        assert_eq!(history.len(), 1000);
+        assert!(matches!(
+            inputs[0].output,
+            Cow::Borrowed(borrowed) if std::ptr::eq(borrowed, history)
+        ));
+    }
 }
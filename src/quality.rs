@@ -0,0 +1,555 @@
+//! Data-quality checks on stored prices.
+//!
+//! [`check_series`] applies configurable rules to a price series and flags
+//! bad prints (non-positive prices, isolated spikes, stale repeated prices)
+//! and points that follow missing trading days. [`check_asset`] runs the
+//! rules over an asset's stored history and stores the flags alongside its
+//! prices; importers and the refresh job call it on ingestion.
+//! [`QualityFilteredProvider`] lets `DataProvider` consumers exclude or
+//! forward-fill flagged points.
+
+use crate::asset_key::AssetKey;
+use crate::calendar::TradingCalendar;
+use crate::sqlite_provider::SqliteDataProvider;
+use crate::time_series::{DataProvider, DataProviderError, DateRange, TimeSeriesPoint};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+
+/// Kind of quality issue found on a price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum QualityFlag {
+    /// Zero, negative or NaN price
+    NonPositive,
+    /// Price far from its neighbours that reverts on the next print
+    Spike,
+    /// Price repeated unchanged for too many observations
+    Stale,
+    /// First price after missing trading days; the price itself is kept
+    Gap,
+}
+
+impl QualityFlag {
+    /// Name used in storage and in the API.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QualityFlag::NonPositive => "non_positive",
+            QualityFlag::Spike => "spike",
+            QualityFlag::Stale => "stale",
+            QualityFlag::Gap => "gap",
+        }
+    }
+
+    /// Parses a flag name as returned by [`QualityFlag::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "non_positive" => Some(QualityFlag::NonPositive),
+            "spike" => Some(QualityFlag::Spike),
+            "stale" => Some(QualityFlag::Stale),
+            "gap" => Some(QualityFlag::Gap),
+            _ => None,
+        }
+    }
+
+    /// Whether the flagged price is a bad print, excluded or forward-filled
+    /// by [`QualityFilteredProvider`]. Gaps only annotate a valid price.
+    pub fn is_bad_print(&self) -> bool {
+        !matches!(self, QualityFlag::Gap)
+    }
+}
+
+/// A flag raised on one stored price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedPoint {
+    pub timestamp: DateTime<Utc>,
+    pub flag: QualityFlag,
+    /// Human-readable explanation
+    pub detail: String,
+}
+
+/// Rules applied by [`check_series`]; `None` or `false` disables a rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityConfig {
+    /// Flag zero, negative and NaN prices (default: true)
+    pub non_positive: bool,
+    /// Flag prices at least this many times above or below the previous
+    /// good price that revert on the next print (default: 10.0)
+    pub spike_ratio: Option<f64>,
+    /// Flag runs of at least this many identical prices, all but the first
+    /// (default: 5)
+    pub stale_run: Option<usize>,
+    /// Flag prices that follow missing trading days (default: true)
+    pub missing_days: bool,
+    /// Calendar defining the expected trading days (default: weekdays)
+    pub calendar: TradingCalendar,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        QualityConfig {
+            non_positive: true,
+            spike_ratio: Some(10.0),
+            stale_run: Some(5),
+            missing_days: true,
+            calendar: TradingCalendar::weekdays(),
+        }
+    }
+}
+
+impl QualityConfig {
+    /// Builds a config from named options, as given in a query string; unset
+    /// options keep their defaults.
+    ///
+    /// Options: `non_positive` and `missing_days` (`true` or `false`),
+    /// `spike_ratio` (a ratio above 1) and `stale_run` (a count of at least
+    /// 2); `off` disables the last two.
+    pub fn from_options<'a, I>(options: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let invalid = |name: &str, value: &str| format!("invalid {} '{}'", name, value);
+        let mut config = QualityConfig::default();
+        for (name, value) in options {
+            let off = value.eq_ignore_ascii_case("off");
+            match name {
+                "non_positive" => {
+                    config.non_positive = value.parse().map_err(|_| invalid(name, value))?
+                }
+                "missing_days" => {
+                    config.missing_days = value.parse().map_err(|_| invalid(name, value))?
+                }
+                "spike_ratio" if off => config.spike_ratio = None,
+                "spike_ratio" => match value.parse::<f64>() {
+                    Ok(ratio) if ratio > 1.0 => config.spike_ratio = Some(ratio),
+                    _ => return Err(invalid(name, value)),
+                },
+                "stale_run" if off => config.stale_run = None,
+                "stale_run" => match value.parse::<usize>() {
+                    Ok(run) if run >= 2 => config.stale_run = Some(run),
+                    _ => return Err(invalid(name, value)),
+                },
+                _ => return Err(format!("unknown option '{}'", name)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Result of checking a price series.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualityReport {
+    /// Number of prices checked
+    pub points: usize,
+    /// Flags in timestamp order
+    pub flags: Vec<FlaggedPoint>,
+    /// Trading days without a price, between the first and last price
+    pub missing_days: Vec<NaiveDate>,
+}
+
+impl QualityReport {
+    /// Number of prices flagged as bad prints.
+    pub fn bad_prints(&self) -> usize {
+        self.flags
+            .iter()
+            .filter(|flagged| flagged.flag.is_bad_print())
+            .map(|flagged| flagged.timestamp)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+/// Checks a price series, sorted by timestamp, against `config`.
+///
+/// Spikes are measured against the last price that is not itself a bad
+/// print, so one bad print does not flag its neighbours, and a jump that
+/// persists (a level shift) is not a spike. Stale runs ignore bad prints.
+pub fn check_series(points: &[TimeSeriesPoint], config: &QualityConfig) -> QualityReport {
+    let mut report = QualityReport {
+        points: points.len(),
+        ..QualityReport::default()
+    };
+    let mut bad = vec![false; points.len()];
+
+    let mut last_good: Option<f64> = None;
+    for (i, point) in points.iter().enumerate() {
+        let price = point.close_price;
+        if config.non_positive && (price.is_nan() || price <= 0.0) {
+            bad[i] = true;
+            report.flags.push(FlaggedPoint {
+                timestamp: point.timestamp,
+                flag: QualityFlag::NonPositive,
+                detail: format!("price {}", price),
+            });
+            continue;
+        }
+        if let (Some(ratio), Some(previous)) = (config.spike_ratio, last_good) {
+            let extreme = |price: f64| price >= previous * ratio || price * ratio <= previous;
+            let reverts = points
+                .get(i + 1)
+                .is_none_or(|next| !extreme(next.close_price));
+            if extreme(price) && reverts {
+                bad[i] = true;
+                report.flags.push(FlaggedPoint {
+                    timestamp: point.timestamp,
+                    flag: QualityFlag::Spike,
+                    detail: format!("price {} against previous {}", price, previous),
+                });
+                continue;
+            }
+        }
+        last_good = Some(price);
+    }
+
+    if let Some(min_run) = config.stale_run {
+        let good: Vec<usize> = (0..points.len()).filter(|i| !bad[*i]).collect();
+        for run in good.chunk_by(|a, b| points[*a].close_price == points[*b].close_price) {
+            if run.len() < min_run {
+                continue;
+            }
+            for &i in &run[1..] {
+                report.flags.push(FlaggedPoint {
+                    timestamp: points[i].timestamp,
+                    flag: QualityFlag::Stale,
+                    detail: format!(
+                        "price {} unchanged for {} observations",
+                        points[i].close_price,
+                        run.len()
+                    ),
+                });
+            }
+        }
+    }
+
+    if config.missing_days {
+        for pair in points.windows(2) {
+            let (previous, current) = (
+                pair[0].timestamp.date_naive(),
+                pair[1].timestamp.date_naive(),
+            );
+            let (Some(first), Some(last)) = (previous.succ_opt(), current.pred_opt()) else {
+                continue;
+            };
+            if first > last {
+                continue;
+            }
+            let missing = config.calendar.trading_days(&DateRange::new(first, last));
+            if missing.is_empty() {
+                continue;
+            }
+            report.flags.push(FlaggedPoint {
+                timestamp: pair[1].timestamp,
+                flag: QualityFlag::Gap,
+                detail: format!("{} missing trading days since {}", missing.len(), previous),
+            });
+            report.missing_days.extend(missing);
+        }
+    }
+
+    report
+        .flags
+        .sort_by_key(|flagged| (flagged.timestamp, flagged.flag));
+    report
+}
+
+/// Date range covering any stored history.
+fn full_history() -> DateRange {
+    DateRange::new(
+        NaiveDate::from_ymd_opt(1900, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(9999, 12, 31).unwrap(),
+    )
+}
+
+/// Checks the stored history of an asset and replaces its stored flags with
+/// the result.
+///
+/// An asset without prices yields an empty report.
+pub fn check_asset(
+    provider: &mut SqliteDataProvider,
+    asset_key: &AssetKey,
+    config: &QualityConfig,
+) -> Result<QualityReport, DataProviderError> {
+    let points = match provider.get_time_series(asset_key, &full_history()) {
+        Ok(points) => points,
+        Err(DataProviderError::AssetNotFound) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    let report = check_series(&points, config);
+    provider.replace_quality_flags(asset_key, &report.flags)?;
+    if report.bad_prints() > 0 {
+        log::warn!(
+            "{} of {} prices of {} flagged as bad prints",
+            report.bad_prints(),
+            report.points,
+            asset_key
+        );
+    }
+    Ok(report)
+}
+
+/// How [`QualityFilteredProvider`] treats prices flagged as bad prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlaggedPoints {
+    /// Return flagged prices unchanged
+    #[default]
+    Include,
+    /// Drop flagged prices
+    Exclude,
+    /// Replace flagged prices with the previous unflagged price, dropping
+    /// flagged prices at the start of the range
+    ForwardFill,
+}
+
+impl FlaggedPoints {
+    /// Parses `include`, `exclude` or `ffill`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "include" => Some(FlaggedPoints::Include),
+            "exclude" => Some(FlaggedPoints::Exclude),
+            "ffill" | "forward_fill" => Some(FlaggedPoints::ForwardFill),
+            _ => None,
+        }
+    }
+}
+
+/// A view of a [`SqliteDataProvider`] that excludes or forward-fills prices
+/// flagged as bad prints.
+///
+/// Flags are not versioned: reads as of a past knowledge time are filtered by
+/// the flags stored now, which only approximate the flags known then.
+#[derive(Debug)]
+pub struct QualityFilteredProvider<'a> {
+    provider: &'a SqliteDataProvider,
+    handling: FlaggedPoints,
+}
+
+impl<'a> QualityFilteredProvider<'a> {
+    pub fn new(provider: &'a SqliteDataProvider, handling: FlaggedPoints) -> Self {
+        QualityFilteredProvider { provider, handling }
+    }
+
+    /// Timestamps of the bad prints of an asset within `date_range`.
+    fn bad_prints(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<HashSet<DateTime<Utc>>, DataProviderError> {
+        Ok(self
+            .provider
+            .get_quality_flags(asset_key, date_range)?
+            .into_iter()
+            .filter(|flagged| flagged.flag.is_bad_print())
+            .map(|flagged| flagged.timestamp)
+            .collect())
+    }
+
+    /// Excludes or forward-fills the bad prints among `points`.
+    fn filter(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+        points: Vec<TimeSeriesPoint>,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        if self.handling == FlaggedPoints::Include {
+            return Ok(points);
+        }
+        let bad = self.bad_prints(asset_key, date_range)?;
+        if bad.is_empty() {
+            return Ok(points);
+        }
+
+        let mut last_good: Option<f64> = None;
+        let mut filtered = Vec::with_capacity(points.len());
+        for mut point in points {
+            if !bad.contains(&point.timestamp) {
+                last_good = Some(point.close_price);
+                filtered.push(point);
+            } else if let (FlaggedPoints::ForwardFill, Some(price)) = (self.handling, last_good) {
+                point.close_price = price;
+                filtered.push(point);
+            }
+        }
+        Ok(filtered)
+    }
+}
+
+impl DataProvider for QualityFilteredProvider<'_> {
+    fn get_time_series(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        let points = self.provider.get_time_series(asset_key, date_range)?;
+        self.filter(asset_key, date_range, points)
+    }
+
+    fn available_dates(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<DateTime<Utc>>, DataProviderError> {
+        Ok(self
+            .get_time_series(asset_key, date_range)?
+            .into_iter()
+            .map(|point| point.timestamp)
+            .collect())
+    }
+
+    /// Filters the prices known at `as_of` by the flags of the current prices,
+    /// so a bad print corrected since is no longer excluded, and a price
+    /// flagged only after `as_of` is excluded already. The result approximates
+    /// what the filter returned at `as_of`.
+    fn get_time_series_as_of(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        let points = self
+            .provider
+            .get_time_series_as_of(asset_key, date_range, as_of)?;
+        self.filter(asset_key, date_range, points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    /// Prices on consecutive weekdays from Monday 2024-01-01.
+    fn series(prices: &[f64]) -> Vec<TimeSeriesPoint> {
+        let mut date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        prices
+            .iter()
+            .map(|&price| {
+                let timestamp = Utc.from_utc_datetime(&date.and_hms_opt(16, 0, 0).unwrap());
+                date += Duration::days(1);
+                while !TradingCalendar::weekdays().is_trading_day(date) {
+                    date += Duration::days(1);
+                }
+                TimeSeriesPoint::new(timestamp, price)
+            })
+            .collect()
+    }
+
+    fn flags(report: &QualityReport) -> Vec<(usize, QualityFlag)> {
+        let points = series(&vec![0.0; report.points]);
+        report
+            .flags
+            .iter()
+            .map(|flagged| {
+                let index = points
+                    .iter()
+                    .position(|p| p.timestamp == flagged.timestamp)
+                    .unwrap();
+                (index, flagged.flag)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_series_flags_bad_prints() {
+        let prices = [
+            100.0, 101.0, 0.0, 102.0, 1020.0, 103.0, 104.0, 104.0, 104.0, 104.0, 104.0, 105.0,
+        ];
+        let report = check_series(&series(&prices), &QualityConfig::default());
+        assert_eq!(
+            flags(&report),
+            vec![
+                (2, QualityFlag::NonPositive),
+                (4, QualityFlag::Spike),
+                (7, QualityFlag::Stale),
+                (8, QualityFlag::Stale),
+                (9, QualityFlag::Stale),
+                (10, QualityFlag::Stale),
+            ]
+        );
+        assert_eq!(report.bad_prints(), 6);
+        assert!(report.missing_days.is_empty());
+    }
+
+    #[test]
+    fn test_check_series_accepts_level_shifts_and_disabled_rules() {
+        // A persistent 20x jump is a level shift, not a spike
+        let prices = [5.0, 5.1, 100.0, 101.0, 102.0];
+        let report = check_series(&series(&prices), &QualityConfig::default());
+        assert!(report.flags.is_empty());
+
+        let config =
+            QualityConfig::from_options([("stale_run", "off"), ("non_positive", "false")]).unwrap();
+        let report = check_series(&series(&[0.0, 1.0, 1.0, 1.0, 1.0, 1.0]), &config);
+        assert!(report.flags.is_empty());
+
+        assert!(QualityConfig::from_options([("spike_ratio", "0.5")]).is_err());
+        assert!(QualityConfig::from_options([("unknown", "1")]).is_err());
+    }
+
+    #[test]
+    fn test_check_series_reports_missing_trading_days() {
+        let mut points = series(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        // Drop Wednesday 3rd and Thursday 4th
+        points.remove(2);
+        points.remove(2);
+        let config = QualityConfig {
+            calendar: TradingCalendar::weekdays()
+                .with_holidays([NaiveDate::from_ymd_opt(2024, 1, 4).unwrap()]),
+            ..QualityConfig::default()
+        };
+        let report = check_series(&points, &config);
+        assert_eq!(
+            report.missing_days,
+            vec![NaiveDate::from_ymd_opt(2024, 1, 3).unwrap()]
+        );
+        assert_eq!(report.flags.len(), 1);
+        assert_eq!(report.flags[0].flag, QualityFlag::Gap);
+        assert_eq!(
+            report.flags[0].timestamp.date_naive().to_string(),
+            "2024-01-05"
+        );
+        assert_eq!(report.bad_prints(), 0);
+    }
+
+    #[test]
+    fn test_filtered_provider_excludes_or_forward_fills_flags() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let key = AssetKey::new_equity("AAPL").unwrap();
+        let points = series(&[0.0, 100.0, 101.0, 1010.0, 102.0]);
+        provider.insert_time_series_batch(&key, &points).unwrap();
+        let report = check_asset(&mut provider, &key, &QualityConfig::default()).unwrap();
+        assert_eq!(report.bad_prints(), 2);
+
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let closes = |provider: &SqliteDataProvider, handling| {
+            QualityFilteredProvider::new(provider, handling)
+                .get_time_series(&key, &range)
+                .unwrap()
+                .iter()
+                .map(|p| p.close_price)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            closes(&provider, FlaggedPoints::Include),
+            vec![0.0, 100.0, 101.0, 1010.0, 102.0]
+        );
+        assert_eq!(
+            closes(&provider, FlaggedPoints::Exclude),
+            vec![100.0, 101.0, 102.0]
+        );
+        assert_eq!(
+            closes(&provider, FlaggedPoints::ForwardFill),
+            vec![100.0, 101.0, 101.0, 102.0]
+        );
+
+        // Fixing the bad print and re-checking clears its flag
+        provider
+            .insert_time_series_point(&key, &TimeSeriesPoint::new(points[3].timestamp, 101.5))
+            .unwrap();
+        check_asset(&mut provider, &key, &QualityConfig::default()).unwrap();
+        assert_eq!(
+            closes(&provider, FlaggedPoints::Exclude),
+            vec![100.0, 101.0, 101.5, 102.0]
+        );
+    }
+}
@@ -13,6 +13,8 @@ use uuid::Uuid;
 pub enum ApiError {
     /// Asset not found in database
     AssetNotFound(String),
+    /// Asset already stored in database
+    AssetExists(String),
     /// Invalid parameter in request
     InvalidParameter(String),
     /// Invalid date range
@@ -31,6 +33,7 @@ impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ApiError::AssetNotFound(asset) => write!(f, "Asset not found: {}", asset),
+            ApiError::AssetExists(asset) => write!(f, "Asset already exists: {}", asset),
             ApiError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
             ApiError::InvalidDateRange(msg) => write!(f, "Invalid date range: {}", msg),
             ApiError::ComputationFailed(msg) => write!(f, "Computation failed: {}", msg),
@@ -51,6 +54,11 @@ impl IntoResponse for ApiError {
                 "AssetNotFound",
                 format!("Asset '{}' not found in database", asset),
             ),
+            ApiError::AssetExists(asset) => (
+                StatusCode::CONFLICT,
+                "AssetExists",
+                format!("Asset '{}' already exists", asset),
+            ),
             ApiError::InvalidParameter(msg) => {
                 (StatusCode::BAD_REQUEST, "InvalidParameter", msg.clone())
             }
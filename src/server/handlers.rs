@@ -1,6 +1,7 @@
 //! HTTP request handlers for API endpoints
 
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
     response::sse::{Event, KeepAlive, Sse},
     Json,
@@ -14,12 +15,23 @@ use std::sync::Arc;
 
 use super::error::ApiError;
 use super::state::{AnalyticConfig, AppState, ReplaySession, SessionStatus};
+use crate::analytics::missing::MissingData;
 use crate::analytics::AnalyticRegistry;
-use crate::asset_key::AssetKey;
+use crate::asset::{Asset, AssetType};
+use crate::asset_key::{AssetKey, AssetKeyError};
 use crate::dag::{AnalyticType, AnalyticsDag, NodeId, NodeKey, NodeOutput, WindowSpec};
-use crate::time_series::DateRange;
-use chrono::Utc;
-use std::collections::HashMap;
+use crate::equity::{CorporateAction, Equity};
+use crate::future::Future;
+use crate::fx::FxRate;
+use crate::importer::{import_bytes, ImportConfig, ImportError};
+use crate::ingest::IngestStatus;
+use crate::quality::{check_asset, FlaggedPoints, QualityConfig, QualityFilteredProvider};
+use crate::sqlite_provider::{AssetFilter, SqliteDataProvider, SyncState};
+use crate::time_series::{
+    AsOfProvider, DataProvider, DataProviderError, DateRange, TimeSeriesPoint,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 /// Health check endpoint
@@ -31,10 +43,35 @@ pub async fn health_check() -> Json<Value> {
     }))
 }
 
+/// Default number of assets returned by one `/assets` page
+const DEFAULT_ASSET_PAGE: usize = 100;
+
+/// Largest page `/assets` accepts
+const MAX_ASSET_PAGE: usize = 1000;
+
+/// Query parameters for asset listing
+#[derive(Debug, Default, Deserialize)]
+pub struct AssetsQueryParams {
+    /// Asset type: "equity", "future" or "fx"
+    #[serde(rename = "type")]
+    pub asset_type: Option<String>,
+    pub exchange: Option<String>,
+    pub sector: Option<String>,
+    pub currency: Option<String>,
+    /// Text searched for in names and tickers
+    pub q: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
 /// Response for asset listing
 #[derive(Debug, Serialize)]
 pub struct AssetsResponse {
     pub assets: Vec<AssetInfo>,
+    /// Number of assets matching the filters, across all pages
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
 }
 
 /// Information about a single asset
@@ -44,41 +81,87 @@ pub struct AssetInfo {
     #[serde(rename = "type")]
     pub asset_type: String,
     pub name: String,
+    pub exchange: String,
+    pub sector: Option<String>,
+    pub currency: String,
     pub data_available_from: Option<String>,
     pub data_available_to: Option<String>,
+    /// Number of stored prices
+    pub points: usize,
+}
+
+/// Name of an asset type in API requests and responses
+fn asset_type_name(asset_type: AssetType) -> &'static str {
+    match asset_type {
+        AssetType::Equity => "equity",
+        AssetType::Future => "future",
+        AssetType::FxRate => "fx",
+    }
 }
 
-/// GET /assets - List all available assets
+/// GET /assets - List stored assets with their data coverage
 pub async fn list_assets(
     State(state): State<Arc<AppState>>,
+    Query(params): Query<AssetsQueryParams>,
 ) -> Result<Json<AssetsResponse>, ApiError> {
-    // For POC, we'll return a simple list
-    // In production, this would query the database for actual assets
-    let assets = vec![
-        AssetInfo {
-            key: "AAPL".to_string(),
-            asset_type: "equity".to_string(),
-            name: "Apple Inc.".to_string(),
-            data_available_from: Some("2020-01-01".to_string()),
-            data_available_to: Some("2024-12-31".to_string()),
-        },
-        AssetInfo {
-            key: "MSFT".to_string(),
-            asset_type: "equity".to_string(),
-            name: "Microsoft Corporation".to_string(),
-            data_available_from: Some("2020-01-01".to_string()),
-            data_available_to: Some("2024-12-31".to_string()),
-        },
-        AssetInfo {
-            key: "GOOG".to_string(),
-            asset_type: "equity".to_string(),
-            name: "Alphabet Inc.".to_string(),
-            data_available_from: Some("2020-01-01".to_string()),
-            data_available_to: Some("2024-12-31".to_string()),
-        },
-    ];
+    let asset_type = match params.asset_type.as_deref().map(str::to_lowercase).as_deref() {
+        None => None,
+        Some("equity") => Some(AssetType::Equity),
+        Some("future") => Some(AssetType::Future),
+        Some("fx") | Some("fx_rate") => Some(AssetType::FxRate),
+        Some(other) => {
+            return Err(ApiError::InvalidParameter(format!(
+                "Invalid asset type '{}': expected equity, future or fx",
+                other
+            )))
+        }
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_ASSET_PAGE);
+    if limit == 0 || limit > MAX_ASSET_PAGE {
+        return Err(ApiError::InvalidParameter(format!(
+            "limit must be between 1 and {}",
+            MAX_ASSET_PAGE
+        )));
+    }
+    let offset = params.offset.unwrap_or(0);
+    let filter = AssetFilter {
+        asset_type,
+        exchange: params.exchange,
+        sector: params.sector,
+        currency: params.currency,
+        search: params.q.filter(|q| !q.trim().is_empty()),
+        offset,
+        limit: Some(limit),
+    };
+
+    let provider = state.data_provider.lock().await;
+    let page = provider
+        .list_assets(&filter)
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+    let format_date = |date: Option<NaiveDate>| date.map(|d| d.format("%Y-%m-%d").to_string());
+    let assets = page
+        .assets
+        .into_iter()
+        .map(|asset| AssetInfo {
+            key: asset.key.as_string(),
+            asset_type: asset_type_name(asset.asset_type).to_string(),
+            name: asset.name,
+            exchange: asset.exchange,
+            sector: asset.sector,
+            currency: asset.currency,
+            data_available_from: format_date(asset.first_date),
+            data_available_to: format_date(asset.last_date),
+            points: asset.point_count,
+        })
+        .collect();
 
-    Ok(Json(AssetsResponse { assets }))
+    Ok(Json(AssetsResponse {
+        assets,
+        total: page.total,
+        limit,
+        offset,
+    }))
 }
 
 /// Response for analytics listing
@@ -109,29 +192,567 @@ pub struct ParameterInfo {
 }
 
 /// GET /dag/nodes - List available analytic types
-pub async fn list_analytics() -> Json<AnalyticsListResponse> {
-    let analytics = vec![
+pub async fn list_analytics(
+    State(state): State<Arc<AppState>>,
+) -> Json<AnalyticsListResponse> {
+    Json(AnalyticsListResponse {
+        analytics: analytic_catalog(&state.registry),
+    })
+}
+
+/// Every analytic type with the parameters it accepts.
+fn analytic_catalog(registry: &AnalyticRegistry) -> Vec<AnalyticInfo> {
+    let mut analytics = vec![
         AnalyticInfo {
             analytic_type: "returns".to_string(),
             description: "Log returns calculation".to_string(),
-            parameters: vec![],
+            parameters: vec![
+                period_parameter("lag", 1),
+                reporting_currency_parameter(false),
+            ],
             burnin_days: "1".to_string(),
         },
         AnalyticInfo {
             analytic_type: "volatility".to_string(),
             description: "Rolling volatility (population std dev)".to_string(),
-            parameters: vec![ParameterInfo {
-                name: "window".to_string(),
-                param_type: "integer".to_string(),
-                required: false,
-                default: Some("10".to_string()),
-                description: "Rolling window size in days".to_string(),
-            }],
+            parameters: vec![
+                ParameterInfo {
+                    name: "window".to_string(),
+                    param_type: "integer".to_string(),
+                    required: false,
+                    default: Some("10".to_string()),
+                    description: "Rolling window size in days".to_string(),
+                },
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window + 1".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "downside_deviation".to_string(),
+            description: "Rolling root mean square of the negative log returns".to_string(),
+            parameters: vec![
+                period_parameter("window", 10),
+                reporting_currency_parameter(false),
+            ],
             burnin_days: "window + 1".to_string(),
         },
+        AnalyticInfo {
+            analytic_type: "fx_conversion".to_string(),
+            description: "Prices converted into a reporting currency".to_string(),
+            parameters: vec![reporting_currency_parameter(true)],
+            burnin_days: "0".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "correlation".to_string(),
+            description: "Rolling correlation of log returns between two assets".to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "with".to_string(),
+                    param_type: "string".to_string(),
+                    required: true,
+                    default: None,
+                    description: "Second asset of the pair".to_string(),
+                },
+                ParameterInfo {
+                    name: "window".to_string(),
+                    param_type: "integer".to_string(),
+                    required: false,
+                    default: Some("10".to_string()),
+                    description: "Rolling window size in days".to_string(),
+                },
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window + 1".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "drawdown".to_string(),
+            description: "Decline of the price from its running peak (0 at a new high)"
+                .to_string(),
+            parameters: vec![reporting_currency_parameter(false)],
+            burnin_days: "0".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "max_drawdown".to_string(),
+            description: "Rolling maximum drawdown (deepest peak-to-trough decline in the window)"
+                .to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "window".to_string(),
+                    param_type: "integer".to_string(),
+                    required: false,
+                    default: Some("10".to_string()),
+                    description: "Rolling window size in days".to_string(),
+                },
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window - 1".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "time_under_water".to_string(),
+            description: "Observations since the price last set a running peak".to_string(),
+            parameters: vec![reporting_currency_parameter(false)],
+            burnin_days: "0".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "sharpe_ratio".to_string(),
+            description: "Rolling annualized Sharpe ratio of log returns".to_string(),
+            parameters: ratio_parameters(Some(risk_free_parameter())),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "sortino_ratio".to_string(),
+            description: "Rolling annualized Sortino ratio (downside deviation)".to_string(),
+            parameters: ratio_parameters(Some(risk_free_parameter())),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "calmar_ratio".to_string(),
+            description: "Annualized mean return over the rolling maximum drawdown".to_string(),
+            parameters: ratio_parameters(None),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "information_ratio".to_string(),
+            description: "Rolling annualized information ratio against a benchmark".to_string(),
+            parameters: ratio_parameters(Some(ParameterInfo {
+                name: "benchmark".to_string(),
+                param_type: "string".to_string(),
+                required: true,
+                default: None,
+                description: "Benchmark asset".to_string(),
+            })),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "value_at_risk".to_string(),
+            description: "Rolling Value-at-Risk of log returns, as a positive loss".to_string(),
+            parameters: tail_risk_parameters(),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "expected_shortfall".to_string(),
+            description: "Rolling Expected Shortfall (average loss beyond VaR)".to_string(),
+            parameters: tail_risk_parameters(),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "sma".to_string(),
+            description: "Simple moving average of the price".to_string(),
+            parameters: vec![period_parameter("window", 20), reporting_currency_parameter(false)],
+            burnin_days: "window - 1".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "ema".to_string(),
+            description: "Exponential moving average of the price (alpha = 2 / (window + 1))"
+                .to_string(),
+            parameters: vec![period_parameter("window", 10), reporting_currency_parameter(false)],
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "rsi".to_string(),
+            description: "Relative Strength Index (0-100, Wilder smoothing)".to_string(),
+            parameters: vec![period_parameter("window", 14), reporting_currency_parameter(false)],
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "macd".to_string(),
+            description: "MACD line, signal or histogram".to_string(),
+            parameters: vec![
+                output_parameter("line, signal or histogram", "line"),
+                period_parameter("fast", 12),
+                period_parameter("slow", 26),
+                period_parameter("signal", 9),
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "slow + signal".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "bollinger".to_string(),
+            description: "Bollinger band: moving average +/- num_std standard deviations"
+                .to_string(),
+            parameters: vec![
+                output_parameter("upper, middle or lower", "middle"),
+                period_parameter("window", 20),
+                ParameterInfo {
+                    name: "num_std".to_string(),
+                    param_type: "number".to_string(),
+                    required: false,
+                    default: Some("2".to_string()),
+                    description: "Band width in standard deviations".to_string(),
+                },
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window - 1".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "atr".to_string(),
+            description: "Average True Range (Wilder smoothing of close-to-close moves)"
+                .to_string(),
+            parameters: vec![period_parameter("window", 14), reporting_currency_parameter(false)],
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "skewness".to_string(),
+            description: "Rolling skewness of log returns".to_string(),
+            parameters: distribution_parameters(None),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "kurtosis".to_string(),
+            description: "Rolling excess kurtosis of log returns".to_string(),
+            parameters: distribution_parameters(None),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "quantile".to_string(),
+            description: "Rolling quantile of log returns (linear interpolation)".to_string(),
+            parameters: distribution_parameters(Some(ParameterInfo {
+                name: "quantile".to_string(),
+                param_type: "number".to_string(),
+                required: false,
+                default: Some("0.5".to_string()),
+                description: "Quantile, between 0 and 1".to_string(),
+            })),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "percentile_rank".to_string(),
+            description: "Percentile rank (0-100) of the latest log return in its window"
+                .to_string(),
+            parameters: distribution_parameters(None),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "z_score".to_string(),
+            description: "Z-score of the latest log return against its window".to_string(),
+            parameters: distribution_parameters(None),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "regression".to_string(),
+            description: "Rolling OLS regression of log returns on factor returns".to_string(),
+            parameters: vec![
+                period_parameter("window", 10),
+                ParameterInfo {
+                    name: "factors".to_string(),
+                    param_type: "string".to_string(),
+                    required: true,
+                    default: None,
+                    description: "Comma-separated factor assets".to_string(),
+                },
+                output_parameter(
+                    "alpha, beta_<factor>, r_squared, residual, residual_volatility",
+                    "alpha",
+                ),
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "garch".to_string(),
+            description: "GARCH(1,1) volatility of log returns fitted by maximum likelihood"
+                .to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "window".to_string(),
+                    param_type: "integer".to_string(),
+                    required: false,
+                    default: Some("250".to_string()),
+                    description: "Training window of returns the parameters are fitted on"
+                        .to_string(),
+                },
+                ParameterInfo {
+                    name: "horizon".to_string(),
+                    param_type: "integer".to_string(),
+                    required: false,
+                    default: Some("1".to_string()),
+                    description: "Steps ahead of the volatility forecast".to_string(),
+                },
+                output_parameter(
+                    "volatility, forecast, omega, alpha, beta, long_run_volatility",
+                    "volatility",
+                ),
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "pca".to_string(),
+            description: "Rolling principal component analysis of log returns across assets"
+                .to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "with".to_string(),
+                    param_type: "string".to_string(),
+                    required: true,
+                    default: None,
+                    description: "Comma-separated other assets of the universe".to_string(),
+                },
+                period_parameter("window", 60),
+                ParameterInfo {
+                    name: "components".to_string(),
+                    param_type: "integer".to_string(),
+                    required: false,
+                    default: Some("3".to_string()),
+                    description: "Leading components to report (at most the asset count)"
+                        .to_string(),
+                },
+                output_parameter(
+                    "factor_<k>, eigenvalue_<k>, explained_ratio_<k>, loading_<k>_<asset>",
+                    "factor_1",
+                ),
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "correlation_matrix".to_string(),
+            description: "Rolling correlation matrix of log returns (GET /analytics/matrix)"
+                .to_string(),
+            parameters: matrix_parameters(),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "covariance_matrix".to_string(),
+            description: "Rolling covariance matrix of log returns (GET /analytics/matrix)"
+                .to_string(),
+            parameters: matrix_parameters(),
+            burnin_days: "window".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "expression".to_string(),
+            description: "Formula over other series, e.g. vol_20 / vol_60 or \
+                          close - ema(close, 0.94)"
+                .to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "expression".to_string(),
+                    param_type: "string".to_string(),
+                    required: true,
+                    default: None,
+                    description: "Formula of series (close, returns, drawdown, \
+                                  <analytic>_<window>), arithmetic, comparisons and \
+                                  lag/sma/sum/std/ema functions"
+                        .to_string(),
+                },
+                reporting_currency_parameter(false),
+            ],
+            burnin_days: "formula lookback".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "portfolio_nav".to_string(),
+            description: "Portfolio NAV, normalized to 1.0 at inception (GET /portfolio/nav)"
+                .to_string(),
+            parameters: portfolio_parameters(),
+            burnin_days: "0".to_string(),
+        },
+        AnalyticInfo {
+            analytic_type: "portfolio_returns".to_string(),
+            description: "Log returns of the portfolio NAV (GET /portfolio/returns)".to_string(),
+            parameters: portfolio_parameters(),
+            burnin_days: "1".to_string(),
+        },
+    ];
+
+    // User-defined analytics are described by their registered schemas
+    analytics.extend(registry.custom_analytics().map(|(custom, schema)| {
+        AnalyticInfo {
+            analytic_type: custom.name().to_string(),
+            description: schema.description.clone(),
+            parameters: schema
+                .parameters
+                .iter()
+                .map(|parameter| ParameterInfo {
+                    name: parameter.name.clone(),
+                    param_type: parameter.kind.name().to_string(),
+                    required: parameter.required,
+                    default: parameter.default.clone(),
+                    description: parameter.description.clone(),
+                })
+                .collect(),
+            burnin_days: schema.burnin_days.clone(),
+        }
+    }));
+
+    // Every node accepts a missing-data policy
+    for analytic in &mut analytics {
+        analytic.parameters.extend(missing_data_parameters());
+    }
+
+    analytics
+}
+
+fn portfolio_parameters() -> Vec<ParameterInfo> {
+    vec![
+        ParameterInfo {
+            name: "assets".to_string(),
+            param_type: "string".to_string(),
+            required: true,
+            default: None,
+            description: "Comma-separated constituents".to_string(),
+        },
+        ParameterInfo {
+            name: "weights".to_string(),
+            param_type: "string".to_string(),
+            required: false,
+            default: Some("equal".to_string()),
+            description: "Comma-separated target weights, one per asset".to_string(),
+        },
+        ParameterInfo {
+            name: "rebalance".to_string(),
+            param_type: "string".to_string(),
+            required: false,
+            default: Some("none".to_string()),
+            description: "Rebalance frequency: none, daily or monthly".to_string(),
+        },
+        reporting_currency_parameter(false),
+    ]
+}
+
+fn ratio_parameters(reference: Option<ParameterInfo>) -> Vec<ParameterInfo> {
+    let mut parameters = vec![
+        ParameterInfo {
+            name: "window".to_string(),
+            param_type: "integer".to_string(),
+            required: false,
+            default: Some("10".to_string()),
+            description: "Rolling window size in days".to_string(),
+        },
+        ParameterInfo {
+            name: "periods_per_year".to_string(),
+            param_type: "number".to_string(),
+            required: false,
+            default: Some("252".to_string()),
+            description: "Observations per year used to annualize".to_string(),
+        },
     ];
+    parameters.extend(reference);
+    parameters.push(reporting_currency_parameter(false));
+    parameters
+}
+
+fn period_parameter(name: &str, default: usize) -> ParameterInfo {
+    ParameterInfo {
+        name: name.to_string(),
+        param_type: "integer".to_string(),
+        required: false,
+        default: Some(default.to_string()),
+        description: "Period in observations".to_string(),
+    }
+}
+
+fn output_parameter(choices: &str, default: &str) -> ParameterInfo {
+    ParameterInfo {
+        name: "output".to_string(),
+        param_type: "string".to_string(),
+        required: false,
+        default: Some(default.to_string()),
+        description: format!("Series to return: {}", choices),
+    }
+}
+
+fn tail_risk_parameters() -> Vec<ParameterInfo> {
+    vec![
+        ParameterInfo {
+            name: "window".to_string(),
+            param_type: "integer".to_string(),
+            required: false,
+            default: Some("10".to_string()),
+            description: "Rolling window size in days".to_string(),
+        },
+        ParameterInfo {
+            name: "confidence".to_string(),
+            param_type: "number".to_string(),
+            required: false,
+            default: Some("0.95".to_string()),
+            description: "Confidence level, between 0 and 1".to_string(),
+        },
+        ParameterInfo {
+            name: "method".to_string(),
+            param_type: "string".to_string(),
+            required: false,
+            default: Some("historical".to_string()),
+            description: "historical, parametric or cornish_fisher".to_string(),
+        },
+        reporting_currency_parameter(false),
+    ]
+}
+
+fn distribution_parameters(extra: Option<ParameterInfo>) -> Vec<ParameterInfo> {
+    let mut parameters = vec![period_parameter("window", 10)];
+    parameters.extend(extra);
+    parameters.push(reporting_currency_parameter(false));
+    parameters
+}
+
+fn matrix_parameters() -> Vec<ParameterInfo> {
+    vec![
+        ParameterInfo {
+            name: "with".to_string(),
+            param_type: "string".to_string(),
+            required: true,
+            default: None,
+            description: "Comma-separated other assets of the matrix".to_string(),
+        },
+        period_parameter("window", 60),
+        ParameterInfo {
+            name: "estimator".to_string(),
+            param_type: "string".to_string(),
+            required: false,
+            default: Some("sample".to_string()),
+            description: "Covariance estimator: sample, ewma or ledoit_wolf".to_string(),
+        },
+        ParameterInfo {
+            name: "lambda".to_string(),
+            param_type: "number".to_string(),
+            required: false,
+            default: Some("0.94".to_string()),
+            description: "EWMA decay between 0 and 1".to_string(),
+        },
+        output_parameter("<asset>_<asset>, observations", "<first>_<second>"),
+        reporting_currency_parameter(false),
+    ]
+}
+
+fn risk_free_parameter() -> ParameterInfo {
+    ParameterInfo {
+        name: "risk_free".to_string(),
+        param_type: "string".to_string(),
+        required: false,
+        default: None,
+        description: "Asset holding the annualized risk-free rate as a decimal (default: 0)"
+            .to_string(),
+    }
+}
+
+fn missing_data_parameters() -> Vec<ParameterInfo> {
+    vec![
+        ParameterInfo {
+            name: "missing_data".to_string(),
+            param_type: "string".to_string(),
+            required: false,
+            default: None,
+            description: "Treatment of missing inputs: skip, ffill, interpolate or nan".to_string(),
+        },
+        ParameterInfo {
+            name: "min_observations".to_string(),
+            param_type: "integer".to_string(),
+            required: false,
+            default: None,
+            description: "Valid inputs the window needs, below which the value is NaN".to_string(),
+        },
+    ]
+}
 
-    Json(AnalyticsListResponse { analytics })
+fn reporting_currency_parameter(required: bool) -> ParameterInfo {
+    ParameterInfo {
+        name: "reporting_currency".to_string(),
+        param_type: "string".to_string(),
+        required,
+        default: None,
+        description: "Currency to express prices in, converted with stored FX rates"
+            .to_string(),
+    }
 }
 
 /// Query parameters for DAG visualization
@@ -149,13 +770,14 @@ pub struct DagVisualizationQueryParams {
 /// GET /dag/visualize?asset={asset}&analytic={type}&start={date}&end={date}
 /// Returns the DAG structure for visualization
 pub async fn visualize_dag(
+    State(state): State<Arc<AppState>>,
     Query(params): Query<DagVisualizationQueryParams>,
 ) -> Result<Json<crate::dag::DagVisualization>, ApiError> {
     use crate::asset_key::AssetKey;
     use chrono::NaiveDate;
 
     // Parse asset
-    let asset_key = AssetKey::new_equity(&params.asset)
+    let asset_key = AssetKey::parse(&params.asset)
         .map_err(|_| ApiError::InvalidParameter(format!("Invalid asset: {}", params.asset)))?;
 
     // Parse dates
@@ -173,6 +795,7 @@ pub async fn visualize_dag(
 
     // Build the DAG
     let (dag, _, _) = build_analytics_dag(
+        &state.registry,
         &asset_key,
         &params.analytic,
         &date_range,
@@ -199,6 +822,11 @@ pub async fn visualize_dag(
 pub struct DataQueryParams {
     pub start: String,
     pub end: String,
+    /// Handling of prices flagged as bad prints: "include", "exclude" or "ffill"
+    pub flagged: Option<String>,
+    /// Knowledge time to read prices at: RFC 3339 timestamp, or a date for
+    /// its end
+    pub as_of: Option<String>,
 }
 
 /// Single data point in response
@@ -208,6 +836,32 @@ pub struct DataPoint {
     pub close: f64,
 }
 
+/// Contract details of a futures asset
+#[derive(Debug, Serialize)]
+pub struct FutureInfo {
+    pub series: String,
+    pub expiry_date: String,
+    pub contract_month: String,
+    pub name: String,
+    pub exchange: String,
+    pub currency: String,
+    pub rollover_date: String,
+}
+
+impl From<&Future> for FutureInfo {
+    fn from(future: &Future) -> Self {
+        FutureInfo {
+            series: future.series().to_string(),
+            expiry_date: future.expiry_date().format("%Y-%m-%d").to_string(),
+            contract_month: future.contract_month().to_string(),
+            name: future.name().to_string(),
+            exchange: future.exchange().to_string(),
+            currency: future.currency().to_string(),
+            rollover_date: future.rollover_date().format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
 /// Response for asset data query
 #[derive(Debug, Serialize)]
 pub struct AssetDataResponse {
@@ -215,6 +869,9 @@ pub struct AssetDataResponse {
     pub start_date: String,
     pub end_date: String,
     pub data: Vec<DataPoint>,
+    /// Stored contract details, for futures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub future: Option<FutureInfo>,
 }
 
 /// GET /assets/{asset}/data - Get raw price data for an asset
@@ -237,22 +894,25 @@ pub async fn get_asset_data(
     }
 
     // Create asset key
-    let asset_key = AssetKey::new_equity(&asset)
+    let asset_key = AssetKey::parse(&asset)
         .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
 
     // Create date range
     let date_range = DateRange::new(start_date, end_date);
 
     // Query data provider
+    let handling = flagged_points(params.flagged.as_deref())?;
+    let as_of = as_of_time(params.as_of.as_deref())?;
     let provider = state.data_provider.lock().await;
-    let data_points =
-        crate::time_series::DataProvider::get_time_series(&*provider, &asset_key, &date_range)
-            .map_err(|e| match e {
-                crate::time_series::DataProviderError::AssetNotFound => {
-                    ApiError::AssetNotFound(asset.clone())
-                }
-                _ => ApiError::InternalError(e.to_string()),
-            })?;
+    let filtered = QualityFilteredProvider::new(&provider, handling);
+    let data_points = AsOfProvider::new(&filtered, as_of)
+        .get_time_series(&asset_key, &date_range)
+        .map_err(|e| match e {
+            crate::time_series::DataProviderError::AssetNotFound => {
+                ApiError::AssetNotFound(asset.clone())
+            }
+            _ => ApiError::InternalError(e.to_string()),
+        })?;
 
     // Convert to response format
     let data: Vec<DataPoint> = data_points
@@ -263,194 +923,1695 @@ pub async fn get_asset_data(
         })
         .collect();
 
+    let future = match asset_key {
+        AssetKey::Future { .. } => match provider.get_asset_future(&asset_key) {
+            Ok(future) => Some(FutureInfo::from(&future)),
+            Err(DataProviderError::AssetNotFound) => None,
+            Err(e) => return Err(ApiError::InternalError(e.to_string())),
+        },
+        _ => None,
+    };
+
     Ok(Json(AssetDataResponse {
-        asset,
+        asset: asset_key.as_string(),
         start_date: params.start,
         end_date: params.end,
         data,
+        future,
     }))
 }
 
-// Task Group 5: Pull-Mode Analytics Endpoints
-
-fn build_node_key(
-    asset: &AssetKey,
-    analytic: AnalyticType,
-    date_range: &DateRange,
-    params: &HashMap<String, String>,
-    override_tag: Option<String>,
-) -> Result<NodeKey, ApiError> {
-    let mut node_params = params.clone();
-
-    let window_spec = match analytic {
-        AnalyticType::Volatility => {
-            let window_size = node_params
-                .get("window")
-                .or_else(|| node_params.get("window_size"))
-                .and_then(|value| value.parse::<usize>().ok())
-                .unwrap_or(10);
-
-            if window_size == 0 {
-                return Err(ApiError::InvalidParameter(
-                    "Window size must be greater than 0".to_string(),
-                ));
-            }
-
-            node_params.insert("window_size".to_string(), window_size.to_string());
-            Some(WindowSpec::fixed(window_size))
-        }
-        _ => None,
-    };
-
-    if analytic == AnalyticType::Returns {
-        let lag = node_params
-            .get("lag")
-            .and_then(|value| value.parse::<usize>().ok())
-            .filter(|&lag| lag > 0)
-            .unwrap_or(1);
-        node_params.insert("lag".to_string(), lag.to_string());
-    }
+/// Response for a price upload
+#[derive(Debug, Serialize)]
+pub struct UploadResponse {
+    pub asset: String,
+    /// Data rows read from the body
+    pub rows: usize,
+    /// Prices written
+    pub inserted: usize,
+    /// Rows dropped because their timestamp was already stored
+    pub skipped: usize,
+    /// Stored prices flagged as bad prints by the quality checks
+    pub flagged: usize,
+}
 
-    if let Some(tag) = &override_tag {
-        node_params.insert("override".to_string(), tag.clone());
+/// POST /assets/{asset}/data - Import a CSV or Parquet body of prices for an asset
+///
+/// Query parameters are the importer options of [`ImportConfig::from_options`],
+/// except `asset_column`, since the path names the asset.
+pub async fn upload_asset_data(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+    Query(options): Query<HashMap<String, String>>,
+    body: Bytes,
+) -> Result<Json<UploadResponse>, ApiError> {
+    let asset_key = AssetKey::parse(&asset)
+        .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
+    if options.contains_key("asset_column") {
+        return Err(ApiError::InvalidParameter(
+            "asset_column cannot be used when the path names the asset".to_string(),
+        ));
     }
-
-    Ok(NodeKey {
-        analytic,
-        assets: vec![asset.clone()],
-        range: Some(date_range.clone()),
-        window: window_spec,
-        override_tag,
-        params: node_params,
-    })
+    let options = options
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()));
+    let config =
+        ImportConfig::from_options(options).map_err(|e| ApiError::InvalidParameter(e.to_string()))?;
+
+    let mut provider = state.data_provider.lock().await;
+    let summary =
+        import_bytes(&mut provider, body, Some(&asset_key), &config).map_err(|e| match e {
+            ImportError::Database(e) => ApiError::InternalError(e.to_string()),
+            e => ApiError::InvalidParameter(e.to_string()),
+        })?;
+
+    Ok(Json(UploadResponse {
+        asset: asset_key.as_string(),
+        rows: summary.rows,
+        inserted: summary.inserted,
+        skipped: summary.skipped,
+        flagged: summary.flagged,
+    }))
 }
 
-/// Helper function to build analytics DAG
-fn build_analytics_dag(
-    asset: &AssetKey,
-    analytic_type: &str,
-    date_range: &DateRange,
-    params: &HashMap<String, String>,
-    override_tag: Option<String>,
-) -> Result<(AnalyticsDag, NodeId, NodeKey), ApiError> {
-    let analytic = AnalyticType::from_str(analytic_type);
-    let registry = AnalyticRegistry::default();
+/// Sync progress of one asset, as reported by `/ingest/status`
+#[derive(Debug, Serialize)]
+pub struct AssetSyncInfo {
+    pub asset: String,
+    /// Last date the asset is synced through
+    pub watermark: Option<NaiveDate>,
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
 
-    if registry.definition(analytic).is_none() {
-        return Err(ApiError::InvalidParameter(format!(
-            "Unknown analytic type: {}",
-            analytic_type
-        )));
+impl From<SyncState> for AssetSyncInfo {
+    fn from(state: SyncState) -> Self {
+        AssetSyncInfo {
+            asset: state.key.as_string(),
+            watermark: state.watermark,
+            last_attempt: state.last_attempt,
+            last_success: state.last_success,
+            last_error: state.last_error,
+        }
     }
+}
 
-    let node_key = build_node_key(asset, analytic, date_range, params, override_tag)?;
-
-    let mut dag = AnalyticsDag::new();
-    let target_node = dag
-        .resolve_node(node_key.clone())
-        .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
-
-    Ok((dag, target_node, node_key))
+/// Response for the ingestion status
+#[derive(Debug, Serialize)]
+pub struct IngestStatusResponse {
+    #[serde(flatten)]
+    pub job: IngestStatus,
+    /// Sync state of every asset refreshed so far
+    pub assets: Vec<AssetSyncInfo>,
 }
 
-/// Query parameters for analytics endpoint
-#[derive(Debug, Deserialize)]
-pub struct AnalyticsQueryParams {
-    pub start: String,
-    pub end: String,
-    pub window: Option<usize>,
-    #[serde(rename = "override")]
-    pub override_tag: Option<String>,
+/// GET /ingest/status - State of the scheduled market data refresh
+pub async fn ingest_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<IngestStatusResponse>, ApiError> {
+    let job = state.ingest.read().await.clone();
+    let assets = state
+        .data_provider
+        .lock()
+        .await
+        .list_sync_states()
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+        .into_iter()
+        .map(AssetSyncInfo::from)
+        .collect();
+
+    Ok(Json(IngestStatusResponse { job, assets }))
 }
 
-/// Single data point in analytics response
+/// Quality flag of one price, as reported by `/assets/{asset}/quality`
 #[derive(Debug, Serialize)]
-pub struct AnalyticDataPoint {
+pub struct QualityFlagInfo {
     pub timestamp: String,
-    pub value: Option<f64>,
+    pub flag: String,
+    pub detail: String,
 }
 
-/// Response for analytics query
+/// Response of `/assets/{asset}/quality`
 #[derive(Debug, Serialize)]
-pub struct AnalyticsResponse {
+pub struct QualityReportResponse {
     pub asset: String,
-    pub analytic: String,
-    pub parameters: HashMap<String, String>,
-    pub start_date: String,
-    pub end_date: String,
-    pub data: Vec<AnalyticDataPoint>,
+    /// Number of stored prices checked
+    pub points: usize,
+    /// Number of flags other than gaps within the reported range
+    pub bad_prints: usize,
+    pub flags: Vec<QualityFlagInfo>,
+    pub missing_days: Vec<NaiveDate>,
+}
+
+/// GET /assets/{asset}/quality - Re-check an asset's prices and report its quality flags
+///
+/// Query parameters other than `start` and `end` configure the rules
+/// (`non_positive`, `spike_ratio`, `stale_run`, `missing_days`). The whole
+/// history is checked and its stored flags replaced; `start` and `end` only
+/// restrict what is reported.
+pub async fn get_asset_quality(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<QualityReportResponse>, ApiError> {
+    let asset_key = parse_asset_path(&asset)?;
+    let parse_date = |name: &str| {
+        params
+            .get(name)
+            .map(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+            .transpose()
+            .map_err(|e| ApiError::InvalidDateRange(format!("Invalid {} date: {}", name, e)))
+    };
+    let start = parse_date("start")?.unwrap_or(NaiveDate::MIN);
+    let end = parse_date("end")?.unwrap_or(NaiveDate::MAX);
+    if start > end {
+        return Err(ApiError::InvalidDateRange(
+            "Start date must be before or equal to end date".to_string(),
+        ));
+    }
+    let config = QualityConfig::from_options(
+        params
+            .iter()
+            .filter(|(name, _)| !matches!(name.as_str(), "start" | "end"))
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    )
+    .map_err(ApiError::InvalidParameter)?;
+
+    let mut provider = state.data_provider.lock().await;
+    let report = check_asset(&mut provider, &asset_key, &config)
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    if report.points == 0 {
+        return Err(ApiError::AssetNotFound(asset));
+    }
+
+    let in_range = |date: NaiveDate| date >= start && date <= end;
+    let flags: Vec<_> = report
+        .flags
+        .iter()
+        .filter(|flagged| in_range(flagged.timestamp.date_naive()))
+        .collect();
+    Ok(Json(QualityReportResponse {
+        asset,
+        points: report.points,
+        bad_prints: flags.iter().filter(|f| f.flag.is_bad_print()).count(),
+        flags: flags
+            .into_iter()
+            .map(|flagged| QualityFlagInfo {
+                timestamp: flagged.timestamp.to_rfc3339(),
+                flag: flagged.flag.name().to_string(),
+                detail: flagged.detail.clone(),
+            })
+            .collect(),
+        missing_days: report
+            .missing_days
+            .into_iter()
+            .filter(|date| in_range(*date))
+            .collect(),
+    }))
+}
+
+// Asset metadata management
+
+/// Corporate action of an equity, as sent and returned by the API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CorporateActionInfo {
+    Split {
+        ratio: f64,
+        effective_date: NaiveDate,
+    },
+    Dividend {
+        amount: f64,
+        ex_date: NaiveDate,
+        payment_date: NaiveDate,
+    },
+}
+
+impl CorporateActionInfo {
+    fn into_action(self) -> Result<CorporateAction, ApiError> {
+        match self {
+            CorporateActionInfo::Split {
+                ratio,
+                effective_date,
+            } => {
+                if !ratio.is_finite() || ratio <= 0.0 {
+                    return Err(ApiError::InvalidParameter(format!(
+                        "Split ratio must be positive, got {}",
+                        ratio
+                    )));
+                }
+                Ok(CorporateAction::Split {
+                    ratio,
+                    effective_date,
+                })
+            }
+            CorporateActionInfo::Dividend {
+                amount,
+                ex_date,
+                payment_date,
+            } => {
+                if !amount.is_finite() || amount < 0.0 {
+                    return Err(ApiError::InvalidParameter(format!(
+                        "Dividend amount must not be negative, got {}",
+                        amount
+                    )));
+                }
+                if payment_date < ex_date {
+                    return Err(ApiError::InvalidParameter(
+                        "Dividend payment date must not precede its ex-date".to_string(),
+                    ));
+                }
+                Ok(CorporateAction::Dividend {
+                    amount,
+                    ex_date,
+                    payment_date,
+                })
+            }
+        }
+    }
+}
+
+impl From<&CorporateAction> for CorporateActionInfo {
+    fn from(action: &CorporateAction) -> Self {
+        match *action {
+            CorporateAction::Split {
+                ratio,
+                effective_date,
+            } => CorporateActionInfo::Split {
+                ratio,
+                effective_date,
+            },
+            CorporateAction::Dividend {
+                amount,
+                ex_date,
+                payment_date,
+            } => CorporateActionInfo::Dividend {
+                amount,
+                ex_date,
+                payment_date,
+            },
+        }
+    }
+}
+
+/// Expiry calendar of a futures contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpiryCalendarInfo {
+    pub calendar_id: String,
+    /// Days before expiry to roll to the next contract
+    pub rollover_days: u32,
+}
+
+/// Stored metadata of an asset, as sent to and returned by the asset endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AssetDefinition {
+    Equity {
+        ticker: String,
+        name: String,
+        exchange: String,
+        currency: String,
+        sector: String,
+        #[serde(default)]
+        corporate_actions: Vec<CorporateActionInfo>,
+    },
+    Future {
+        series: String,
+        expiry_date: NaiveDate,
+        contract_month: String,
+        name: String,
+        exchange: String,
+        currency: String,
+        expiry_calendar: ExpiryCalendarInfo,
+    },
+    Fx {
+        base: String,
+        quote: String,
+        name: String,
+        /// Source of the fixing (e.g. "WMR")
+        source: String,
+    },
+}
+
+/// Asset built from a validated definition, ready to be stored
+enum StoredAsset {
+    Equity(Equity),
+    Future(Future),
+    Fx(FxRate),
+}
+
+impl StoredAsset {
+    fn key(&self) -> &AssetKey {
+        match self {
+            StoredAsset::Equity(equity) => equity.key(),
+            StoredAsset::Future(future) => future.key(),
+            StoredAsset::Fx(fx) => fx.key(),
+        }
+    }
+
+    fn store(&self, provider: &SqliteDataProvider) -> Result<(), ApiError> {
+        match self {
+            StoredAsset::Equity(equity) => provider.store_asset_equity(equity),
+            StoredAsset::Future(future) => provider.store_asset_future(future),
+            StoredAsset::Fx(fx) => provider.store_asset_fx(fx),
+        }
+        .map_err(|e| ApiError::InternalError(e.to_string()))
+    }
+
+    fn definition(&self) -> AssetDefinition {
+        match self {
+            StoredAsset::Equity(equity) => AssetDefinition::Equity {
+                ticker: equity.key().as_string(),
+                name: equity.name().to_string(),
+                exchange: equity.exchange().to_string(),
+                currency: equity.currency().to_string(),
+                sector: equity.sector().to_string(),
+                corporate_actions: equity
+                    .corporate_actions()
+                    .iter()
+                    .map(CorporateActionInfo::from)
+                    .collect(),
+            },
+            StoredAsset::Future(future) => AssetDefinition::Future {
+                series: future.series().to_string(),
+                expiry_date: future.expiry_date(),
+                contract_month: future.contract_month().to_string(),
+                name: future.name().to_string(),
+                exchange: future.exchange().to_string(),
+                currency: future.currency().to_string(),
+                expiry_calendar: ExpiryCalendarInfo {
+                    calendar_id: future.expiry_calendar().calendar_id.clone(),
+                    rollover_days: future.expiry_calendar().rollover_days,
+                },
+            },
+            StoredAsset::Fx(fx) => AssetDefinition::Fx {
+                base: fx.base().to_string(),
+                quote: fx.quote().to_string(),
+                name: fx.name().to_string(),
+                source: fx.source().to_string(),
+            },
+        }
+    }
+}
+
+/// Rejects an empty descriptive field such as a name or exchange
+fn require_text(field: &str, value: &str) -> Result<(), ApiError> {
+    if value.trim().is_empty() {
+        return Err(ApiError::InvalidParameter(format!(
+            "'{}' must not be empty",
+            field
+        )));
+    }
+    Ok(())
+}
+
+impl AssetDefinition {
+    /// Validates the definition and builds the asset it describes
+    fn build(self) -> Result<StoredAsset, ApiError> {
+        let invalid =
+            |e: AssetKeyError| ApiError::InvalidParameter(format!("Invalid asset: {}", e));
+        let invalid_currency =
+            |e: AssetKeyError| ApiError::InvalidParameter(format!("Invalid currency: {}", e));
+        match self {
+            AssetDefinition::Equity {
+                ticker,
+                name,
+                exchange,
+                currency,
+                sector,
+                corporate_actions,
+            } => {
+                require_text("name", &name)?;
+                require_text("exchange", &exchange)?;
+                AssetKey::validate_currency_code(&currency).map_err(invalid_currency)?;
+                let corporate_actions = corporate_actions
+                    .into_iter()
+                    .map(CorporateActionInfo::into_action)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let equity = Equity::with_corporate_actions(
+                    ticker,
+                    name,
+                    exchange,
+                    currency,
+                    sector,
+                    corporate_actions,
+                )
+                .map_err(invalid)?;
+                Ok(StoredAsset::Equity(equity))
+            }
+            AssetDefinition::Future {
+                series,
+                expiry_date,
+                contract_month,
+                name,
+                exchange,
+                currency,
+                expiry_calendar,
+            } => {
+                require_text("name", &name)?;
+                require_text("exchange", &exchange)?;
+                require_text("calendar_id", &expiry_calendar.calendar_id)?;
+                AssetKey::validate_currency_code(&currency).map_err(invalid_currency)?;
+                let future = Future::new(
+                    series,
+                    expiry_date,
+                    contract_month,
+                    name,
+                    exchange,
+                    currency,
+                    expiry_calendar.calendar_id,
+                    expiry_calendar.rollover_days,
+                )
+                .map_err(invalid)?;
+                Ok(StoredAsset::Future(future))
+            }
+            AssetDefinition::Fx {
+                base,
+                quote,
+                name,
+                source,
+            } => {
+                require_text("name", &name)?;
+                let fx = FxRate::new(base, quote, name, source).map_err(invalid)?;
+                Ok(StoredAsset::Fx(fx))
+            }
+        }
+    }
+}
+
+/// Loads the stored metadata of an asset
+fn load_asset(
+    provider: &SqliteDataProvider,
+    asset_key: &AssetKey,
+) -> Result<StoredAsset, ApiError> {
+    let loaded = match asset_key {
+        AssetKey::Equity(_) => provider.get_asset_equity(asset_key).map(StoredAsset::Equity),
+        AssetKey::Future { .. } => provider.get_asset_future(asset_key).map(StoredAsset::Future),
+        AssetKey::FxRate { .. } => provider.get_asset_fx(asset_key).map(StoredAsset::Fx),
+    };
+    loaded.map_err(|e| match e {
+        DataProviderError::AssetNotFound => ApiError::AssetNotFound(asset_key.as_string()),
+        _ => ApiError::InternalError(e.to_string()),
+    })
+}
+
+fn parse_asset_path(asset: &str) -> Result<AssetKey, ApiError> {
+    AssetKey::parse(asset).map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))
+}
+
+fn flagged_points(value: Option<&str>) -> Result<FlaggedPoints, ApiError> {
+    value.map_or(Ok(FlaggedPoints::Include), |value| {
+        FlaggedPoints::from_name(value).ok_or_else(|| {
+            ApiError::InvalidParameter(format!(
+                "Invalid flagged option '{}': expected include, exclude or ffill",
+                value
+            ))
+        })
+    })
+}
+
+/// Parses an `as_of` knowledge time; a bare date means the end of that day.
+fn as_of_time(value: Option<&str>) -> Result<Option<DateTime<Utc>>, ApiError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(time.with_timezone(&Utc)));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.succ_opt())
+        .and_then(|next| next.and_hms_opt(0, 0, 0))
+        .map(|midnight| Some(midnight.and_utc() - chrono::Duration::microseconds(1)))
+        .ok_or_else(|| {
+            ApiError::InvalidParameter(format!(
+                "Invalid as_of '{}': expected an RFC 3339 timestamp or YYYY-MM-DD",
+                value
+            ))
+        })
+}
+
+/// POST /assets - Store a new asset
+pub async fn create_asset(
+    State(state): State<Arc<AppState>>,
+    Json(definition): Json<AssetDefinition>,
+) -> Result<Json<AssetDefinition>, ApiError> {
+    let asset = definition.build()?;
+    let provider = state.data_provider.lock().await;
+    if provider.asset_exists(asset.key())? {
+        return Err(ApiError::AssetExists(asset.key().as_string()));
+    }
+    asset.store(&provider)?;
+    Ok(Json(asset.definition()))
+}
+
+/// GET /assets/{asset} - Get the stored metadata of an asset
+pub async fn get_asset(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+) -> Result<Json<AssetDefinition>, ApiError> {
+    let asset_key = parse_asset_path(&asset)?;
+    let provider = state.data_provider.lock().await;
+    Ok(Json(load_asset(&provider, &asset_key)?.definition()))
+}
+
+/// PUT /assets/{asset} - Replace the stored metadata of an asset
+pub async fn update_asset(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+    Json(definition): Json<AssetDefinition>,
+) -> Result<Json<AssetDefinition>, ApiError> {
+    let asset_key = parse_asset_path(&asset)?;
+    let asset = definition.build()?;
+    if *asset.key() != asset_key {
+        return Err(ApiError::InvalidParameter(format!(
+            "Body describes asset '{}', not '{}'",
+            asset.key(),
+            asset_key
+        )));
+    }
+    let provider = state.data_provider.lock().await;
+    load_asset(&provider, &asset_key)?;
+    asset.store(&provider)?;
+    Ok(Json(asset.definition()))
+}
+
+/// Response for asset deletion
+#[derive(Debug, Serialize)]
+pub struct DeleteAssetResponse {
+    pub asset: String,
+    pub deleted: bool,
+}
+
+/// DELETE /assets/{asset} - Delete the stored metadata of an asset, keeping its prices
+pub async fn delete_asset(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+) -> Result<Json<DeleteAssetResponse>, ApiError> {
+    let asset_key = parse_asset_path(&asset)?;
+    let provider = state.data_provider.lock().await;
+    provider.delete_asset(&asset_key).map_err(|e| match e {
+        DataProviderError::AssetNotFound => ApiError::AssetNotFound(asset_key.as_string()),
+        _ => ApiError::InternalError(e.to_string()),
+    })?;
+    Ok(Json(DeleteAssetResponse {
+        asset: asset_key.as_string(),
+        deleted: true,
+    }))
+}
+
+/// PUT /assets/{asset}/corporate_actions - Replace the corporate actions of an equity
+pub async fn update_corporate_actions(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+    Json(actions): Json<Vec<CorporateActionInfo>>,
+) -> Result<Json<AssetDefinition>, ApiError> {
+    let asset_key = parse_asset_path(&asset)?;
+    let provider = state.data_provider.lock().await;
+    let StoredAsset::Equity(equity) = load_asset(&provider, &asset_key)? else {
+        return Err(ApiError::InvalidParameter(format!(
+            "Corporate actions apply to equities only, not '{}'",
+            asset_key
+        )));
+    };
+    let actions = actions
+        .into_iter()
+        .map(CorporateActionInfo::into_action)
+        .collect::<Result<Vec<_>, _>>()?;
+    let equity = Equity::with_corporate_actions(
+        equity.key().as_string(),
+        equity.name(),
+        equity.exchange(),
+        equity.currency(),
+        equity.sector(),
+        actions,
+    )
+    .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    let asset = StoredAsset::Equity(equity);
+    asset.store(&provider)?;
+    Ok(Json(asset.definition()))
+}
+
+/// PUT /assets/{asset}/expiry_calendar - Replace the expiry calendar of a future
+pub async fn update_expiry_calendar(
+    State(state): State<Arc<AppState>>,
+    Path(asset): Path<String>,
+    Json(calendar): Json<ExpiryCalendarInfo>,
+) -> Result<Json<AssetDefinition>, ApiError> {
+    let asset_key = parse_asset_path(&asset)?;
+    require_text("calendar_id", &calendar.calendar_id)?;
+    let provider = state.data_provider.lock().await;
+    let StoredAsset::Future(future) = load_asset(&provider, &asset_key)? else {
+        return Err(ApiError::InvalidParameter(format!(
+            "Expiry calendars apply to futures only, not '{}'",
+            asset_key
+        )));
+    };
+    let future = Future::new(
+        future.series(),
+        future.expiry_date(),
+        future.contract_month(),
+        future.name(),
+        future.exchange(),
+        future.currency(),
+        calendar.calendar_id,
+        calendar.rollover_days,
+    )
+    .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    let asset = StoredAsset::Future(future);
+    asset.store(&provider)?;
+    Ok(Json(asset.definition()))
+}
+
+// Task Group 5: Pull-Mode Analytics Endpoints
+
+/// A count parameter such as a window or lag: a positive integer.
+fn parse_positive(value: &str) -> Option<usize> {
+    value
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|&count| count > 0)
+}
+
+fn build_node_key(
+    asset: &AssetKey,
+    analytic: AnalyticType,
+    date_range: &DateRange,
+    params: &HashMap<String, String>,
+    override_tag: Option<String>,
+) -> Result<NodeKey, ApiError> {
+    let mut node_params = params.clone();
+    let mut assets = related_assets(asset, analytic, params)?;
+    let missing = MissingData::from_params(params).map_err(ApiError::InvalidParameter)?;
+    if let Some(policy) = missing.policy {
+        node_params.insert("missing_data".to_string(), policy.to_string());
+    }
+    if let Some(min_observations) = missing.min_observations {
+        node_params.insert("min_observations".to_string(), min_observations.to_string());
+    }
+
+    let window_spec = match analytic {
+        AnalyticType::Volatility
+        | AnalyticType::DownsideDeviation
+        | AnalyticType::Correlation
+        | AnalyticType::MaxDrawdown
+        | AnalyticType::SharpeRatio
+        | AnalyticType::SortinoRatio
+        | AnalyticType::CalmarRatio
+        | AnalyticType::InformationRatio
+        | AnalyticType::ValueAtRisk
+        | AnalyticType::ExpectedShortfall
+        | AnalyticType::Sma
+        | AnalyticType::ExponentialMovingAverage
+        | AnalyticType::Rsi
+        | AnalyticType::BollingerBands
+        | AnalyticType::Atr
+        | AnalyticType::Skewness
+        | AnalyticType::Kurtosis
+        | AnalyticType::Quantile
+        | AnalyticType::PercentileRank
+        | AnalyticType::ZScore
+        | AnalyticType::Regression
+        | AnalyticType::Garch
+        | AnalyticType::Pca
+        | AnalyticType::CorrelationMatrix
+        | AnalyticType::CovarianceMatrix => {
+            let window_size = match node_params
+                .get("window")
+                .or_else(|| node_params.get("window_size"))
+            {
+                Some(value) => parse_positive(value).ok_or_else(|| {
+                    ApiError::InvalidParameter(format!(
+                        "Window size must be a positive integer: {}",
+                        value
+                    ))
+                })?,
+                None => default_window(analytic),
+            };
+
+            node_params.insert("window_size".to_string(), window_size.to_string());
+            Some(WindowSpec::fixed(window_size))
+        }
+        _ => None,
+    };
+
+    if analytic == AnalyticType::Returns {
+        let lag = match node_params.get("lag") {
+            Some(value) => parse_positive(value).ok_or_else(|| {
+                ApiError::InvalidParameter(format!("Lag must be a positive integer: {}", value))
+            })?,
+            None => 1,
+        };
+        node_params.insert("lag".to_string(), lag.to_string());
+    }
+
+    if matches!(
+        analytic,
+        AnalyticType::ValueAtRisk | AnalyticType::ExpectedShortfall
+    ) {
+        use crate::analytics::risk::{parse_confidence, VarMethod};
+
+        let confidence = parse_confidence(node_params.get("confidence").map(String::as_str))
+            .map_err(ApiError::InvalidParameter)?;
+        let method = match node_params.get("method") {
+            Some(value) => VarMethod::parse(value).ok_or_else(|| {
+                ApiError::InvalidParameter(format!("Unknown VaR method: {}", value))
+            })?,
+            None => VarMethod::Historical,
+        };
+        node_params.insert("confidence".to_string(), confidence.to_string());
+        node_params.insert("method".to_string(), method.to_string());
+    }
+
+    if analytic == AnalyticType::Quantile {
+        let quantile = match node_params.get("quantile") {
+            Some(value) => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|q| (0.0..=1.0).contains(q))
+                .ok_or_else(|| {
+                    ApiError::InvalidParameter(format!(
+                        "Quantile must be between 0 and 1: {}",
+                        value
+                    ))
+                })?,
+            None => 0.5,
+        };
+        node_params.insert("quantile".to_string(), quantile.to_string());
+    }
+
+    if analytic == AnalyticType::Garch {
+        let horizon = match node_params.get("horizon") {
+            Some(value) => value.trim().parse::<usize>().ok().filter(|h| *h > 0).ok_or_else(|| {
+                ApiError::InvalidParameter(format!("Invalid forecast horizon: {}", value))
+            })?,
+            None => 1,
+        };
+        node_params.insert("horizon".to_string(), horizon.to_string());
+    }
+
+    if analytic == AnalyticType::Pca {
+        let components = crate::analytics::pca::parse_components(
+            node_params.get("components").map(String::as_str),
+            assets.len(),
+        )
+        .map_err(ApiError::InvalidParameter)?;
+        node_params.insert("components".to_string(), components.to_string());
+    }
+
+    if matches!(
+        analytic,
+        AnalyticType::CorrelationMatrix | AnalyticType::CovarianceMatrix
+    ) {
+        use crate::analytics::matrix::{parse_lambda, MatrixEstimator};
+
+        let estimator = match node_params.get("estimator") {
+            Some(value) => MatrixEstimator::parse(value).ok_or_else(|| {
+                ApiError::InvalidParameter(format!("Unknown covariance estimator: {}", value))
+            })?,
+            None => MatrixEstimator::Sample,
+        };
+        node_params.insert("estimator".to_string(), estimator.to_string());
+        // Only EWMA uses a decay, so other estimators share nodes whatever it is
+        if estimator == MatrixEstimator::Ewma {
+            let lambda = parse_lambda(node_params.get("lambda").map(String::as_str))
+                .map_err(ApiError::InvalidParameter)?;
+            node_params.insert("lambda".to_string(), lambda.to_string());
+        } else {
+            node_params.remove("lambda");
+        }
+    }
+
+    if analytic == AnalyticType::Expression {
+        use crate::analytics::expression::Formula;
+
+        let text = node_params.get("expression").ok_or_else(|| {
+            ApiError::InvalidParameter("expression requires an 'expression' parameter".to_string())
+        })?;
+        // Keyed by the canonical formula, so spacing and aliases share a node
+        let formula = Formula::parse(text).map_err(|e| {
+            ApiError::InvalidParameter(format!("Invalid expression '{}': {}", text, e))
+        })?;
+        node_params.insert("expression".to_string(), formula.to_string());
+    }
+
+    if analytic == AnalyticType::Regression {
+        let factors = regression_factors(&node_params)?;
+        node_params.insert(
+            "factors".to_string(),
+            factors
+                .iter()
+                .map(|factor| factor.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    if analytic == AnalyticType::Macd {
+        use crate::analytics::indicators::MacdOutput;
+
+        let output = node_params.get("output").map(String::as_str).unwrap_or("line");
+        let output = MacdOutput::parse(output).ok_or_else(|| {
+            ApiError::InvalidParameter(format!("Unknown MACD output: {}", output))
+        })?;
+        node_params.insert("output".to_string(), output.name().to_string());
+        for (name, default) in [("fast", 12), ("slow", 26), ("signal", 9)] {
+            let period = match node_params.get(name) {
+                Some(value) => value.parse::<usize>().ok().filter(|p| *p > 0).ok_or_else(|| {
+                    ApiError::InvalidParameter(format!("Invalid MACD {} period: {}", name, value))
+                })?,
+                None => default,
+            };
+            node_params.insert(name.to_string(), period.to_string());
+        }
+    }
+
+    if analytic == AnalyticType::BollingerBands {
+        use crate::analytics::indicators::BandOutput;
+
+        let output = node_params.get("output").map(String::as_str).unwrap_or("middle");
+        let output = BandOutput::parse(output).ok_or_else(|| {
+            ApiError::InvalidParameter(format!("Unknown Bollinger band: {}", output))
+        })?;
+        node_params.insert("output".to_string(), output.name().to_string());
+    }
+
+    if analytic == AnalyticType::FxConversion {
+        let reporting = node_params.get("reporting_currency").ok_or_else(|| {
+            ApiError::InvalidParameter("fx_conversion requires reporting_currency".to_string())
+        })?;
+        let native = node_params.get("currency").ok_or_else(|| {
+            ApiError::InvalidParameter(format!("No currency known for asset {}", asset))
+        })?;
+        let invert = node_params.get("fx_invert").map(|v| v == "true").unwrap_or(false);
+        let (pair, _) = crate::fx::conversion_pair(native, reporting, invert)
+            .map_err(|e| ApiError::InvalidParameter(format!("Invalid currency: {}", e)))?
+            .ok_or_else(|| {
+                ApiError::InvalidParameter(format!(
+                    "Asset {} is already quoted in {}",
+                    asset, reporting
+                ))
+            })?;
+        assets.push(pair);
+    }
+
+    if let Some(tag) = &override_tag {
+        node_params.insert("override".to_string(), tag.clone());
+    }
+
+    Ok(NodeKey {
+        analytic,
+        assets,
+        range: Some(date_range.clone()),
+        window: window_spec,
+        override_tag,
+        params: node_params,
+    })
+}
+
+/// Window used when a windowed analytic's query does not set one.
+fn default_window(analytic: AnalyticType) -> usize {
+    match analytic {
+        AnalyticType::Sma | AnalyticType::BollingerBands => 20,
+        AnalyticType::Rsi | AnalyticType::Atr => 14,
+        AnalyticType::Garch => 250,
+        AnalyticType::Pca | AnalyticType::CorrelationMatrix | AnalyticType::CovarianceMatrix => 60,
+        _ => 10,
+    }
+}
+
+/// Analytic computed by `GET /portfolio/{type}`, if it can run on a portfolio.
+fn portfolio_analytic(name: &str) -> Option<AnalyticType> {
+    match name.to_lowercase().as_str() {
+        "nav" | "portfolio_nav" => Some(AnalyticType::PortfolioNav),
+        "returns" | "portfolio_returns" => Some(AnalyticType::PortfolioReturns),
+        "volatility" => Some(AnalyticType::Volatility),
+        "downside_deviation" => Some(AnalyticType::DownsideDeviation),
+        "drawdown" => Some(AnalyticType::Drawdown),
+        "max_drawdown" => Some(AnalyticType::MaxDrawdown),
+        "time_under_water" => Some(AnalyticType::TimeUnderWater),
+        "sharpe" | "sharpe_ratio" => Some(AnalyticType::SharpeRatio),
+        "sortino" | "sortino_ratio" => Some(AnalyticType::SortinoRatio),
+        "calmar" | "calmar_ratio" => Some(AnalyticType::CalmarRatio),
+        "information_ratio" => Some(AnalyticType::InformationRatio),
+        "var" | "value_at_risk" => Some(AnalyticType::ValueAtRisk),
+        "es" | "expected_shortfall" => Some(AnalyticType::ExpectedShortfall),
+        "skewness" | "kurtosis" | "quantile" | "percentile_rank" | "z_score" | "regression"
+        | "garch" => {
+            Some(AnalyticType::from_str(name))
+        }
+        _ => None,
+    }
+}
+
+/// Builds the key of an analytic over a portfolio of `assets`.
+///
+/// Weights and rebalance frequency are validated and normalized so that equal
+/// portfolios share DAG nodes however they were spelled in the request.
+fn build_portfolio_key(
+    assets: &[AssetKey],
+    analytic: AnalyticType,
+    date_range: &DateRange,
+    params: &HashMap<String, String>,
+    override_tag: Option<String>,
+) -> Result<NodeKey, ApiError> {
+    use crate::analytics::portfolio::{parse_weights, RebalanceFrequency};
+
+    let first = assets
+        .first()
+        .ok_or_else(|| ApiError::InvalidParameter("Portfolio requires assets".to_string()))?;
+
+    let weights = parse_weights(params.get("weights").map(String::as_str), assets.len())
+        .map_err(ApiError::InvalidParameter)?;
+    let rebalance = match params.get("rebalance") {
+        Some(value) => RebalanceFrequency::parse(value).ok_or_else(|| {
+            ApiError::InvalidParameter(format!("Unknown rebalance frequency: {}", value))
+        })?,
+        None => RebalanceFrequency::None,
+    };
+
+    let mut params = params.clone();
+    params.insert(
+        "weights".to_string(),
+        weights
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    params.insert("rebalance".to_string(), rebalance.to_string());
+
+    let mut key = build_node_key(first, analytic, date_range, &params, override_tag)?;
+    key.assets = assets.to_vec();
+    Ok(key)
+}
+
+/// Assets an analytic query spans: the path asset plus, for pairwise analytics,
+/// the asset named by the `with` parameter.
+fn related_assets(
+    asset: &AssetKey,
+    analytic: AnalyticType,
+    params: &HashMap<String, String>,
+) -> Result<Vec<AssetKey>, ApiError> {
+    let mut assets = vec![asset.clone()];
+    if analytic == AnalyticType::Correlation {
+        let other = params.get("with").ok_or_else(|| {
+            ApiError::InvalidParameter("correlation requires a 'with' asset".to_string())
+        })?;
+        let other = AssetKey::parse(other)
+            .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
+        assets.push(other);
+    }
+    if matches!(
+        analytic,
+        AnalyticType::Pca | AnalyticType::CorrelationMatrix | AnalyticType::CovarianceMatrix
+    ) {
+        let others = params.get("with").ok_or_else(|| {
+            ApiError::InvalidParameter(format!("{} requires 'with' assets", analytic))
+        })?;
+        for other in others.split(',').map(str::trim) {
+            let other = AssetKey::parse(other)
+                .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
+            if assets.contains(&other) {
+                return Err(ApiError::InvalidParameter(format!(
+                    "Asset {} is listed twice",
+                    other
+                )));
+            }
+            assets.push(other);
+        }
+    }
+    Ok(assets)
+}
+
+/// Factor assets of a regression, named by the `factors` parameter.
+fn regression_factors(params: &HashMap<String, String>) -> Result<Vec<AssetKey>, ApiError> {
+    crate::analytics::regression::parse_factors(params.get("factors").map(String::as_str))
+        .map_err(ApiError::InvalidParameter)
+}
+
+/// Assets an analytic measures against: the `benchmark` of an information
+/// ratio or the `factors` of a regression.
+fn reference_assets(
+    analytic: AnalyticType,
+    params: &HashMap<String, String>,
+) -> Result<Vec<AssetKey>, ApiError> {
+    match analytic {
+        AnalyticType::InformationRatio => {
+            let benchmark = params.get("benchmark").ok_or_else(|| {
+                ApiError::InvalidParameter(
+                    "information_ratio requires a 'benchmark' asset".to_string(),
+                )
+            })?;
+            AssetKey::parse(benchmark)
+                .map(|benchmark| vec![benchmark])
+                .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))
+        }
+        AnalyticType::Regression => regression_factors(params),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Assets whose prices feed a query, in the order their currency entries are
+/// listed: the assets the node spans, then any benchmark or factors.
+fn currency_assets(
+    asset: &AssetKey,
+    analytic: AnalyticType,
+    params: &HashMap<String, String>,
+) -> Result<Vec<AssetKey>, ApiError> {
+    let mut assets = related_assets(asset, analytic, params)?;
+    assets.extend(reference_assets(analytic, params)?);
+    Ok(assets)
+}
+
+/// Resolves `reporting_currency` into the per-asset conversion parameters.
+///
+/// Looks up each asset's native currency from stored metadata and picks the FX
+/// pair to convert with: the direct `<native>/<reporting>` pair when it has data
+/// in the range, otherwise the inverse pair (flagged with `fx_invert`).
+fn resolve_currency_params(
+    provider: &SqliteDataProvider,
+    assets: &[AssetKey],
+    date_range: &DateRange,
+    params: &mut HashMap<String, String>,
+) -> Result<(), ApiError> {
+    let reporting = match params.get("reporting_currency") {
+        Some(currency) => currency.to_uppercase(),
+        None => return Ok(()),
+    };
+
+    let mut currencies = Vec::with_capacity(assets.len());
+    let mut inverts = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let native = provider.asset_currency(asset).map_err(|e| match e {
+            DataProviderError::AssetNotFound => ApiError::InvalidParameter(format!(
+                "No currency metadata stored for asset {}",
+                asset
+            )),
+            other => ApiError::InternalError(other.to_string()),
+        })?;
+
+        let invert = match crate::fx::conversion_pair(&native, &reporting, false)
+            .map_err(|e| ApiError::InvalidParameter(format!("Invalid currency: {}", e)))?
+        {
+            Some((direct, _)) => provider
+                .get_time_series(&direct, date_range)
+                .map(|points| points.is_empty())
+                .unwrap_or(true),
+            None => false,
+        };
+
+        currencies.push(native);
+        inverts.push(invert.to_string());
+    }
+
+    params.insert("reporting_currency".to_string(), reporting);
+    params.insert("currency".to_string(), currencies.join(","));
+    params.insert("fx_invert".to_string(), inverts.join(","));
+    Ok(())
+}
+
+/// Helper function to build analytics DAG
+fn build_analytics_dag(
+    registry: &Arc<AnalyticRegistry>,
+    asset: &AssetKey,
+    analytic_type: &str,
+    date_range: &DateRange,
+    params: &HashMap<String, String>,
+    override_tag: Option<String>,
+) -> Result<(AnalyticsDag, NodeId, NodeKey), ApiError> {
+    let analytic = registry.analytic_type(analytic_type);
+
+    if registry.definition(analytic).is_none() {
+        return Err(ApiError::InvalidParameter(format!(
+            "Unknown analytic type: {}",
+            analytic_type
+        )));
+    }
+
+    let node_key = build_node_key(asset, analytic, date_range, params, override_tag)?;
+    let node_key = registry
+        .apply_schema(node_key)
+        .map_err(|e| ApiError::InvalidParameter(e.to_string()))?;
+
+    let mut dag = AnalyticsDag::new_with_registry(registry.clone());
+    let target_node = dag
+        .resolve_node(node_key.clone())
+        .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
+
+    Ok((dag, target_node, node_key))
+}
+
+/// Query parameters for analytics endpoint
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQueryParams {
+    pub start: String,
+    pub end: String,
+    pub window: Option<usize>,
+    #[serde(rename = "override")]
+    pub override_tag: Option<String>,
+    /// Handling of prices flagged as bad prints: "include", "exclude" or "ffill"
+    pub flagged: Option<String>,
+    /// Knowledge time to read prices at: RFC 3339 timestamp, or a date for
+    /// its end
+    pub as_of: Option<String>,
+}
+
+/// Single data point in analytics response
+#[derive(Debug, Serialize)]
+pub struct AnalyticDataPoint {
+    pub timestamp: String,
+    pub value: Option<f64>,
+}
+
+/// Response for analytics query
+#[derive(Debug, Serialize)]
+pub struct AnalyticsResponse {
+    pub asset: String,
+    pub analytic: String,
+    pub parameters: HashMap<String, String>,
+    pub start_date: String,
+    pub end_date: String,
+    /// Selected output (the primary output unless `output` names another)
+    pub data: Vec<AnalyticDataPoint>,
+    /// Every output of a multi-output analytic, keyed by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<BTreeMap<String, Vec<AnalyticDataPoint>>>,
+}
+
+fn data_points(points: &[TimeSeriesPoint]) -> Vec<AnalyticDataPoint> {
+    points
+        .iter()
+        .map(|point| AnalyticDataPoint {
+            timestamp: point.timestamp.to_rfc3339(),
+            value: if point.close_price.is_nan() {
+                None
+            } else {
+                Some(point.close_price)
+            },
+        })
+        .collect()
+}
+
+/// Named outputs of a pull-mode query, keyed by name
+type OutputPoints = BTreeMap<String, Vec<AnalyticDataPoint>>;
+
+/// Executes a pull-mode query for `node`, returning the series selected by the
+/// key's `output` param and, for multi-output analytics, every output by name.
+fn pull_analytic(
+    dag: &AnalyticsDag,
+    node: NodeId,
+    key: &NodeKey,
+    date_range: DateRange,
+    provider: &dyn DataProvider,
+) -> Result<(Vec<AnalyticDataPoint>, Option<OutputPoints>), ApiError> {
+    let declared = dag.node_outputs(node);
+    let Some(primary) = declared.first() else {
+        let result = dag
+            .execute_pull_mode(node, date_range, provider)
+            .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
+        return Ok((data_points(&result), None));
+    };
+
+    let outputs = dag
+        .execute_pull_mode_outputs(node, date_range, provider)
+        .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
+    let (_, selected) = dag
+        .split_output(key.clone())
+        .map_err(|e| ApiError::InvalidParameter(e.to_string()))?;
+    let selected = selected.unwrap_or_else(|| primary.to_string());
+    let data = outputs
+        .get(&selected)
+        .map(|points| data_points(points))
+        .unwrap_or_default();
+    let outputs = outputs
+        .iter()
+        .map(|(name, points)| (name.clone(), data_points(points)))
+        .collect();
+
+    Ok((data, Some(outputs)))
+}
+
+/// GET /analytics/{asset}/{type} - Execute pull-mode analytics query
+pub async fn get_analytics(
+    State(state): State<Arc<AppState>>,
+    Path((asset, analytic_type)): Path<(String, String)>,
+    Query(query_params): Query<AnalyticsQueryParams>,
+    Query(extra_params): Query<HashMap<String, String>>,
+) -> Result<Json<AnalyticsResponse>, ApiError> {
+    // Parse dates
+    let start_date = NaiveDate::parse_from_str(&query_params.start, "%Y-%m-%d")
+        .map_err(|e| ApiError::InvalidDateRange(format!("Invalid start date: {}", e)))?;
+    let end_date = NaiveDate::parse_from_str(&query_params.end, "%Y-%m-%d")
+        .map_err(|e| ApiError::InvalidDateRange(format!("Invalid end date: {}", e)))?;
+
+    // Validate date range
+    if start_date > end_date {
+        return Err(ApiError::InvalidDateRange(
+            "Start date must be before or equal to end date".to_string(),
+        ));
+    }
+
+    // Create asset key
+    let asset_key = AssetKey::parse(&asset)
+        .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
+
+    // Build parameters map
+    let mut params = HashMap::new();
+    if let Some(window) = query_params.window {
+        params.insert("window".to_string(), window.to_string());
+    }
+    if let Some(tag) = &query_params.override_tag {
+        params.insert("override".to_string(), tag.clone());
+    }
+    // Other parameters must be declared by the analytic
+    let analytic = state.registry.analytic_type(&analytic_type);
+    let declared: Vec<String> = analytic_catalog(&state.registry)
+        .into_iter()
+        .filter(|info| state.registry.analytic_type(&info.analytic_type) == analytic)
+        .flat_map(|info| info.parameters)
+        .chain(missing_data_parameters())
+        .map(|parameter| parameter.name)
+        .collect();
+    for (key, value) in extra_params {
+        if matches!(
+            key.as_str(),
+            "start" | "end" | "window" | "override" | "flagged" | "as_of"
+        ) {
+            continue;
+        }
+        // `window_size` is the older spelling of `window`
+        let name = if key == "window_size" {
+            "window"
+        } else {
+            key.as_str()
+        };
+        if !declared.iter().any(|declared| declared == name) {
+            return Err(ApiError::InvalidParameter(format!(
+                "Unknown parameter '{}' for {}",
+                key, analytic_type
+            )));
+        }
+        params.insert(key, value);
+    }
+    let handling = flagged_points(query_params.flagged.as_deref())?;
+    let as_of = as_of_time(query_params.as_of.as_deref())?;
+
+    // Create date range
+    let date_range = DateRange::new(start_date, end_date);
+
+    // Resolve currency conversion against stored asset metadata
+    let provider = state.data_provider.lock().await;
+    let assets = currency_assets(&asset_key, analytic, &params)?;
+    resolve_currency_params(&provider, &assets, &date_range, &mut params)?;
+
+    // Build DAG
+    let (dag, target_node, node_key) = build_analytics_dag(
+        &state.registry,
+        &asset_key,
+        &analytic_type,
+        &date_range,
+        &params,
+        query_params.override_tag.clone(),
+    )?;
+
+    // Execute pull-mode query
+    let provider = QualityFilteredProvider::new(&provider, handling);
+    let provider = AsOfProvider::new(&provider, as_of);
+    let (data, outputs) = pull_analytic(&dag, target_node, &node_key, date_range, &provider)?;
+
+    Ok(Json(AnalyticsResponse {
+        asset,
+        analytic: analytic_type,
+        parameters: params,
+        start_date: query_params.start,
+        end_date: query_params.end,
+        data,
+        outputs,
+    }))
+}
+
+/// Query parameters for portfolio analytics endpoint
+#[derive(Debug, Deserialize)]
+pub struct PortfolioQueryParams {
+    pub assets: String,
+    pub start: String,
+    pub end: String,
+    pub weights: Option<String>,
+    pub rebalance: Option<String>,
+    pub window: Option<usize>,
+    pub reporting_currency: Option<String>,
+    pub risk_free: Option<String>,
+    pub benchmark: Option<String>,
+    pub periods_per_year: Option<f64>,
+    pub confidence: Option<f64>,
+    pub method: Option<String>,
+    pub quantile: Option<f64>,
+    pub factors: Option<String>,
+    pub horizon: Option<usize>,
+    pub output: Option<String>,
+    #[serde(rename = "override")]
+    pub override_tag: Option<String>,
+    pub as_of: Option<String>,
+}
+
+/// GET /portfolio/{type} - Execute pull-mode analytics on a weighted portfolio
+pub async fn get_portfolio_analytics(
+    State(state): State<Arc<AppState>>,
+    Path(analytic_type): Path<String>,
+    Query(query_params): Query<PortfolioQueryParams>,
+) -> Result<Json<AnalyticsResponse>, ApiError> {
+    let analytic = portfolio_analytic(&analytic_type).ok_or_else(|| {
+        ApiError::InvalidParameter(format!(
+            "Analytic {} is not available for portfolios",
+            analytic_type
+        ))
+    })?;
+
+    // Parse dates
+    let start_date = NaiveDate::parse_from_str(&query_params.start, "%Y-%m-%d")
+        .map_err(|e| ApiError::InvalidDateRange(format!("Invalid start date: {}", e)))?;
+    let end_date = NaiveDate::parse_from_str(&query_params.end, "%Y-%m-%d")
+        .map_err(|e| ApiError::InvalidDateRange(format!("Invalid end date: {}", e)))?;
+    if start_date > end_date {
+        return Err(ApiError::InvalidDateRange(
+            "Start date must be before or equal to end date".to_string(),
+        ));
+    }
+    let date_range = DateRange::new(start_date, end_date);
+    let as_of = as_of_time(query_params.as_of.as_deref())?;
+
+    // Parse constituents
+    let assets = query_params
+        .assets
+        .split(',')
+        .map(|symbol| {
+            AssetKey::parse(symbol)
+                .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Build parameters map
+    let mut params = HashMap::new();
+    if let Some(weights) = &query_params.weights {
+        params.insert("weights".to_string(), weights.clone());
+    }
+    if let Some(rebalance) = &query_params.rebalance {
+        params.insert("rebalance".to_string(), rebalance.clone());
+    }
+    if let Some(window) = query_params.window {
+        params.insert("window".to_string(), window.to_string());
+    }
+    if let Some(currency) = &query_params.reporting_currency {
+        params.insert("reporting_currency".to_string(), currency.clone());
+    }
+    if let Some(risk_free) = &query_params.risk_free {
+        params.insert("risk_free".to_string(), risk_free.clone());
+    }
+    if let Some(benchmark) = &query_params.benchmark {
+        params.insert("benchmark".to_string(), benchmark.clone());
+    }
+    if let Some(periods) = query_params.periods_per_year {
+        params.insert("periods_per_year".to_string(), periods.to_string());
+    }
+    if let Some(confidence) = query_params.confidence {
+        params.insert("confidence".to_string(), confidence.to_string());
+    }
+    if let Some(method) = &query_params.method {
+        params.insert("method".to_string(), method.clone());
+    }
+    if let Some(quantile) = query_params.quantile {
+        params.insert("quantile".to_string(), quantile.to_string());
+    }
+    if let Some(factors) = &query_params.factors {
+        params.insert("factors".to_string(), factors.clone());
+    }
+    if let Some(horizon) = query_params.horizon {
+        params.insert("horizon".to_string(), horizon.to_string());
+    }
+    if let Some(output) = &query_params.output {
+        params.insert("output".to_string(), output.clone());
+    }
+
+    // Resolve currency conversion against stored asset metadata
+    let provider = state.data_provider.lock().await;
+    let mut priced_assets = assets.clone();
+    priced_assets.extend(reference_assets(analytic, &params)?);
+    resolve_currency_params(&provider, &priced_assets, &date_range, &mut params)?;
+
+    let node_key = build_portfolio_key(
+        &assets,
+        analytic,
+        &date_range,
+        &params,
+        query_params.override_tag.clone(),
+    )?;
+    let mut dag = AnalyticsDag::new_with_registry(state.registry.clone());
+    let target_node = dag
+        .resolve_node(node_key.clone())
+        .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
+
+    // Execute pull-mode query
+    let provider = AsOfProvider::new(&*provider, as_of);
+    let (data, outputs) = pull_analytic(&dag, target_node, &node_key, date_range, &provider)?;
+
+    Ok(Json(AnalyticsResponse {
+        asset: query_params.assets,
+        analytic: analytic_type,
+        parameters: node_key.params,
+        start_date: query_params.start,
+        end_date: query_params.end,
+        data,
+        outputs,
+    }))
+}
+
+/// Query parameters for the matrix endpoint
+#[derive(Debug, Deserialize)]
+pub struct MatrixQueryParams {
+    pub assets: String,
+    /// "correlation" (default) or "covariance"
+    #[serde(rename = "type")]
+    pub matrix_type: Option<String>,
+    /// Single matrix as of this date, unless `start` and `end` ask for a series
+    pub as_of: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub window: Option<usize>,
+    pub estimator: Option<String>,
+    pub lambda: Option<f64>,
+    pub reporting_currency: Option<String>,
+    #[serde(rename = "override")]
+    pub override_tag: Option<String>,
+}
+
+/// One matrix of a matrix query
+#[derive(Debug, Serialize)]
+pub struct MatrixPoint {
+    pub timestamp: String,
+    /// Dates in the estimation window
+    pub observations: usize,
+    /// Rows and columns follow `assets`; null until the window is full
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+/// Response for matrix query
+#[derive(Debug, Serialize)]
+pub struct MatrixResponse {
+    pub matrix_type: String,
+    pub assets: Vec<String>,
+    pub parameters: HashMap<String, String>,
+    pub start_date: String,
+    pub end_date: String,
+    pub matrices: Vec<MatrixPoint>,
+}
+
+/// Start of the range holding the last `window` returns up to `as_of` of every
+/// asset, however long ago they were; `as_of` itself if no asset has a price.
+fn matrix_as_of_start(
+    provider: &SqliteDataProvider,
+    assets: &[AssetKey],
+    as_of: NaiveDate,
+    window: usize,
+) -> Result<NaiveDate, ApiError> {
+    let history = DateRange::new(NaiveDate::MIN, as_of);
+    let mut start: Option<NaiveDate> = None;
+    for asset in assets {
+        let dates = provider
+            .available_dates(asset, &history)
+            .map_err(|e| match e {
+                DataProviderError::AssetNotFound => ApiError::AssetNotFound(asset.as_string()),
+                other => ApiError::InternalError(other.to_string()),
+            })?;
+        // The first return of the window needs the price before it
+        if let Some(first) = dates.get(dates.len().saturating_sub(window + 1)) {
+            let first = first.date_naive();
+            start = Some(start.map_or(first, |start| start.min(first)));
+        }
+    }
+    Ok(start.unwrap_or(as_of))
+}
+
+/// Reassembles the matrices of a matrix node from its named outputs.
+fn matrix_points(
+    assets: &[AssetKey],
+    outputs: &BTreeMap<String, Vec<TimeSeriesPoint>>,
+) -> Vec<MatrixPoint> {
+    use crate::analytics::matrix::{entry_output, entry_positions, OBSERVATIONS};
+
+    let Some(observations) = outputs.get(OBSERVATIONS) else {
+        return Vec::new();
+    };
+    observations
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            let mut values = vec![vec![None; assets.len()]; assets.len()];
+            for (i, j) in entry_positions(assets.len()) {
+                let value = outputs
+                    .get(&entry_output(&assets[i], &assets[j]))
+                    .and_then(|series| series.get(index))
+                    .map(|point| point.close_price)
+                    .filter(|value| !value.is_nan());
+                values[i][j] = value;
+                values[j][i] = value;
+            }
+            MatrixPoint {
+                timestamp: point.timestamp.to_rfc3339(),
+                observations: point.close_price as usize,
+                values,
+            }
+        })
+        .collect()
 }
 
-/// GET /analytics/{asset}/{type} - Execute pull-mode analytics query
-pub async fn get_analytics(
+/// GET /analytics/matrix - Correlation or covariance matrix of several assets
+pub async fn get_matrix(
     State(state): State<Arc<AppState>>,
-    Path((asset, analytic_type)): Path<(String, String)>,
-    Query(query_params): Query<AnalyticsQueryParams>,
-) -> Result<Json<AnalyticsResponse>, ApiError> {
-    // Parse dates
-    let start_date = NaiveDate::parse_from_str(&query_params.start, "%Y-%m-%d")
-        .map_err(|e| ApiError::InvalidDateRange(format!("Invalid start date: {}", e)))?;
-    let end_date = NaiveDate::parse_from_str(&query_params.end, "%Y-%m-%d")
-        .map_err(|e| ApiError::InvalidDateRange(format!("Invalid end date: {}", e)))?;
+    Query(query_params): Query<MatrixQueryParams>,
+) -> Result<Json<MatrixResponse>, ApiError> {
+    use crate::analytics::matrix::MatrixKind;
+
+    let kind = match &query_params.matrix_type {
+        Some(value) => MatrixKind::parse(value).ok_or_else(|| {
+            ApiError::InvalidParameter(format!("Unknown matrix type: {}", value))
+        })?,
+        None => MatrixKind::Correlation,
+    };
+    let analytic = match kind {
+        MatrixKind::Correlation => AnalyticType::CorrelationMatrix,
+        MatrixKind::Covariance => AnalyticType::CovarianceMatrix,
+    };
 
-    // Validate date range
-    if start_date > end_date {
-        return Err(ApiError::InvalidDateRange(
-            "Start date must be before or equal to end date".to_string(),
-        ));
-    }
+    // Either one date or a range
+    let parse_date = |value: &str, label: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|e| ApiError::InvalidDateRange(format!("Invalid {} date: {}", label, e)))
+    };
+    let (date_range, as_of) = match (&query_params.as_of, &query_params.start, &query_params.end) {
+        // Extended back over the latest prices once the assets are known
+        (Some(as_of), None, None) => {
+            let as_of = parse_date(as_of, "as_of")?;
+            (DateRange::new(as_of, as_of), true)
+        }
+        (None, Some(start), Some(end)) => {
+            let (start, end) = (parse_date(start, "start")?, parse_date(end, "end")?);
+            if start > end {
+                return Err(ApiError::InvalidDateRange(
+                    "Start date must be before or equal to end date".to_string(),
+                ));
+            }
+            (DateRange::new(start, end), false)
+        }
+        _ => {
+            return Err(ApiError::InvalidDateRange(
+                "Provide either as_of or both start and end".to_string(),
+            ))
+        }
+    };
 
-    // Create asset key
-    let asset_key = AssetKey::new_equity(&asset)
-        .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
+    // Parse assets; the first anchors the key and the rest are its `with` assets
+    let assets = query_params
+        .assets
+        .split(',')
+        .map(|symbol| {
+            AssetKey::parse(symbol)
+                .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let (first, others) = match assets.split_first() {
+        Some((first, others)) if !others.is_empty() => (first, others),
+        _ => {
+            return Err(ApiError::InvalidParameter(
+                "A matrix requires at least two assets".to_string(),
+            ))
+        }
+    };
 
-    // Build parameters map
     let mut params = HashMap::new();
+    params.insert(
+        "with".to_string(),
+        others
+            .iter()
+            .map(|asset| asset.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    );
     if let Some(window) = query_params.window {
         params.insert("window".to_string(), window.to_string());
     }
-    if let Some(tag) = &query_params.override_tag {
-        params.insert("override".to_string(), tag.clone());
+    if let Some(estimator) = &query_params.estimator {
+        params.insert("estimator".to_string(), estimator.clone());
+    }
+    if let Some(lambda) = query_params.lambda {
+        params.insert("lambda".to_string(), lambda.to_string());
+    }
+    if let Some(currency) = &query_params.reporting_currency {
+        params.insert("reporting_currency".to_string(), currency.clone());
     }
 
-    // Create date range
-    let date_range = DateRange::new(start_date, end_date);
+    let provider = state.data_provider.lock().await;
+    let date_range = if as_of {
+        let window = query_params.window.unwrap_or(default_window(analytic));
+        let start = matrix_as_of_start(&provider, &assets, date_range.end, window)?;
+        DateRange::new(start, date_range.end)
+    } else {
+        date_range
+    };
 
-    // Build DAG
-    let (dag, target_node, _) = build_analytics_dag(
-        &asset_key,
-        &analytic_type,
+    // Resolve currency conversion against stored asset metadata
+    resolve_currency_params(&provider, &assets, &date_range, &mut params)?;
+
+    let node_key = build_node_key(
+        first,
+        analytic,
         &date_range,
         &params,
         query_params.override_tag.clone(),
     )?;
-
-    // Execute pull-mode query
-    let provider = state.data_provider.lock().await;
-    let result = dag
-        .execute_pull_mode(target_node, date_range, &*provider)
+    let mut dag = AnalyticsDag::new_with_registry(state.registry.clone());
+    let target_node = dag
+        .resolve_node(node_key.clone())
+        .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
+    let outputs = dag
+        .execute_pull_mode_outputs(target_node, date_range.clone(), &*provider)
         .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
 
-    // Convert to response format
-    let data: Vec<AnalyticDataPoint> = result
-        .iter()
-        .map(|point| AnalyticDataPoint {
-            timestamp: point.timestamp.to_rfc3339(),
-            value: if point.close_price.is_nan() {
-                None
-            } else {
-                Some(point.close_price)
-            },
-        })
-        .collect();
+    let mut matrices = matrix_points(&node_key.assets, &outputs);
+    if as_of {
+        matrices = matrices.pop().into_iter().collect();
+    }
 
-    Ok(Json(AnalyticsResponse {
-        asset,
-        analytic: analytic_type,
-        parameters: params,
-        start_date: query_params.start,
-        end_date: query_params.end,
-        data,
+    Ok(Json(MatrixResponse {
+        matrix_type: kind.to_string(),
+        assets: node_key.assets.iter().map(|asset| asset.to_string()).collect(),
+        parameters: node_key.params,
+        start_date: date_range.start.to_string(),
+        end_date: date_range.end.to_string(),
+        matrices,
     }))
 }
 
@@ -472,6 +2633,8 @@ pub struct BatchQuery {
     #[serde(rename = "override")]
     #[serde(default)]
     pub override_tag: Option<String>,
+    #[serde(default)]
+    pub as_of: Option<String>,
 }
 
 /// Response for batch query
@@ -524,11 +2687,12 @@ async fn execute_single_batch_query(
         .map_err(|e| ApiError::InvalidDateRange(format!("Invalid end date: {}", e)))?;
 
     // Create asset key
-    let asset_key = AssetKey::new_equity(&query.asset)
+    let asset_key = AssetKey::parse(&query.asset)
         .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset: {}", e)))?;
 
     // Create date range
     let date_range = DateRange::new(start_date, end_date);
+    let as_of = as_of_time(query.as_of.as_deref())?;
 
     // Prepare parameters (include override tag)
     let mut params = query.parameters.clone();
@@ -536,8 +2700,15 @@ async fn execute_single_batch_query(
         params.insert("override".to_string(), tag.clone());
     }
 
+    // Resolve currency conversion against stored asset metadata
+    let provider = state.data_provider.lock().await;
+    let analytic = state.registry.analytic_type(&query.analytic);
+    let assets = currency_assets(&asset_key, analytic, &params)?;
+    resolve_currency_params(&provider, &assets, &date_range, &mut params)?;
+
     // Build DAG
-    let (dag, target_node, _) = build_analytics_dag(
+    let (dag, target_node, node_key) = build_analytics_dag(
+        &state.registry,
         &asset_key,
         &query.analytic,
         &date_range,
@@ -546,23 +2717,8 @@ async fn execute_single_batch_query(
     )?;
 
     // Execute pull-mode query
-    let provider = state.data_provider.lock().await;
-    let result = dag
-        .execute_pull_mode(target_node, date_range, &*provider)
-        .map_err(|e| ApiError::ComputationFailed(e.to_string()))?;
-
-    // Convert to response format
-    let data: Vec<AnalyticDataPoint> = result
-        .iter()
-        .map(|point| AnalyticDataPoint {
-            timestamp: point.timestamp.to_rfc3339(),
-            value: if point.close_price.is_nan() {
-                None
-            } else {
-                Some(point.close_price)
-            },
-        })
-        .collect();
+    let provider = AsOfProvider::new(&*provider, as_of);
+    let (data, outputs) = pull_analytic(&dag, target_node, &node_key, date_range, &provider)?;
 
     Ok(AnalyticsResponse {
         asset: query.asset,
@@ -571,6 +2727,7 @@ async fn execute_single_batch_query(
         start_date: query.start_date,
         end_date: query.end_date,
         data,
+        outputs,
     })
 }
 
@@ -636,7 +2793,7 @@ pub async fn create_replay_session(
         .assets
         .iter()
         .map(|a| {
-            AssetKey::new_equity(a)
+            AssetKey::parse(a)
                 .map_err(|e| ApiError::InvalidParameter(format!("Invalid asset {}: {}", a, e)))
         })
         .collect();
@@ -813,8 +2970,24 @@ pub async fn handle_stream(
                     params.insert("override".to_string(), tag.clone());
                 }
 
+                // Resolve currency conversion against stored asset metadata
+                let provider = state_clone.data_provider.lock().await;
+                let currency_result = currency_assets(
+                    asset_key,
+                    state_clone.registry.analytic_type(&analytic.analytic_type),
+                    &params,
+                )
+                .and_then(|assets| {
+                    resolve_currency_params(&provider, &assets, &replay_range, &mut params)
+                });
+                if let Err(e) = currency_result {
+                    tracing::error!("Replay: Failed to resolve currencies: {}", e);
+                    continue;
+                }
+
                 // Build DAG for this asset and analytic
                 let dag_result = build_analytics_dag(
+                    &state_clone.registry,
                     asset_key,
                     &analytic.analytic_type,
                     &replay_range,
@@ -826,13 +2999,14 @@ pub async fn handle_stream(
                     continue;
                 }
 
-                let (dag, target_node, _) = dag_result.unwrap();
+                let (dag, target_node, node_key) = dag_result.unwrap();
+                let data_assets = dag.data_assets();
+                let selected_output = node_key.params.get("output").cloned();
 
                 // Create push-mode engine
                 let mut push_engine = PushModeEngine::new(dag);
 
                 // Initialize with historical data (for burn-in)
-                let provider = state_clone.data_provider.lock().await;
                 let init_end = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
 
                 if let Err(e) = push_engine.initialize(&*provider, init_end, 50) {
@@ -841,18 +3015,27 @@ pub async fn handle_stream(
                     continue;
                 }
 
-                // Load all data for this asset in the date range
-                use crate::time_series::DataProvider;
+                // Load all data feeding the DAG (the asset plus any FX pairs or
+                // paired assets) and merge it chronologically
                 let date_range = replay_range.clone();
-                let all_data = match (*provider).get_time_series(asset_key, &date_range) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        tracing::error!("Replay: Failed to load data: {}", e);
-                        drop(provider);
-                        continue;
+                let mut all_data = Vec::new();
+                let mut load_failed = false;
+                for data_asset in &data_assets {
+                    match (*provider).get_time_series(data_asset, &date_range) {
+                        Ok(data) => all_data
+                            .extend(data.into_iter().map(|point| (data_asset.clone(), point))),
+                        Err(e) => {
+                            tracing::error!("Replay: Failed to load data: {}", e);
+                            load_failed = true;
+                            break;
+                        }
                     }
-                };
+                }
                 drop(provider);
+                if load_failed {
+                    continue;
+                }
+                all_data.sort_by_key(|(_, point)| point.timestamp);
 
                 tracing::info!(
                     "Replay: Loaded {} data points, will stream incrementally",
@@ -912,6 +3095,34 @@ pub async fn handle_stream(
                                     }
                                 }
                             }
+                            NodeOutput::Named(ref values) => {
+                                // Stream the selected output, plus every output by name
+                                let value = selected_output
+                                    .as_ref()
+                                    .and_then(|name| values.iter().find(|(n, _)| n == name))
+                                    .or_else(|| values.first())
+                                    .map(|(_, value)| *value);
+                                if let (Some(ts), Some(value)) = (timestamp, value) {
+                                    if !value.is_nan() {
+                                        let outputs: serde_json::Map<String, Value> = values
+                                            .iter()
+                                            .map(|(name, value)| (name.clone(), json!(value)))
+                                            .collect();
+                                        let _ = tx_clone.send(Ok(Event::default()
+                                            .event("update")
+                                            .data(
+                                                json!({
+                                                    "asset": asset_str,
+                                                    "analytic": analytic_str,
+                                                    "timestamp": ts.to_rfc3339(),
+                                                    "value": value,
+                                                    "outputs": outputs,
+                                                })
+                                                .to_string(),
+                                            )));
+                                    }
+                                }
+                            }
                             _ => {}
                         }
                     }),
@@ -922,7 +3133,7 @@ pub async fn handle_stream(
 
                 // Now feed data incrementally
                 let num_points = all_data.len();
-                for (i, point) in all_data.iter().enumerate() {
+                for (i, (data_asset, point)) in all_data.iter().enumerate() {
                     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
 
                     let progress = (i as f64) / (num_points as f64);
@@ -936,7 +3147,7 @@ pub async fn handle_stream(
 
                     // Push data point - this triggers incremental computation
                     if let Err(e) =
-                        push_engine.push_data(asset_key.clone(), point.timestamp, point.close_price)
+                        push_engine.push_data(data_asset.clone(), point.timestamp, point.close_price)
                     {
                         tracing::error!("Replay: Failed to push data: {}", e);
                     }
@@ -970,7 +3181,7 @@ mod tests {
     use crate::asset_key::AssetKey;
     use crate::dag::AnalyticType;
     use crate::time_series::DateRange;
-    use chrono::NaiveDate;
+    use chrono::{NaiveDate, TimeZone};
     use std::collections::HashMap;
 
     #[test]
@@ -996,4 +3207,702 @@ mod tests {
         assert_ne!(base_key, override_key);
         assert!(override_key.override_tag.as_deref() == Some("arith"));
     }
+
+    #[test]
+    fn build_node_key_normalizes_tail_risk_params() {
+        let asset = AssetKey::new_equity("AAPL").unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let mut params = HashMap::new();
+        params.insert("method".to_string(), "Parametric".to_string());
+
+        let key =
+            build_node_key(&asset, AnalyticType::ValueAtRisk, &range, &params, None).unwrap();
+        assert_eq!(key.params.get("method").map(String::as_str), Some("parametric"));
+        assert_eq!(key.params.get("confidence").map(String::as_str), Some("0.95"));
+
+        params.insert("confidence".to_string(), "95".to_string());
+        assert!(build_node_key(&asset, AnalyticType::ValueAtRisk, &range, &params, None).is_err());
+    }
+
+    #[test]
+    fn build_node_key_rejects_invalid_windows_and_lags() {
+        let asset = AssetKey::new_equity("AAPL").unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let build = |analytic, name: &str, value: &str| {
+            let params = HashMap::from([(name.to_string(), value.to_string())]);
+            build_node_key(&asset, analytic, &range, &params, None)
+        };
+
+        let key = build(AnalyticType::Volatility, "window", " 20").unwrap();
+        assert_eq!(key.window, Some(WindowSpec::fixed(20)));
+        let key = build(AnalyticType::Returns, "lag", "5").unwrap();
+        assert_eq!(key.params.get("lag").map(String::as_str), Some("5"));
+
+        for (name, value) in [("window", "abc"), ("window", "-5"), ("window_size", "0")] {
+            assert!(
+                matches!(
+                    build(AnalyticType::Volatility, name, value),
+                    Err(ApiError::InvalidParameter(_))
+                ),
+                "{name}={value}"
+            );
+        }
+        for value in ["abc", "-1", "0"] {
+            assert!(
+                matches!(
+                    build(AnalyticType::Returns, "lag", value),
+                    Err(ApiError::InvalidParameter(_))
+                ),
+                "lag={value}"
+            );
+        }
+    }
+
+    #[test]
+    fn build_node_key_checks_missing_data_params() {
+        let asset = AssetKey::new_equity("AAPL").unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let mut params = HashMap::new();
+        params.insert("missing_data".to_string(), "Forward_Fill".to_string());
+        params.insert("min_observations".to_string(), " 5".to_string());
+
+        let key = build_node_key(&asset, AnalyticType::Volatility, &range, &params, None).unwrap();
+        let param = |name: &str| key.params.get(name).map(String::as_str);
+        assert_eq!(param("missing_data"), Some("ffill"));
+        assert_eq!(param("min_observations"), Some("5"));
+
+        params.insert("missing_data".to_string(), "zero".to_string());
+        assert!(matches!(
+            build_node_key(&asset, AnalyticType::Volatility, &range, &params, None),
+            Err(ApiError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn build_node_key_canonicalizes_expressions() {
+        let asset = AssetKey::new_equity("AAPL").unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let key_for = |expression: &str| {
+            let params = HashMap::from([("expression".to_string(), expression.to_string())]);
+            build_node_key(&asset, AnalyticType::Expression, &range, &params, None)
+        };
+
+        let key = key_for("VOL_20/vol_60").unwrap();
+        assert_eq!(key.params["expression"], "volatility_20 / volatility_60");
+        assert_eq!(key, key_for("(volatility_20) / vol_60").unwrap());
+        assert!(key_for("vol_20 /").is_err());
+    }
+
+    #[test]
+    fn build_node_key_collects_pca_universe() {
+        let asset = AssetKey::new_equity("AAPL").unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        );
+        let mut params = HashMap::new();
+        params.insert("with".to_string(), "MSFT, GOOG".to_string());
+
+        let key = build_node_key(&asset, AnalyticType::Pca, &range, &params, None).unwrap();
+        let tickers: Vec<String> = key.assets.iter().map(|a| a.to_string()).collect();
+        assert_eq!(tickers, ["AAPL", "MSFT", "GOOG"]);
+        assert_eq!(key.params.get("components").map(String::as_str), Some("3"));
+        assert_eq!(key.params.get("window_size").map(String::as_str), Some("60"));
+
+        params.insert("components".to_string(), "4".to_string());
+        assert!(build_node_key(&asset, AnalyticType::Pca, &range, &params, None).is_err());
+        params.insert("with".to_string(), "MSFT,AAPL".to_string());
+        params.remove("components");
+        assert!(build_node_key(&asset, AnalyticType::Pca, &range, &params, None).is_err());
+    }
+
+    #[test]
+    fn matrix_keys_normalize_estimator_and_rebuild_symmetric_matrices() {
+        let asset = AssetKey::new_equity("SPY").unwrap();
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        );
+        let mut params = HashMap::new();
+        params.insert("with".to_string(), "TLT".to_string());
+        params.insert("lambda".to_string(), "0.9".to_string());
+
+        let analytic = AnalyticType::CovarianceMatrix;
+        let sample = build_node_key(&asset, analytic, &range, &params, None).unwrap();
+        assert_eq!(sample.params.get("estimator").map(String::as_str), Some("sample"));
+        assert!(!sample.params.contains_key("lambda"));
+        params.insert("estimator".to_string(), "EWMA".to_string());
+        let ewma = build_node_key(&asset, analytic, &range, &params, None).unwrap();
+        assert_eq!(ewma.params.get("lambda").map(String::as_str), Some("0.9"));
+        params.insert("estimator".to_string(), "robust".to_string());
+        assert!(build_node_key(&asset, analytic, &range, &params, None).is_err());
+
+        let timestamp = Utc.with_ymd_and_hms(2024, 3, 29, 16, 0, 0).unwrap();
+        let outputs: BTreeMap<String, Vec<TimeSeriesPoint>> = [
+            ("spy_tlt", -0.5),
+            ("spy_spy", 2.0),
+            ("tlt_tlt", f64::NAN),
+            ("observations", 60.0),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), vec![TimeSeriesPoint::new(timestamp, value)]))
+        .collect();
+        let matrices = matrix_points(&ewma.assets, &outputs);
+        assert_eq!(matrices.len(), 1);
+        assert_eq!(matrices[0].observations, 60);
+        assert_eq!(
+            matrices[0].values,
+            vec![vec![Some(2.0), Some(-0.5)], vec![Some(-0.5), None]]
+        );
+    }
+
+    #[test]
+    fn build_portfolio_key_normalizes_weights() {
+        let assets = vec![
+            AssetKey::new_equity("AAPL").unwrap(),
+            AssetKey::new_equity("MSFT").unwrap(),
+        ];
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+
+        let mut params = HashMap::new();
+        let equal =
+            build_portfolio_key(&assets, AnalyticType::PortfolioNav, &range, &params, None)
+                .unwrap();
+        params.insert("weights".to_string(), "0.50, 0.5".to_string());
+        params.insert("rebalance".to_string(), "None".to_string());
+        let explicit =
+            build_portfolio_key(&assets, AnalyticType::PortfolioNav, &range, &params, None)
+                .unwrap();
+        assert_eq!(equal, explicit);
+        assert_eq!(explicit.assets, assets);
+
+        params.insert("weights".to_string(), "1.0".to_string());
+        assert!(
+            build_portfolio_key(&assets, AnalyticType::PortfolioNav, &range, &params, None)
+                .is_err()
+        );
+        assert_eq!(portfolio_analytic("nav"), Some(AnalyticType::PortfolioNav));
+        assert_eq!(portfolio_analytic("correlation"), None);
+    }
+
+    #[test]
+    fn resolve_currency_params_prefers_pair_with_data() {
+        use crate::asset::Asset;
+        use crate::equity::Equity;
+        use crate::time_series::TimeSeriesPoint;
+        use chrono::TimeZone;
+
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        let sap = Equity::new("SAP", "SAP SE", "XETRA", "EUR", "Technology").unwrap();
+        let aapl = Equity::new("AAPL", "Apple Inc.", "NASDAQ", "USD", "Technology").unwrap();
+        provider.store_asset_equity(&sap).unwrap();
+        provider.store_asset_equity(&aapl).unwrap();
+
+        // Only the inverse USD/EUR pair is stored
+        let usdeur = AssetKey::new_fx("USD", "EUR").unwrap();
+        provider
+            .insert_time_series_point(
+                &usdeur,
+                &TimeSeriesPoint::new(Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap(), 0.9),
+            )
+            .unwrap();
+
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let mut params = HashMap::new();
+        params.insert("reporting_currency".to_string(), "usd".to_string());
+        resolve_currency_params(
+            &provider,
+            &[sap.key().clone(), aapl.key().clone()],
+            &range,
+            &mut params,
+        )
+        .unwrap();
+
+        assert_eq!(params.get("reporting_currency").unwrap(), "USD");
+        assert_eq!(params.get("currency").unwrap(), "EUR,USD");
+        assert_eq!(params.get("fx_invert").unwrap(), "true,false");
+
+        let missing = AssetKey::new_equity("MISSING").unwrap();
+        assert!(resolve_currency_params(&provider, &[missing], &range, &mut params).is_err());
+    }
+
+    #[tokio::test]
+    async fn list_assets_filters_the_stored_catalog() {
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        for (ticker, name, exchange, currency) in [
+            ("AAPL", "Apple Inc.", "NASDAQ", "USD"),
+            ("SAP", "SAP SE", "XETRA", "EUR"),
+        ] {
+            let equity =
+                crate::equity::Equity::new(ticker, name, exchange, currency, "Technology").unwrap();
+            provider.store_asset_equity(&equity).unwrap();
+        }
+        let state = Arc::new(AppState::new(provider));
+
+        let query = AssetsQueryParams {
+            q: Some("apple".to_string()),
+            ..AssetsQueryParams::default()
+        };
+        let Json(response) = list_assets(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(response.total, 1);
+        assert_eq!(response.assets[0].key, "AAPL");
+        assert_eq!(response.assets[0].asset_type, "equity");
+        assert_eq!(response.assets[0].data_available_from, None);
+
+        let query = AssetsQueryParams {
+            asset_type: Some("bond".to_string()),
+            ..AssetsQueryParams::default()
+        };
+        assert!(list_assets(State(state), Query(query)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn asset_data_accepts_futures_keys_and_returns_contract_details() {
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        let expiry = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+        let future =
+            Future::new("ES", expiry, "2024-12", "E-mini S&P 500", "CME", "USD", "CME", 5).unwrap();
+        provider.store_asset_future(&future).unwrap();
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let key = AssetKey::new_future("ES", expiry).unwrap();
+        provider
+            .insert_time_series_point(&key, &TimeSeriesPoint::new(timestamp, 4750.0))
+            .unwrap();
+        let state = Arc::new(AppState::new(provider));
+
+        let query = DataQueryParams {
+            start: "2024-01-01".to_string(),
+            end: "2024-01-31".to_string(),
+            flagged: None,
+            as_of: None,
+        };
+        let Json(response) = get_asset_data(
+            State(state),
+            Path("ES-2024-12-20".to_string()),
+            Query(query),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.asset, "ES-2024-12-20");
+        assert_eq!(response.data.len(), 1);
+        let details = response.future.unwrap();
+        assert_eq!(details.series, "ES");
+        assert_eq!(details.rollover_date, "2024-12-15");
+    }
+
+    #[tokio::test]
+    async fn asset_endpoints_create_update_and_delete_metadata() {
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        let state = Arc::new(AppState::new(provider));
+        let definition: AssetDefinition = serde_json::from_value(json!({
+            "type": "equity",
+            "ticker": "AAPL",
+            "name": "Apple Inc.",
+            "exchange": "NASDAQ",
+            "currency": "USD",
+            "sector": "Technology"
+        }))
+        .unwrap();
+
+        let Json(created) = create_asset(State(state.clone()), Json(definition.clone()))
+            .await
+            .unwrap();
+        assert_eq!(created, definition);
+        assert!(matches!(
+            create_asset(State(state.clone()), Json(definition)).await,
+            Err(ApiError::AssetExists(_))
+        ));
+
+        let split = CorporateActionInfo::Split {
+            ratio: 4.0,
+            effective_date: NaiveDate::from_ymd_opt(2020, 8, 31).unwrap(),
+        };
+        let Json(updated) = update_corporate_actions(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Json(vec![split.clone()]),
+        )
+        .await
+        .unwrap();
+        let Json(stored) = get_asset(State(state.clone()), Path("AAPL".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(stored, updated);
+        let AssetDefinition::Equity {
+            corporate_actions, ..
+        } = stored
+        else {
+            panic!("expected an equity");
+        };
+        assert_eq!(corporate_actions, vec![split]);
+
+        let invalid: AssetDefinition = serde_json::from_value(json!({
+            "type": "equity",
+            "ticker": "AAPL",
+            "name": "Apple Inc.",
+            "exchange": "NASDAQ",
+            "currency": "usd",
+            "sector": "Technology"
+        }))
+        .unwrap();
+        assert!(matches!(
+            update_asset(State(state.clone()), Path("AAPL".to_string()), Json(invalid)).await,
+            Err(ApiError::InvalidParameter(_))
+        ));
+
+        let Json(deleted) = delete_asset(State(state.clone()), Path("AAPL".to_string()))
+            .await
+            .unwrap();
+        assert!(deleted.deleted);
+        assert!(matches!(
+            get_asset(State(state), Path("AAPL".to_string())).await,
+            Err(ApiError::AssetNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn upload_imports_csv_prices_for_the_path_asset() {
+        let state = Arc::new(AppState::new(SqliteDataProvider::new_in_memory().unwrap()));
+        let options = HashMap::from([
+            ("date_column".to_string(), "Date".to_string()),
+            ("existing".to_string(), "skip".to_string()),
+        ]);
+        let body = Bytes::from("Date,Close\n2024-01-02,185.5\n2024-01-03,184.0\n");
+
+        let Json(response) = upload_asset_data(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(options.clone()),
+            body.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!((response.rows, response.inserted, response.skipped), (2, 2, 0));
+
+        let Json(response) = upload_asset_data(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(options),
+            body.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!((response.inserted, response.skipped), (0, 2));
+
+        let options = HashMap::from([("asset_column".to_string(), "Symbol".to_string())]);
+        assert!(matches!(
+            upload_asset_data(State(state), Path("AAPL".to_string()), Query(options), body).await,
+            Err(ApiError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn ingest_status_reports_job_and_asset_sync_state() {
+        let state = Arc::new(AppState::new(SqliteDataProvider::new_in_memory().unwrap()));
+        let response = ingest_status(State(state.clone())).await.unwrap();
+        assert!(!response.job.enabled);
+        assert!(response.assets.is_empty());
+
+        let at = Utc.with_ymd_and_hms(2024, 1, 8, 22, 0, 0).unwrap();
+        {
+            let provider = state.data_provider.lock().await;
+            let aapl = AssetKey::new_equity("AAPL").unwrap();
+            let watermark = NaiveDate::from_ymd_opt(2024, 1, 5);
+            provider.record_sync_success(&aapl, watermark, at).unwrap();
+            let msft = AssetKey::new_equity("MSFT").unwrap();
+            provider.record_sync_failure(&msft, "HTTP 500", at).unwrap();
+        }
+        {
+            let mut job = state.ingest.write().await;
+            job.enabled = true;
+            job.interval_seconds = Some(3600);
+        }
+
+        let Json(response) = ingest_status(State(state)).await.unwrap();
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["enabled"], true);
+        assert_eq!(json["interval_seconds"], 3600);
+        assert_eq!(json["assets"][0]["asset"], "AAPL");
+        assert_eq!(json["assets"][0]["watermark"], "2024-01-05");
+        assert_eq!(json["assets"][1]["last_error"], "HTTP 500");
+        assert!(json["assets"][1]["watermark"].is_null());
+    }
+
+    #[tokio::test]
+    async fn quality_report_flags_bad_prints_that_data_queries_can_exclude() {
+        let state = Arc::new(AppState::new(SqliteDataProvider::new_in_memory().unwrap()));
+        let body = Bytes::from(
+            "Date,Close\n2024-01-02,100.0\n2024-01-03,1000.0\n\
+             2024-01-04,101.0\n2024-01-08,102.0\n",
+        );
+        let options = HashMap::from([("date_column".to_string(), "Date".to_string())]);
+        let Json(response) = upload_asset_data(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(options),
+            body,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.flagged, 1);
+
+        let Json(report) = get_asset_quality(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(HashMap::new()),
+        )
+        .await
+        .unwrap();
+        assert_eq!((report.points, report.bad_prints), (4, 1));
+        let flags: Vec<&str> = report.flags.iter().map(|f| f.flag.as_str()).collect();
+        assert_eq!(flags, vec!["spike", "gap"]);
+        assert_eq!(
+            report.missing_days,
+            vec![NaiveDate::from_ymd_opt(2024, 1, 5).unwrap()]
+        );
+
+        // Rule options and the reporting range narrow the report
+        let params = HashMap::from([
+            ("missing_days".to_string(), "false".to_string()),
+            ("end".to_string(), "2024-01-02".to_string()),
+        ]);
+        let Json(report) = get_asset_quality(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(params),
+        )
+        .await
+        .unwrap();
+        assert!(report.flags.is_empty() && report.missing_days.is_empty());
+        let params = HashMap::from([("spike_ratio".to_string(), "0.5".to_string())]);
+        assert!(matches!(
+            get_asset_quality(
+                State(state.clone()),
+                Path("AAPL".to_string()),
+                Query(params)
+            )
+            .await,
+            Err(ApiError::InvalidParameter(_))
+        ));
+
+        let query = |flagged: &str| DataQueryParams {
+            start: "2024-01-01".to_string(),
+            end: "2024-01-31".to_string(),
+            flagged: Some(flagged.to_string()),
+            as_of: None,
+        };
+        let closes = |response: AssetDataResponse| -> Vec<f64> {
+            response.data.iter().map(|point| point.close).collect()
+        };
+        let Json(response) = get_asset_data(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(query("exclude")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(closes(response), vec![100.0, 101.0, 102.0]);
+        let Json(response) = get_asset_data(
+            State(state.clone()),
+            Path("AAPL".to_string()),
+            Query(query("ffill")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(closes(response), vec![100.0, 100.0, 101.0, 102.0]);
+        assert!(matches!(
+            get_asset_data(State(state), Path("AAPL".to_string()), Query(query("drop"))).await,
+            Err(ApiError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn as_of_queries_read_prices_as_known_then() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let key = AssetKey::new_equity("AAPL").unwrap();
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap();
+        let loaded = Utc.with_ymd_and_hms(2024, 2, 1, 18, 0, 0).unwrap();
+        let corrected = Utc.with_ymd_and_hms(2024, 2, 5, 18, 0, 0).unwrap();
+        let points: Vec<TimeSeriesPoint> = [(2, 100.0), (3, 101.0), (4, 102.0)]
+            .iter()
+            .map(|&(d, close)| TimeSeriesPoint::new(day(d), close))
+            .collect();
+        provider
+            .record_time_series_batch(&key, &points, loaded)
+            .unwrap();
+        let correction = [
+            TimeSeriesPoint::new(day(3), 111.0),
+            TimeSeriesPoint::new(day(5), 103.0),
+        ];
+        provider
+            .record_time_series_batch(&key, &correction, corrected)
+            .unwrap();
+        let state = Arc::new(AppState::new(provider));
+
+        let query = |as_of: Option<&str>| DataQueryParams {
+            start: "2024-01-01".to_string(),
+            end: "2024-01-31".to_string(),
+            flagged: None,
+            as_of: as_of.map(str::to_string),
+        };
+        let closes = |response: AssetDataResponse| -> Vec<f64> {
+            response.data.iter().map(|point| point.close).collect()
+        };
+        let data = |as_of| {
+            get_asset_data(
+                State(state.clone()),
+                Path("AAPL".to_string()),
+                Query(query(as_of)),
+            )
+        };
+        assert_eq!(
+            closes(data(None).await.unwrap().0),
+            vec![100.0, 111.0, 102.0, 103.0]
+        );
+        assert_eq!(
+            closes(data(Some("2024-02-02")).await.unwrap().0),
+            vec![100.0, 101.0, 102.0]
+        );
+        assert_eq!(
+            closes(data(Some("2024-02-05T18:00:00Z")).await.unwrap().0),
+            vec![100.0, 111.0, 102.0, 103.0]
+        );
+        assert!(matches!(
+            data(Some("yesterday")).await,
+            Err(ApiError::InvalidParameter(_))
+        ));
+
+        let Json(response) = get_analytics(
+            State(state.clone()),
+            Path(("AAPL".to_string(), "returns".to_string())),
+            Query(AnalyticsQueryParams {
+                start: "2024-01-01".to_string(),
+                end: "2024-01-31".to_string(),
+                window: None,
+                override_tag: None,
+                flagged: None,
+                as_of: Some("2024-02-02".to_string()),
+            }),
+            Query(HashMap::from([(
+                "as_of".to_string(),
+                "2024-02-02".to_string(),
+            )])),
+        )
+        .await
+        .unwrap();
+        assert!(!response.parameters.contains_key("as_of"));
+        let returns: Vec<Option<f64>> = response.data.iter().map(|point| point.value).collect();
+        assert_eq!(returns.len(), 3);
+        assert!((returns[1].unwrap() - (101.0f64 / 100.0).ln()).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn analytics_queries_accept_only_declared_parameters() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let key = AssetKey::new_equity("AAPL").unwrap();
+        let points: Vec<TimeSeriesPoint> = [(2, 100.0), (3, 101.0), (4, 102.0)]
+            .iter()
+            .map(|&(d, close)| {
+                TimeSeriesPoint::new(Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap(), close)
+            })
+            .collect();
+        provider.insert_time_series_batch(&key, &points).unwrap();
+        let state = Arc::new(AppState::new(provider));
+
+        let analytics = |analytic: &str, name: &str, value: &str| {
+            get_analytics(
+                State(state.clone()),
+                Path(("AAPL".to_string(), analytic.to_string())),
+                Query(AnalyticsQueryParams {
+                    start: "2024-01-01".to_string(),
+                    end: "2024-01-31".to_string(),
+                    window: None,
+                    override_tag: None,
+                    flagged: None,
+                    as_of: None,
+                }),
+                Query(HashMap::from([(name.to_string(), value.to_string())])),
+            )
+        };
+        let Json(response) = analytics("returns", "lag", "1").await.unwrap();
+        assert_eq!(
+            response.parameters.get("lag").map(String::as_str),
+            Some("1")
+        );
+        assert!(analytics("volatility", "window_size", "2").await.is_ok());
+        assert!(analytics("returns", "missing_data", "skip").await.is_ok());
+        // Accepted, though the asset has no currency to convert from
+        assert!(!matches!(
+            analytics("volatility", "reporting_currency", "USD").await,
+            Err(ApiError::InvalidParameter(message)) if message.starts_with("Unknown parameter")
+        ));
+
+        for (analytic, name) in [("returns", "with"), ("volatility", "unknown")] {
+            assert!(matches!(
+                analytics(analytic, name, "MSFT").await,
+                Err(ApiError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn matrices_as_of_a_date_after_a_long_break_use_the_latest_prices() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let recorded = Utc.with_ymd_and_hms(2024, 2, 1, 18, 0, 0).unwrap();
+        for (ticker, days) in [("SPY", 11), ("XLK", 12)] {
+            let points: Vec<TimeSeriesPoint> = (1..=days)
+                .map(|d| {
+                    let timestamp = Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap();
+                    TimeSeriesPoint::new(timestamp, 100.0 + (d * d % 7) as f64)
+                })
+                .collect();
+            provider
+                .record_time_series_batch(&AssetKey::new_equity(ticker).unwrap(), &points, recorded)
+                .unwrap();
+        }
+        let state = Arc::new(AppState::new(provider));
+
+        let Json(response) = get_matrix(
+            State(state),
+            Query(MatrixQueryParams {
+                assets: "SPY,XLK".to_string(),
+                matrix_type: None,
+                as_of: Some("2024-02-15".to_string()),
+                start: None,
+                end: None,
+                window: Some(3),
+                estimator: None,
+                lambda: None,
+                reporting_currency: None,
+                override_tag: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.start_date, "2024-01-08");
+        assert_eq!(response.matrices.len(), 1);
+        assert!(response.matrices[0].timestamp.starts_with("2024-01-12"));
+        assert_eq!(response.matrices[0].observations, 3);
+        assert!(response.matrices[0].values[0][1].is_some());
+    }
 }
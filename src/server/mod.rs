@@ -8,8 +8,12 @@ mod state;
 pub use error::ApiError;
 pub use state::{AnalyticConfig, AppState, SessionStatus};
 
+use crate::analytics::AnalyticRegistry;
+use crate::ingest::{spawn_refresh_job, RefreshConfig};
 use crate::sqlite_provider::SqliteDataProvider;
+use crate::yahoo_finance::YahooFinanceDownloader;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::prelude::*;
 
 /// Server configuration
@@ -21,6 +25,11 @@ pub struct ServerConfig {
     pub port: u16,
     /// Path to SQLite database
     pub database_path: String,
+    /// Seconds between scheduled refreshes of the catalog from Yahoo Finance
+    /// (default: `None`, no scheduled refresh)
+    pub refresh_interval_seconds: Option<u64>,
+    /// Trading calendars, history start and quality rules of the scheduled refresh
+    pub refresh: RefreshConfig,
 }
 
 impl Default for ServerConfig {
@@ -29,6 +38,8 @@ impl Default for ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
             database_path: "analytics.db".to_string(),
+            refresh_interval_seconds: None,
+            refresh: RefreshConfig::default(),
         }
     }
 }
@@ -40,8 +51,22 @@ impl ServerConfig {
             host: host.into(),
             port,
             database_path: database_path.into(),
+            refresh_interval_seconds: None,
+            refresh: RefreshConfig::default(),
         }
     }
+
+    /// Refreshes the catalog from Yahoo Finance every `seconds` while serving
+    pub fn with_refresh_interval(mut self, seconds: u64) -> Self {
+        self.refresh_interval_seconds = Some(seconds);
+        self
+    }
+
+    /// Sets the configuration of the scheduled refresh
+    pub fn with_refresh_config(mut self, refresh: RefreshConfig) -> Self {
+        self.refresh = refresh;
+        self
+    }
 }
 
 /// Runs the API server
@@ -64,6 +89,15 @@ impl ServerConfig {
 /// }
 /// ```
 pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::Error>> {
+    run_server_with_registry(config, AnalyticRegistry::default()).await
+}
+
+/// Runs the API server with the analytics of `registry`, which may include
+/// user-defined analytics added with `AnalyticRegistry::register`
+pub async fn run_server_with_registry(
+    config: ServerConfig,
+    registry: AnalyticRegistry,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing with environment filter
     // Set RUST_LOG environment variable to control log level:
     //   RUST_LOG=debug - debug level and above
@@ -82,7 +116,23 @@ pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::
     let data_provider = SqliteDataProvider::new(&config.database_path)?;
 
     // Create application state
-    let state = Arc::new(AppState::new(data_provider));
+    let state = Arc::new(AppState::with_registry(data_provider, registry));
+
+    // Schedule the market data refresh
+    let refresh_interval = config
+        .refresh_interval_seconds
+        .filter(|seconds| *seconds > 0);
+    if let Some(seconds) = refresh_interval {
+        let downloader = YahooFinanceDownloader::new()?;
+        spawn_refresh_job(
+            downloader,
+            state.data_provider.clone(),
+            state.ingest.clone(),
+            config.refresh.clone(),
+            Duration::from_secs(seconds),
+        );
+        tracing::info!("Refreshing market data every {} seconds", seconds);
+    }
 
     // Create router
     let app = routes::create_router(state);
@@ -1,7 +1,8 @@
 //! Route definitions for the API server
 
 use axum::{
-    routing::{delete, get, post},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -13,6 +14,9 @@ use tower_http::{
 use super::handlers;
 use super::state::AppState;
 
+/// Largest price file accepted by `POST /assets/:asset/data`
+const MAX_UPLOAD_BYTES: usize = 64 * 1024 * 1024;
+
 /// Creates the main application router with all routes and middleware
 pub fn create_router(state: Arc<AppState>) -> Router {
     // Create CORS layer (allow all origins for POC)
@@ -26,14 +30,42 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // Health check
         .route("/health", get(handlers::health_check))
         // Static information endpoints
-        .route("/assets", get(handlers::list_assets))
+        .route(
+            "/assets",
+            get(handlers::list_assets).post(handlers::create_asset),
+        )
         .route("/dag/nodes", get(handlers::list_analytics))
         .route("/dag/visualize", get(handlers::visualize_dag))
+        // Asset metadata management
+        .route(
+            "/assets/:asset",
+            get(handlers::get_asset)
+                .put(handlers::update_asset)
+                .delete(handlers::delete_asset),
+        )
+        .route(
+            "/assets/:asset/corporate_actions",
+            put(handlers::update_corporate_actions),
+        )
+        .route(
+            "/assets/:asset/expiry_calendar",
+            put(handlers::update_expiry_calendar),
+        )
         // Asset data query
-        .route("/assets/:asset/data", get(handlers::get_asset_data))
+        .route(
+            "/assets/:asset/data",
+            get(handlers::get_asset_data)
+                .post(handlers::upload_asset_data)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/assets/:asset/quality", get(handlers::get_asset_quality))
+        // Market data ingestion
+        .route("/ingest/status", get(handlers::ingest_status))
         // Pull-mode analytics
         .route("/analytics/:asset/:type", get(handlers::get_analytics))
         .route("/analytics/batch", post(handlers::batch_analytics))
+        .route("/analytics/matrix", get(handlers::get_matrix))
+        .route("/portfolio/:type", get(handlers::get_portfolio_analytics))
         // Replay session management
         .route("/replay", post(handlers::create_replay_session))
         .route("/replay/:session_id", get(handlers::get_session_status))
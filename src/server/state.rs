@@ -1,7 +1,9 @@
 //! Shared application state for the API server
 
+use crate::analytics::AnalyticRegistry;
 use crate::asset_key::AssetKey;
 use crate::dag::AnalyticsDag;
+use crate::ingest::IngestStatus;
 use crate::push_mode::PushModeEngine;
 use crate::replay::ReplayEngine;
 use crate::sqlite_provider::SqliteDataProvider;
@@ -23,15 +25,26 @@ pub struct AppState {
     pub sessions: Arc<RwLock<HashMap<Uuid, ReplaySession>>>,
     /// SSE broadcasters for each session
     pub broadcasters: Arc<RwLock<HashMap<Uuid, Sender<Event>>>>,
+    /// Analytics served by the API, including user-defined ones
+    pub registry: Arc<AnalyticRegistry>,
+    /// Progress of the scheduled market data refresh
+    pub ingest: Arc<RwLock<IngestStatus>>,
 }
 
 impl AppState {
-    /// Creates a new application state
+    /// Creates a new application state serving the built-in analytics
     pub fn new(data_provider: SqliteDataProvider) -> Self {
+        Self::with_registry(data_provider, AnalyticRegistry::default())
+    }
+
+    /// Creates a new application state serving the analytics of `registry`
+    pub fn with_registry(data_provider: SqliteDataProvider, registry: AnalyticRegistry) -> Self {
         AppState {
             data_provider: Arc::new(Mutex::new(data_provider)),
             sessions: Arc::new(RwLock::new(HashMap::new())),
             broadcasters: Arc::new(RwLock::new(HashMap::new())),
+            registry: Arc::new(registry),
+            ingest: Arc::new(RwLock::new(IngestStatus::default())),
         }
     }
 }
@@ -1,17 +1,109 @@
-use crate::asset::Asset;
+use crate::asset::{Asset, AssetType};
 use crate::asset_key::AssetKey;
 use crate::equity::Equity;
 use crate::future::Future;
+use crate::fx::FxRate;
+use crate::migrations::{self, SchemaError};
+use crate::quality::{FlaggedPoint, QualityFlag};
 use crate::time_series::{DataProvider, DataProviderError, DateRange, TimeSeriesPoint};
-use chrono::{DateTime, NaiveDate, Utc};
-use rusqlite::{Connection, Result as SqliteResult};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension};
 use serde_json;
 use std::path::Path;
 
+/// Catalog entry for a stored asset, with the coverage of its price series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetSummary {
+    pub key: AssetKey,
+    pub asset_type: AssetType,
+    pub name: String,
+    /// Exchange, or the fixing source for FX pairs
+    pub exchange: String,
+    /// Sector classification (equities only)
+    pub sector: Option<String>,
+    /// Currency prices are quoted in
+    pub currency: String,
+    /// Date of the first stored price, if any
+    pub first_date: Option<NaiveDate>,
+    /// Date of the last stored price, if any
+    pub last_date: Option<NaiveDate>,
+    /// Number of stored prices
+    pub point_count: usize,
+}
+
+/// Filters and pagination for [`SqliteDataProvider::list_assets`].
+///
+/// Text filters are case-insensitive; `None` matches every asset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetFilter {
+    pub asset_type: Option<AssetType>,
+    pub exchange: Option<String>,
+    pub sector: Option<String>,
+    pub currency: Option<String>,
+    /// Substring of the asset's name or key (ticker)
+    pub search: Option<String>,
+    /// Number of matching assets to skip
+    pub offset: usize,
+    /// Maximum number of assets to return
+    pub limit: Option<usize>,
+}
+
+impl AssetFilter {
+    fn matches(&self, summary: &AssetSummary) -> bool {
+        let equals = |filter: &Option<String>, value: Option<&str>| match filter {
+            Some(filter) => value.is_some_and(|value| value.eq_ignore_ascii_case(filter)),
+            None => true,
+        };
+        let search = match &self.search {
+            Some(search) => {
+                let search = search.to_lowercase();
+                summary.name.to_lowercase().contains(&search)
+                    || summary.key.as_string().to_lowercase().contains(&search)
+            }
+            None => true,
+        };
+        self.asset_type
+            .is_none_or(|asset_type| asset_type == summary.asset_type)
+            && equals(&self.exchange, Some(&summary.exchange))
+            && equals(&self.sector, summary.sector.as_deref())
+            && equals(&self.currency, Some(&summary.currency))
+            && search
+    }
+}
+
+/// One page of the asset catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetPage {
+    /// Number of assets matching the filter, across all pages
+    pub total: usize,
+    pub assets: Vec<AssetSummary>,
+}
+
+/// Refresh progress of an asset synced from a market data source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncState {
+    pub key: AssetKey,
+    /// Last date the asset is synced through; later dates are still to fetch
+    pub watermark: Option<NaiveDate>,
+    /// When the last refresh of the asset started
+    pub last_attempt: Option<DateTime<Utc>>,
+    /// When the asset was last refreshed without error
+    pub last_success: Option<DateTime<Utc>>,
+    /// Error of the last refresh, cleared by the next successful one
+    pub last_error: Option<String>,
+}
+
 /// SQLite-based data provider implementation.
 ///
 /// Stores asset data, time-series data, and analytics in SQLite database.
-/// Automatically creates schema on first use.
+/// Automatically creates schema on first use, and migrates databases created
+/// with an older schema.
+///
+/// Prices are bitemporal: `time_series_data` holds the current value of each
+/// timestamp (valid time) with the moment it was recorded (knowledge time),
+/// and `time_series_history` every value it replaced, with the interval during
+/// which that value was known. [`DataProvider::get_time_series_as_of`] reads
+/// the prices as they were known at a past moment.
 #[derive(Debug)]
 pub struct SqliteDataProvider {
     conn: Connection,
@@ -27,8 +119,9 @@ impl SqliteDataProvider {
     /// Returns `Ok(SqliteDataProvider)` if successful, or an error if connection fails.
     ///
     /// # Errors
-    /// Returns an error if the database connection cannot be established.
-    pub fn new<P: AsRef<Path>>(db_path: P) -> SqliteResult<Self> {
+    /// Returns an error if the database connection cannot be established, if the
+    /// database has a newer schema than this build knows, or if migrating it fails.
+    pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self, SchemaError> {
         let conn = Connection::open(db_path)?;
         let provider = SqliteDataProvider { conn };
         provider.ensure_schema()?;
@@ -41,63 +134,29 @@ impl SqliteDataProvider {
     ///
     /// # Returns
     /// Returns `Ok(SqliteDataProvider)` if successful, or an error if connection fails.
-    pub fn new_in_memory() -> SqliteResult<Self> {
+    pub fn new_in_memory() -> Result<Self, SchemaError> {
         let conn = Connection::open_in_memory()?;
         let provider = SqliteDataProvider { conn };
         provider.ensure_schema()?;
         Ok(provider)
     }
 
-    /// Ensures the database schema exists, creating tables if they don't exist.
+    /// Brings the database schema to the latest version, applying any
+    /// migrations it lacks (see [`crate::migrations`]).
     ///
     /// # Returns
-    /// Returns `Ok(())` if successful, or an error if schema creation fails.
-    fn ensure_schema(&self) -> SqliteResult<()> {
-        // Create assets table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS assets (
-                asset_key TEXT PRIMARY KEY,
-                asset_data TEXT NOT NULL
-            )",
-            [],
-        )?;
-
-        // Create time_series_data table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS time_series_data (
-                asset_key TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                close_price REAL NOT NULL,
-                PRIMARY KEY (asset_key, timestamp)
-            )",
-            [],
-        )?;
-
-        // Create indexes on time_series_data for query performance
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_time_series_asset_key ON time_series_data(asset_key)",
-            [],
-        )?;
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_time_series_timestamp ON time_series_data(timestamp)",
-            [],
-        )?;
-
-        // Create analytics table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS analytics (
-                asset_key TEXT NOT NULL,
-                date TEXT NOT NULL,
-                analytics_name TEXT NOT NULL,
-                value TEXT NOT NULL,
-                PRIMARY KEY (asset_key, date, analytics_name)
-            )",
-            [],
-        )?;
-
+    /// Returns `Ok(())` if successful, or an error if the database is newer than
+    /// this build or a migration fails.
+    fn ensure_schema(&self) -> Result<(), SchemaError> {
+        migrations::migrate(&self.conn)?;
         Ok(())
     }
 
+    /// Returns the schema version of the database.
+    pub fn schema_version(&self) -> Result<u32, SchemaError> {
+        migrations::schema_version(&self.conn)
+    }
+
     /// Checks if a table exists in the database.
     ///
     /// # Arguments
@@ -106,7 +165,7 @@ impl SqliteDataProvider {
     /// # Returns
     /// Returns `true` if the table exists, `false` otherwise.
     #[cfg(test)]
-    fn table_exists(&self, table_name: &str) -> SqliteResult<bool> {
+    fn table_exists(&self, table_name: &str) -> rusqlite::Result<bool> {
         let mut stmt = self
             .conn
             .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name=?1")?;
@@ -124,6 +183,8 @@ impl SqliteDataProvider {
     /// Inserts a single time-series point into the database.
     ///
     /// If a point with the same asset_key and timestamp already exists, it will be replaced (upsert).
+    /// A changed price is recorded as known from now; the replaced price stays
+    /// available to as-of queries.
     ///
     /// # Arguments
     /// * `asset_key` - The asset key for this time-series point
@@ -139,17 +200,13 @@ impl SqliteDataProvider {
         asset_key: &AssetKey,
         point: &TimeSeriesPoint,
     ) -> Result<(), DataProviderError> {
-        let asset_key_str = asset_key.as_string();
-        let timestamp_str = point.timestamp.to_rfc3339();
-
-        self.conn
-            .execute(
-                "INSERT OR REPLACE INTO time_series_data (asset_key, timestamp, close_price) VALUES (?1, ?2, ?3)",
-                rusqlite::params![asset_key_str, timestamp_str, point.close_price],
-            )
-            .map_err(|e| DataProviderError::Other(format!("Failed to insert time-series point: {}", e)))?;
-
-        Ok(())
+        record_point(
+            &self.conn,
+            &asset_key.as_string(),
+            point,
+            &knowledge_time(Utc::now()),
+        )
+        .map_err(|e| DataProviderError::Other(format!("Failed to insert time-series point: {}", e)))
     }
 
     /// Inserts multiple time-series points in a single transaction.
@@ -170,35 +227,42 @@ impl SqliteDataProvider {
         &mut self,
         asset_key: &AssetKey,
         points: &[TimeSeriesPoint],
+    ) -> Result<(), DataProviderError> {
+        self.record_time_series_batch(asset_key, points, Utc::now())
+    }
+
+    /// Inserts multiple time-series points in a single transaction, as known
+    /// from `recorded_at`.
+    ///
+    /// Changed prices replace the current ones, which move to the history with
+    /// `recorded_at` as the end of the time they were known; unchanged prices
+    /// keep their original knowledge time.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails, or if `recorded_at`
+    /// precedes the knowledge time of a price it would replace. The transaction
+    /// will be rolled back.
+    pub fn record_time_series_batch(
+        &mut self,
+        asset_key: &AssetKey,
+        points: &[TimeSeriesPoint],
+        recorded_at: DateTime<Utc>,
     ) -> Result<(), DataProviderError> {
         if points.is_empty() {
             return Ok(());
         }
 
         let asset_key_str = asset_key.as_string();
+        let recorded_at = knowledge_time(recorded_at);
         let transaction = self
             .conn
             .transaction()
             .map_err(|e| DataProviderError::Other(format!("Failed to start transaction: {}", e)))?;
 
-        {
-            let mut stmt = transaction
-                .prepare(
-                    "INSERT OR REPLACE INTO time_series_data (asset_key, timestamp, close_price) VALUES (?1, ?2, ?3)",
-                )
-                .map_err(|e| DataProviderError::Other(format!("Failed to prepare statement: {}", e)))?;
-
-            for point in points {
-                let timestamp_str = point.timestamp.to_rfc3339();
-                stmt.execute(rusqlite::params![
-                    asset_key_str,
-                    timestamp_str,
-                    point.close_price
-                ])
-                .map_err(|e| {
-                    DataProviderError::Other(format!("Failed to insert point in batch: {}", e))
-                })?;
-            }
+        for point in points {
+            record_point(&transaction, &asset_key_str, point, &recorded_at).map_err(|e| {
+                DataProviderError::Other(format!("Failed to insert point in batch: {}", e))
+            })?;
         }
 
         transaction.commit().map_err(|e| {
@@ -210,6 +274,8 @@ impl SqliteDataProvider {
 
     /// Updates an existing time-series point in the database.
     ///
+    /// Like an insert, a changed price is recorded as known from now.
+    ///
     /// # Arguments
     /// * `asset_key` - The asset key for this time-series point
     /// * `point` - The time-series point to update (must have matching timestamp)
@@ -227,22 +293,26 @@ impl SqliteDataProvider {
         let asset_key_str = asset_key.as_string();
         let timestamp_str = point.timestamp.to_rfc3339();
 
-        let rows_affected = self
+        let exists = self
             .conn
-            .execute(
-                "UPDATE time_series_data SET close_price = ?3 WHERE asset_key = ?1 AND timestamp = ?2",
-                rusqlite::params![asset_key_str, timestamp_str, point.close_price],
-            )
-            .map_err(|e| DataProviderError::Other(format!("Failed to update time-series point: {}", e)))?;
+            .prepare("SELECT 1 FROM time_series_data WHERE asset_key = ?1 AND timestamp = ?2")
+            .and_then(|mut stmt| stmt.exists([&asset_key_str, &timestamp_str]))
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
 
-        if rows_affected == 0 {
+        if !exists {
             return Err(DataProviderError::Other(format!(
                 "Time-series point not found for asset_key={} and timestamp={}",
                 asset_key_str, timestamp_str
             )));
         }
 
-        Ok(())
+        record_point(
+            &self.conn,
+            &asset_key_str,
+            point,
+            &knowledge_time(Utc::now()),
+        )
+        .map_err(|e| DataProviderError::Other(format!("Failed to update time-series point: {}", e)))
     }
 
     /// Stores an Equity asset in the database as a JSON blob.
@@ -385,6 +455,435 @@ impl SqliteDataProvider {
         Ok(future)
     }
 
+    /// Stores an FX rate asset in the database as a JSON blob.
+    ///
+    /// If an asset with the same asset_key already exists, it will be replaced.
+    ///
+    /// # Errors
+    /// Returns an error if serialization or database operation fails.
+    pub fn store_asset_fx(&self, fx: &FxRate) -> Result<(), DataProviderError> {
+        let asset_key_str = fx.key().as_string();
+        let asset_json = serde_json::to_string(fx)
+            .map_err(|e| DataProviderError::Other(format!("Failed to serialize FxRate: {}", e)))?;
+
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO assets (asset_key, asset_data) VALUES (?1, ?2)",
+                rusqlite::params![asset_key_str, asset_json],
+            )
+            .map_err(|e| DataProviderError::Other(format!("Failed to store FxRate: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Retrieves an FX rate asset from the database by asset_key.
+    ///
+    /// # Errors
+    /// Returns an error if the asset is not found, is not an FX rate, or deserialization fails.
+    pub fn get_asset_fx(&self, asset_key: &AssetKey) -> Result<FxRate, DataProviderError> {
+        let asset_json = self.get_asset_json(asset_key)?;
+
+        let fx: FxRate = serde_json::from_str(&asset_json).map_err(|e| {
+            DataProviderError::Other(format!("Failed to deserialize FxRate: {}", e))
+        })?;
+
+        if fx.key() != asset_key {
+            return Err(DataProviderError::Other(format!(
+                "Asset key mismatch: expected {:?}, got {:?}",
+                asset_key,
+                fx.key()
+            )));
+        }
+
+        Ok(fx)
+    }
+
+    /// Returns the currency in which an asset's prices are quoted.
+    ///
+    /// FX pairs are quoted in their quote currency and need no stored metadata.
+    /// Equities and futures read the currency from their stored metadata.
+    ///
+    /// # Errors
+    /// Returns `AssetNotFound` if the asset has not been stored, or `Other` if
+    /// the stored metadata carries no currency.
+    pub fn asset_currency(&self, asset_key: &AssetKey) -> Result<String, DataProviderError> {
+        if let AssetKey::FxRate { quote, .. } = asset_key {
+            return Ok(quote.clone());
+        }
+
+        let asset_json = self.get_asset_json(asset_key)?;
+        let value: serde_json::Value = serde_json::from_str(&asset_json)
+            .map_err(|e| DataProviderError::Other(format!("Failed to parse asset: {}", e)))?;
+
+        value
+            .get("metadata")
+            .and_then(|metadata| metadata.get("currency"))
+            .and_then(|currency| currency.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                DataProviderError::Other(format!("Asset {} has no currency", asset_key))
+            })
+    }
+
+    /// Deletes an asset's stored metadata.
+    ///
+    /// Stored prices and analytics are kept, so an asset can be deleted and
+    /// re-created without losing its history.
+    ///
+    /// # Errors
+    /// Returns `AssetNotFound` if the asset has not been stored.
+    pub fn delete_asset(&self, asset_key: &AssetKey) -> Result<(), DataProviderError> {
+        let deleted = self
+            .conn
+            .execute(
+                "DELETE FROM assets WHERE asset_key = ?1",
+                [asset_key.as_string()],
+            )
+            .map_err(|e| DataProviderError::Other(format!("Failed to delete asset: {}", e)))?;
+
+        if deleted == 0 {
+            return Err(DataProviderError::AssetNotFound);
+        }
+        Ok(())
+    }
+
+    /// Returns whether metadata is stored for an asset.
+    pub fn asset_exists(&self, asset_key: &AssetKey) -> Result<bool, DataProviderError> {
+        match self.get_asset_json(asset_key) {
+            Ok(_) => Ok(true),
+            Err(DataProviderError::AssetNotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Loads the raw JSON blob stored for an asset.
+    fn get_asset_json(&self, asset_key: &AssetKey) -> Result<String, DataProviderError> {
+        self.conn
+            .query_row(
+                "SELECT asset_data FROM assets WHERE asset_key = ?1",
+                [asset_key.as_string()],
+                |row| row.get(0),
+            )
+            .map_err(|e| {
+                if let rusqlite::Error::QueryReturnedNoRows = e {
+                    DataProviderError::AssetNotFound
+                } else {
+                    DataProviderError::Other(format!("Failed to retrieve asset: {}", e))
+                }
+            })
+    }
+
+    /// Lists stored assets matching `filter`, ordered by asset key.
+    ///
+    /// Each entry carries the asset's metadata and the first date, last date
+    /// and number of its stored prices. `total` counts every matching asset,
+    /// while `assets` holds the page selected by `offset` and `limit`.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails or stored metadata cannot be parsed.
+    pub fn list_assets(&self, filter: &AssetFilter) -> Result<AssetPage, DataProviderError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT a.asset_data, MIN(t.timestamp), MAX(t.timestamp), COUNT(t.timestamp)
+                FROM assets a
+                LEFT JOIN time_series_data t ON t.asset_key = a.asset_key
+                GROUP BY a.asset_key
+                ORDER BY a.asset_key",
+            )
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let mut matching = Vec::new();
+        for row in rows {
+            let (asset_json, first, last, count) =
+                row.map_err(|e| DataProviderError::Other(format!("Row parsing error: {}", e)))?;
+            let summary = AssetSummary {
+                first_date: first.as_deref().map(parse_stored_date).transpose()?,
+                last_date: last.as_deref().map(parse_stored_date).transpose()?,
+                point_count: count as usize,
+                ..summarize_asset(&asset_json)?
+            };
+            if filter.matches(&summary) {
+                matching.push(summary);
+            }
+        }
+
+        let total = matching.len();
+        let assets = matching
+            .into_iter()
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(AssetPage { total, assets })
+    }
+
+    /// Replaces every stored quality flag of an asset with `flags`.
+    pub fn replace_quality_flags(
+        &mut self,
+        asset_key: &AssetKey,
+        flags: &[FlaggedPoint],
+    ) -> Result<(), DataProviderError> {
+        let asset_key_str = asset_key.as_string();
+        let transaction = self
+            .conn
+            .transaction()
+            .map_err(|e| DataProviderError::Other(format!("Failed to start transaction: {}", e)))?;
+        transaction
+            .execute(
+                "DELETE FROM quality_flags WHERE asset_key = ?1",
+                [&asset_key_str],
+            )
+            .map_err(|e| DataProviderError::Other(format!("Failed to clear flags: {}", e)))?;
+        {
+            let mut stmt = transaction
+                .prepare(
+                    "INSERT OR REPLACE INTO quality_flags (asset_key, timestamp, flag, detail)
+                    VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+            for flagged in flags {
+                stmt.execute(rusqlite::params![
+                    asset_key_str,
+                    flagged.timestamp.to_rfc3339(),
+                    flagged.flag.name(),
+                    flagged.detail
+                ])
+                .map_err(|e| DataProviderError::Other(format!("Failed to store flag: {}", e)))?;
+            }
+        }
+        transaction.commit().map_err(|e| {
+            DataProviderError::Other(format!("Failed to commit transaction: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Returns the stored quality flags of an asset within `date_range`, in
+    /// timestamp order.
+    pub fn get_quality_flags(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<FlaggedPoint>, DataProviderError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT timestamp, flag, detail FROM quality_flags
+                WHERE asset_key = ?1
+                AND date(timestamp) >= ?2
+                AND date(timestamp) <= ?3
+                ORDER BY timestamp, flag",
+            )
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let rows = stmt
+            .query_map(
+                [
+                    asset_key.as_string(),
+                    date_range.start.format("%Y-%m-%d").to_string(),
+                    date_range.end.format("%Y-%m-%d").to_string(),
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let mut flags = Vec::new();
+        for row in rows {
+            let (timestamp, flag, detail) =
+                row.map_err(|e| DataProviderError::Other(format!("Row parsing error: {}", e)))?;
+            flags.push(FlaggedPoint {
+                timestamp: DateTime::parse_from_rfc3339(&timestamp)
+                    .map_err(|e| DataProviderError::Other(format!("Invalid timestamp: {}", e)))?
+                    .with_timezone(&Utc),
+                flag: QualityFlag::from_name(&flag).ok_or_else(|| {
+                    DataProviderError::Other(format!("Unknown quality flag '{}'", flag))
+                })?,
+                detail,
+            });
+        }
+        Ok(flags)
+    }
+
+    /// Returns the refresh progress of an asset, or `None` if it was never refreshed.
+    pub fn get_sync_state(
+        &self,
+        asset_key: &AssetKey,
+    ) -> Result<Option<SyncState>, DataProviderError> {
+        Ok(self
+            .query_sync_states("WHERE asset_key = ?1", [asset_key.as_string()])?
+            .pop())
+    }
+
+    /// Lists the refresh progress of every refreshed asset, ordered by asset key.
+    pub fn list_sync_states(&self) -> Result<Vec<SyncState>, DataProviderError> {
+        self.query_sync_states("", [])
+    }
+
+    /// Records a successful refresh: the asset is synced through `watermark`
+    /// (if any) and its last error is cleared.
+    pub fn record_sync_success(
+        &self,
+        asset_key: &AssetKey,
+        watermark: Option<NaiveDate>,
+        at: DateTime<Utc>,
+    ) -> Result<(), DataProviderError> {
+        let watermark = watermark.map(|date| date.format("%Y-%m-%d").to_string());
+        self.conn
+            .execute(
+                "INSERT INTO sync_state (asset_key, watermark, last_attempt, last_success)
+                VALUES (?1, ?2, ?3, ?3)
+                ON CONFLICT(asset_key) DO UPDATE SET
+                    watermark = COALESCE(excluded.watermark, watermark),
+                    last_attempt = excluded.last_attempt,
+                    last_success = excluded.last_success,
+                    last_error = NULL",
+                rusqlite::params![asset_key.as_string(), watermark, at.to_rfc3339()],
+            )
+            .map_err(|e| DataProviderError::Other(format!("Failed to record sync: {}", e)))?;
+        Ok(())
+    }
+
+    /// Records a failed refresh, keeping the previous watermark.
+    pub fn record_sync_failure(
+        &self,
+        asset_key: &AssetKey,
+        error: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), DataProviderError> {
+        self.conn
+            .execute(
+                "INSERT INTO sync_state (asset_key, last_attempt, last_error)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(asset_key) DO UPDATE SET
+                    last_attempt = excluded.last_attempt,
+                    last_error = excluded.last_error",
+                rusqlite::params![asset_key.as_string(), at.to_rfc3339(), error],
+            )
+            .map_err(|e| DataProviderError::Other(format!("Failed to record sync: {}", e)))?;
+        Ok(())
+    }
+
+    /// Runs a query selecting `(timestamp, close_price)` rows as points.
+    fn read_points<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        // Query time-series data using prepared statement for performance
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let rows = stmt
+            .query_map(params, |row| {
+                let timestamp_str: String = row.get(0)?;
+                let close_price: f64 = row.get(1)?;
+
+                // Parse timestamp from string (stored as ISO 8601)
+                let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
+                    .map_err(|e| {
+                        rusqlite::Error::InvalidColumnType(
+                            0,
+                            format!("Invalid timestamp: {}", e),
+                            rusqlite::types::Type::Text,
+                        )
+                    })?
+                    .with_timezone(&Utc);
+
+                Ok(TimeSeriesPoint::new(timestamp, close_price))
+            })
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let mut points = Vec::new();
+        for row_result in rows {
+            match row_result {
+                Ok(point) => points.push(point),
+                Err(e) => {
+                    return Err(DataProviderError::Other(format!(
+                        "Row parsing error: {}",
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(points)
+    }
+
+    fn query_sync_states<P: rusqlite::Params>(
+        &self,
+        condition: &str,
+        params: P,
+    ) -> Result<Vec<SyncState>, DataProviderError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT asset_key, watermark, last_attempt, last_success, last_error
+                FROM sync_state {} ORDER BY asset_key",
+                condition
+            ))
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })
+            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+
+        let parse_time = |value: Option<String>| {
+            value
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|time| time.with_timezone(&Utc))
+                        .map_err(|e| DataProviderError::Other(format!("Invalid timestamp: {}", e)))
+                })
+                .transpose()
+        };
+
+        let mut states = Vec::new();
+        for row in rows {
+            let (key, watermark, last_attempt, last_success, last_error) =
+                row.map_err(|e| DataProviderError::Other(format!("Row parsing error: {}", e)))?;
+            states.push(SyncState {
+                key: AssetKey::parse(&key)
+                    .map_err(|e| DataProviderError::Other(format!("Invalid asset key: {}", e)))?,
+                watermark: watermark
+                    .map(|date| {
+                        NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| {
+                            DataProviderError::Other(format!("Invalid watermark: {}", e))
+                        })
+                    })
+                    .transpose()?,
+                last_attempt: parse_time(last_attempt)?,
+                last_success: parse_time(last_success)?,
+                last_error,
+            });
+        }
+        Ok(states)
+    }
+
     /// Stores an analytics result in the database.
     ///
     /// The analytics value is stored as a JSON blob for flexibility.
@@ -568,6 +1067,124 @@ impl SqliteDataProvider {
     }
 }
 
+/// Builds a catalog entry, without coverage, from a stored asset blob.
+fn summarize_asset(asset_json: &str) -> Result<AssetSummary, DataProviderError> {
+    let invalid = |e: serde_json::Error| {
+        DataProviderError::Other(format!("Failed to deserialize asset: {}", e))
+    };
+    let value: serde_json::Value = serde_json::from_str(asset_json).map_err(invalid)?;
+    let key: AssetKey = serde_json::from_value(value["key"].clone()).map_err(invalid)?;
+
+    let summary = |asset_type, name: &str, exchange: &str, sector, currency: &str| AssetSummary {
+        key: key.clone(),
+        asset_type,
+        name: name.to_string(),
+        exchange: exchange.to_string(),
+        sector,
+        currency: currency.to_string(),
+        first_date: None,
+        last_date: None,
+        point_count: 0,
+    };
+    Ok(match key {
+        AssetKey::Equity(_) => {
+            let equity: Equity = serde_json::from_value(value).map_err(invalid)?;
+            summary(
+                AssetType::Equity,
+                equity.name(),
+                equity.exchange(),
+                Some(equity.sector().to_string()),
+                equity.currency(),
+            )
+        }
+        AssetKey::Future { .. } => {
+            let future: Future = serde_json::from_value(value).map_err(invalid)?;
+            summary(
+                AssetType::Future,
+                future.name(),
+                future.exchange(),
+                None,
+                future.currency(),
+            )
+        }
+        AssetKey::FxRate { .. } => {
+            let fx: FxRate = serde_json::from_value(value).map_err(invalid)?;
+            summary(
+                AssetType::FxRate,
+                fx.name(),
+                fx.source(),
+                None,
+                fx.currency(),
+            )
+        }
+    })
+}
+
+/// Formats a knowledge time so that stored knowledge times sort chronologically.
+fn knowledge_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Records `point` as the price of its timestamp known from `recorded_at`.
+///
+/// A changed price moves the current one to `time_series_history`, closing its
+/// knowledge interval at `recorded_at`; an unchanged price is left as stored.
+fn record_point(
+    conn: &Connection,
+    asset_key: &str,
+    point: &TimeSeriesPoint,
+    recorded_at: &str,
+) -> Result<(), DataProviderError> {
+    let sql_error = |e: rusqlite::Error| DataProviderError::Other(format!("SQL error: {}", e));
+    let timestamp = point.timestamp.to_rfc3339();
+    let current: Option<(f64, String)> = conn
+        .query_row(
+            "SELECT close_price, recorded_at FROM time_series_data
+            WHERE asset_key = ?1 AND timestamp = ?2",
+            [asset_key, &timestamp],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(sql_error)?;
+
+    if let Some((price, known_since)) = current {
+        if price == point.close_price {
+            return Ok(());
+        }
+        if recorded_at < known_since.as_str() {
+            return Err(DataProviderError::Other(format!(
+                "Price at {} was recorded at {}, after {}",
+                timestamp, known_since, recorded_at
+            )));
+        }
+        // A price replaced at the moment it was recorded was never known
+        if recorded_at > known_since.as_str() {
+            conn.execute(
+                "INSERT INTO time_series_history
+                (asset_key, timestamp, close_price, recorded_at, superseded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![asset_key, timestamp, price, known_since, recorded_at],
+            )
+            .map_err(sql_error)?;
+        }
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO time_series_data (asset_key, timestamp, close_price, recorded_at)
+        VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![asset_key, timestamp, point.close_price, recorded_at],
+    )
+    .map_err(sql_error)?;
+    Ok(())
+}
+
+/// Parses the date of a stored RFC 3339 timestamp.
+fn parse_stored_date(timestamp: &str) -> Result<NaiveDate, DataProviderError> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|timestamp| timestamp.with_timezone(&Utc).date_naive())
+        .map_err(|e| DataProviderError::Other(format!("Invalid timestamp: {}", e)))
+}
+
 impl DataProvider for SqliteDataProvider {
     fn get_time_series(
         &self,
@@ -586,67 +1203,59 @@ impl DataProvider for SqliteDataProvider {
         let start_date_str = date_range.start.format("%Y-%m-%d").to_string();
         let end_date_str = date_range.end.format("%Y-%m-%d").to_string();
 
-        // Query time-series data using prepared statement for performance
         // Use date() function to extract date part from timestamp for comparison
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT timestamp, close_price FROM time_series_data 
+        let points = self.read_points(
+            "SELECT timestamp, close_price FROM time_series_data 
              WHERE asset_key = ?1 
              AND date(timestamp) >= ?2 
              AND date(timestamp) <= ?3 
              ORDER BY timestamp",
-            )
-            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
-
-        let rows = stmt
-            .query_map([&asset_key_str, &start_date_str, &end_date_str], |row| {
-                let timestamp_str: String = row.get(0)?;
-                let close_price: f64 = row.get(1)?;
+            [&asset_key_str, &start_date_str, &end_date_str],
+        )?;
 
-                // Parse timestamp from string (stored as ISO 8601)
-                let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-                    .map_err(|e| {
-                        rusqlite::Error::InvalidColumnType(
-                            0,
-                            format!("Invalid timestamp: {}", e),
-                            rusqlite::types::Type::Text,
-                        )
-                    })?
-                    .with_timezone(&Utc);
+        // If no points found, check if asset exists at all
+        if points.is_empty() && !self.asset_exists(asset_key)? {
+            return Err(DataProviderError::AssetNotFound);
+        }
 
-                Ok(TimeSeriesPoint::new(timestamp, close_price))
-            })
-            .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+        Ok(points)
+    }
 
-        let mut points = Vec::new();
-        for row_result in rows {
-            match row_result {
-                Ok(point) => points.push(point),
-                Err(e) => {
-                    return Err(DataProviderError::Other(format!(
-                        "Row parsing error: {}",
-                        e
-                    )))
-                }
-            }
+    fn get_time_series_as_of(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        if date_range.start > date_range.end {
+            return Err(DataProviderError::InvalidDateRange);
         }
 
-        // If no points found, check if asset exists at all
-        if points.is_empty() {
-            // Check if asset exists in assets table
-            let mut check_stmt = self
-                .conn
-                .prepare("SELECT 1 FROM assets WHERE asset_key = ?1 LIMIT 1")
-                .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+        let asset_key_str = asset_key.as_string();
+        let start_date_str = date_range.start.format("%Y-%m-%d").to_string();
+        let end_date_str = date_range.end.format("%Y-%m-%d").to_string();
+        let as_of_str = knowledge_time(as_of);
 
-            let asset_exists = check_stmt
-                .exists([&asset_key_str])
-                .map_err(|e| DataProviderError::Other(format!("SQL error: {}", e)))?;
+        // Each timestamp has at most one version whose knowledge interval holds as_of
+        let points = self.read_points(
+            "SELECT timestamp, close_price FROM time_series_data
+                WHERE asset_key = ?1
+                AND date(timestamp) >= ?2
+                AND date(timestamp) <= ?3
+                AND recorded_at <= ?4
+            UNION ALL
+            SELECT timestamp, close_price FROM time_series_history
+                WHERE asset_key = ?1
+                AND date(timestamp) >= ?2
+                AND date(timestamp) <= ?3
+                AND recorded_at <= ?4
+                AND superseded_at > ?4
+            ORDER BY timestamp",
+            [&asset_key_str, &start_date_str, &end_date_str, &as_of_str],
+        )?;
 
-            if !asset_exists {
-                return Err(DataProviderError::AssetNotFound);
-            }
+        if points.is_empty() && !self.asset_exists(asset_key)? {
+            return Err(DataProviderError::AssetNotFound);
         }
 
         Ok(points)
@@ -1081,6 +1690,114 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
 
+    #[test]
+    fn test_corrections_keep_prices_as_known_before() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let asset_key = AssetKey::new_equity("AAPL").unwrap();
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 16, 0, 0).unwrap();
+        let loaded = Utc.with_ymd_and_hms(2024, 2, 1, 18, 0, 0).unwrap();
+        let corrected = Utc.with_ymd_and_hms(2024, 2, 5, 18, 0, 0).unwrap();
+        let date_range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let closes = |points: Vec<TimeSeriesPoint>| -> Vec<f64> {
+            points.iter().map(|point| point.close_price).collect()
+        };
+
+        let original = [
+            TimeSeriesPoint::new(day(15), 150.0),
+            TimeSeriesPoint::new(day(16), 151.0),
+        ];
+        provider
+            .record_time_series_batch(&asset_key, &original, loaded)
+            .unwrap();
+        // A vendor correction of the 16th, re-sending the unchanged 15th
+        let correction = [
+            TimeSeriesPoint::new(day(15), 150.0),
+            TimeSeriesPoint::new(day(16), 155.0),
+            TimeSeriesPoint::new(day(17), 152.0),
+        ];
+        provider
+            .record_time_series_batch(&asset_key, &correction, corrected)
+            .unwrap();
+
+        let current = provider.get_time_series(&asset_key, &date_range).unwrap();
+        assert_eq!(closes(current), vec![150.0, 155.0, 152.0]);
+        let as_of = |time| {
+            provider
+                .get_time_series_as_of(&asset_key, &date_range, time)
+                .map(closes)
+        };
+        assert_eq!(as_of(loaded).unwrap(), vec![150.0, 151.0]);
+        assert_eq!(
+            as_of(corrected - chrono::Duration::seconds(1)).unwrap(),
+            vec![150.0, 151.0]
+        );
+        assert_eq!(as_of(corrected).unwrap(), vec![150.0, 155.0, 152.0]);
+        // Nothing was known before the first load, and the asset was never stored
+        assert_eq!(
+            as_of(loaded - chrono::Duration::days(1)),
+            Err(DataProviderError::AssetNotFound)
+        );
+
+        // The unchanged price kept its knowledge time and only one version was replaced
+        let history: i64 = provider
+            .connection()
+            .query_row("SELECT COUNT(*) FROM time_series_history", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(history, 1);
+
+        // Knowledge time cannot go backwards for a price it replaces
+        let backdated = [TimeSeriesPoint::new(day(16), 160.0)];
+        assert!(provider
+            .record_time_series_batch(&asset_key, &backdated, loaded)
+            .is_err());
+    }
+
+    #[test]
+    fn test_prices_stored_before_versioning_are_known_since_ever() {
+        let temp_dir = std::env::temp_dir();
+        let db_path = temp_dir.join(format!("test_unversioned_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute(
+                "CREATE TABLE time_series_data (
+                    asset_key TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    close_price REAL NOT NULL,
+                    PRIMARY KEY (asset_key, timestamp)
+                )",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO time_series_data (asset_key, timestamp, close_price) VALUES (?1, ?2, ?3)",
+                rusqlite::params!["AAPL", "2024-01-15T16:00:00+00:00", 150.0],
+            )
+            .unwrap();
+        }
+
+        let provider = SqliteDataProvider::new(&db_path).unwrap();
+        let asset_key = AssetKey::new_equity("AAPL").unwrap();
+        let date_range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        );
+        let as_of = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+        let result = provider
+            .get_time_series_as_of(&asset_key, &date_range, as_of)
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].close_price, 150.0);
+
+        drop(provider);
+        let _ = std::fs::remove_file(&db_path);
+    }
+
     #[test]
     fn test_store_asset_equity() {
         let provider = SqliteDataProvider::new_in_memory().unwrap();
@@ -1124,6 +1841,157 @@ mod tests {
         assert_eq!(retrieved.name(), future.name());
     }
 
+    #[test]
+    fn test_store_asset_fx_and_currency_lookup() {
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        let fx = FxRate::new("EUR", "USD", "Euro / US Dollar", "WMR").unwrap();
+        provider.store_asset_fx(&fx).unwrap();
+
+        let retrieved = provider.get_asset_fx(fx.key()).unwrap();
+        assert_eq!(retrieved, fx);
+
+        let equity = Equity::new("SAP", "SAP SE", "XETRA", "EUR", "Technology").unwrap();
+        provider.store_asset_equity(&equity).unwrap();
+        assert_eq!(provider.asset_currency(equity.key()).unwrap(), "EUR");
+        assert_eq!(provider.asset_currency(fx.key()).unwrap(), "USD");
+
+        let missing = AssetKey::new_equity("MISSING").unwrap();
+        assert!(matches!(
+            provider.asset_currency(&missing),
+            Err(DataProviderError::AssetNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_list_assets_filters_and_reports_coverage() {
+        let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+        let apple = Equity::new("AAPL", "Apple Inc.", "NASDAQ", "USD", "Technology").unwrap();
+        let sap = Equity::new("SAP", "SAP SE", "XETRA", "EUR", "Technology").unwrap();
+        let future = Future::new(
+            "ES",
+            NaiveDate::from_ymd_opt(2024, 12, 20).unwrap(),
+            "2024-12",
+            "E-mini S&P 500",
+            "CME",
+            "USD",
+            "CME",
+            5,
+        )
+        .unwrap();
+        provider.store_asset_equity(&apple).unwrap();
+        provider.store_asset_equity(&sap).unwrap();
+        provider.store_asset_future(&future).unwrap();
+        let points: Vec<_> = (1..=3)
+            .map(|day| {
+                TimeSeriesPoint::new(Utc.with_ymd_and_hms(2024, 1, day, 16, 0, 0).unwrap(), 100.0)
+            })
+            .collect();
+        provider
+            .insert_time_series_batch(apple.key(), &points)
+            .unwrap();
+
+        let all = provider.list_assets(&AssetFilter::default()).unwrap();
+        assert_eq!(all.total, 3);
+        let keys: Vec<_> = all.assets.iter().map(|a| a.key.as_string()).collect();
+        assert_eq!(keys, ["AAPL", "ES-2024-12-20", "SAP"]);
+        assert_eq!(all.assets[0].point_count, 3);
+        assert_eq!(
+            all.assets[0].first_date,
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+        );
+        assert_eq!(all.assets[0].last_date, NaiveDate::from_ymd_opt(2024, 1, 3));
+        assert_eq!(all.assets[1].asset_type, AssetType::Future);
+        assert_eq!(all.assets[2].point_count, 0);
+        assert_eq!(all.assets[2].first_date, None);
+
+        let technology = AssetFilter {
+            sector: Some("technology".to_string()),
+            currency: Some("EUR".to_string()),
+            ..AssetFilter::default()
+        };
+        let page = provider.list_assets(&technology).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.assets[0].name, "SAP SE");
+
+        let search = AssetFilter {
+            search: Some("s&p".to_string()),
+            ..AssetFilter::default()
+        };
+        assert_eq!(
+            provider.list_assets(&search).unwrap().assets[0].key,
+            *future.key()
+        );
+
+        let paged = AssetFilter {
+            offset: 1,
+            limit: Some(1),
+            ..AssetFilter::default()
+        };
+        let page = provider.list_assets(&paged).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.assets.len(), 1);
+        assert_eq!(page.assets[0].exchange, "CME");
+    }
+
+    #[test]
+    fn test_delete_asset_keeps_prices() {
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        let equity = Equity::new("AAPL", "Apple Inc.", "NASDAQ", "USD", "Technology").unwrap();
+        provider.store_asset_equity(&equity).unwrap();
+        let point =
+            TimeSeriesPoint::new(Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap(), 185.0);
+        provider
+            .insert_time_series_point(equity.key(), &point)
+            .unwrap();
+
+        assert!(provider.asset_exists(equity.key()).unwrap());
+        provider.delete_asset(equity.key()).unwrap();
+        assert!(!provider.asset_exists(equity.key()).unwrap());
+        assert!(matches!(
+            provider.delete_asset(equity.key()),
+            Err(DataProviderError::AssetNotFound)
+        ));
+
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+        let prices = provider.get_time_series(equity.key(), &range).unwrap();
+        assert_eq!(prices.len(), 1);
+    }
+
+    #[test]
+    fn test_sync_state_keeps_watermark_across_failures() {
+        let provider = SqliteDataProvider::new_in_memory().unwrap();
+        let aapl = AssetKey::new_equity("AAPL").unwrap();
+        assert_eq!(provider.get_sync_state(&aapl).unwrap(), None);
+
+        let first = Utc.with_ymd_and_hms(2024, 1, 5, 22, 0, 0).unwrap();
+        let watermark = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        provider
+            .record_sync_success(&aapl, Some(watermark), first)
+            .unwrap();
+
+        let second = Utc.with_ymd_and_hms(2024, 1, 8, 22, 0, 0).unwrap();
+        provider
+            .record_sync_failure(&aapl, "API error: HTTP 500", second)
+            .unwrap();
+        let state = provider.get_sync_state(&aapl).unwrap().unwrap();
+        assert_eq!(state.watermark, Some(watermark));
+        assert_eq!(state.last_attempt, Some(second));
+        assert_eq!(state.last_success, Some(first));
+        assert_eq!(state.last_error.as_deref(), Some("API error: HTTP 500"));
+
+        // A success without new prices clears the error but keeps the watermark
+        let third = Utc.with_ymd_and_hms(2024, 1, 9, 22, 0, 0).unwrap();
+        provider.record_sync_success(&aapl, None, third).unwrap();
+        let states = provider.list_sync_states().unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].watermark, Some(watermark));
+        assert_eq!(states[0].last_success, Some(third));
+        assert_eq!(states[0].last_error, None);
+    }
+
     #[test]
     fn test_get_asset_equity_not_found() {
         let provider = SqliteDataProvider::new_in_memory().unwrap();
@@ -83,6 +83,26 @@ pub trait DataProvider {
         asset_key: &AssetKey,
         date_range: &DateRange,
     ) -> Result<Vec<DateTime<Utc>>, DataProviderError>;
+
+    /// Retrieves time-series data as it was known at `as_of`: for each
+    /// timestamp, the value recorded at or before `as_of` and not yet replaced
+    /// by a correction then.
+    ///
+    /// # Errors
+    /// Returns an error like [`DataProvider::get_time_series`], and by default
+    /// for providers that keep no history of replaced values.
+    fn get_time_series_as_of(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        let _ = (asset_key, date_range);
+        Err(DataProviderError::Other(format!(
+            "Data as of {} is unavailable: the provider keeps no price history",
+            as_of.to_rfc3339()
+        )))
+    }
 }
 
 /// Errors that can occur when querying a data provider.
@@ -204,6 +224,63 @@ impl DataProvider for InMemoryDataProvider {
     }
 }
 
+/// A view of a provider that reads its data as known at a past moment.
+///
+/// Wrapping the provider of a pull-mode query reproduces the results that were
+/// computed at `as_of`, before any later corrections. Without `as_of` the view
+/// reads the current data.
+#[derive(Clone, Copy)]
+pub struct AsOfProvider<'a> {
+    provider: &'a dyn DataProvider,
+    as_of: Option<DateTime<Utc>>,
+}
+
+impl<'a> AsOfProvider<'a> {
+    pub fn new(provider: &'a dyn DataProvider, as_of: Option<DateTime<Utc>>) -> Self {
+        AsOfProvider { provider, as_of }
+    }
+}
+
+impl DataProvider for AsOfProvider<'_> {
+    fn get_time_series(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        match self.as_of {
+            Some(as_of) => self
+                .provider
+                .get_time_series_as_of(asset_key, date_range, as_of),
+            None => self.provider.get_time_series(asset_key, date_range),
+        }
+    }
+
+    fn available_dates(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<DateTime<Utc>>, DataProviderError> {
+        if self.as_of.is_none() {
+            return self.provider.available_dates(asset_key, date_range);
+        }
+        Ok(self
+            .get_time_series(asset_key, date_range)?
+            .into_iter()
+            .map(|point| point.timestamp)
+            .collect())
+    }
+
+    fn get_time_series_as_of(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<TimeSeriesPoint>, DataProviderError> {
+        self.provider
+            .get_time_series_as_of(asset_key, date_range, as_of)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,21 +1,33 @@
 use crate::asset_key::AssetKey;
+use crate::market_data::{self, MarketDataSource};
 use crate::sqlite_provider::SqliteDataProvider;
-use crate::time_series::{DataProvider, DateRange, TimeSeriesPoint};
+use crate::time_series::{DateRange, TimeSeriesPoint};
 use chrono::{NaiveDate, Utc};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use std::collections::HashMap;
 use std::io::Cursor;
 use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Default Yahoo Finance API host.
+pub const YAHOO_FINANCE_BASE_URL: &str = "https://query1.finance.yahoo.com";
 
 /// Configuration for Yahoo Finance downloader
 #[derive(Debug, Clone)]
 pub struct DownloaderConfig {
     /// Maximum number of retry attempts (default: 3)
     pub max_retries: u32,
-    /// Rate limit: requests per second (default: 1.0)
+    /// Rate limit: requests per second (default: 1.0, zero or less disables throttling)
     pub requests_per_second: f64,
     /// Request timeout in seconds (default: 30)
     pub timeout_seconds: u64,
+    /// API host the download URLs are built against (default: Yahoo Finance)
+    pub base_url: String,
+    /// Delay before the first retry in milliseconds, doubled on each attempt (default: 1000)
+    pub retry_delay_ms: u64,
+    /// Longest `Retry-After` delay honoured, in seconds; longer ones are cut to it (default: 60)
+    pub max_retry_after_seconds: u64,
 }
 
 impl Default for DownloaderConfig {
@@ -24,6 +36,9 @@ impl Default for DownloaderConfig {
             max_retries: 3,
             requests_per_second: 1.0,
             timeout_seconds: 30,
+            base_url: YAHOO_FINANCE_BASE_URL.to_string(),
+            retry_delay_ms: 1000,
+            max_retry_after_seconds: 60,
         }
     }
 }
@@ -35,6 +50,7 @@ impl Default for DownloaderConfig {
 pub struct YahooFinanceDownloader {
     client: Client,
     config: DownloaderConfig,
+    last_request: Mutex<Option<Instant>>,
 }
 
 impl YahooFinanceDownloader {
@@ -60,7 +76,11 @@ impl YahooFinanceDownloader {
             .build()
             .map_err(|e| DownloadError::ClientCreation(e.to_string()))?;
 
-        Ok(YahooFinanceDownloader { client, config })
+        Ok(YahooFinanceDownloader {
+            client,
+            config,
+            last_request: Mutex::new(None),
+        })
     }
 
     /// Converts an AssetKey to Yahoo Finance symbol format.
@@ -74,6 +94,7 @@ impl YahooFinanceDownloader {
     /// # Examples
     /// - Equity "AAPL" -> "AAPL"
     /// - Future "ES" with expiry -> "ES=F" or contract-specific format
+    /// - FX rate "EUR/USD" -> "EURUSD=X"
     pub fn asset_key_to_symbol(&self, asset_key: &AssetKey) -> String {
         match asset_key {
             AssetKey::Equity(ticker) => ticker.clone(),
@@ -87,6 +108,7 @@ impl YahooFinanceDownloader {
                 // Note: expiry_date is not used in the current implementation but may be needed for specific contracts
                 format!("{}={}", series, "F")
             }
+            AssetKey::FxRate { base, quote } => format!("{}{}=X", base, quote),
         }
     }
 
@@ -120,8 +142,8 @@ impl YahooFinanceDownloader {
         end_date: NaiveDate,
         attempt: u32,
     ) -> Result<String, DownloadError> {
-        // Yahoo Finance historical data endpoint
-        // Format: https://query1.finance.yahoo.com/v7/finance/download/{symbol}?period1={start_timestamp}&period2={end_timestamp}&interval=1d&events=history
+        // Yahoo Finance historical data endpoint, relative to `config.base_url`
+        // Format: {base_url}/v7/finance/download/{symbol}?period1={start_timestamp}&period2={end_timestamp}&interval=1d&events=history
         let start_timestamp = start_date
             .and_hms_opt(0, 0, 0)
             .ok_or_else(|| DownloadError::InvalidDate("Invalid start date".to_string()))?
@@ -137,12 +159,16 @@ impl YahooFinanceDownloader {
             .timestamp();
 
         let url = format!(
-            "https://query1.finance.yahoo.com/v7/finance/download/{}?period1={}&period2={}&interval=1d&events=history",
-            symbol, start_timestamp, end_timestamp
+            "{}/v7/finance/download/{}?period1={}&period2={}&interval=1d&events=history",
+            self.config.base_url.trim_end_matches('/'),
+            symbol,
+            start_timestamp,
+            end_timestamp
         );
 
         let mut current_attempt = attempt;
         loop {
+            self.throttle().await;
             let response = match self.client.get(&url).send().await {
                 Ok(r) => r,
                 Err(e) => {
@@ -155,15 +181,7 @@ impl YahooFinanceDownloader {
                     );
 
                     if current_attempt < self.config.max_retries {
-                        let backoff_seconds = 2_u64.pow(current_attempt);
-                        log::info!(
-                            "Retrying {} in {} seconds (attempt {}/{})",
-                            symbol,
-                            backoff_seconds,
-                            current_attempt + 1,
-                            self.config.max_retries
-                        );
-                        tokio::time::sleep(Duration::from_secs(backoff_seconds)).await;
+                        self.wait_before_retry(symbol, current_attempt, None).await;
                         current_attempt += 1;
                         continue;
                     } else {
@@ -192,15 +210,21 @@ impl YahooFinanceDownloader {
                 );
 
                 if current_attempt < self.config.max_retries {
-                    let backoff_seconds = 2_u64.pow(current_attempt);
-                    log::info!(
-                        "Retrying {} in {} seconds (attempt {}/{})",
-                        symbol,
-                        backoff_seconds,
-                        current_attempt + 1,
-                        self.config.max_retries
-                    );
-                    tokio::time::sleep(Duration::from_secs(backoff_seconds)).await;
+                    // A 429 tells us how long to back off; honour it over our own
+                    // schedule, up to the configured maximum
+                    let retry_after = if status == StatusCode::TOO_MANY_REQUESTS {
+                        response
+                            .headers()
+                            .get(reqwest::header::RETRY_AFTER)
+                            .and_then(|value| value.to_str().ok())
+                            .and_then(|value| value.trim().parse::<u64>().ok())
+                            .map(|seconds| seconds.min(self.config.max_retry_after_seconds))
+                            .map(Duration::from_secs)
+                    } else {
+                        None
+                    };
+                    self.wait_before_retry(symbol, current_attempt, retry_after)
+                        .await;
                     current_attempt += 1;
                     continue;
                 } else {
@@ -225,15 +249,7 @@ impl YahooFinanceDownloader {
                     );
 
                     if current_attempt < self.config.max_retries {
-                        let backoff_seconds = 2_u64.pow(current_attempt);
-                        log::info!(
-                            "Retrying {} in {} seconds (attempt {}/{})",
-                            symbol,
-                            backoff_seconds,
-                            current_attempt + 1,
-                            self.config.max_retries
-                        );
-                        tokio::time::sleep(Duration::from_secs(backoff_seconds)).await;
+                        self.wait_before_retry(symbol, current_attempt, None).await;
                         current_attempt += 1;
                         continue;
                     } else {
@@ -261,6 +277,36 @@ impl YahooFinanceDownloader {
         }
     }
 
+    /// Waits until the configured request rate allows another request.
+    async fn throttle(&self) {
+        if self.config.requests_per_second <= 0.0 {
+            return;
+        }
+        let interval = Duration::from_secs_f64(1.0 / self.config.requests_per_second);
+        let mut last_request = self.last_request.lock().await;
+        if let Some(previous) = *last_request {
+            tokio::time::sleep_until(previous + interval).await;
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    /// Sleeps before the next attempt, using exponential backoff unless the
+    /// server asked for a specific delay.
+    async fn wait_before_retry(&self, symbol: &str, attempt: u32, retry_after: Option<Duration>) {
+        let backoff = 1_u64 << attempt.min(16);
+        let delay = retry_after.unwrap_or(Duration::from_millis(
+            self.config.retry_delay_ms.saturating_mul(backoff),
+        ));
+        log::info!(
+            "Retrying {} in {:?} (attempt {}/{})",
+            symbol,
+            delay,
+            attempt + 1,
+            self.config.max_retries
+        );
+        tokio::time::sleep(delay).await;
+    }
+
     /// Parses Yahoo Finance CSV response and converts to TimeSeriesPoint structs.
     ///
     /// # Arguments
@@ -376,104 +422,7 @@ impl YahooFinanceDownloader {
         asset_key: &AssetKey,
         date_range: &DateRange,
     ) -> Result<usize, DownloadError> {
-        let asset_str = asset_key.to_string();
-        log::debug!(
-            "Starting download for asset: {} ({} to {})",
-            asset_str,
-            date_range.start,
-            date_range.end
-        );
-
-        // Check for existing data in SQLite
-        // If asset not found, treat as empty (no existing data)
-        let existing_data = match provider.get_time_series(asset_key, date_range) {
-            Ok(data) => data,
-            Err(crate::time_series::DataProviderError::AssetNotFound) => {
-                // Asset doesn't exist yet - no existing data
-                log::debug!(
-                    "Asset {} not found in database, will download all dates",
-                    asset_str
-                );
-                Vec::new()
-            }
-            Err(e) => {
-                log::error!("Failed to query existing data for {}: {}", asset_str, e);
-                return Err(DownloadError::ParseError(format!(
-                    "Failed to query existing data: {}",
-                    e
-                )));
-            }
-        };
-
-        // Extract existing dates
-        let existing_dates: std::collections::HashSet<NaiveDate> = existing_data
-            .iter()
-            .map(|point| point.timestamp.date_naive())
-            .collect();
-
-        // Determine which dates need to be downloaded
-        let mut dates_to_download = Vec::new();
-        let mut current_date = date_range.start;
-        while current_date <= date_range.end {
-            if !existing_dates.contains(&current_date) {
-                dates_to_download.push(current_date);
-            }
-            current_date = current_date.succ_opt().unwrap_or(current_date);
-        }
-
-        // If all dates already exist, return early
-        if dates_to_download.is_empty() {
-            log::info!(
-                "All dates already exist for {}, skipping download",
-                asset_str
-            );
-            return Ok(0);
-        }
-
-        log::info!(
-            "Downloading {} missing dates for {}",
-            dates_to_download.len(),
-            asset_str
-        );
-
-        // Download data for the date range (Yahoo Finance API downloads the full range)
-        let symbol = self.asset_key_to_symbol(asset_key);
-        let points = self
-            .download_and_parse(&symbol, date_range.start, date_range.end)
-            .await?;
-
-        // Filter out points that already exist in database
-        let new_points: Vec<TimeSeriesPoint> = points
-            .into_iter()
-            .filter(|point| {
-                let point_date = point.timestamp.date_naive();
-                !existing_dates.contains(&point_date)
-            })
-            .collect();
-
-        // Store new points using batch insert
-        if !new_points.is_empty() {
-            log::debug!(
-                "Storing {} new data points for {}",
-                new_points.len(),
-                asset_str
-            );
-            provider
-                .insert_time_series_batch(asset_key, &new_points)
-                .map_err(|e| {
-                    log::error!("Failed to store data for {}: {}", asset_str, e);
-                    DownloadError::ParseError(format!("Failed to store data: {}", e))
-                })?;
-            log::info!(
-                "Successfully stored {} data points for {}",
-                new_points.len(),
-                asset_str
-            );
-        } else {
-            log::info!("No new data points to store for {}", asset_str);
-        }
-
-        Ok(new_points.len())
+        market_data::download_to_sqlite(self, provider, asset_key, date_range).await
     }
 
     /// Downloads historical data for multiple assets with partial failure handling.
@@ -519,94 +468,8 @@ impl YahooFinanceDownloader {
         provider: &mut SqliteDataProvider,
         assets: &[(AssetKey, DateRange)],
     ) -> DownloadResult {
-        let mut successful: HashMap<String, usize> = HashMap::new();
-        let mut failed: HashMap<String, String> = HashMap::new();
-        let mut to_retry: Vec<(AssetKey, DateRange)> = Vec::new();
-
-        log::info!("Starting download for {} assets", assets.len());
-
-        // First attempt: download all assets
-        for (asset_key, date_range) in assets {
-            let asset_str = asset_key.to_string();
-            log::info!("Downloading data for asset: {}", asset_str);
-
-            match self
-                .download_to_sqlite(provider, asset_key, date_range)
-                .await
-            {
-                Ok(count) => {
-                    log::info!(
-                        "Successfully downloaded {} data points for {}",
-                        count,
-                        asset_str
-                    );
-                    successful.insert(asset_str.clone(), count);
-                }
-                Err(e) => {
-                    log::warn!("Failed to download {}: {}", asset_str, e);
-                    to_retry.push((asset_key.clone(), date_range.clone()));
-                }
-            }
-        }
-
-        // Retry failed assets
-        if !to_retry.is_empty() {
-            log::info!("Retrying {} failed assets", to_retry.len());
-            let mut retry_attempt = 0;
-            let mut remaining_failures = to_retry;
-
-            while retry_attempt < self.config.max_retries && !remaining_failures.is_empty() {
-                retry_attempt += 1;
-                log::info!(
-                    "Retry attempt {}/{} for {} assets",
-                    retry_attempt,
-                    self.config.max_retries,
-                    remaining_failures.len()
-                );
-
-                let mut next_retry = Vec::new();
-
-                for (asset_key, date_range) in remaining_failures {
-                    let asset_str = asset_key.to_string();
-
-                    match self
-                        .download_to_sqlite(provider, &asset_key, &date_range)
-                        .await
-                    {
-                        Ok(count) => {
-                            log::info!(
-                                "Successfully downloaded {} data points for {} on retry",
-                                count,
-                                asset_str
-                            );
-                            successful.insert(asset_str.clone(), count);
-                        }
-                        Err(e) => {
-                            log::warn!("Retry {} failed for {}: {}", retry_attempt, asset_str, e);
-                            next_retry.push((asset_key, date_range));
-                        }
-                    }
-                }
-
-                remaining_failures = next_retry;
-            }
-
-            // Record final failures
-            for (asset_key, _) in remaining_failures {
-                let asset_str = asset_key.to_string();
-                let error_msg = format!("Failed after {} retry attempts", self.config.max_retries);
-                log::error!("Asset {} failed after all retries", asset_str);
-                failed.insert(asset_str, error_msg);
-            }
-        }
-
-        log::info!(
-            "Download complete: {} successful, {} failed",
-            successful.len(),
-            failed.len()
-        );
-
-        DownloadResult { successful, failed }
+        market_data::download_multiple_to_sqlite(self, provider, assets, self.config.max_retries)
+            .await
     }
 
     /// Returns a reference to the HTTP client.
@@ -620,6 +483,22 @@ impl YahooFinanceDownloader {
     }
 }
 
+impl MarketDataSource for YahooFinanceDownloader {
+    fn name(&self) -> &str {
+        "yahoo"
+    }
+
+    async fn fetch_history(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<TimeSeriesPoint>, DownloadError> {
+        let symbol = self.asset_key_to_symbol(asset_key);
+        self.download_and_parse(&symbol, date_range.start, date_range.end)
+            .await
+    }
+}
+
 /// Result of downloading multiple assets.
 ///
 /// Contains maps of successful and failed downloads.
@@ -702,6 +581,7 @@ impl std::error::Error for DownloadError {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::time_series::DataProvider;
 
     #[tokio::test]
     #[ignore = "requires network"]
@@ -717,6 +597,7 @@ mod tests {
             max_retries: 5,
             requests_per_second: 2.0,
             timeout_seconds: 60,
+            ..Default::default()
         };
         let downloader = YahooFinanceDownloader::with_config(config);
         assert!(downloader.is_ok());
@@ -746,6 +627,14 @@ mod tests {
         assert_eq!(symbol, "ES=F");
     }
 
+    #[tokio::test]
+    async fn test_asset_key_to_symbol_fx() {
+        let downloader = YahooFinanceDownloader::new().unwrap();
+        let asset_key = AssetKey::new_fx("EUR", "USD").unwrap();
+        let symbol = downloader.asset_key_to_symbol(&asset_key);
+        assert_eq!(symbol, "EURUSD=X");
+    }
+
     #[tokio::test]
     #[ignore = "requires network"]
     async fn test_fetch_historical_data_success() {
@@ -1184,6 +1073,7 @@ mod tests {
             max_retries: 2,
             requests_per_second: 1.0,
             timeout_seconds: 1, // Short timeout to trigger failures
+            ..Default::default()
         };
         let downloader = YahooFinanceDownloader::with_config(config).unwrap();
 
@@ -1222,6 +1112,7 @@ mod tests {
             max_retries: 1, // Very low retry limit
             requests_per_second: 1.0,
             timeout_seconds: 30,
+            ..Default::default()
         };
         let downloader = YahooFinanceDownloader::with_config(config).unwrap();
 
@@ -1395,6 +1286,7 @@ mod tests {
             max_retries: 2,
             requests_per_second: 1.0,
             timeout_seconds: 30,
+            ..Default::default()
         };
         let downloader = YahooFinanceDownloader::with_config(config).unwrap();
 
@@ -1434,6 +1326,7 @@ mod tests {
             max_retries: 3,
             requests_per_second: 1.0,
             timeout_seconds: 30,
+            ..Default::default()
         };
         let downloader = YahooFinanceDownloader::with_config(config).unwrap();
 
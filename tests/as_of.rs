@@ -0,0 +1,77 @@
+mod common;
+
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::sqlite_provider::SqliteDataProvider;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use common::{asset, provider, series, Fixture};
+
+const PRICES: [f64; 8] = [100.0, 101.0, 99.0, 103.0, 104.0, 102.0, 106.0, 108.0];
+const FIXTURE: Fixture = Fixture::new(8).with_window(3);
+
+fn volatility_key() -> NodeKey {
+    FIXTURE.key(AnalyticType::Volatility, vec![asset("AAA")], &[])
+}
+
+fn loaded_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 9, 6, 0, 0).unwrap()
+}
+
+#[test]
+fn as_of_reproduces_results_computed_before_a_correction() {
+    let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+    provider
+        .record_time_series_batch(&asset("AAA"), &series(&PRICES), loaded_at())
+        .unwrap();
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(volatility_key()).unwrap();
+    let before = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    // The vendor corrects day 3 a day later
+    let mut corrected = PRICES;
+    corrected[2] = 102.0;
+    provider
+        .record_time_series_batch(
+            &asset("AAA"),
+            &series(&corrected),
+            loaded_at() + Duration::days(1),
+        )
+        .unwrap();
+
+    let after = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+    let as_of = dag
+        .execute_pull_mode_as_of(node, FIXTURE.range(), &provider, loaded_at())
+        .unwrap();
+    assert_eq!(as_of.len(), before.len());
+    for (then, now) in as_of.iter().zip(&before) {
+        assert_eq!(then.timestamp, now.timestamp);
+        assert!(
+            (then.close_price.is_nan() && now.close_price.is_nan())
+                || then.close_price == now.close_price
+        );
+    }
+    assert_ne!(after[3].close_price, before[3].close_price);
+
+    // Before the load nothing was known
+    let earlier = loaded_at() - Duration::hours(1);
+    assert!(dag
+        .execute_pull_mode_as_of(node, FIXTURE.range(), &provider, earlier)
+        .is_err());
+}
+
+#[test]
+fn providers_without_history_refuse_as_of_queries() {
+    let provider = provider(&[("AAA", &PRICES)]);
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(volatility_key()).unwrap();
+
+    assert!(dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .is_ok());
+    assert!(dag
+        .execute_pull_mode_as_of(node, FIXTURE.range(), &provider, loaded_at())
+        .is_err());
+}
@@ -0,0 +1,122 @@
+//! Fixtures shared by the analytic integration tests: daily closes from
+//! 2024-01-01 16:00 UTC, the date range they cover and node keys over it.
+//!
+//! Each test binary compiles this module on its own and uses a subset of it.
+#![allow(dead_code)]
+
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey, WindowSpec};
+use analytics::time_series::{DateRange, InMemoryDataProvider, TimeSeriesPoint};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// Daily points at 16:00 UTC starting on 2024-01-01.
+pub fn series(prices: &[f64]) -> Vec<TimeSeriesPoint> {
+    let base = Utc.with_ymd_and_hms(2024, 1, 1, 16, 0, 0).unwrap();
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &price)| TimeSeriesPoint::new(base + Duration::days(i as i64), price))
+        .collect()
+}
+
+pub fn asset(ticker: &str) -> AssetKey {
+    AssetKey::new_equity(ticker).unwrap()
+}
+
+pub fn assets(tickers: &[&str]) -> Vec<AssetKey> {
+    tickers.iter().map(|ticker| asset(ticker)).collect()
+}
+
+/// A provider holding the `series` of each ticker's prices.
+pub fn provider(data: &[(&str, &[f64])]) -> InMemoryDataProvider {
+    let mut provider = InMemoryDataProvider::new();
+    for (ticker, prices) in data {
+        provider.add_data(asset(ticker), series(prices));
+    }
+    provider
+}
+
+pub fn log_returns(prices: &[f64]) -> Vec<f64> {
+    prices.windows(2).map(|p| (p[1] / p[0]).ln()).collect()
+}
+
+/// The days a test covers and the rolling window its analytics use.
+#[derive(Debug, Clone, Copy)]
+pub struct Fixture {
+    pub days: usize,
+    pub window: Option<usize>,
+}
+
+impl Fixture {
+    pub const fn new(days: usize) -> Self {
+        Fixture { days, window: None }
+    }
+
+    pub const fn with_window(self, window: usize) -> Self {
+        Fixture {
+            window: Some(window),
+            ..self
+        }
+    }
+
+    /// 2024-01-01 through the last of `days` days.
+    pub fn range(&self) -> DateRange {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        DateRange::new(start, start + Duration::days(self.days as i64 - 1))
+    }
+
+    /// A key over the fixture's range. The window, when set, becomes the
+    /// `window_size` parameter; `extra` parameters are added after it and
+    /// may override it.
+    pub fn key(
+        &self,
+        analytic: AnalyticType,
+        assets: Vec<AssetKey>,
+        extra: &[(&str, &str)],
+    ) -> NodeKey {
+        let mut params = HashMap::new();
+        if let Some(window) = self.window {
+            params.insert("window_size".to_string(), window.to_string());
+        }
+        for (name, value) in extra {
+            params.insert(name.to_string(), value.to_string());
+        }
+        let window = params
+            .get("window_size")
+            .and_then(|value| value.parse().ok())
+            .map(WindowSpec::fixed);
+        NodeKey {
+            analytic,
+            assets,
+            range: Some(self.range()),
+            window,
+            override_tag: None,
+            params,
+        }
+    }
+}
+
+/// Pulls the key over its own range and returns the values.
+pub fn pull(key: NodeKey, provider: &InMemoryDataProvider) -> Vec<f64> {
+    let range = key.range.clone().unwrap();
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    dag.execute_pull_mode(node, range, provider)
+        .unwrap()
+        .iter()
+        .map(|p| p.close_price)
+        .collect()
+}
+
+/// Pulls every named output of the key over its own range.
+pub fn pull_outputs(
+    key: NodeKey,
+    provider: &InMemoryDataProvider,
+) -> BTreeMap<String, Vec<TimeSeriesPoint>> {
+    let range = key.range.clone().unwrap();
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    dag.execute_pull_mode_outputs(node, range, provider)
+        .unwrap()
+}
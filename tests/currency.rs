@@ -0,0 +1,142 @@
+mod common;
+
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{series, Fixture};
+
+const FIXTURE: Fixture = Fixture::new(6);
+
+fn converted_key(
+    analytic: AnalyticType,
+    assets: Vec<AssetKey>,
+    currency: &str,
+    invert: &str,
+) -> NodeKey {
+    FIXTURE.key(
+        analytic,
+        assets,
+        &[
+            ("reporting_currency", "USD"),
+            ("currency", currency),
+            ("fx_invert", invert),
+        ],
+    )
+}
+
+#[test]
+fn returns_in_reporting_currency_use_converted_prices() {
+    let sap = AssetKey::new_equity("SAP").unwrap();
+    let eurusd = AssetKey::new_fx("EUR", "USD").unwrap();
+    let prices = [100.0, 102.0, 101.0, 103.0, 104.0, 102.0];
+    let rates = [1.10, 1.12, 1.11, 1.09, 1.10, 1.13];
+
+    let mut provider = InMemoryDataProvider::new();
+    provider.add_data(sap.clone(), series(&prices));
+    provider.add_data(eurusd.clone(), series(&rates));
+
+    let mut dag = AnalyticsDag::new();
+    let key = converted_key(AnalyticType::Returns, vec![sap.clone()], "EUR", "false");
+    let node = dag.resolve_node(key).unwrap();
+    assert!(dag.data_assets().contains(&eurusd));
+
+    let result = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+    assert_eq!(result.len(), prices.len());
+    for i in 1..prices.len() {
+        let expected = ((prices[i] * rates[i]) / (prices[i - 1] * rates[i - 1])).ln();
+        assert!((result[i].close_price - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn inverse_pair_divides_by_rate_and_carries_rate_forward() {
+    let sap = AssetKey::new_equity("SAP").unwrap();
+    let usdeur = AssetKey::new_fx("USD", "EUR").unwrap();
+
+    let mut provider = InMemoryDataProvider::new();
+    provider.add_data(sap.clone(), series(&[100.0, 110.0, 120.0]));
+    // No FX fixing on the last day: the previous rate applies.
+    provider.add_data(usdeur, series(&[0.8, 0.5]));
+
+    let mut dag = AnalyticsDag::new();
+    let key = converted_key(
+        AnalyticType::FxConversion,
+        vec![sap.clone(), AssetKey::new_fx("USD", "EUR").unwrap()],
+        "EUR",
+        "true",
+    );
+    let node = dag.resolve_node(key).unwrap();
+
+    let result = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+    let values: Vec<f64> = result.iter().map(|p| p.close_price).collect();
+    assert_eq!(values, vec![125.0, 220.0, 240.0]);
+}
+
+#[test]
+fn correlation_of_scaled_returns_is_one() {
+    let a = AssetKey::new_equity("AAA").unwrap();
+    let b = AssetKey::new_equity("BBB").unwrap();
+    let prices_a = [100.0, 101.0, 99.0, 102.0, 104.0, 103.0];
+    let prices_b: Vec<f64> = prices_a.iter().map(|p| p * 3.0).collect();
+
+    let mut provider = InMemoryDataProvider::new();
+    provider.add_data(a.clone(), series(&prices_a));
+    provider.add_data(b.clone(), series(&prices_b));
+
+    let key = FIXTURE
+        .with_window(4)
+        .key(AnalyticType::Correlation, vec![a, b], &[]);
+
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    let result = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let last = result.last().unwrap().close_price;
+    assert!((last - 1.0).abs() < 1e-9, "expected 1.0, got {}", last);
+}
+
+#[test]
+fn push_mode_matches_pull_mode_for_converted_returns() {
+    let sap = AssetKey::new_equity("SAP").unwrap();
+    let eurusd = AssetKey::new_fx("EUR", "USD").unwrap();
+    let prices = series(&[100.0, 102.0, 101.0, 103.0]);
+    let rates = series(&[1.10, 1.12, 1.11, 1.09]);
+
+    let mut provider = InMemoryDataProvider::new();
+    provider.add_data(sap.clone(), prices.clone());
+    provider.add_data(eurusd.clone(), rates.clone());
+
+    let key = converted_key(AnalyticType::Returns, vec![sap.clone()], "EUR", "false");
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    // The asset arrives before its FX fixing on each day; the later fixing
+    // must amend that day's value instead of appending a second point.
+    for (price, rate) in prices.iter().zip(rates.iter()) {
+        engine
+            .push_data(sap.clone(), price.timestamp, price.close_price)
+            .unwrap();
+        engine
+            .push_data(eurusd.clone(), rate.timestamp, rate.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()).skip(1) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert!((p.close_price - q.close_price).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use analytics::analytics::calculators::{
+    excess_kurtosis, percentile_rank, quantile, skewness, z_score,
+};
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, log_returns, provider, pull, series, Fixture};
+
+const PRICES: [f64; 12] = [
+    100.0, 102.0, 99.0, 104.0, 101.0, 97.0, 103.0, 106.0, 100.0, 105.0, 104.0, 109.0,
+];
+const WINDOW: usize = 5;
+const FIXTURE: Fixture = Fixture::new(12).with_window(WINDOW);
+
+/// A statistic of a window of returns.
+type Statistic = fn(&[f64]) -> f64;
+
+fn setup() -> (AssetKey, InMemoryDataProvider) {
+    (asset("AAA"), provider(&[("AAA", &PRICES)]))
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9 || (a.is_nan() && b.is_nan())
+}
+
+#[test]
+fn moments_and_z_score_use_last_window_of_returns() {
+    let (asset, provider) = setup();
+    let returns = log_returns(&PRICES);
+    let window = &returns[returns.len() - WINDOW..];
+
+    let cases: [(AnalyticType, Statistic); 3] = [
+        (AnalyticType::Skewness, skewness),
+        (AnalyticType::Kurtosis, excess_kurtosis),
+        (AnalyticType::ZScore, z_score),
+    ];
+    for (analytic, statistic) in cases {
+        let values = pull(FIXTURE.key(analytic, vec![asset.clone()], &[]), &provider);
+        assert_eq!(values.len(), PRICES.len());
+        // The window fills once it holds five observations of the returns series
+        assert!(values[..WINDOW - 1].iter().all(|v| v.is_nan()));
+        assert!(close(*values.last().unwrap(), statistic(window)));
+    }
+}
+
+#[test]
+fn quantile_and_percentile_rank_use_last_window_of_returns() {
+    let (asset, provider) = setup();
+    let returns = log_returns(&PRICES);
+    let window = &returns[returns.len() - WINDOW..];
+
+    let quantiles = pull(
+        FIXTURE.key(
+            AnalyticType::Quantile,
+            vec![asset.clone()],
+            &[("quantile", "0.25")],
+        ),
+        &provider,
+    );
+    assert!(close(*quantiles.last().unwrap(), quantile(window, 0.25)));
+
+    let ranks = pull(
+        FIXTURE.key(AnalyticType::PercentileRank, vec![asset], &[]),
+        &provider,
+    );
+    let latest = *window.last().unwrap();
+    assert!(close(
+        *ranks.last().unwrap(),
+        percentile_rank(window, latest)
+    ));
+    assert!(ranks
+        .iter()
+        .filter(|v| !v.is_nan())
+        .all(|v| (0.0..=100.0).contains(v)));
+}
+
+#[test]
+fn invalid_quantile_is_rejected() {
+    let (asset, _) = setup();
+    let mut dag = AnalyticsDag::new();
+    assert!(dag
+        .resolve_node(FIXTURE.key(AnalyticType::Quantile, vec![asset], &[("quantile", "1.5")]))
+        .is_err());
+}
+
+#[test]
+fn push_mode_portfolio_updates_match_pull() {
+    let (a, mut provider) = setup();
+    let b = asset("BBB");
+    let prices_b = [
+        50.0, 50.5, 49.0, 51.0, 51.5, 50.0, 52.0, 51.0, 52.5, 53.0, 52.0, 54.0,
+    ];
+    provider.add_data(b.clone(), series(&prices_b));
+
+    let portfolio = |analytic, extra: &[(&str, &str)]| {
+        let mut key = FIXTURE.key(analytic, vec![a.clone(), b.clone()], extra);
+        key.params
+            .insert("weights".to_string(), "0.5,0.5".to_string());
+        key
+    };
+    let keys = [
+        portfolio(AnalyticType::Skewness, &[]),
+        portfolio(AnalyticType::Kurtosis, &[]),
+        portfolio(AnalyticType::ZScore, &[]),
+        portfolio(AnalyticType::Quantile, &[("quantile", "0.9")]),
+        portfolio(AnalyticType::PercentileRank, &[]),
+    ];
+
+    let mut dag = AnalyticsDag::new();
+    let nodes: Vec<_> = keys
+        .iter()
+        .map(|key| dag.resolve_node(key.clone()).unwrap())
+        .collect();
+    let pulled: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            dag.execute_pull_mode(*node, FIXTURE.range(), &provider)
+                .unwrap()
+        })
+        .collect();
+
+    // Each timestamp fires the portfolio twice (once per constituent); the
+    // second update must replace the first observation in the rolling window.
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for (pa, pb) in series(&PRICES).iter().zip(series(&prices_b).iter()) {
+        engine
+            .push_data(a.clone(), pa.timestamp, pa.close_price)
+            .unwrap();
+        engine
+            .push_data(b.clone(), pb.timestamp, pb.close_price)
+            .unwrap();
+    }
+
+    for (node, pulled) in nodes.iter().zip(pulled.iter()) {
+        let pushed = engine.get_history(*node).unwrap();
+        assert_eq!(pushed.len(), pulled.len());
+        assert!(pulled.iter().any(|p| !p.close_price.is_nan()));
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!(close(p.close_price, q.close_price));
+        }
+    }
+}
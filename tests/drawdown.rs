@@ -0,0 +1,101 @@
+mod common;
+
+use analytics::dag::{AnalyticType, AnalyticsDag};
+use analytics::push_mode::PushModeEngine;
+use common::{asset, provider, pull, series, Fixture};
+
+const PRICES: [f64; 8] = [100.0, 110.0, 99.0, 88.0, 105.0, 121.0, 110.0, 115.0];
+const FIXTURE: Fixture = Fixture::new(8);
+
+fn pull_one(analytic: AnalyticType) -> Vec<f64> {
+    let provider = provider(&[("AAA", &PRICES)]);
+    pull(FIXTURE.key(analytic, vec![asset("AAA")], &[]), &provider)
+}
+
+#[test]
+fn drawdown_is_measured_from_running_peak() {
+    let values = pull_one(AnalyticType::Drawdown);
+    let expected = [
+        0.0,
+        0.0,
+        99.0 / 110.0 - 1.0,
+        88.0 / 110.0 - 1.0,
+        105.0 / 110.0 - 1.0,
+        0.0,
+        110.0 / 121.0 - 1.0,
+        115.0 / 121.0 - 1.0,
+    ];
+    assert_eq!(values.len(), expected.len());
+    for (got, want) in values.iter().zip(expected.iter()) {
+        assert!((got - want).abs() < 1e-12, "got {}, want {}", got, want);
+    }
+}
+
+#[test]
+fn time_under_water_counts_since_last_peak() {
+    let values = pull_one(AnalyticType::TimeUnderWater);
+    assert_eq!(values, vec![0.0, 0.0, 1.0, 2.0, 3.0, 0.0, 1.0, 2.0]);
+}
+
+#[test]
+fn rolling_max_drawdown_only_sees_its_window() {
+    let provider = provider(&[("AAA", &PRICES)]);
+    let mdd_key = FIXTURE
+        .with_window(3)
+        .key(AnalyticType::MaxDrawdown, vec![asset("AAA")], &[]);
+    let values = pull(mdd_key, &provider);
+
+    assert!(values[0].is_nan() && values[1].is_nan());
+    // [110, 99, 88]
+    assert!((values[3] - (88.0 / 110.0 - 1.0)).abs() < 1e-12);
+    // [88, 105, 121] never falls below its running peak
+    assert_eq!(values[5], 0.0);
+    // [121, 110, 115]
+    assert!((values[7] - (110.0 / 121.0 - 1.0)).abs() < 1e-12);
+}
+
+#[test]
+fn push_mode_drawdown_matches_pull_for_portfolio() {
+    let (a, b) = (asset("AAA"), asset("BBB"));
+    let prices_b = [50.0, 48.0, 52.0, 47.0, 45.0, 51.0, 53.0, 49.0];
+    let provider = provider(&[("AAA", &PRICES), ("BBB", &prices_b)]);
+
+    let mut portfolio = FIXTURE.key(
+        AnalyticType::Drawdown,
+        vec![a.clone(), b.clone()],
+        &[("weights", "0.5,0.5")],
+    );
+
+    let mut dag = AnalyticsDag::new();
+    let dd_node = dag.resolve_node(portfolio.clone()).unwrap();
+    portfolio.analytic = AnalyticType::TimeUnderWater;
+    let tuw_node = dag.resolve_node(portfolio).unwrap();
+    let pulled_dd = dag
+        .execute_pull_mode(dd_node, FIXTURE.range(), &provider)
+        .unwrap();
+    let pulled_tuw = dag
+        .execute_pull_mode(tuw_node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    // Each timestamp fires the portfolio twice (once per constituent); the
+    // second update must replace the first rather than advance the peak state.
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for (pa, pb) in series(&PRICES).iter().zip(series(&prices_b).iter()) {
+        engine
+            .push_data(a.clone(), pa.timestamp, pa.close_price)
+            .unwrap();
+        engine
+            .push_data(b.clone(), pb.timestamp, pb.close_price)
+            .unwrap();
+    }
+
+    for (node, pulled) in [(dd_node, pulled_dd), (tuw_node, pulled_tuw)] {
+        let pushed = engine.get_history(node).unwrap();
+        assert_eq!(pushed.len(), pulled.len());
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!((p.close_price - q.close_price).abs() < 1e-12);
+        }
+    }
+}
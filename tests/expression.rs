@@ -0,0 +1,137 @@
+mod common;
+
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, provider, series, Fixture};
+
+const PRICES: [f64; 12] = [
+    400.0, 404.0, 399.0, 406.0, 403.0, 395.0, 402.0, 409.0, 401.0, 407.0, 408.0, 414.0,
+];
+const FIXTURE: Fixture = Fixture::new(12);
+
+fn setup() -> InMemoryDataProvider {
+    provider(&[("SPY", &PRICES)])
+}
+
+fn expression_key(expression: &str) -> NodeKey {
+    FIXTURE.key(
+        AnalyticType::Expression,
+        vec![asset("SPY")],
+        &[("expression", expression)],
+    )
+}
+
+fn volatility_key(window: usize) -> NodeKey {
+    FIXTURE
+        .with_window(window)
+        .key(AnalyticType::Volatility, vec![asset("SPY")], &[])
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-12 || (actual.is_nan() && expected.is_nan()),
+        "{actual} != {expected}"
+    );
+}
+
+#[test]
+fn volatility_ratio_matches_its_inputs() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let ratio = dag.resolve_node(expression_key("vol_3 / vol_5")).unwrap();
+    let short = dag.resolve_node(volatility_key(3)).unwrap();
+    let long = dag.resolve_node(volatility_key(5)).unwrap();
+
+    let ratio = dag
+        .execute_pull_mode(ratio, FIXTURE.range(), &provider)
+        .unwrap();
+    let short = dag
+        .execute_pull_mode(short, FIXTURE.range(), &provider)
+        .unwrap();
+    let long = dag
+        .execute_pull_mode(long, FIXTURE.range(), &provider)
+        .unwrap();
+
+    assert_eq!(ratio.len(), PRICES.len());
+    for ((r, s), l) in ratio.iter().zip(&short).zip(&long) {
+        assert_eq!(r.timestamp, s.timestamp);
+        assert_close(r.close_price, s.close_price / l.close_price);
+    }
+    assert!(!ratio.last().unwrap().close_price.is_nan());
+}
+
+#[test]
+fn formulas_apply_lags_comparisons_and_ema() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let up = dag
+        .resolve_node(expression_key("close > lag(close, 1)"))
+        .unwrap();
+    let up = dag
+        .execute_pull_mode(up, FIXTURE.range(), &provider)
+        .unwrap();
+    assert!(up[0].close_price.is_nan());
+    for i in 1..PRICES.len() {
+        let expected = if PRICES[i] > PRICES[i - 1] { 1.0 } else { 0.0 };
+        assert_eq!(up[i].close_price, expected);
+    }
+
+    let gap = dag
+        .resolve_node(expression_key("close - ema(close, 0.94)"))
+        .unwrap();
+    let gap = dag
+        .execute_pull_mode(gap, FIXTURE.range(), &provider)
+        .unwrap();
+    let mut ema = PRICES[0];
+    for (i, point) in gap.iter().enumerate() {
+        if i > 0 {
+            ema = 0.94 * ema + 0.06 * PRICES[i];
+        }
+        assert_close(point.close_price, PRICES[i] - ema);
+    }
+}
+
+#[test]
+fn invalid_expressions_are_rejected() {
+    let mut dag = AnalyticsDag::new();
+    for expression in ["", "vol_3 /", "unknown_3 + 1", "lag(close, 0)", "2 * 3"] {
+        assert!(
+            dag.resolve_node(expression_key(expression)).is_err(),
+            "{expression}"
+        );
+    }
+    let mut key = expression_key("close");
+    key.params.clear();
+    assert!(dag.resolve_node(key).is_err());
+}
+
+#[test]
+fn push_mode_expression_matches_pull() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(expression_key(
+            "sma(returns, 3) / vol_4 + (close >= rolling_max(close, 3)) - drawdown",
+        ))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for point in series(&PRICES) {
+        engine
+            .push_data(asset("SPY"), point.timestamp, point.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert_close(p.close_price, q.close_price);
+    }
+    assert!(!pulled.last().unwrap().close_price.is_nan());
+}
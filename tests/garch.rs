@@ -0,0 +1,159 @@
+mod common;
+
+use analytics::analytics::garch::fit_garch;
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::{DateRange, InMemoryDataProvider, TimeSeriesPoint};
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use common::{asset, log_returns, provider, pull_outputs, series, Fixture};
+
+const DAYS: usize = 80;
+const WINDOW: usize = 40;
+const HORIZON: usize = 5;
+const FIXTURE: Fixture = Fixture::new(DAYS);
+
+/// Prices whose returns cluster into calm and turbulent stretches.
+fn prices() -> Vec<f64> {
+    let mut price = 100.0;
+    (0..DAYS)
+        .map(|i| {
+            let i = i as f64;
+            price *= (0.01 * (i * 1.7).sin() * (1.0 + 0.6 * (i * 0.15).cos())).exp();
+            price
+        })
+        .collect()
+}
+
+fn setup() -> (AssetKey, InMemoryDataProvider) {
+    (asset("AAA"), provider(&[("AAA", &prices())]))
+}
+
+fn garch_key(asset: AssetKey, window: usize, extra: &[(&str, &str)]) -> NodeKey {
+    let horizon = HORIZON.to_string();
+    let mut params = vec![("horizon", horizon.as_str())];
+    params.extend_from_slice(extra);
+    FIXTURE
+        .with_window(window)
+        .key(AnalyticType::Garch, vec![asset], &params)
+}
+
+#[test]
+fn parameters_are_fitted_on_training_window_then_filtered() {
+    let (asset, provider) = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(garch_key(asset, WINDOW, &[])).unwrap();
+    let outputs = dag
+        .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let returns = log_returns(&prices());
+    let fit = fit_garch(&returns[..WINDOW]).unwrap();
+    let params = fit.params;
+
+    // The first return is undefined, so training completes on day WINDOW + 1
+    let volatility = &outputs["volatility"];
+    assert_eq!(volatility.len(), DAYS);
+    assert!(volatility[..WINDOW].iter().all(|p| p.close_price.is_nan()));
+    for (name, value) in [
+        ("omega", params.omega),
+        ("alpha", params.alpha),
+        ("beta", params.beta),
+        ("long_run_volatility", params.long_run_variance().sqrt()),
+    ] {
+        assert!(outputs[name][WINDOW..]
+            .iter()
+            .all(|p| (p.close_price - value).abs() < 1e-15));
+    }
+
+    let mut next = fit.next_variance;
+    assert!((volatility[WINDOW].close_price - next.sqrt()).abs() < 1e-15);
+    for (offset, &ret) in returns[WINDOW..].iter().enumerate() {
+        next = params.next_variance(next, ret);
+        let day = WINDOW + 1 + offset;
+        assert!((volatility[day].close_price - next.sqrt()).abs() < 1e-12);
+    }
+    let forecast = params.forecast_variance(next, HORIZON).sqrt();
+    assert!((outputs["forecast"].last().unwrap().close_price - forecast).abs() < 1e-12);
+    let primary = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+    assert_eq!(primary.last(), volatility.last());
+}
+
+#[test]
+fn range_starting_a_training_window_after_the_first_price_is_fitted() {
+    // Prices on trading days only, so the training window spans weekends
+    let days: Vec<NaiveDate> = NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .iter_days()
+        .filter(|day| day.weekday().number_from_monday() <= 5)
+        .take(DAYS)
+        .collect();
+    let points: Vec<TimeSeriesPoint> = days
+        .iter()
+        .zip(prices())
+        .map(|(day, price)| {
+            let close = Utc.from_utc_datetime(&day.and_hms_opt(16, 0, 0).unwrap());
+            TimeSeriesPoint::new(close, price)
+        })
+        .collect();
+    let mut provider = InMemoryDataProvider::new();
+    provider.add_data(asset("AAA"), points);
+
+    let mut key = garch_key(asset("AAA"), WINDOW, &[]);
+    key.range = Some(DateRange::new(days[WINDOW], days[DAYS - 1]));
+    let outputs = pull_outputs(key, &provider);
+
+    // The first requested day is the first on which the training window is full
+    let volatility = &outputs["volatility"];
+    assert_eq!(volatility.len(), DAYS - WINDOW);
+    assert_eq!(volatility[0].timestamp.date_naive(), days[WINDOW]);
+    let fit = fit_garch(&log_returns(&prices())[..WINDOW]).unwrap();
+    assert!((volatility[0].close_price - fit.next_variance.sqrt()).abs() < 1e-15);
+}
+
+#[test]
+fn invalid_garch_parameters_are_rejected() {
+    let (asset, _) = setup();
+    let mut dag = AnalyticsDag::new();
+    assert!(dag.resolve_node(garch_key(asset.clone(), 5, &[])).is_err());
+    assert!(dag
+        .resolve_node(garch_key(asset, WINDOW, &[("output", "gamma")]))
+        .is_err());
+}
+
+#[test]
+fn push_mode_garch_matches_pull() {
+    let (asset, provider) = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(garch_key(asset.clone(), WINDOW, &[("output", "forecast")]))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for point in series(&prices()) {
+        engine
+            .push_data(asset.clone(), point.timestamp, point.close_price)
+            .unwrap();
+    }
+
+    for (name, pulled) in &pulled {
+        let pushed = engine.get_output_history(node, name).unwrap();
+        assert_eq!(pushed.len(), pulled.len(), "{name}");
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!(
+                (p.close_price - q.close_price).abs() < 1e-12
+                    || (p.close_price.is_nan() && q.close_price.is_nan()),
+                "{name}: {} != {}",
+                p.close_price,
+                q.close_price
+            );
+        }
+    }
+}
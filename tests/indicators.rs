@@ -0,0 +1,213 @@
+mod common;
+
+use analytics::analytics::indicators::{bollinger_band, sma, BandOutput, Rsi};
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::{InMemoryDataProvider, TimeSeriesPoint};
+use common::{asset, provider, pull, pull_outputs, series, Fixture};
+
+const PRICES: [f64; 12] = [
+    100.0, 102.0, 101.0, 104.0, 103.0, 99.0, 98.0, 101.0, 105.0, 107.0, 106.0, 108.0,
+];
+const FIXTURE: Fixture = Fixture::new(12);
+
+fn setup() -> (AssetKey, InMemoryDataProvider) {
+    (asset("AAA"), provider(&[("AAA", &PRICES)]))
+}
+
+/// Pulls each key, reading the named output a key selects with `output`.
+fn pull_all(keys: Vec<NodeKey>, provider: &InMemoryDataProvider) -> Vec<Vec<f64>> {
+    keys.into_iter()
+        .map(|key| match key.params.get("output").cloned() {
+            Some(name) => pull_outputs(key, provider)[&name]
+                .iter()
+                .map(|p| p.close_price)
+                .collect(),
+            None => pull(key, provider),
+        })
+        .collect()
+}
+
+#[test]
+fn sma_and_bollinger_bands_use_last_window() {
+    let (asset, provider) = setup();
+    let keys = ["upper", "middle", "lower"]
+        .iter()
+        .map(|band| {
+            FIXTURE.key(
+                AnalyticType::BollingerBands,
+                vec![asset.clone()],
+                &[("window_size", "5"), ("output", band)],
+            )
+        })
+        .chain(std::iter::once(FIXTURE.key(
+            AnalyticType::Sma,
+            vec![asset.clone()],
+            &[("window_size", "5")],
+        )))
+        .collect();
+    let values = pull_all(keys, &provider);
+
+    let window = &PRICES[PRICES.len() - 5..];
+    for (band, series) in [BandOutput::Upper, BandOutput::Middle, BandOutput::Lower]
+        .iter()
+        .zip(values.iter())
+    {
+        assert!(series[..4].iter().all(|v| v.is_nan()));
+        let expected = bollinger_band(window, 2.0, *band);
+        assert!((series.last().unwrap() - expected).abs() < 1e-12);
+    }
+    assert!((values[3].last().unwrap() - sma(window)).abs() < 1e-12);
+}
+
+#[test]
+fn rsi_replays_wilder_smoothing_over_history() {
+    let (asset, provider) = setup();
+    let values = pull_all(
+        vec![FIXTURE.key(AnalyticType::Rsi, vec![asset], &[("window_size", "4")])],
+        &provider,
+    );
+
+    let mut rsi = Rsi::new(4);
+    for (price, got) in PRICES.iter().zip(values[0].iter()) {
+        rsi = rsi.update(*price);
+        let want = rsi.value();
+        assert!((got - want).abs() < 1e-12 || (got.is_nan() && want.is_nan()));
+    }
+    assert!(values[0][..4].iter().all(|v| v.is_nan()));
+}
+
+#[test]
+fn macd_outputs_share_one_node() {
+    let (asset, provider) = setup();
+    let key = |output: &str| {
+        FIXTURE.key(
+            AnalyticType::Macd,
+            vec![asset.clone()],
+            &[
+                ("fast", "3"),
+                ("slow", "6"),
+                ("signal", "3"),
+                ("output", output),
+            ],
+        )
+    };
+
+    let mut dag = AnalyticsDag::new();
+    let line = dag.resolve_node(key("line")).unwrap();
+    let signal = dag.resolve_node(key("signal")).unwrap();
+    assert_eq!(line, signal);
+    assert_eq!(dag.node_outputs(line), ["line", "signal", "histogram"]);
+    // The primary output is the MACD line
+    let outputs = dag
+        .execute_pull_mode_outputs(line, FIXTURE.range(), &provider)
+        .unwrap();
+    assert_eq!(
+        dag.execute_pull_mode(line, FIXTURE.range(), &provider)
+            .unwrap(),
+        outputs["line"]
+    );
+
+    let values = pull_all(
+        vec![key("line"), key("signal"), key("histogram")],
+        &provider,
+    );
+    assert_eq!(values[2].len(), PRICES.len());
+    for (i, histogram) in values[2].iter().enumerate() {
+        assert!((histogram - (values[0][i] - values[1][i])).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn invalid_indicator_parameters_are_rejected() {
+    let (asset, _) = setup();
+    let mut dag = AnalyticsDag::new();
+    assert!(dag
+        .resolve_node(FIXTURE.key(
+            AnalyticType::BollingerBands,
+            vec![asset.clone()],
+            &[("output", "outer")]
+        ))
+        .is_err());
+    assert!(dag
+        .resolve_node(FIXTURE.key(
+            AnalyticType::Macd,
+            vec![asset],
+            &[("fast", "26"), ("slow", "12")]
+        ))
+        .is_err());
+}
+
+#[test]
+fn push_mode_indicators_match_pull() {
+    let (asset, provider) = setup();
+    let keys = [
+        FIXTURE.key(
+            AnalyticType::Atr,
+            vec![asset.clone()],
+            &[("window_size", "3")],
+        ),
+        FIXTURE.key(
+            AnalyticType::Sma,
+            vec![asset.clone()],
+            &[("window_size", "4")],
+        ),
+        FIXTURE.key(
+            AnalyticType::ExponentialMovingAverage,
+            vec![asset.clone()],
+            &[("window_size", "4")],
+        ),
+        FIXTURE.key(
+            AnalyticType::Macd,
+            vec![asset.clone()],
+            &[("output", "histogram")],
+        ),
+    ];
+    let macd = keys.len() - 1;
+
+    let mut dag = AnalyticsDag::new();
+    let nodes: Vec<_> = keys
+        .iter()
+        .map(|key| dag.resolve_node(key.clone()).unwrap())
+        .collect();
+    let pulled: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            dag.execute_pull_mode(*node, FIXTURE.range(), &provider)
+                .unwrap()
+        })
+        .collect();
+    let macd_outputs = dag
+        .execute_pull_mode_outputs(nodes[macd], FIXTURE.range(), &provider)
+        .unwrap();
+    assert_eq!(macd_outputs.len(), 3);
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for point in series(&PRICES) {
+        engine
+            .push_data(asset.clone(), point.timestamp, point.close_price)
+            .unwrap();
+    }
+
+    let assert_matches = |pushed: &[TimeSeriesPoint], pulled: &[TimeSeriesPoint]| {
+        assert_eq!(pushed.len(), pulled.len());
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!(
+                (p.close_price - q.close_price).abs() < 1e-12
+                    || (p.close_price.is_nan() && q.close_price.is_nan())
+            );
+        }
+    };
+    for (node, pulled) in nodes.iter().zip(pulled.iter()) {
+        assert_matches(&engine.get_history(*node).unwrap(), pulled);
+    }
+    for (name, pulled) in &macd_outputs {
+        assert_matches(
+            &engine.get_output_history(nodes[macd], name).unwrap(),
+            pulled,
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use analytics::ingest::{refresh_all, spawn_refresh_job, IngestStatus, RefreshConfig};
+use analytics::time_series::{DataProvider, DateRange, TimeSeriesPoint};
+use analytics::{
+    AssetKey, DownloaderConfig, Equity, MockFailure, MockVendorServer, SqliteDataProvider,
+    TradingCalendar, YahooFinanceDownloader,
+};
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+}
+
+fn point(day: u32, price: f64) -> TimeSeriesPoint {
+    TimeSeriesPoint::new(Utc.with_ymd_and_hms(2024, 1, day, 16, 0, 0).unwrap(), price)
+}
+
+/// Vendor prices for every January trading day up to `last_day`.
+fn vendor_series(last_day: u32) -> Vec<TimeSeriesPoint> {
+    calendar()
+        .trading_days(&DateRange::new(date(1), date(last_day)))
+        .into_iter()
+        .map(|day| point(day.day(), 100.0 + day.day() as f64))
+        .collect()
+}
+
+fn calendar() -> TradingCalendar {
+    TradingCalendar::weekdays().with_holidays([date(1), date(10), date(15)])
+}
+
+fn config() -> RefreshConfig {
+    RefreshConfig {
+        history_start: date(8),
+        ..Default::default()
+    }
+    .with_calendar("nasdaq", calendar())
+}
+
+fn downloader(server: &MockVendorServer) -> YahooFinanceDownloader {
+    YahooFinanceDownloader::with_config(DownloaderConfig {
+        max_retries: 0,
+        requests_per_second: 0.0,
+        base_url: server.base_url(),
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+/// A catalog with AAPL (prices on Jan 2, 3 and 8) and MSFT (no prices).
+fn catalog() -> Mutex<SqliteDataProvider> {
+    let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+    for ticker in ["AAPL", "MSFT"] {
+        let equity = Equity::new(ticker, ticker, "NASDAQ", "USD", "Technology").unwrap();
+        provider.store_asset_equity(&equity).unwrap();
+    }
+    let aapl = AssetKey::new_equity("AAPL").unwrap();
+    provider
+        .insert_time_series_batch(&aapl, &[point(2, 1.0), point(3, 1.0), point(8, 1.0)])
+        .unwrap();
+    Mutex::new(provider)
+}
+
+#[tokio::test]
+async fn refresh_fetches_only_missing_trading_days() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("AAPL", &vendor_series(31));
+    server.fail_next("MSFT", MockFailure::Status(500));
+    let source = downloader(&server);
+    let provider = catalog();
+    let aapl = AssetKey::new_equity("AAPL").unwrap();
+    let msft = AssetKey::new_equity("MSFT").unwrap();
+
+    let summary = refresh_all(&source, &provider, &config(), date(12))
+        .await
+        .unwrap();
+    assert_eq!(summary.assets, 2);
+    assert_eq!(summary.refreshed, 2);
+    assert_eq!(summary.points_added, 5);
+    assert_eq!(summary.failed.len(), 1);
+    assert!(summary.failed["MSFT"].contains("HTTP 500"));
+
+    // Jan 4-5 and Jan 9-12 (the 10th is a holiday) were fetched, nothing else
+    let requests: Vec<(i64, i64)> = server
+        .requests()
+        .iter()
+        .filter(|request| request.symbol == "AAPL")
+        .map(|request| (request.period1.unwrap(), request.period2.unwrap()))
+        .collect();
+    let day_start = |day: u32| Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap();
+    let day_end = |day: u32| Utc.with_ymd_and_hms(2024, 1, day, 23, 59, 59).unwrap();
+    assert_eq!(
+        requests,
+        vec![
+            (day_start(4).timestamp(), day_end(5).timestamp()),
+            (day_start(9).timestamp(), day_end(12).timestamp()),
+        ]
+    );
+
+    {
+        let provider = provider.lock().await;
+        let stored = provider
+            .get_time_series(&aapl, &DateRange::new(date(1), date(31)))
+            .unwrap();
+        let days: Vec<u32> = stored.iter().map(|p| p.timestamp.day()).collect();
+        assert_eq!(days, vec![2, 3, 4, 5, 8, 9, 11, 12]);
+        assert_eq!(stored[2].close_price, 104.0);
+        // Stored prices are not overwritten
+        assert_eq!(stored[4].close_price, 1.0);
+
+        let state = provider.get_sync_state(&aapl).unwrap().unwrap();
+        assert_eq!(state.watermark, Some(date(12)));
+        assert_eq!(state.last_error, None);
+        let state = provider.get_sync_state(&msft).unwrap().unwrap();
+        assert_eq!(state.watermark, None);
+        assert!(state.last_error.unwrap().contains("HTTP 500"));
+    }
+
+    // Up to date: no requests for AAPL until a new trading day passes
+    server.add_series("MSFT", &vendor_series(31));
+    let before = server.request_count("AAPL");
+    let summary = refresh_all(&source, &provider, &config(), date(14))
+        .await
+        .unwrap();
+    assert_eq!(server.request_count("AAPL"), before);
+    assert_eq!(summary.points_added, 4);
+    assert!(summary.failed.is_empty());
+
+    let summary = refresh_all(&source, &provider, &config(), date(17))
+        .await
+        .unwrap();
+    assert_eq!(server.request_count("AAPL"), before + 1);
+    assert_eq!(summary.points_added, 4);
+
+    let provider = provider.lock().await;
+    assert_eq!(
+        provider.get_sync_state(&aapl).unwrap().unwrap().watermark,
+        Some(date(17))
+    );
+    assert_eq!(
+        provider.get_sync_state(&msft).unwrap().unwrap().watermark,
+        Some(date(17))
+    );
+}
+
+#[tokio::test]
+async fn refresh_job_publishes_its_status() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("AAPL", &vendor_series(31));
+    server.add_series("MSFT", &vendor_series(31));
+    let provider = Arc::new(catalog());
+    let status = Arc::new(RwLock::new(IngestStatus::default()));
+
+    let job = spawn_refresh_job(
+        downloader(&server),
+        provider.clone(),
+        status.clone(),
+        RefreshConfig::default(),
+        Duration::from_secs(3600),
+    );
+
+    let summary = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Some(summary) = status.read().await.last_run.clone() {
+                return summary;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    job.abort();
+
+    assert_eq!(summary.assets, 2);
+    assert!(summary.failed.is_empty());
+    let status = status.read().await;
+    assert!(status.enabled);
+    assert!(!status.running);
+    assert_eq!(status.source.as_deref(), Some("yahoo"));
+    assert_eq!(status.interval_seconds, Some(3600));
+    assert!(status.next_run.unwrap() > summary.finished_at);
+}
@@ -0,0 +1,266 @@
+use analytics::market_data::{self, MarketDataSource};
+use analytics::time_series::{DataProvider, DateRange, TimeSeriesPoint};
+use analytics::{
+    AssetKey, DownloadError, DownloaderConfig, MockFailure, MockVendorServer, SqliteDataProvider,
+    YahooFinanceDownloader,
+};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+}
+
+fn range() -> DateRange {
+    DateRange::new(date(2), date(5))
+}
+
+fn series(prices: &[f64]) -> Vec<TimeSeriesPoint> {
+    let base = Utc.with_ymd_and_hms(2024, 1, 1, 16, 0, 0).unwrap();
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &price)| TimeSeriesPoint::new(base + Duration::days(i as i64), price))
+        .collect()
+}
+
+/// Points the downloader at the mock server with fast retries and no throttling.
+fn downloader(server: &MockVendorServer, max_retries: u32) -> YahooFinanceDownloader {
+    YahooFinanceDownloader::with_config(DownloaderConfig {
+        max_retries,
+        requests_per_second: 0.0,
+        base_url: server.base_url(),
+        retry_delay_ms: 10,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn downloads_canned_csv_into_sqlite() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("AAPL", &series(&[100.0, 101.5, 99.0, 102.0, 103.0, 104.0]));
+    let downloader = downloader(&server, 0);
+    let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+    let aapl = AssetKey::new_equity("AAPL").unwrap();
+
+    let stored = downloader
+        .download_to_sqlite(&mut provider, &aapl, &range())
+        .await
+        .unwrap();
+    assert_eq!(stored, 4);
+
+    let points = provider.get_time_series(&aapl, &range()).unwrap();
+    let closes: Vec<f64> = points.iter().map(|p| p.close_price).collect();
+    assert_eq!(closes, vec![101.5, 99.0, 102.0, 103.0]);
+    assert_eq!(
+        points[0].timestamp,
+        Utc.with_ymd_and_hms(2024, 1, 2, 16, 0, 0).unwrap()
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].symbol, "AAPL");
+    assert_eq!(requests[0].period1, Some(1704153600));
+    assert_eq!(requests[0].period2, Some(1704499199));
+
+    // Everything is stored now, so a second run adds nothing
+    let stored = downloader
+        .download_to_sqlite(&mut provider, &aapl, &range())
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("MSFT", &series(&[1.0, 2.0, 3.0]));
+    server.fail_next("MSFT", MockFailure::Status(500));
+    server.fail_next("MSFT", MockFailure::Status(503));
+
+    let points = downloader(&server, 3)
+        .download_and_parse("MSFT", date(1), date(3))
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 3);
+    assert_eq!(server.request_count("MSFT"), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("MSFT", &series(&[1.0]));
+    for _ in 0..3 {
+        server.fail_next("MSFT", MockFailure::Status(500));
+    }
+
+    let result = downloader(&server, 2)
+        .fetch_historical_data("MSFT", date(1), date(3))
+        .await;
+    assert_eq!(
+        result,
+        Err(DownloadError::ApiError(
+            "HTTP 500: Internal Server Error".to_string()
+        ))
+    );
+    assert_eq!(server.request_count("MSFT"), 3);
+
+    let result = downloader(&server, 0)
+        .fetch_historical_data("UNKNOWN", date(1), date(3))
+        .await;
+    assert_eq!(
+        result,
+        Err(DownloadError::ApiError("HTTP 404: Not Found".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn waits_for_retry_after_when_rate_limited() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("SPY", &series(&[400.0, 401.0]));
+    server.fail_next(
+        "SPY",
+        MockFailure::RateLimited {
+            retry_after_seconds: 1,
+        },
+    );
+
+    let points = downloader(&server, 1)
+        .download_and_parse("SPY", date(1), date(2))
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 2);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let waited = requests[1].received_at - requests[0].received_at;
+    assert!(waited >= std::time::Duration::from_secs(1), "{waited:?}");
+}
+
+#[tokio::test]
+async fn caps_retry_after_at_the_configured_maximum() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.add_series("SPY", &series(&[400.0, 401.0]));
+    server.fail_next(
+        "SPY",
+        MockFailure::RateLimited {
+            retry_after_seconds: 3600,
+        },
+    );
+
+    let downloader = YahooFinanceDownloader::with_config(DownloaderConfig {
+        max_retries: 1,
+        requests_per_second: 0.0,
+        base_url: server.base_url(),
+        max_retry_after_seconds: 1,
+        ..Default::default()
+    })
+    .unwrap();
+    let points = tokio::time::timeout(
+        std::time::Duration::from_secs(30),
+        downloader.download_and_parse("SPY", date(1), date(2)),
+    )
+    .await
+    .expect("Retry-After was not capped")
+    .unwrap();
+    assert_eq!(points.len(), 2);
+
+    let requests = server.requests();
+    let waited = requests[1].received_at - requests[0].received_at;
+    assert!(waited >= std::time::Duration::from_secs(1), "{waited:?}");
+}
+
+#[tokio::test]
+async fn throttles_to_requests_per_second() {
+    let server = MockVendorServer::start().await.unwrap();
+    let symbols = ["A", "B", "C", "D"];
+    for symbol in symbols {
+        server.add_series(symbol, &series(&[1.0]));
+    }
+    let downloader = YahooFinanceDownloader::with_config(DownloaderConfig {
+        requests_per_second: 20.0,
+        base_url: server.base_url(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    for symbol in symbols {
+        downloader
+            .download_and_parse(symbol, date(1), date(1))
+            .await
+            .unwrap();
+    }
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), symbols.len());
+    let elapsed = requests[3].received_at - requests[0].received_at;
+    assert!(
+        elapsed >= std::time::Duration::from_millis(140),
+        "{elapsed:?}"
+    );
+}
+
+#[tokio::test]
+async fn malformed_csv_is_a_parse_error() {
+    let server = MockVendorServer::start().await.unwrap();
+    server.fail_next(
+        "BAD",
+        MockFailure::Body("Date,Open,High,Low,Close\n2024-01-02,1,1,1,n/a\n".to_string()),
+    );
+
+    let result = downloader(&server, 3)
+        .download_and_parse("BAD", date(1), date(3))
+        .await;
+    assert!(matches!(result, Err(DownloadError::ParseError(_))));
+    assert_eq!(server.request_count("BAD"), 1);
+}
+
+/// A source serving fixed series, failing for assets it does not know.
+struct FixedSource {
+    known: AssetKey,
+}
+
+impl MarketDataSource for FixedSource {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    async fn fetch_history(
+        &self,
+        asset_key: &AssetKey,
+        date_range: &DateRange,
+    ) -> Result<Vec<TimeSeriesPoint>, DownloadError> {
+        if asset_key != &self.known {
+            return Err(DownloadError::ApiError(format!("unknown {}", asset_key)));
+        }
+        Ok(series(&[10.0, 11.0, 12.0, 13.0, 14.0, 15.0])
+            .into_iter()
+            .filter(|p| {
+                let day = p.timestamp.date_naive();
+                day >= date_range.start && day <= date_range.end
+            })
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn other_sources_plug_into_the_download_helpers() {
+    let known = AssetKey::new_equity("AAPL").unwrap();
+    let unknown = AssetKey::new_equity("NOPE").unwrap();
+    let source = FixedSource {
+        known: known.clone(),
+    };
+    let mut provider = SqliteDataProvider::new_in_memory().unwrap();
+
+    let result = market_data::download_multiple_to_sqlite(
+        &source,
+        &mut provider,
+        &[(known.clone(), range()), (unknown.clone(), range())],
+        1,
+    )
+    .await;
+
+    assert_eq!(result.successful.get("AAPL"), Some(&4));
+    assert!(result.failed.contains_key("NOPE"));
+    assert_eq!(provider.get_time_series(&known, &range()).unwrap().len(), 4);
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use analytics::analytics::matrix::{correlation, MatrixEstimator};
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, assets, log_returns, provider, series, Fixture};
+
+const PRICES_SPY: [f64; 12] = [
+    400.0, 404.0, 399.0, 406.0, 403.0, 395.0, 402.0, 409.0, 401.0, 407.0, 408.0, 414.0,
+];
+const PRICES_XLK: [f64; 12] = [
+    150.0, 151.0, 150.5, 153.0, 152.0, 149.0, 151.5, 154.0, 152.5, 153.5, 153.0, 156.0,
+];
+const PRICES_TLT: [f64; 12] = [
+    95.0, 94.5, 95.5, 94.0, 94.8, 96.0, 95.2, 94.1, 95.0, 94.6, 94.9, 93.8,
+];
+const WINDOW: usize = 6;
+const FIXTURE: Fixture = Fixture::new(12).with_window(WINDOW);
+
+fn setup() -> InMemoryDataProvider {
+    provider(&[
+        ("SPY", &PRICES_SPY),
+        ("XLK", &PRICES_XLK),
+        ("TLT", &PRICES_TLT),
+    ])
+}
+
+fn matrix_key(analytic: AnalyticType, universe: &[&str], estimator: &str) -> NodeKey {
+    FIXTURE.key(analytic, assets(universe), &[("estimator", estimator)])
+}
+
+fn last_window(prices: &[f64]) -> Vec<f64> {
+    let returns = log_returns(prices);
+    returns[returns.len() - WINDOW..].to_vec()
+}
+
+#[test]
+fn matrix_entries_match_estimators_on_last_window() {
+    let provider = setup();
+    let columns = [
+        last_window(&PRICES_SPY),
+        last_window(&PRICES_XLK),
+        last_window(&PRICES_TLT),
+    ];
+    let tickers = ["spy", "xlk", "tlt"];
+
+    for estimator in [
+        MatrixEstimator::Sample,
+        MatrixEstimator::Ewma,
+        MatrixEstimator::LedoitWolf,
+    ] {
+        let covariance = estimator.covariance(&columns, 0.94).unwrap();
+        let expected = [
+            (AnalyticType::CovarianceMatrix, covariance.clone()),
+            (AnalyticType::CorrelationMatrix, correlation(&covariance)),
+        ];
+        for (analytic, matrix) in expected {
+            let mut dag = AnalyticsDag::new();
+            let node = dag
+                .resolve_node(matrix_key(
+                    analytic,
+                    &["SPY", "XLK", "TLT"],
+                    &estimator.to_string(),
+                ))
+                .unwrap();
+            let outputs = dag
+                .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+                .unwrap();
+            assert_eq!(outputs["observations"].last().unwrap().close_price, 6.0);
+            for i in 0..3 {
+                for j in i..3 {
+                    let name = format!("{}_{}", tickers[i], tickers[j]);
+                    let value = outputs[&name].last().unwrap().close_price;
+                    assert!((value - matrix[i][j]).abs() < 1e-12, "{estimator} {name}");
+                }
+            }
+
+            // The first return is undefined, so the first full window ends on day seven
+            assert!(outputs["spy_xlk"][..WINDOW]
+                .iter()
+                .all(|p| p.close_price.is_nan()));
+            assert_eq!(outputs["observations"][WINDOW - 1].close_price, 5.0);
+        }
+    }
+}
+
+#[test]
+fn two_asset_sample_correlation_matches_pairwise_node() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let matrix = dag
+        .resolve_node(matrix_key(
+            AnalyticType::CorrelationMatrix,
+            &["SPY", "TLT"],
+            "sample",
+        ))
+        .unwrap();
+    let mut pair_key = matrix_key(AnalyticType::Correlation, &["SPY", "TLT"], "sample");
+    pair_key.params.remove("estimator");
+    let pair = dag.resolve_node(pair_key).unwrap();
+
+    let from_matrix = dag
+        .execute_pull_mode(matrix, FIXTURE.range(), &provider)
+        .unwrap();
+    let pairwise = dag
+        .execute_pull_mode(pair, FIXTURE.range(), &provider)
+        .unwrap();
+    let (a, b) = (from_matrix.last().unwrap(), pairwise.last().unwrap());
+    assert_eq!(a.timestamp, b.timestamp);
+    assert!((a.close_price - b.close_price).abs() < 1e-12);
+}
+
+#[test]
+fn invalid_matrices_are_rejected() {
+    let mut dag = AnalyticsDag::new();
+    let analytic = AnalyticType::CovarianceMatrix;
+    assert!(dag
+        .resolve_node(matrix_key(analytic, &["SPY"], "sample"))
+        .is_err());
+    assert!(dag
+        .resolve_node(matrix_key(analytic, &["SPY", "XLK"], "garch"))
+        .is_err());
+    let mut key = matrix_key(analytic, &["SPY", "XLK"], "ewma");
+    key.params.insert("lambda".to_string(), "1.5".to_string());
+    assert!(dag.resolve_node(key).is_err());
+}
+
+#[test]
+fn push_mode_matrix_matches_pull() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(matrix_key(
+            AnalyticType::CorrelationMatrix,
+            &["SPY", "XLK", "TLT"],
+            "ledoit_wolf",
+        ))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for i in 0..PRICES_SPY.len() {
+        for (ticker, prices) in [
+            ("SPY", PRICES_SPY),
+            ("XLK", PRICES_XLK),
+            ("TLT", PRICES_TLT),
+        ] {
+            let point = &series(&prices)[i];
+            engine
+                .push_data(asset(ticker), point.timestamp, point.close_price)
+                .unwrap();
+        }
+    }
+
+    for (name, pulled) in &pulled {
+        let pushed = engine.get_output_history(node, name).unwrap();
+        assert_eq!(pushed.len(), pulled.len(), "{name}");
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!(
+                (p.close_price - q.close_price).abs() < 1e-12
+                    || (p.close_price.is_nan() && q.close_price.is_nan()),
+                "{name}: {} != {}",
+                p.close_price,
+                q.close_price
+            );
+        }
+    }
+}
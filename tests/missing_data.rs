@@ -0,0 +1,160 @@
+mod common;
+
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::TimeSeriesPoint;
+use common::{asset, provider, series, Fixture};
+
+/// A zero print on day 3 makes the returns of days 3 and 4 missing.
+const PRICES: [f64; 8] = [100.0, 101.0, 0.0, 103.0, 104.0, 105.0, 106.0, 108.0];
+const FIXTURE: Fixture = Fixture::new(8).with_window(3);
+
+/// Volatility over three returns with the given node parameters.
+fn volatility_key(params: &[(&str, &str)]) -> NodeKey {
+    FIXTURE.key(AnalyticType::Volatility, vec![asset("AAA")], params)
+}
+
+fn pull(key: NodeKey) -> Vec<TimeSeriesPoint> {
+    let provider = provider(&[("AAA", &PRICES)]);
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    dag.execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap()
+}
+
+fn push(key: NodeKey) -> Vec<TimeSeriesPoint> {
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for point in series(&PRICES) {
+        engine
+            .push_data(asset("AAA"), point.timestamp, point.close_price)
+            .unwrap();
+    }
+    engine.get_history(node).unwrap()
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+fn returns() -> Vec<f64> {
+    let mut returns = vec![f64::NAN];
+    returns.extend(PRICES.windows(2).map(|pair| {
+        if pair[0] > 0.0 && pair[1] > 0.0 {
+            (pair[1] / pair[0]).ln()
+        } else {
+            f64::NAN
+        }
+    }));
+    returns
+}
+
+fn assert_close(got: &[TimeSeriesPoint], days: &[usize], want: &[f64]) {
+    let got_days: Vec<usize> = got
+        .iter()
+        .map(|p| (p.timestamp - series(&PRICES)[0].timestamp).num_days() as usize)
+        .collect();
+    assert_eq!(got_days, days);
+    for (point, want) in got.iter().zip(want) {
+        let got = point.close_price;
+        assert!(
+            (got.is_nan() && want.is_nan()) || (got - want).abs() < 1e-12,
+            "day {}: got {}, want {}",
+            point.timestamp,
+            got,
+            want
+        );
+    }
+}
+
+#[test]
+fn invalid_prices_give_missing_returns_instead_of_zeros() {
+    let returns_only = Fixture {
+        window: None,
+        ..FIXTURE
+    };
+    let key = returns_only.key(AnalyticType::Returns, vec![asset("AAA")], &[("lag", "1")]);
+
+    let values: Vec<f64> = pull(key).iter().map(|p| p.close_price).collect();
+    assert!(values[2].is_nan() && values[3].is_nan());
+    assert!((values[4] - returns()[4]).abs() < 1e-12);
+}
+
+#[test]
+fn policies_decide_how_missing_returns_reach_the_window() {
+    let r = returns();
+    let nan = f64::NAN;
+    let all_days: Vec<usize> = (0..8).collect();
+
+    // nan: any missing return in the window makes the volatility missing
+    let values = pull(volatility_key(&[("missing_data", "nan")]));
+    let mut want = vec![nan; 6];
+    want.extend([std_dev(&r[4..7]), std_dev(&r[5..8])]);
+    assert_close(&values, &all_days, &want);
+
+    // skip: no value on days with a missing return; windows hold valid returns
+    let values = pull(volatility_key(&[("missing_data", "skip")]));
+    let want = [
+        std_dev(&r[1..2]),
+        std_dev(&[r[1], r[4]]),
+        std_dev(&[r[1], r[4], r[5]]),
+        std_dev(&r[4..7]),
+        std_dev(&r[5..8]),
+    ];
+    assert_close(&values, &[1, 4, 5, 6, 7], &want);
+
+    // ffill: missing returns repeat the last valid one
+    let values = pull(volatility_key(&[("missing_data", "ffill")]));
+    let filled = [nan, r[1], r[1], r[1], r[4], r[5], r[6], r[7]];
+    let want: Vec<f64> = (0..8usize)
+        .map(|day| {
+            let window: Vec<f64> = filled[day.saturating_sub(2)..=day]
+                .iter()
+                .copied()
+                .filter(|v| !v.is_nan())
+                .collect();
+            std_dev(&window)
+        })
+        .collect();
+    assert_close(&values, &all_days, &want);
+
+    // interpolate: once day 5 arrives, days 3 and 4 lie between days 2 and 5
+    let values = pull(volatility_key(&[("missing_data", "interpolate")]));
+    let step = (r[4] - r[1]) / 3.0;
+    assert!(
+        (values[4].close_price - std_dev(&[r[1] + step, r[1] + 2.0 * step, r[4]])).abs() < 1e-12
+    );
+    // before it does, the gap is forward-filled
+    assert_eq!(values[3].close_price, 0.0);
+
+    // min_observations: fewer than three valid returns in the window gives NaN
+    let values = pull(volatility_key(&[("min_observations", "3")]));
+    let mut want = vec![nan; 6];
+    want.extend([std_dev(&r[4..7]), std_dev(&r[5..8])]);
+    assert_close(&values, &all_days, &want);
+}
+
+#[test]
+fn push_mode_honors_the_same_policies() {
+    for policy in ["nan", "skip", "ffill", "interpolate"] {
+        let key = volatility_key(&[("missing_data", policy), ("min_observations", "2")]);
+        let pulled = pull(key.clone());
+        let pushed = push(key);
+        assert_eq!(pushed.len(), pulled.len(), "{}", policy);
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp, "{}", policy);
+            assert!(
+                (p.close_price.is_nan() && q.close_price.is_nan())
+                    || (p.close_price - q.close_price).abs() < 1e-12,
+                "{}: pushed {} pulled {}",
+                policy,
+                p.close_price,
+                q.close_price
+            );
+        }
+    }
+}
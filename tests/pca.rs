@@ -0,0 +1,158 @@
+mod common;
+
+use analytics::analytics::pca::principal_components;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, assets, log_returns, provider, series, Fixture};
+
+const PRICES_SPY: [f64; 12] = [
+    400.0, 404.0, 399.0, 406.0, 403.0, 395.0, 402.0, 409.0, 401.0, 407.0, 408.0, 414.0,
+];
+const PRICES_XLK: [f64; 12] = [
+    150.0, 151.0, 150.5, 153.0, 152.0, 149.0, 151.5, 154.0, 152.5, 153.5, 153.0, 156.0,
+];
+const PRICES_TLT: [f64; 12] = [
+    95.0, 94.5, 95.5, 94.0, 94.8, 96.0, 95.2, 94.1, 95.0, 94.6, 94.9, 93.8,
+];
+const WINDOW: usize = 6;
+const FIXTURE: Fixture = Fixture::new(12);
+
+fn setup() -> InMemoryDataProvider {
+    provider(&[
+        ("SPY", &PRICES_SPY),
+        ("XLK", &PRICES_XLK),
+        ("TLT", &PRICES_TLT),
+    ])
+}
+
+fn pca_key(universe: &[&str], window: usize, components: Option<&str>) -> NodeKey {
+    let extra: Vec<_> = components
+        .map(|components| ("components", components))
+        .into_iter()
+        .collect();
+    FIXTURE
+        .with_window(window)
+        .key(AnalyticType::Pca, assets(universe), &extra)
+}
+
+fn last_window(prices: &[f64]) -> Vec<f64> {
+    let returns = log_returns(prices);
+    returns[returns.len() - WINDOW..].to_vec()
+}
+
+#[test]
+fn pca_matches_decomposition_of_last_window() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(pca_key(&["SPY", "XLK", "TLT"], WINDOW, Some("2")))
+        .unwrap();
+    assert_eq!(
+        dag.node_outputs(node),
+        [
+            "factor_1",
+            "factor_2",
+            "eigenvalue_1",
+            "eigenvalue_2",
+            "explained_ratio_1",
+            "explained_ratio_2",
+            "loading_1_spy",
+            "loading_1_xlk",
+            "loading_1_tlt",
+            "loading_2_spy",
+            "loading_2_xlk",
+            "loading_2_tlt"
+        ]
+    );
+
+    let outputs = dag
+        .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+        .unwrap();
+    let columns = [
+        last_window(&PRICES_SPY),
+        last_window(&PRICES_XLK),
+        last_window(&PRICES_TLT),
+    ];
+    let pca = principal_components(&columns).unwrap();
+    let latest: Vec<f64> = columns.iter().map(|c| c[WINDOW - 1]).collect();
+    let last = |name: &str| outputs[name].last().unwrap().close_price;
+
+    for (index, number) in [(0, "1"), (1, "2")] {
+        assert!((last(&format!("eigenvalue_{number}")) - pca.eigenvalues[index]).abs() < 1e-15);
+        assert!(
+            (last(&format!("explained_ratio_{number}")) - pca.explained_ratio(index)).abs() < 1e-12
+        );
+        assert!(
+            (last(&format!("factor_{number}")) - pca.factor_return(index, &latest)).abs() < 1e-12
+        );
+        for (position, ticker) in ["spy", "xlk", "tlt"].iter().enumerate() {
+            let loading = last(&format!("loading_{number}_{ticker}"));
+            assert!((loading - pca.loadings[index][position]).abs() < 1e-12);
+        }
+    }
+    // Equities move together against bonds in the first component
+    assert!(last("loading_1_spy") > 0.0 && last("loading_1_tlt") < 0.0);
+
+    // The first return is undefined, so the first full window ends on day seven
+    assert_eq!(outputs["factor_1"].len(), PRICES_SPY.len());
+    assert!(outputs["factor_1"][..WINDOW]
+        .iter()
+        .all(|p| p.close_price.is_nan()));
+    assert!(!outputs["factor_1"][WINDOW].close_price.is_nan());
+}
+
+#[test]
+fn invalid_universes_are_rejected() {
+    let mut dag = AnalyticsDag::new();
+    assert!(dag.resolve_node(pca_key(&["SPY"], WINDOW, None)).is_err());
+    assert!(dag
+        .resolve_node(pca_key(&["SPY", "SPY"], WINDOW, None))
+        .is_err());
+    assert!(dag
+        .resolve_node(pca_key(&["SPY", "XLK"], WINDOW, Some("3")))
+        .is_err());
+    assert!(dag.resolve_node(pca_key(&["SPY", "XLK"], 1, None)).is_err());
+}
+
+#[test]
+fn push_mode_pca_matches_pull() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(pca_key(&["SPY", "XLK", "TLT"], WINDOW, None))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for i in 0..PRICES_SPY.len() {
+        for (ticker, prices) in [
+            ("SPY", PRICES_SPY),
+            ("XLK", PRICES_XLK),
+            ("TLT", PRICES_TLT),
+        ] {
+            let point = &series(&prices)[i];
+            engine
+                .push_data(asset(ticker), point.timestamp, point.close_price)
+                .unwrap();
+        }
+    }
+
+    for (name, pulled) in &pulled {
+        let pushed = engine.get_output_history(node, name).unwrap();
+        assert_eq!(pushed.len(), pulled.len(), "{name}");
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!(
+                (p.close_price - q.close_price).abs() < 1e-12
+                    || (p.close_price.is_nan() && q.close_price.is_nan()),
+                "{name}: {} != {}",
+                p.close_price,
+                q.close_price
+            );
+        }
+    }
+}
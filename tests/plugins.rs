@@ -0,0 +1,242 @@
+mod common;
+
+use analytics::analytics::plugin::{AnalyticSchema, ParameterKind, ParameterSchema};
+use analytics::analytics::registry::{AnalyticDefinition, AnalyticExecutor, ParentOutput};
+use analytics::analytics::AnalyticRegistry;
+use analytics::dag::{
+    AnalyticType, AnalyticsDag, CustomAnalytic, DagError, Node, NodeKey, NodeOutput, NodeParams,
+    WindowSpec,
+};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use chrono::{DateTime, Utc};
+use common::{asset, provider, series, Fixture};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const PRICES: [f64; 12] = [
+    400.0, 404.0, 399.0, 406.0, 403.0, 395.0, 402.0, 409.0, 401.0, 407.0, 408.0, 414.0,
+];
+const WINDOW: usize = 3;
+const FIXTURE: Fixture = Fixture::new(12);
+
+/// Rolling mean of prices multiplied by a `factor`, as a team might add outside the crate.
+struct ScaledMeanDefinition {
+    name: &'static str,
+    executor: ScaledMeanExecutor,
+}
+
+impl ScaledMeanDefinition {
+    fn named(name: &'static str) -> Box<Self> {
+        Box::new(ScaledMeanDefinition {
+            name,
+            executor: ScaledMeanExecutor,
+        })
+    }
+}
+
+impl AnalyticDefinition for ScaledMeanDefinition {
+    fn analytic_type(&self) -> AnalyticType {
+        AnalyticType::Custom(CustomAnalytic::new(self.name))
+    }
+
+    fn node_type(&self) -> &'static str {
+        "scaled_mean"
+    }
+
+    fn dependencies(&self, key: &NodeKey) -> Result<Vec<NodeKey>, DagError> {
+        Ok(vec![NodeKey {
+            analytic: AnalyticType::DataProvider,
+            assets: key.assets.clone(),
+            range: key.range.clone(),
+            window: None,
+            override_tag: key.override_tag.clone(),
+            params: HashMap::new(),
+        }])
+    }
+
+    fn executor(&self) -> &dyn AnalyticExecutor {
+        &self.executor
+    }
+}
+
+struct ScaledMeanExecutor;
+
+impl AnalyticExecutor for ScaledMeanExecutor {
+    fn execute_push(
+        &self,
+        node: &Node,
+        parent_outputs: &[ParentOutput],
+        _timestamp: DateTime<Utc>,
+        _value: f64,
+    ) -> Result<NodeOutput, DagError> {
+        let NodeParams::Map(params) = &node.params else {
+            return Err(DagError::ExecutionError("missing parameters".to_string()));
+        };
+        let window: usize = params["window_size"].parse().unwrap();
+        let factor: f64 = params["factor"].parse().unwrap();
+        let prices = &parent_outputs[0].output;
+        if prices.len() < window {
+            return Ok(NodeOutput::Scalar(f64::NAN));
+        }
+        let mean = prices[prices.len() - window..]
+            .iter()
+            .map(|point| point.close_price)
+            .sum::<f64>()
+            / window as f64;
+        Ok(NodeOutput::Scalar(factor * mean))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn schema() -> AnalyticSchema {
+    AnalyticSchema::new("Rolling mean price times a factor")
+        .parameter(ParameterSchema::required(
+            "factor",
+            ParameterKind::Number,
+            "Multiplier applied to the mean",
+        ))
+        .parameter(ParameterSchema::optional(
+            "window_size",
+            ParameterKind::Integer,
+            "3",
+            "Rolling window size in days",
+        ))
+        .burnin("window")
+}
+
+fn registry() -> Arc<AnalyticRegistry> {
+    let mut registry = AnalyticRegistry::new();
+    registry
+        .register(ScaledMeanDefinition::named("scaled_mean"), schema())
+        .unwrap();
+    Arc::new(registry)
+}
+
+fn setup() -> InMemoryDataProvider {
+    provider(&[("SPY", &PRICES)])
+}
+
+fn custom_key(registry: &AnalyticRegistry, params: &[(&str, &str)]) -> NodeKey {
+    FIXTURE.key(
+        registry.analytic_type("Scaled_Mean"),
+        vec![asset("SPY")],
+        params,
+    )
+}
+
+#[test]
+fn registered_analytic_resolves_by_name_and_computes() {
+    let registry = registry();
+    let provider = setup();
+    let mut dag = AnalyticsDag::new_with_registry(registry.clone());
+
+    let key = custom_key(&registry, &[("factor", "2")]);
+    assert_eq!(key.analytic.to_string(), "scaled_mean");
+    let node = dag.resolve_node(key).unwrap();
+
+    // The schema's default window is applied before the node is keyed
+    let resolved = dag.node_key(node).unwrap();
+    assert_eq!(resolved.params["window_size"], WINDOW.to_string());
+    assert_eq!(resolved.window, Some(WindowSpec::fixed(WINDOW)));
+    let explicit = custom_key(&registry, &[("factor", "2"), ("window_size", "3")]);
+    assert_eq!(dag.resolve_node(explicit).unwrap(), node);
+
+    let result = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+    assert_eq!(result.len(), PRICES.len());
+    assert!(result[..WINDOW - 1].iter().all(|p| p.close_price.is_nan()));
+    for (i, point) in result.iter().enumerate().skip(WINDOW - 1) {
+        let mean = PRICES[i + 1 - WINDOW..=i].iter().sum::<f64>() / WINDOW as f64;
+        assert!((point.close_price - 2.0 * mean).abs() < 1e-9);
+    }
+
+    let listed: Vec<_> = registry.custom_analytics().collect();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].0.name(), "scaled_mean");
+    assert_eq!(listed[0].1, &schema());
+}
+
+#[test]
+fn invalid_registrations_and_parameters_are_rejected() {
+    let registry = registry();
+    let mut dag = AnalyticsDag::new_with_registry(registry.clone());
+    assert!(dag.resolve_node(custom_key(&registry, &[])).is_err());
+    assert!(dag
+        .resolve_node(custom_key(&registry, &[("factor", "two")]))
+        .is_err());
+    assert!(dag
+        .resolve_node(custom_key(
+            &registry,
+            &[("factor", "2"), ("window_size", "0")]
+        ))
+        .is_err());
+    assert_eq!(registry.analytic_type("returns"), AnalyticType::Returns);
+    assert!(matches!(
+        registry.analytic_type(" Scaled_Mean"),
+        AnalyticType::Custom(custom) if custom.name() == "scaled_mean"
+    ));
+
+    let mut other = AnalyticRegistry::new();
+    other
+        .register(ScaledMeanDefinition::named("scaled_mean"), schema())
+        .unwrap();
+    for name in [
+        "scaled_mean",
+        "returns",
+        "garch11",
+        "data_provider",
+        "dataprovider",
+        "scaled mean",
+        "",
+    ] {
+        assert!(
+            other
+                .register(ScaledMeanDefinition::named(name), schema())
+                .is_err(),
+            "{name}"
+        );
+    }
+
+    // Custom analytics are unknown to registries they were not added to
+    let mut builtin = AnalyticsDag::new();
+    assert!(builtin
+        .resolve_node(custom_key(&registry, &[("factor", "2")]))
+        .is_err());
+}
+
+#[test]
+fn push_mode_custom_analytic_matches_pull() {
+    let registry = registry();
+    let provider = setup();
+    let mut dag = AnalyticsDag::new_with_registry(registry.clone());
+    let node = dag
+        .resolve_node(custom_key(&registry, &[("factor", "0.5")]))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for point in series(&PRICES) {
+        engine
+            .push_data(asset("SPY"), point.timestamp, point.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert!(
+            (p.close_price - q.close_price).abs() < 1e-9
+                || (p.close_price.is_nan() && q.close_price.is_nan())
+        );
+    }
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use analytics::analytics::portfolio::{nav_series, RebalanceFrequency};
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, provider, series, Fixture};
+
+const PRICES_A: [f64; 8] = [100.0, 102.0, 101.0, 105.0, 107.0, 104.0, 108.0, 110.0];
+const PRICES_B: [f64; 8] = [50.0, 49.0, 51.0, 50.5, 52.0, 53.0, 51.5, 52.5];
+const WEIGHTS: &str = "0.6,0.4";
+const FIXTURE: Fixture = Fixture::new(8);
+
+fn setup() -> (AssetKey, AssetKey, InMemoryDataProvider) {
+    let provider = provider(&[("AAA", &PRICES_A), ("BBB", &PRICES_B)]);
+    (asset("AAA"), asset("BBB"), provider)
+}
+
+fn portfolio_key(analytic: AnalyticType, assets: Vec<AssetKey>, rebalance: &str) -> NodeKey {
+    FIXTURE.key(
+        analytic,
+        assets,
+        &[("weights", WEIGHTS), ("rebalance", rebalance)],
+    )
+}
+
+#[test]
+fn pull_mode_nav_matches_portfolio_calculator() {
+    let (a, b, provider) = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(portfolio_key(
+            AnalyticType::PortfolioNav,
+            vec![a, b],
+            "daily",
+        ))
+        .unwrap();
+
+    let result = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+    let expected = nav_series(
+        &[&series(&PRICES_A), &series(&PRICES_B)],
+        &[0.6, 0.4],
+        RebalanceFrequency::Daily,
+    );
+
+    assert_eq!(result.len(), expected.len());
+    for (got, want) in result.iter().zip(expected.iter()) {
+        assert_eq!(got.timestamp, want.timestamp);
+        assert!((got.close_price - want.close_price).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn portfolio_returns_are_log_returns_of_nav() {
+    let (a, b, provider) = setup();
+    let mut dag = AnalyticsDag::new();
+    let nav_node = dag
+        .resolve_node(portfolio_key(
+            AnalyticType::PortfolioNav,
+            vec![a.clone(), b.clone()],
+            "none",
+        ))
+        .unwrap();
+    let returns_node = dag
+        .resolve_node(portfolio_key(
+            AnalyticType::PortfolioReturns,
+            vec![a, b],
+            "none",
+        ))
+        .unwrap();
+
+    let nav = dag
+        .execute_pull_mode(nav_node, FIXTURE.range(), &provider)
+        .unwrap();
+    let returns = dag
+        .execute_pull_mode(returns_node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    assert!(returns[0].close_price.is_nan());
+    for i in 1..nav.len() {
+        let expected = (nav[i].close_price / nav[i - 1].close_price).ln();
+        assert!((returns[i].close_price - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn volatility_runs_on_portfolio_returns() {
+    let (a, b, provider) = setup();
+    let key = FIXTURE.with_window(3).key(
+        AnalyticType::Volatility,
+        vec![a.clone(), b.clone()],
+        &[("weights", WEIGHTS), ("rebalance", "none")],
+    );
+
+    let mut dag = AnalyticsDag::new();
+    let vol_node = dag.resolve_node(key).unwrap();
+    let returns_node = dag
+        .resolve_node(portfolio_key(
+            AnalyticType::PortfolioReturns,
+            vec![a, b],
+            "none",
+        ))
+        .unwrap();
+    assert!(dag.get_parents(vol_node).iter().all(|parent| {
+        dag.node_key(*parent).map(|k| k.analytic) == Some(AnalyticType::PortfolioReturns)
+    }));
+
+    let vol = dag
+        .execute_pull_mode(vol_node, FIXTURE.range(), &provider)
+        .unwrap();
+    let returns = dag
+        .execute_pull_mode(returns_node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let window: Vec<f64> = returns[returns.len() - 3..]
+        .iter()
+        .map(|p| p.close_price)
+        .collect();
+    let mean = window.iter().sum::<f64>() / 3.0;
+    let expected = (window.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / 3.0).sqrt();
+    assert!((vol.last().unwrap().close_price - expected).abs() < 1e-12);
+}
+
+#[test]
+fn push_mode_matches_pull_mode_for_portfolio_returns() {
+    let (a, b, provider) = setup();
+    let key = portfolio_key(
+        AnalyticType::PortfolioReturns,
+        vec![a.clone(), b.clone()],
+        "monthly",
+    );
+
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for (pa, pb) in series(&PRICES_A).iter().zip(series(&PRICES_B).iter()) {
+        engine
+            .push_data(a.clone(), pa.timestamp, pa.close_price)
+            .unwrap();
+        engine
+            .push_data(b.clone(), pb.timestamp, pb.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()).skip(1) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert!((p.close_price - q.close_price).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn push_mode_nav_matches_pull_mode_with_daily_rebalancing() {
+    let (a, b, provider) = setup();
+    let key = portfolio_key(
+        AnalyticType::PortfolioNav,
+        vec![a.clone(), b.clone()],
+        "daily",
+    );
+
+    let mut dag = AnalyticsDag::new();
+    let node = dag.resolve_node(key).unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for (pa, pb) in series(&PRICES_A).iter().zip(series(&PRICES_B).iter()) {
+        // The NAV is first updated with A's new price alone, then revised
+        engine
+            .push_data(a.clone(), pa.timestamp, pa.close_price)
+            .unwrap();
+        engine
+            .push_data(b.clone(), pb.timestamp, pb.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert!((p.close_price - q.close_price).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn invalid_portfolio_definition_is_rejected() {
+    let (a, b, _) = setup();
+    let mut dag = AnalyticsDag::new();
+
+    let mut key = portfolio_key(
+        AnalyticType::PortfolioNav,
+        vec![a.clone(), b.clone()],
+        "weekly",
+    );
+    assert!(dag.resolve_node(key.clone()).is_err());
+
+    key.params
+        .insert("rebalance".to_string(), "none".to_string());
+    key.params.insert("weights".to_string(), "1.0".to_string());
+    assert!(dag.resolve_node(key).is_err());
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use analytics::analytics::calculators::{
+    calmar_ratio, downside_deviation, information_ratio, max_drawdown, population_std_dev,
+    sharpe_ratio, sortino_ratio,
+};
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, log_returns, provider, pull, series, Fixture};
+
+const PRICES_A: [f64; 8] = [100.0, 102.0, 99.0, 104.0, 101.0, 107.0, 103.0, 110.0];
+const PRICES_B: [f64; 8] = [50.0, 50.5, 50.0, 51.0, 51.5, 51.0, 52.0, 52.5];
+const RATES: [f64; 8] = [0.02, 0.05, 0.01, 0.08, 0.03, 0.10, 0.04, 0.12];
+const WINDOW: usize = 4;
+const FIXTURE: Fixture = Fixture::new(8).with_window(WINDOW);
+
+fn setup() -> (AssetKey, InMemoryDataProvider) {
+    let provider = provider(&[("AAA", &PRICES_A), ("BBB", &PRICES_B), ("RF", &RATES)]);
+    (asset("AAA"), provider)
+}
+
+fn last_log_returns(prices: &[f64]) -> Vec<f64> {
+    log_returns(&prices[prices.len() - WINDOW - 1..])
+}
+
+/// Returns of the last window less the risk-free rate of each date per period.
+fn last_excess_returns() -> Vec<f64> {
+    last_log_returns(&PRICES_A)
+        .iter()
+        .zip(&RATES[RATES.len() - WINDOW..])
+        .map(|(r, rate)| r - rate / 252.0)
+        .collect()
+}
+
+fn last_value(key: NodeKey, provider: &InMemoryDataProvider) -> f64 {
+    let values = pull(key, provider);
+    assert_eq!(values.len(), PRICES_A.len());
+    *values.last().unwrap()
+}
+
+#[test]
+fn sharpe_subtracts_per_period_risk_free_rate_over_raw_volatility() {
+    let (a, provider) = setup();
+    let key = FIXTURE.key(AnalyticType::SharpeRatio, vec![a], &[("risk_free", "RF")]);
+
+    let returns = last_log_returns(&PRICES_A);
+    let excess = last_excess_returns();
+    let sharpe = last_value(key, &provider);
+    // The volatility node is shared with other analytics of the returns, so a
+    // varying rate moves the numerator but not the denominator
+    let expected = sharpe_ratio(&excess, population_std_dev(&returns), 252.0);
+    assert!((sharpe - expected).abs() < 1e-9);
+    let on_excess = sharpe_ratio(&excess, population_std_dev(&excess), 252.0);
+    assert!((sharpe - on_excess).abs() > 1e-6);
+}
+
+#[test]
+fn sortino_divides_by_downside_deviation_of_returns() {
+    let (a, provider) = setup();
+    let key = FIXTURE.key(AnalyticType::SortinoRatio, vec![a], &[("risk_free", "RF")]);
+
+    let returns = last_log_returns(&PRICES_A);
+    let excess = last_excess_returns();
+    let expected = sortino_ratio(&excess, downside_deviation(&returns), 252.0);
+    assert!((last_value(key, &provider) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn information_ratio_uses_active_returns_against_benchmark() {
+    let (a, provider) = setup();
+    let key = FIXTURE.key(
+        AnalyticType::InformationRatio,
+        vec![a],
+        &[("benchmark", "BBB")],
+    );
+
+    let active: Vec<f64> = last_log_returns(&PRICES_A)
+        .iter()
+        .zip(last_log_returns(&PRICES_B).iter())
+        .map(|(x, y)| x - y)
+        .collect();
+    let expected = information_ratio(&active, 252.0);
+    assert!((last_value(key, &provider) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn calmar_divides_by_rolling_max_drawdown() {
+    let (a, provider) = setup();
+    let key = FIXTURE.key(
+        AnalyticType::CalmarRatio,
+        vec![a],
+        &[("periods_per_year", "12")],
+    );
+
+    let mdd = max_drawdown(&PRICES_A[PRICES_A.len() - WINDOW..]);
+    let expected = calmar_ratio(&last_log_returns(&PRICES_A), mdd, 12.0);
+    assert!((last_value(key, &provider) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn ratios_compose_dispersion_nodes_over_shared_returns() {
+    let (a, _) = setup();
+    let mut dag = AnalyticsDag::new();
+    let sharpe = dag
+        .resolve_node(FIXTURE.key(AnalyticType::SharpeRatio, vec![a.clone()], &[]))
+        .unwrap();
+    let nodes_before = dag.node_count();
+    let sortino = dag
+        .resolve_node(FIXTURE.key(AnalyticType::SortinoRatio, vec![a], &[]))
+        .unwrap();
+    // The returns node is shared; only the Sortino node and its downside
+    // deviation are new
+    assert_eq!(dag.node_count(), nodes_before + 2);
+
+    let parent_types = |node| {
+        let mut types: Vec<String> = dag
+            .get_parents(node)
+            .into_iter()
+            .map(|parent| dag.get_node(parent).unwrap().node_type.clone())
+            .collect();
+        types.sort();
+        types
+    };
+    assert_eq!(parent_types(sharpe), ["returns", "volatility"]);
+    assert_eq!(parent_types(sortino), ["downside_deviation", "returns"]);
+}
+
+#[test]
+fn invalid_ratio_parameters_are_rejected() {
+    let (a, _) = setup();
+    let mut dag = AnalyticsDag::new();
+    assert!(dag
+        .resolve_node(FIXTURE.key(AnalyticType::InformationRatio, vec![a.clone()], &[]))
+        .is_err());
+    assert!(dag
+        .resolve_node(FIXTURE.key(AnalyticType::SharpeRatio, vec![a], &[("risk_free", "")]))
+        .is_err());
+}
+
+#[test]
+fn push_mode_sharpe_matches_pull() {
+    let (a, provider) = setup();
+    let rf = asset("RF");
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(FIXTURE.key(
+            AnalyticType::SharpeRatio,
+            vec![a.clone()],
+            &[("risk_free", "RF")],
+        ))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for (rate, price) in series(&RATES).iter().zip(series(&PRICES_A).iter()) {
+        engine
+            .push_data(rf.clone(), rate.timestamp, rate.close_price)
+            .unwrap();
+        engine
+            .push_data(a.clone(), price.timestamp, price.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert!(
+            (p.close_price - q.close_price).abs() < 1e-12
+                || (p.close_price.is_nan() && q.close_price.is_nan())
+        );
+    }
+}
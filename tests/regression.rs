@@ -0,0 +1,156 @@
+mod common;
+
+use analytics::analytics::regression::ols;
+use analytics::dag::{AnalyticType, AnalyticsDag, NodeKey};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::{InMemoryDataProvider, TimeSeriesPoint};
+use common::{asset, log_returns, provider, pull_outputs, series, Fixture};
+use std::collections::BTreeMap;
+
+const PRICES_A: [f64; 12] = [
+    100.0, 102.0, 99.0, 104.0, 101.0, 97.0, 103.0, 106.0, 100.0, 105.0, 104.0, 109.0,
+];
+const PRICES_SPY: [f64; 12] = [
+    400.0, 404.0, 399.0, 406.0, 403.0, 395.0, 402.0, 409.0, 401.0, 407.0, 408.0, 414.0,
+];
+const PRICES_XLK: [f64; 12] = [
+    150.0, 151.0, 150.5, 153.0, 152.0, 149.0, 151.5, 154.0, 152.5, 153.5, 153.0, 156.0,
+];
+const WINDOW: usize = 6;
+const FIXTURE: Fixture = Fixture::new(12);
+
+fn setup() -> InMemoryDataProvider {
+    provider(&[
+        ("AAA", &PRICES_A),
+        ("SPY", &PRICES_SPY),
+        ("XLK", &PRICES_XLK),
+    ])
+}
+
+fn regression_key(factors: &str, window: usize, output: Option<&str>) -> NodeKey {
+    let mut extra = vec![("factors", factors)];
+    extra.extend(output.map(|output| ("output", output)));
+    FIXTURE
+        .with_window(window)
+        .key(AnalyticType::Regression, vec![asset("AAA")], &extra)
+}
+
+fn last_window(prices: &[f64]) -> Vec<f64> {
+    let returns = log_returns(prices);
+    returns[returns.len() - WINDOW..].to_vec()
+}
+
+fn last(outputs: &BTreeMap<String, Vec<TimeSeriesPoint>>, name: &str) -> f64 {
+    outputs[name].last().unwrap().close_price
+}
+
+#[test]
+fn single_factor_regression_matches_ols_on_last_window() {
+    let provider = setup();
+    let outputs = pull_outputs(regression_key("SPY", WINDOW, None), &provider);
+
+    let fit = ols(&last_window(&PRICES_A), &[last_window(&PRICES_SPY)]).unwrap();
+    assert!((last(&outputs, "alpha") - fit.alpha).abs() < 1e-12);
+    assert!((last(&outputs, "beta_spy") - fit.betas[0]).abs() < 1e-12);
+    assert!((last(&outputs, "r_squared") - fit.r_squared).abs() < 1e-12);
+    assert!((last(&outputs, "residual") - fit.residuals[WINDOW - 1]).abs() < 1e-12);
+    assert!((last(&outputs, "residual_volatility") - fit.residual_volatility()).abs() < 1e-12);
+
+    // The first return is undefined, so the first full window ends on day seven
+    assert_eq!(outputs["alpha"].len(), PRICES_A.len());
+    assert!(outputs["alpha"][..WINDOW]
+        .iter()
+        .all(|p| p.close_price.is_nan()));
+    assert!(!outputs["alpha"][WINDOW].close_price.is_nan());
+}
+
+#[test]
+fn multi_factor_outputs_share_one_node() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let alpha = dag
+        .resolve_node(regression_key("SPY,XLK", WINDOW, Some("alpha")))
+        .unwrap();
+    let beta = dag
+        .resolve_node(regression_key("SPY,XLK", WINDOW, Some("beta_xlk")))
+        .unwrap();
+    assert_eq!(alpha, beta);
+    assert_eq!(
+        dag.node_outputs(alpha),
+        [
+            "alpha",
+            "beta_spy",
+            "beta_xlk",
+            "r_squared",
+            "residual",
+            "residual_volatility"
+        ]
+    );
+
+    let outputs = dag
+        .execute_pull_mode_outputs(alpha, FIXTURE.range(), &provider)
+        .unwrap();
+    let fit = ols(
+        &last_window(&PRICES_A),
+        &[last_window(&PRICES_SPY), last_window(&PRICES_XLK)],
+    )
+    .unwrap();
+    assert!((last(&outputs, "beta_spy") - fit.betas[0]).abs() < 1e-12);
+    assert!((last(&outputs, "beta_xlk") - fit.betas[1]).abs() < 1e-12);
+    assert!((last(&outputs, "r_squared") - fit.r_squared).abs() < 1e-12);
+}
+
+#[test]
+fn invalid_regressions_are_rejected() {
+    let mut dag = AnalyticsDag::new();
+    let mut missing = regression_key("SPY", WINDOW, None);
+    missing.params.remove("factors");
+    assert!(dag.resolve_node(missing).is_err());
+    assert!(dag
+        .resolve_node(regression_key("AAA", WINDOW, None))
+        .is_err());
+    assert!(dag
+        .resolve_node(regression_key("SPY,XLK", 3, None))
+        .is_err());
+    assert!(dag
+        .resolve_node(regression_key("SPY", WINDOW, Some("beta_xlk")))
+        .is_err());
+}
+
+#[test]
+fn push_mode_regression_matches_pull() {
+    let provider = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(regression_key("SPY,XLK", WINDOW, None))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode_outputs(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for i in 0..PRICES_A.len() {
+        for (ticker, prices) in [("SPY", PRICES_SPY), ("XLK", PRICES_XLK), ("AAA", PRICES_A)] {
+            let point = &series(&prices)[i];
+            engine
+                .push_data(asset(ticker), point.timestamp, point.close_price)
+                .unwrap();
+        }
+    }
+
+    for (name, pulled) in &pulled {
+        let pushed = engine.get_output_history(node, name).unwrap();
+        assert_eq!(pushed.len(), pulled.len(), "{name}");
+        for (p, q) in pushed.iter().zip(pulled.iter()) {
+            assert_eq!(p.timestamp, q.timestamp);
+            assert!(
+                (p.close_price - q.close_price).abs() < 1e-12
+                    || (p.close_price.is_nan() && q.close_price.is_nan()),
+                "{name}: {} != {}",
+                p.close_price,
+                q.close_price
+            );
+        }
+    }
+}
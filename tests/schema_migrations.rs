@@ -0,0 +1,172 @@
+use analytics::asset_key::AssetKey;
+use analytics::migrations::{latest_version, SchemaError};
+use analytics::sqlite_provider::SqliteDataProvider;
+use analytics::time_series::{DataProvider, DateRange, TimeSeriesPoint};
+use chrono::{NaiveDate, TimeZone, Utc};
+use rusqlite::Connection;
+use std::path::PathBuf;
+
+/// The schema databases were created with before it was versioned.
+const UNVERSIONED_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS assets (
+        asset_key TEXT PRIMARY KEY,
+        asset_data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS time_series_data (
+        asset_key TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        close_price REAL NOT NULL,
+        recorded_at TEXT NOT NULL DEFAULT '1970-01-01T00:00:00.000000Z',
+        PRIMARY KEY (asset_key, timestamp)
+    );
+    CREATE TABLE IF NOT EXISTS time_series_history (
+        asset_key TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        close_price REAL NOT NULL,
+        recorded_at TEXT NOT NULL,
+        superseded_at TEXT NOT NULL,
+        PRIMARY KEY (asset_key, timestamp, recorded_at)
+    );
+    CREATE INDEX IF NOT EXISTS idx_time_series_asset_key ON time_series_data(asset_key);
+    CREATE INDEX IF NOT EXISTS idx_time_series_timestamp ON time_series_data(timestamp);
+    CREATE TABLE IF NOT EXISTS analytics (
+        asset_key TEXT NOT NULL,
+        date TEXT NOT NULL,
+        analytics_name TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (asset_key, date, analytics_name)
+    );
+    CREATE TABLE IF NOT EXISTS quality_flags (
+        asset_key TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        flag TEXT NOT NULL,
+        detail TEXT NOT NULL,
+        PRIMARY KEY (asset_key, timestamp, flag)
+    );
+    CREATE TABLE IF NOT EXISTS sync_state (
+        asset_key TEXT PRIMARY KEY,
+        watermark TEXT,
+        last_attempt TEXT,
+        last_success TEXT,
+        last_error TEXT
+    );";
+
+/// A database file removed when dropped.
+struct TempDb(PathBuf);
+
+impl TempDb {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("schema_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        TempDb(path)
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn january() -> DateRange {
+    DateRange::new(
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+    )
+}
+
+#[test]
+fn databases_created_by_the_unversioned_schema_are_migrated_in_place() {
+    let db = TempDb::new("unversioned");
+    {
+        let conn = Connection::open(&db.0).unwrap();
+        conn.execute_batch(UNVERSIONED_SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO time_series_data (asset_key, timestamp, close_price, recorded_at)
+            VALUES ('AAPL', '2024-01-15T16:00:00+00:00', 155.0, '2024-02-05T18:00:00.000000Z');
+            INSERT INTO time_series_history
+            VALUES ('AAPL', '2024-01-15T16:00:00+00:00', 150.0,
+                '2024-02-01T18:00:00.000000Z', '2024-02-05T18:00:00.000000Z');
+            INSERT INTO analytics VALUES ('AAPL', '2024-01-15', 'returns', '0.01');
+            INSERT INTO sync_state (asset_key, watermark) VALUES ('AAPL', '2024-01-15');",
+        )
+        .unwrap();
+    }
+
+    let provider = SqliteDataProvider::new(&db.0).unwrap();
+    assert_eq!(provider.schema_version().unwrap(), latest_version());
+
+    // Prices, their history and the other tables survive the migration
+    let aapl = AssetKey::new_equity("AAPL").unwrap();
+    let current = provider.get_time_series(&aapl, &january()).unwrap();
+    assert_eq!(current[0].close_price, 155.0);
+    let known_then = Utc.with_ymd_and_hms(2024, 2, 2, 0, 0, 0).unwrap();
+    let as_of = provider
+        .get_time_series_as_of(&aapl, &january(), known_then)
+        .unwrap();
+    assert_eq!(as_of[0].close_price, 150.0);
+    assert_eq!(provider.get_analytics(&aapl, &january()).unwrap().len(), 1);
+    let state = provider.get_sync_state(&aapl).unwrap().unwrap();
+    assert_eq!(state.watermark, NaiveDate::from_ymd_opt(2024, 1, 15));
+
+    // The migrated database keeps working and reopens without migrating again
+    let point = TimeSeriesPoint::new(Utc.with_ymd_and_hms(2024, 1, 16, 16, 0, 0).unwrap(), 156.0);
+    provider.insert_time_series_point(&aapl, &point).unwrap();
+    drop(provider);
+    let provider = SqliteDataProvider::new(&db.0).unwrap();
+    assert_eq!(provider.schema_version().unwrap(), latest_version());
+    assert_eq!(
+        provider.get_time_series(&aapl, &january()).unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn databases_with_a_newer_schema_are_refused() {
+    let db = TempDb::new("newer");
+    drop(SqliteDataProvider::new(&db.0).unwrap());
+    {
+        let conn = Connection::open(&db.0).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at)
+            VALUES (?1, 'from a newer release', '2030-01-01T00:00:00+00:00')",
+            [latest_version() + 1],
+        )
+        .unwrap();
+    }
+
+    match SqliteDataProvider::new(&db.0) {
+        Err(SchemaError::NewerVersion { found, latest }) => {
+            assert_eq!((found, latest), (latest_version() + 1, latest_version()));
+        }
+        other => panic!("expected a newer-schema error, got {:?}", other),
+    }
+}
+
+#[test]
+fn databases_opened_by_two_connections_at_once_are_migrated_once() {
+    for attempt in 0..5 {
+        let db = TempDb::new(&format!("concurrent_{}", attempt));
+        let start = std::sync::Barrier::new(2);
+        std::thread::scope(|scope| {
+            let openers: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let conn = Connection::open(&db.0).unwrap();
+                        start.wait();
+                        analytics::migrations::migrate(&conn)
+                    })
+                })
+                .collect();
+            for opener in openers {
+                assert_eq!(opener.join().unwrap().unwrap(), latest_version());
+            }
+        });
+
+        let conn = Connection::open(&db.0).unwrap();
+        let applied: u32 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(applied, latest_version());
+    }
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use analytics::analytics::risk::{expected_shortfall, value_at_risk, VarMethod};
+use analytics::asset_key::AssetKey;
+use analytics::dag::{AnalyticType, AnalyticsDag};
+use analytics::push_mode::PushModeEngine;
+use analytics::time_series::InMemoryDataProvider;
+use common::{asset, log_returns, provider, pull, series, Fixture};
+
+const PRICES_A: [f64; 10] = [
+    100.0, 102.0, 99.0, 104.0, 101.0, 97.0, 103.0, 106.0, 100.0, 105.0,
+];
+const PRICES_B: [f64; 10] = [50.0, 50.5, 49.0, 51.0, 51.5, 50.0, 52.0, 51.0, 52.5, 53.0];
+const WINDOW: usize = 5;
+const FIXTURE: Fixture = Fixture::new(10).with_window(WINDOW);
+
+fn setup() -> (AssetKey, AssetKey, InMemoryDataProvider) {
+    let provider = provider(&[("AAA", &PRICES_A), ("BBB", &PRICES_B)]);
+    (asset("AAA"), asset("BBB"), provider)
+}
+
+#[test]
+fn historical_var_uses_last_window_of_returns() {
+    let (a, _, provider) = setup();
+    let values = pull(
+        FIXTURE.key(AnalyticType::ValueAtRisk, vec![a], &[("confidence", "0.8")]),
+        &provider,
+    );
+
+    let returns = log_returns(&PRICES_A);
+    let expected = value_at_risk(
+        &returns[returns.len() - WINDOW..],
+        0.8,
+        VarMethod::Historical,
+    );
+    assert_eq!(values.len(), PRICES_A.len());
+    // The first return is undefined, so the first full window ends on day six
+    assert!(values[..WINDOW - 1].iter().all(|v| v.is_nan()));
+    assert!((values.last().unwrap() - expected).abs() < 1e-12);
+}
+
+#[test]
+fn parametric_and_cornish_fisher_es_match_calculator() {
+    let (a, _, provider) = setup();
+    let returns = log_returns(&PRICES_A);
+    let window = &returns[returns.len() - WINDOW..];
+
+    for method in [VarMethod::Parametric, VarMethod::CornishFisher] {
+        let values = pull(
+            FIXTURE.key(
+                AnalyticType::ExpectedShortfall,
+                vec![a.clone()],
+                &[("method", &method.to_string()), ("confidence", "0.9")],
+            ),
+            &provider,
+        );
+        let expected = expected_shortfall(window, 0.9, method);
+        assert!((values.last().unwrap() - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn portfolio_var_runs_on_portfolio_returns() {
+    let (a, b, provider) = setup();
+    let mut key = FIXTURE.key(
+        AnalyticType::ValueAtRisk,
+        vec![a.clone(), b.clone()],
+        &[("weights", "0.5,0.5"), ("method", "parametric")],
+    );
+    let var = pull(key.clone(), &provider);
+
+    key.analytic = AnalyticType::PortfolioReturns;
+    key.window = None;
+    let returns = pull(key, &provider);
+    let expected = value_at_risk(
+        &returns[returns.len() - WINDOW..],
+        0.95,
+        VarMethod::Parametric,
+    );
+    assert!((var.last().unwrap() - expected).abs() < 1e-12);
+}
+
+#[test]
+fn invalid_tail_risk_parameters_are_rejected() {
+    let (a, _, _) = setup();
+    let mut dag = AnalyticsDag::new();
+    assert!(dag
+        .resolve_node(FIXTURE.key(
+            AnalyticType::ValueAtRisk,
+            vec![a.clone()],
+            &[("confidence", "1.5")]
+        ))
+        .is_err());
+    assert!(dag
+        .resolve_node(FIXTURE.key(
+            AnalyticType::ExpectedShortfall,
+            vec![a],
+            &[("method", "monte_carlo")]
+        ))
+        .is_err());
+}
+
+#[test]
+fn push_mode_var_matches_pull() {
+    let (a, _, provider) = setup();
+    let mut dag = AnalyticsDag::new();
+    let node = dag
+        .resolve_node(FIXTURE.key(
+            AnalyticType::ValueAtRisk,
+            vec![a.clone()],
+            &[("method", "cornish_fisher")],
+        ))
+        .unwrap();
+    let pulled = dag
+        .execute_pull_mode(node, FIXTURE.range(), &provider)
+        .unwrap();
+
+    let mut engine = PushModeEngine::new(dag);
+    engine.is_initialized = true;
+    for point in series(&PRICES_A) {
+        engine
+            .push_data(a.clone(), point.timestamp, point.close_price)
+            .unwrap();
+    }
+
+    let pushed = engine.get_history(node).unwrap();
+    assert_eq!(pushed.len(), pulled.len());
+    for (p, q) in pushed.iter().zip(pulled.iter()) {
+        assert_eq!(p.timestamp, q.timestamp);
+        assert!(
+            (p.close_price - q.close_price).abs() < 1e-12
+                || (p.close_price.is_nan() && q.close_price.is_nan())
+        );
+    }
+}